@@ -5,12 +5,77 @@ mod updates;
 
 pub use lifecycle::*;
 pub use queries::*;
-use types::CanisterId;
+use types::{CanisterId, CommentId, JwtVerificationKey, Milliseconds, ModerationAction, NobleId, PostId, ReportReason, SessionRevocation, RoleGrant, Suspension};
 pub use updates::*;
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub enum Event {
     LocalUserIndexCanisterAdded(Box<LocalUserIndexCanisterAdded>),
+    JwtKeysUpdated(Box<JwtKeysUpdated>),
+    SessionRevoked(Box<SessionRevoked>),
+    EditWindowUpdated(Box<EditWindowUpdated>),
+    UserFollowed(Box<FollowUser>),
+    UserUnfollowed(Box<FollowUser>),
+    UserBlocked(Box<BlockUser>),
+    UserUnblocked(Box<BlockUser>),
+    ContentModerated(Box<ContentModerated>),
+    RolesUpdated(Box<RolesUpdated>),
+    UserSuspended(Box<UserSuspended>),
+    SuspensionLifted(Box<SuspensionLifted>),
+}
+
+// A moderator resolved a report about the post, or one of its comments when `comment_id` is set.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ContentModerated {
+    pub post_id: PostId,
+    pub comment_id: Option<CommentId>,
+    pub action: ModerationAction,
+    pub reason: ReportReason,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct FollowUser {
+    pub sender_id: NobleId,
+    pub receiver_id: NobleId,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct BlockUser {
+    pub sender_id: NobleId,
+    pub receiver_id: NobleId,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct JwtKeysUpdated {
+    pub keys: Vec<JwtVerificationKey>,
+}
+
+// Every role grant, replacing the ones the canister had.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct RolesUpdated {
+    pub grants: Vec<RoleGrant>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct UserSuspended {
+    pub noble_id: NobleId,
+    pub suspension: Suspension,
+}
+
+// Lifted by a moderator or because it's over.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SuspensionLifted {
+    pub noble_id: NobleId,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SessionRevoked {
+    pub revocation: SessionRevocation,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct EditWindowUpdated {
+    pub edit_window: Option<Milliseconds>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -0,0 +1,15 @@
+use candid::CandidType;
+use serde::{Serialize, Deserialize};
+use types::{CommentId, PostId};
+
+#[derive(CandidType, Serialize, Deserialize, Debug)]
+pub struct Args {
+    pub post_id: PostId,
+    pub comment_id: CommentId,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Debug)]
+pub enum Response {
+    Yes,
+    No,
+}
@@ -0,0 +1,18 @@
+use candid::CandidType;
+use serde::Deserialize;
+use types::{PostId, CommentId, CommentRevision};
+
+#[derive(CandidType, Deserialize, Debug)]
+pub struct Args {
+    pub jwt: String,
+    pub post_id: PostId,
+    pub comment_id: CommentId,
+}
+
+#[derive(CandidType, Deserialize, Debug)]
+pub enum Response {
+    Success(Vec<CommentRevision>),
+    PermissionDenied,
+    PostNotFound,
+    CommentNotFound,
+}
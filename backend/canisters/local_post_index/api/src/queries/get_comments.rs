@@ -1,6 +1,6 @@
 use candid::CandidType;
 use serde::Deserialize;
-use types::{CommentDetail, PostId, CommentId, NobleId};
+use types::{CommentDetail, PostId, CommentId};
 
 #[derive(CandidType, Deserialize, Debug)]
 pub struct Args {
@@ -9,8 +9,6 @@ pub struct Args {
     pub comment_id: CommentId,
     pub from: u32,
     pub limit: u32,
-    pub following_list: Vec<NobleId>,
-    pub block_me_users: Vec<NobleId>,
 }
 
 #[derive(CandidType, Deserialize, Debug)]
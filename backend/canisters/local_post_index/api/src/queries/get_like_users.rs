@@ -7,8 +7,6 @@ pub struct Args {
     pub jwt: String,
     pub post_id: PostId,
     pub comment_id: CommentId,
-    pub following_list: Vec<NobleId>,
-    pub block_me_users: Vec<NobleId>,
 }
 
 #[derive(CandidType, Deserialize, Debug)]
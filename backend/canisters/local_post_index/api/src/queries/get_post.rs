@@ -1,14 +1,12 @@
 use candid::CandidType;
 use serde::Deserialize;
-use types::{PostDetail, PostId, CommentDetail, NobleId};
+use types::{PostDetail, PostId, CommentDetail};
 
 #[derive(CandidType, Deserialize, Debug)]
 pub struct Args {
     pub jwt: String,
     pub post_id: PostId,
     pub limit: u32,
-    pub following_list: Vec<NobleId>,
-    pub block_me_users: Vec<NobleId>,
     pub bookmarks: Vec<PostId>,
 }
 
@@ -0,0 +1,16 @@
+use candid::CandidType;
+use serde::Deserialize;
+use types::{PostId, PostRevision};
+
+#[derive(CandidType, Deserialize, Debug)]
+pub struct Args {
+    pub jwt: String,
+    pub post_id: PostId,
+}
+
+#[derive(CandidType, Deserialize, Debug)]
+pub enum Response {
+    Success(Vec<PostRevision>),
+    PermissionDenied,
+    PostNotFound,
+}
@@ -1,3 +1,6 @@
+pub mod c2c_is_nobleblocks_comment;
+pub mod get_comment_revisions;
 pub mod get_comments;
 pub mod get_like_users;
-pub mod get_post;
\ No newline at end of file
+pub mod get_post;
+pub mod get_post_revisions;
\ No newline at end of file
@@ -2,7 +2,7 @@ use std::collections::HashSet;
 
 use candid::CandidType;
 use serde::{Deserialize, Serialize};
-use types::{NobleId, PostPrivacy, PostId};
+use types::{NobleId, PostPrivacy, PostId, TimestampMillis};
 
 #[derive(CandidType, Serialize, Deserialize, Debug, Default)]
 pub struct Args {
@@ -19,7 +19,9 @@ pub enum Response {
     Success,
     Error(ErrorResult),
     PermissionDenied,
+    EditWindowClosed,
     PostNotFound,
+    Suspended { until: Option<TimestampMillis> },
 }
 
 #[derive(CandidType, Deserialize, Debug, PartialEq, Eq)]
@@ -0,0 +1,27 @@
+use candid::CandidType;
+use serde::{Deserialize, Serialize};
+use types::{FileId, TimestampMillis};
+
+#[derive(CandidType, Serialize, Deserialize, Debug)]
+pub struct Args {
+    pub jwt: String,
+    pub file_id: FileId,
+}
+
+#[derive(CandidType, Deserialize, Debug, PartialEq, Eq)]
+pub enum Response {
+    Success(SuccessResult),
+    PermissionDenied,
+    UploadNotFound,
+    PostNotFound,
+    ChunksMissing(Vec<u32>),
+    HashMismatch,
+    Suspended { until: Option<TimestampMillis> },
+}
+
+#[derive(CandidType, Deserialize, Debug, PartialEq, Eq)]
+pub struct SuccessResult {
+    pub file_id: FileId,
+    pub mime_type: String,
+    pub size: u64,
+}
@@ -1,6 +1,6 @@
 use candid::CandidType;
 use serde::{Deserialize, Serialize};
-use types::{PostId, CommentId};
+use types::{PostId, CommentId, TimestampMillis};
 
 #[derive(CandidType, Serialize, Deserialize, Debug)]
 pub struct Args {
@@ -16,4 +16,5 @@ pub enum Response {
     PermissionDenied,
     PostNotFound,
     CommentNotFound,
+    Suspended { until: Option<TimestampMillis> },
 }
@@ -0,0 +1,40 @@
+use candid::CandidType;
+use serde::{Deserialize, Serialize};
+use types::{FileId, PostId, TimestampMillis};
+
+#[derive(CandidType, Serialize, Deserialize, Debug)]
+pub struct Args {
+    pub jwt: String,
+    pub post_id: PostId,
+    pub file_name: String,
+    pub size: u64,
+    // Hex encoded SHA-256 of the whole file, checked once every chunk is in.
+    pub hash: String,
+}
+
+#[derive(CandidType, Deserialize, Debug, PartialEq, Eq)]
+pub enum Response {
+    Success(SuccessResult),
+    PermissionDenied,
+    PostNotFound,
+    InvalidFileName,
+    InvalidHash,
+    FileEmpty,
+    FileTooLarge(u64),
+    QuotaExceeded(QuotaExceededResult),
+    Suspended { until: Option<TimestampMillis> },
+}
+
+#[derive(CandidType, Deserialize, Debug, PartialEq, Eq)]
+pub struct SuccessResult {
+    pub file_id: FileId,
+    pub chunk_size: u32,
+    pub chunk_count: u32,
+}
+
+// Both count only this canister's files.
+#[derive(CandidType, Deserialize, Debug, PartialEq, Eq)]
+pub struct QuotaExceededResult {
+    pub used: u64,
+    pub quota: u64,
+}
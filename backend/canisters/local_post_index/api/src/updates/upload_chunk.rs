@@ -0,0 +1,20 @@
+use candid::CandidType;
+use serde::{Deserialize, Serialize};
+use serde_bytes::ByteBuf;
+use types::FileId;
+
+#[derive(CandidType, Serialize, Deserialize, Debug)]
+pub struct Args {
+    pub jwt: String,
+    pub file_id: FileId,
+    pub index: u32,
+    pub bytes: ByteBuf,
+}
+
+#[derive(CandidType, Deserialize, Debug, PartialEq, Eq)]
+pub enum Response {
+    Success,
+    PermissionDenied,
+    UploadNotFound,
+    InvalidChunk,
+}
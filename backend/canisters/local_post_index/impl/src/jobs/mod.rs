@@ -1,9 +1,13 @@
 use crate::RuntimeState;
 
+pub mod remove_deleted_posts;
+pub mod remove_expired_uploads;
 pub mod sync_events_to_post_index_canister;
 pub mod sync_events_to_user_index_canister;
 
 pub(crate) fn start(state: &RuntimeState) {
+    remove_deleted_posts::start_job_if_required(state);
+    remove_expired_uploads::start_job_if_required(state);
     sync_events_to_post_index_canister::start_job_if_required(state);
     sync_events_to_user_index_canister::start_job_if_required(state);
 }
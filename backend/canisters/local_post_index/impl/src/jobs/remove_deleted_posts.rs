@@ -0,0 +1,36 @@
+use crate::{mutate_state, RuntimeState};
+use ic_cdk_timers::TimerId;
+use std::cell::Cell;
+use std::time::Duration;
+use tracing::{info, trace};
+
+// Posts are removed at most this long after their grace period is over.
+const INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+thread_local! {
+    static TIMER_ID: Cell<Option<TimerId>> = Cell::default();
+}
+
+pub(crate) fn start_job_if_required(_state: &RuntimeState) -> bool {
+    if TIMER_ID.with(|t| t.get().is_none()) {
+        let timer_id = ic_cdk_timers::set_timer_interval(INTERVAL, run);
+        TIMER_ID.with(|t| t.set(Some(timer_id)));
+        trace!("'remove_deleted_posts' job started");
+        true
+    } else {
+        false
+    }
+}
+
+fn run() {
+    mutate_state(remove_deleted_posts);
+}
+
+fn remove_deleted_posts(state: &mut RuntimeState) {
+    let removed = state.data.posts.remove_expired(state.env.now());
+
+    for post in removed {
+        state.on_post_removed(post.post_id);
+        info!(post_id = post.post_id, "Deleted post removed");
+    }
+}
@@ -0,0 +1,35 @@
+use crate::{mutate_state, RuntimeState};
+use ic_cdk_timers::TimerId;
+use std::cell::Cell;
+use std::time::Duration;
+use tracing::{info, trace};
+
+// Uploads are abandoned at most this long after they expire.
+const INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+thread_local! {
+    static TIMER_ID: Cell<Option<TimerId>> = Cell::default();
+}
+
+pub(crate) fn start_job_if_required(_state: &RuntimeState) -> bool {
+    if TIMER_ID.with(|t| t.get().is_none()) {
+        let timer_id = ic_cdk_timers::set_timer_interval(INTERVAL, run);
+        TIMER_ID.with(|t| t.set(Some(timer_id)));
+        trace!("'remove_expired_uploads' job started");
+        true
+    } else {
+        false
+    }
+}
+
+fn run() {
+    mutate_state(remove_expired_uploads);
+}
+
+// Gives the space reserved by uploads that were never finished back to their users.
+fn remove_expired_uploads(state: &mut RuntimeState) {
+    let removed = state.data.files.remove_expired_uploads(state.env.now());
+    if removed > 0 {
+        info!(removed, "Expired uploads removed");
+    }
+}
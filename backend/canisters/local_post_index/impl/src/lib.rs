@@ -2,12 +2,13 @@ use std::{cell::RefCell, collections::HashSet};
 
 use canister_state_macros::canister_state;
 use candid::{Principal, CandidType};
+use model::files::{File, Files};
 use model::post_map::PostMap;
-use post_index_canister::Event as PostIndexEvent;
+use post_index_canister::{Event as PostIndexEvent, PostDeleted, PostTrashed};
 use user_index_canister::Event as UserIndexEvent;
 use serde::{Deserialize, Serialize};
-use types::{CanisterId, TimestampMillis, Cycles, Timestamped, Version};
-use utils::{env::Environment, canister_event_sync_queue::CanisterEventSyncQueue};
+use types::{CanisterId, FileId, NobleId, PostId, PostPrivacy, TimestampMillis, Milliseconds, Cycles, Timestamped, Version, JwtVerificationKey, SessionRevocations, SocialGraph, SuspendedUsers};
+use utils::{env::Environment, canister_event_sync_queue::CanisterEventSyncQueue, rbac::{Permission, Roles}};
 
 mod guards;
 mod lifecycle;
@@ -22,6 +23,13 @@ pub const POST_LIMIT: usize = 200;
 pub const MAX_COMMENT_LENGTH: usize = 200;
 pub const MAX_TITLE_LENGTH: usize = 50;
 pub const MAX_DESCRIPTION_LENGTH: usize = 1_000;
+pub const MAX_FILE_NAME_LENGTH: usize = 255;
+pub const MAX_FILE_SIZE: u64 = 64 * 1_024 * 1_024; // 64 MB
+// What each user's files can take up in this canister, counting unfinished uploads. It's counted per
+// canister, not across the platform: a user's posts land in whichever local_post_index has room, so
+// their total grows with the number of canisters. Four files at the largest size, so no one user can
+// take a big share of the POST_LIMIT posts' worth of files a canister holds.
+pub const FILE_QUOTA_PER_USER_PER_CANISTER: u64 = 4 * MAX_FILE_SIZE; // 256 MB
 
 thread_local! {
     static WASM_VERSION: RefCell<Timestamped<Version>> = RefCell::default();
@@ -44,9 +52,11 @@ impl RuntimeState {
         self.data.post_index_canister_id == caller
     }
 
-    pub fn caller_is_super_admin(&self) -> bool {
-        let caller = self.env.caller();
-        self.data.super_admin == caller
+    // Moderators can edit, delete and restore anyone's posts and comments, whether they're signed
+    // in as a user or calling as a principal.
+    pub fn can_moderate(&self, noble_id: NobleId) -> bool {
+        self.data.roles.user_has_permission(noble_id, Permission::ModerateContent)
+            || self.data.roles.principal_has_permission(self.env.caller(), Permission::ModerateContent)
     }
 
     pub fn push_event_to_post_index(&mut self, event: PostIndexEvent) {
@@ -65,12 +75,47 @@ impl RuntimeState {
         jobs::sync_events_to_user_index_canister::start_job_if_required(self);
     }
 
+    // Files are served over http, where there's no JWT to check the post's privacy against, so only
+    // the files of posts everyone can see are.
+    pub fn servable_file(&self, file_id: FileId) -> Option<&File> {
+        let file = self.data.files.get(file_id)?;
+        let post = self.data.posts.get(file.post_id)?;
+        let visible = post.post_privacy == PostPrivacy::Everyone
+            && !self.data.suspended_users.hides_content(post.noble_id, self.env.now());
+        visible.then_some(file)
+    }
+
+    // Once the window has passed only moderators can edit a post or comment.
+    pub fn is_edit_window_open(&self, date_created: TimestampMillis) -> bool {
+        self.data.edit_window.map_or(true, |window| self.env.now() < date_created.saturating_add(window))
+    }
+
+    // Hides the post until it's restored or `remove_deleted_posts` removes it for good.
+    pub fn delete_post(&mut self, post_id: PostId) -> bool {
+        if self.data.posts.delete_post(post_id, self.env.now()) {
+            self.push_event_to_post_index(PostIndexEvent::PostTrashed(Box::new(PostTrashed { post_id })));
+            true
+        } else {
+            false
+        }
+    }
+
+    // Once a post is removed for good, it's dropped from post_index, which updates the local index's
+    // post count, and from every user's bookmarks and liked posts through user_index. Its files go
+    // with it.
+    pub fn on_post_removed(&mut self, post_id: PostId) {
+        self.data.files.remove_post_files(post_id);
+        self.push_event_to_post_index(PostIndexEvent::PostDeleted(Box::new(PostDeleted { post_id })));
+        self.push_event_to_user_index(UserIndexEvent::PostDeleted(Box::new(user_index_canister::PostDeleted { post_id })));
+    }
+
     pub fn metrics(&self) -> Metrics {
         Metrics {
             now: self.env.now(),
             memory_used: utils::memory::used(),
             cycles_balance: self.env.cycles_balance(),
             post_count: self.data.posts.len(),
+            file_count: self.data.files.len(),
             wasm_version: WASM_VERSION.with(|v| **v.borrow()),
             canister_ids: CanisterIds {
                 user_index_canister_id: self.data.user_index_canister_id,
@@ -91,6 +136,24 @@ struct Data {
     pub super_admin: Principal,
     #[serde(default = "default_local_user_index_canister_ids")]
     pub local_user_index_canister_ids: HashSet<CanisterId>,
+    #[serde(default)]
+    pub jwt_verification_keys: Vec<JwtVerificationKey>,
+    #[serde(default)]
+    pub session_revocations: SessionRevocations,
+    // Set by governance through post_index, None means posts and comments can always be edited.
+    #[serde(default)]
+    pub edit_window: Option<Milliseconds>,
+    // Kept up to date by post_index, decides who can see what.
+    #[serde(default)]
+    pub social_graph: SocialGraph,
+    #[serde(default)]
+    pub files: Files,
+    // A copy of the grants kept by user_index, sent through post_index.
+    #[serde(default)]
+    pub roles: Roles,
+    // A copy of the suspensions kept by user_index, sent through post_index.
+    #[serde(default)]
+    pub suspended_users: SuspendedUsers,
 }
 
 fn default_local_user_index_canister_ids() -> HashSet<CanisterId>{
@@ -105,6 +168,7 @@ impl Data {
         post_index_canister_id: CanisterId,
         local_user_index_canister_ids: HashSet<CanisterId>,
         super_admin: Principal,
+        now: TimestampMillis,
     ) -> Self {
         Data {
             posts: PostMap::default(),
@@ -114,6 +178,13 @@ impl Data {
             post_index_event_sync_queue: CanisterEventSyncQueue::default(),
             user_index_event_sync_queue: CanisterEventSyncQueue::default(),
             local_user_index_canister_ids,
+            jwt_verification_keys: Vec::default(),
+            session_revocations: SessionRevocations::default(),
+            edit_window: None,
+            social_graph: SocialGraph::default(),
+            files: Files::default(),
+            roles: Roles::new(super_admin, now),
+            suspended_users: SuspendedUsers::default(),
         }
     }
 }
@@ -129,6 +200,13 @@ impl Default for Data {
             post_index_event_sync_queue: CanisterEventSyncQueue::default(),
             user_index_event_sync_queue: CanisterEventSyncQueue::default(),
             local_user_index_canister_ids: HashSet::default(),
+            jwt_verification_keys: Vec::default(),
+            session_revocations: SessionRevocations::default(),
+            edit_window: None,
+            social_graph: SocialGraph::default(),
+            files: Files::default(),
+            roles: Roles::default(),
+            suspended_users: SuspendedUsers::default(),
         }
     }
 }
@@ -139,6 +217,7 @@ pub struct Metrics {
     pub memory_used: u64,
     pub cycles_balance: Cycles,
     pub post_count: usize,
+    pub file_count: usize,
     pub wasm_version: Version,
     pub canister_ids: CanisterIds,
 }
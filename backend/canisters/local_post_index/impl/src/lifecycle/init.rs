@@ -14,6 +14,7 @@ fn init(args: Args) {
         args.post_index_canister_id,
         args.local_user_index_canister_ids,
         args.super_admin,
+        env.now(),
     );
 
     init_state(env, data, args.wasm_version);
@@ -22,6 +22,9 @@ fn init_state(env: Box<dyn Environment>, data: Data, wasm_version: Version) {
     let now = env.now();
     let state = RuntimeState::new(env, data);
 
+    types::set_jwt_verification_keys(&state.data.jwt_verification_keys);
+    types::set_session_revocations(&state.data.session_revocations);
+
     crate::jobs::start(&state);
     crate::init_state(state);
     WASM_VERSION.with(|v| *v.borrow_mut() = Timestamped::new(wasm_version, now));
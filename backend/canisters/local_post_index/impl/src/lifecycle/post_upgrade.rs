@@ -6,6 +6,7 @@ use ic_cdk_macros::post_upgrade;
 use ic_stable_structures::reader::{BufferedReader, Reader};
 use local_post_index_canister::post_upgrade::Args;
 use tracing::info;
+use utils::rbac::Roles;
 
 #[post_upgrade]
 fn post_upgrade(args: Args) {
@@ -14,10 +15,15 @@ fn post_upgrade(args: Args) {
     let memory = get_upgrades_memory();
     let reader = BufferedReader::new(UPGRADE_BUFFER_SIZE, Reader::new(&memory, 0));
 
-    let (data, logs, traces): (Data, Vec<LogEntry>, Vec<LogEntry>) = serializer::deserialize(reader).unwrap();
+    let (mut data, logs, traces): (Data, Vec<LogEntry>, Vec<LogEntry>) = serializer::deserialize(reader).unwrap();
 
     canister_logger::init_with_logs(false, logs, traces);
 
+    // Until post_index sends the grants, the super admin can still moderate.
+    if data.roles.is_empty() {
+        data.roles = Roles::new(data.super_admin, env.now());
+    }
+
     init_state(env, data, args.wasm_version);
 
     info!(version = %args.wasm_version, "Post-upgrade complete");
@@ -4,6 +4,11 @@ use ic_stable_structures::{
 };
 
 const UPGRADES: MemoryId = MemoryId::new(0);
+const FILE_CHUNKS: MemoryId = MemoryId::new(1);
+const POSTS_INDEX: MemoryId = MemoryId::new(2);
+const POSTS: MemoryId = MemoryId::new(3);
+const COMMENTS_INDEX: MemoryId = MemoryId::new(4);
+const COMMENTS: MemoryId = MemoryId::new(5);
 
 pub type Memory = VirtualMemory<DefaultMemoryImpl>;
 
@@ -16,6 +21,18 @@ pub fn get_upgrades_memory() -> Memory {
     get_memory(UPGRADES)
 }
 
+pub fn get_file_chunks_memory() -> Memory {
+    get_memory(FILE_CHUNKS)
+}
+
+pub fn get_posts_memories() -> (Memory, Memory) {
+    (get_memory(POSTS_INDEX), get_memory(POSTS))
+}
+
+pub fn get_comments_memories() -> (Memory, Memory) {
+    (get_memory(COMMENTS_INDEX), get_memory(COMMENTS))
+}
+
 fn get_memory(id: MemoryId) -> Memory {
     MEMORY_MANAGER.with(|m| m.get(id))
 }
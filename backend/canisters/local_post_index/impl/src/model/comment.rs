@@ -2,9 +2,11 @@ use candid::CandidType;
 use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
 use types::{
-    TimestampMillis, NobleId, CommentId, CommentDetail
+    TimestampMillis, NobleId, CommentId, CommentDetail, CommentRevision, SocialGraph
 };
 
+use super::revisions::push_revision;
+
 #[derive(CandidType, Serialize, Deserialize, Clone, Debug, Default)]
 pub struct Comment {
     pub noble_id: NobleId,
@@ -18,9 +20,21 @@ pub struct Comment {
     pub first_child: Option<CommentId>,
     pub last_child: Option<CommentId>,
     pub date_created: TimestampMillis,
+    // Unlinked comments are removed, see `CommentMap`. Only false in unlinked slots of posts saved
+    // before comments were kept apart.
     pub is_alive: bool,
+    // A deleted comment which still has replies, kept in the tree as "[deleted]" so they stay visible.
+    #[serde(default)]
+    pub deleted: bool,
+    #[serde(default)]
+    pub date_updated: TimestampMillis,
+    // Every version, oldest first, once the comment has been edited.
+    #[serde(default)]
+    pub revisions: Vec<CommentRevision>,
 }
 
+pub const DELETED_COMMENT_TEXT: &str = "[deleted]";
+
 impl Comment {
     pub fn new(
         noble_id: NobleId,
@@ -40,11 +54,60 @@ impl Comment {
             last_child: None,
             date_created: now,
             is_alive: true,
+            deleted: false,
+            date_updated: now,
+            revisions: Vec::new(),
         }
     }
 
-    pub fn can_show(&self, block_me_users: &Vec<NobleId>) -> bool {
-        !block_me_users.contains(&self.noble_id)
+    // Alive and not a tombstone, ie. one which can be liked, edited or replied to.
+    pub fn is_visible(&self) -> bool {
+        self.is_alive && !self.deleted
+    }
+
+    // Keeps the tree links and counts, drops the content.
+    pub fn tombstone(&mut self) {
+        self.noble_id = 0;
+        self.description.clear();
+        self.liked_users.clear();
+        self.revisions.clear();
+        self.deleted = true;
+    }
+
+    pub fn edit(&mut self, editor: NobleId, description: String, now: TimestampMillis) {
+        if description == self.description {
+            return;
+        }
+        let original = CommentRevision {
+            description: self.description.clone(),
+            editor: self.noble_id,
+            date_updated: self.date_created,
+        };
+        push_revision(&mut self.revisions, original, CommentRevision { description: description.clone(), editor, date_updated: now });
+        self.description = description;
+        self.date_updated = now;
+    }
+
+    pub fn is_edited(&self) -> bool {
+        !self.revisions.is_empty()
+    }
+
+    // Oldest first, just the current version if the comment was never edited.
+    pub fn get_revisions(&self) -> Vec<CommentRevision> {
+        if self.is_edited() {
+            self.revisions.clone()
+        } else {
+            vec![CommentRevision {
+                description: self.description.clone(),
+                editor: self.noble_id,
+                date_updated: self.date_created,
+            }]
+        }
+    }
+
+    // Hidden from users its author has blocked.
+    pub fn can_show(&self, noble_id: NobleId, social_graph: &SocialGraph) -> bool {
+        !social_graph.has_blocked(self.noble_id, noble_id)
     }
 
     pub fn to_detail(&self, comment_id: CommentId, noble_id: NobleId) -> CommentDetail {
@@ -52,28 +115,16 @@ impl Comment {
             noble_id: self.noble_id,
             comment_id,
             parent_comment_id: self.parent.unwrap(),
-            description: self.description.clone(),
+            description: if self.deleted { DELETED_COMMENT_TEXT.to_string() } else { self.description.clone() },
             liked_users_count: self.liked_users.len() as u32,
             comments_count: self.comments_count,
             date_created: self.date_created,
+            date_updated: if self.is_edited() { self.date_updated } else { self.date_created },
+            edited: self.is_edited(),
             like_state: self.liked_users.contains(&noble_id),
             loading_like: false,
             loading_delete: false,
+            deleted: self.deleted,
         }
     }
-
-    pub fn clear(&mut self) {
-        self.noble_id = 0;
-        self.description.clear();
-        self.liked_users.clear();
-        self.comments_count = 0;
-        self.children_count = 0;
-        self.parent = None;
-        self.prev_sibling = None;
-        self.next_sibling = None;
-        self.first_child = None;
-        self.last_child = None;
-        self.date_created = 0;
-        self.is_alive = false;
-    }
-}
\ No newline at end of file
+}
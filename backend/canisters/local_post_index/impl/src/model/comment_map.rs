@@ -0,0 +1,491 @@
+use crate::memory::{get_comments_memories, Memory};
+use crate::model::comment::Comment;
+use stable_map::StableMap;
+use types::{CommentDetail, CommentId, NobleId, PostId, SocialGraph, SuspendedUsers, TimestampMillis};
+
+// Comments are kept in stable memory apart from their posts, so reading a post doesn't load them
+// and a comment is saved without writing its post back. Comment 0 of a post is the post itself, the
+// root of its comment tree. Unlinked comments are removed, their ids are never given out again.
+pub struct CommentMap {
+    comments: StableMap<(PostId, CommentId), Comment, Memory>,
+}
+
+impl Default for CommentMap {
+    fn default() -> Self {
+        let (comments_index_memory, comments_memory) = get_comments_memories();
+        CommentMap {
+            comments: StableMap::init(comments_index_memory, comments_memory),
+        }
+    }
+}
+
+impl CommentMap {
+    // Only comments which can be liked, edited or replied to, see `Comment::is_visible`.
+    pub fn get(&self, post_id: PostId, comment_id: CommentId) -> Option<Comment> {
+        self.comments.get((post_id, comment_id)).filter(|comment| comment.is_visible())
+    }
+
+    // Saves the changes made to a comment read with `get`.
+    pub fn update(&mut self, post_id: PostId, comment_id: CommentId, comment: &Comment) -> bool {
+        if self.comments.contains_key((post_id, comment_id)) {
+            self.comments.insert((post_id, comment_id), comment);
+            true
+        } else {
+            false
+        }
+    }
+
+    // The visible comments under the post.
+    pub fn comments_count(&self, post_id: PostId) -> u32 {
+        self.comments.get((post_id, 0)).map_or(0, |root| root.comments_count)
+    }
+
+    pub fn add_root(&mut self, post_id: PostId, noble_id: NobleId, now: TimestampMillis) {
+        self.comments.insert((post_id, 0), &Comment::new(noble_id, String::new(), now));
+    }
+
+    // The new comment takes `next_comment_id`, which is then moved on, see `Post::next_comment_id`.
+    pub fn add(
+        &mut self,
+        post_id: PostId,
+        next_comment_id: &mut CommentId,
+        noble_id: NobleId,
+        parent_id: CommentId,
+        description: String,
+        now: TimestampMillis,
+    ) -> Option<CommentId> {
+        let mut parent = self.get(post_id, parent_id)?;
+        let new_comment_id = *next_comment_id;
+        *next_comment_id += 1;
+
+        let last_child_id = parent.last_child;
+        parent.last_child = Some(new_comment_id);
+        parent.children_count += 1;
+        match last_child_id {
+            Some(id) => self.modify(post_id, id, |comment| comment.next_sibling = Some(new_comment_id)),
+            None => parent.first_child = Some(new_comment_id),
+        }
+        self.comments.insert((post_id, parent_id), &parent);
+
+        let mut new_comment = Comment::new(noble_id, description, now);
+        new_comment.parent = Some(parent_id);
+        new_comment.prev_sibling = last_child_id;
+        self.comments.insert((post_id, new_comment_id), &new_comment);
+
+        self.update_parent(post_id, Some(parent_id), 1);
+        Some(new_comment_id)
+    }
+
+    // A comment with replies becomes a "[deleted]" tombstone so the replies stay where they are,
+    // otherwise it's unlinked along with any tombstones left without replies.
+    pub fn remove(&mut self, post_id: PostId, comment_id: CommentId) -> bool {
+        let mut comment = match self.get(post_id, comment_id) {
+            Some(comment) if comment_id != 0 => comment,
+            _ => return false,
+        };
+        let parent_id = comment.parent;
+        self.update_parent(post_id, parent_id, -1);
+
+        if comment.first_child.is_some() {
+            comment.tombstone();
+            self.comments.insert((post_id, comment_id), &comment);
+        } else {
+            self.unlink(post_id, comment_id, &comment);
+
+            let mut id = parent_id;
+            while let Some(parent) = id.map(|id| self.linked(post_id, id)) {
+                if !parent.deleted || parent.first_child.is_some() {
+                    break;
+                }
+                self.unlink(post_id, id.unwrap(), &parent);
+                id = parent.parent;
+            }
+        }
+        true
+    }
+
+    // Removes every comment of a post, `next_comment_id` being the post's.
+    pub fn remove_post(&mut self, post_id: PostId, next_comment_id: CommentId) {
+        for comment_id in 0..next_comment_id {
+            self.comments.remove((post_id, comment_id));
+        }
+    }
+
+    pub fn get_sub_comments(
+        &self,
+        post_id: PostId,
+        comment_id: CommentId,
+        noble_id: NobleId,
+        from: u32,
+        limit: u32,
+        social_graph: &SocialGraph,
+        suspended_users: &SuspendedUsers,
+        now: TimestampMillis,
+    ) -> (Vec<CommentDetail>, bool) {
+        // Tombstones are included, their replies are still shown.
+        let mut id = match self.comments.get((post_id, comment_id)) {
+            Some(comment) => comment.first_child,
+            None => return (vec![], false),
+        };
+        let mut comments = vec![];
+        let mut cnt = 0;
+
+        while let Some(child_id) = id {
+            let comment = self.linked(post_id, child_id);
+            id = comment.next_sibling;
+            if !comment.can_show(noble_id, social_graph) || suspended_users.hides_content_from(comment.noble_id, noble_id, now) {
+                continue;
+            }
+            if cnt >= from + limit {
+                return (comments, true);
+            }
+            if from <= cnt {
+                comments.push(comment.to_detail(child_id, noble_id));
+            }
+            cnt += 1;
+        }
+        (comments, false)
+    }
+
+    // Moves the comments of a post saved before they were kept apart, which were indexed by their
+    // ids. Counts are recomputed from the tree links, and anything left in unlinked slots, eg. likes
+    // added to a deleted comment before ids stopped being reused, is dropped along with the slot.
+    pub fn migrate(&mut self, post_id: PostId, mut comments: Vec<Comment>) {
+        fn count(comments: &mut [Comment], comment_id: CommentId, linked: &mut [bool]) -> u32 {
+            linked[comment_id as usize] = true;
+            let mut comments_count = 0;
+            let mut children_count = 0;
+            let mut child = comments[comment_id as usize].first_child;
+            while let Some(id) = child {
+                let visible = comments[id as usize].is_visible() as u32;
+                comments_count += visible + count(comments, id, linked);
+                children_count += 1;
+                child = comments[id as usize].next_sibling;
+            }
+            let comment = &mut comments[comment_id as usize];
+            comment.comments_count = comments_count;
+            comment.children_count = children_count;
+            comments_count
+        }
+
+        if comments.is_empty() {
+            return;
+        }
+        let mut linked = vec![false; comments.len()];
+        count(&mut comments, 0, &mut linked);
+
+        for (comment_id, (comment, linked)) in comments.iter().zip(linked).enumerate() {
+            if linked {
+                self.comments.insert((post_id, comment_id as CommentId), comment);
+            }
+        }
+    }
+
+    // Adds `delta` to the comment count of the comment and each of its ancestors.
+    fn update_parent(&mut self, post_id: PostId, mut comment_id: Option<CommentId>, delta: i32) {
+        while let Some(id) = comment_id {
+            let mut comment = self.linked(post_id, id);
+            comment.comments_count = comment.comments_count.saturating_add_signed(delta);
+            self.comments.insert((post_id, id), &comment);
+            comment_id = comment.parent;
+        }
+    }
+
+    fn unlink(&mut self, post_id: PostId, comment_id: CommentId, comment: &Comment) {
+        let parent_id = comment.parent.unwrap();
+        let mut parent = self.linked(post_id, parent_id);
+        let prev_sibling_id = comment.prev_sibling;
+        let next_sibling_id = comment.next_sibling;
+
+        match prev_sibling_id {
+            Some(id) => self.modify(post_id, id, |sibling| sibling.next_sibling = next_sibling_id),
+            None => parent.first_child = next_sibling_id,
+        }
+        match next_sibling_id {
+            Some(id) => self.modify(post_id, id, |sibling| sibling.prev_sibling = prev_sibling_id),
+            None => parent.last_child = prev_sibling_id,
+        }
+        parent.children_count -= 1;
+        self.comments.insert((post_id, parent_id), &parent);
+        self.comments.remove((post_id, comment_id));
+    }
+
+    fn modify(&mut self, post_id: PostId, comment_id: CommentId, f: impl FnOnce(&mut Comment)) {
+        let mut comment = self.linked(post_id, comment_id);
+        f(&mut comment);
+        self.comments.insert((post_id, comment_id), &comment);
+    }
+
+    // Comments are only removed once nothing links to them.
+    fn linked(&self, post_id: PostId, comment_id: CommentId) -> Comment {
+        self.comments.get((post_id, comment_id)).unwrap_or_else(|| panic!("Comment {comment_id} of post {post_id} is missing"))
+    }
+
+    // Checks the links and counts of the comment tree. `comments_count` counts the visible
+    // comments below, `children_count` the linked children including tombstones. Unlinked
+    // comments must be gone.
+    #[cfg(test)]
+    pub fn validate_state(&self, post_id: PostId, next_comment_id: CommentId) -> bool {
+        #[derive(Debug, Default, Clone)]
+        struct State {
+            pub comments_count: u32,
+            pub children_count: u32,
+            pub is_visited: bool,
+        }
+        let comments: Vec<Option<Comment>> = (0..next_comment_id).map(|id| self.comments.get((post_id, id))).collect();
+        let mut states = vec![State::default(); comments.len()];
+
+        fn dfs(comments: &[Option<Comment>], comment_id: CommentId, states: &mut [State]) -> bool {
+            let comment = match comments.get(comment_id as usize) {
+                Some(Some(comment)) => comment,
+                _ => return false,
+            };
+            if states[comment_id as usize].is_visited || (comment.deleted && comment.first_child.is_none()) {
+                return false;
+            }
+            states[comment_id as usize].is_visited = true;
+
+            let mut prev = None;
+            let mut child = comment.first_child;
+            while let Some(id) = child {
+                let child_comment = match comments.get(id as usize) {
+                    Some(Some(child_comment)) => child_comment,
+                    _ => return false,
+                };
+                if child_comment.parent != Some(comment_id) || child_comment.prev_sibling != prev || !dfs(comments, id, states) {
+                    return false;
+                }
+                let counted = child_comment.is_visible() as u32 + states[id as usize].comments_count;
+                let state = &mut states[comment_id as usize];
+                state.children_count += 1;
+                state.comments_count += counted;
+                prev = child;
+                child = child_comment.next_sibling;
+            }
+            comment.last_child == prev
+        }
+
+        if !dfs(&comments, 0, &mut states) {
+            return false;
+        }
+
+        comments.iter().zip(states.iter()).all(|(comment, state)| match comment {
+            Some(comment) => {
+                comment.is_alive &&
+                state.is_visited &&
+                comment.comments_count == state.comments_count &&
+                comment.children_count == state.children_count
+            }
+            None => !state.is_visited,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::revisions::MAX_REVISIONS;
+    use rand::rngs::StdRng;
+    use rand::{Rng, SeedableRng};
+    use types::Suspension;
+
+    const POST_ID: PostId = 1;
+
+    struct Tree {
+        comments: CommentMap,
+        next_comment_id: CommentId,
+    }
+
+    impl Tree {
+        fn new() -> Tree {
+            let mut comments = CommentMap::default();
+            comments.add_root(POST_ID, 1, 0);
+            Tree { comments, next_comment_id: 1 }
+        }
+
+        fn add(&mut self, noble_id: NobleId, parent_id: CommentId, description: &str, now: TimestampMillis) -> Option<CommentId> {
+            self.comments.add(POST_ID, &mut self.next_comment_id, noble_id, parent_id, description.to_string(), now)
+        }
+
+        fn remove(&mut self, comment_id: CommentId) -> bool {
+            self.comments.remove(POST_ID, comment_id)
+        }
+
+        fn raw(&self, comment_id: CommentId) -> Option<Comment> {
+            self.comments.comments.get((POST_ID, comment_id))
+        }
+
+        fn is_valid(&self) -> bool {
+            self.comments.validate_state(POST_ID, self.next_comment_id)
+        }
+
+        fn sub_comments(&self, comment_id: CommentId, suspended_users: &SuspendedUsers, now: TimestampMillis) -> Vec<CommentDetail> {
+            self.comments.get_sub_comments(POST_ID, comment_id, 1, 0, 10, &SocialGraph::default(), suspended_users, now).0
+        }
+    }
+
+    #[test]
+    fn total_test() {
+        let mut tree = Tree::new();
+
+        tree.add(1, 0, "", 0);
+        tree.add(1, 1, "", 0);
+        tree.add(1, 0, "", 0);
+        tree.add(1, 0, "", 0);
+        tree.add(1, 0, "", 0);
+        tree.add(1, 4, "", 0);
+        tree.add(1, 4, "", 0);
+        tree.add(1, 4, "", 0);
+        tree.add(1, 7, "", 0);
+        tree.add(1, 7, "", 0);
+        assert!(tree.is_valid());
+        tree.remove(2);
+        assert!(tree.is_valid());
+        tree.remove(1);
+        assert!(tree.is_valid());
+        tree.remove(4);
+        assert!(tree.is_valid());
+        tree.remove(5);
+        assert!(tree.is_valid());
+    }
+
+    #[test]
+    fn deleted_comment_with_replies_is_tombstoned() {
+        let mut tree = Tree::new();
+        let parent = tree.add(2, 0, "parent", 1).unwrap();
+        let reply = tree.add(3, parent, "reply", 2).unwrap();
+
+        assert!(tree.remove(parent));
+        assert!(tree.is_valid());
+        assert!(tree.raw(parent).unwrap().deleted);
+        assert_eq!(tree.comments.comments_count(POST_ID), 1);
+
+        let comments = tree.sub_comments(0, &SuspendedUsers::default(), 0);
+        assert_eq!(comments[0].description, "[deleted]");
+        assert!(comments[0].deleted);
+        assert_eq!(tree.sub_comments(parent, &SuspendedUsers::default(), 0)[0].comment_id, reply);
+
+        // Tombstones can't be replied to, and go away with their last reply.
+        assert!(tree.add(3, parent, "", 3).is_none());
+        assert!(tree.remove(reply));
+        assert!(tree.is_valid());
+        assert!(tree.raw(parent).is_none());
+        assert_eq!(tree.raw(0).unwrap().first_child, None);
+    }
+
+    #[test]
+    fn comment_ids_are_never_reused() {
+        let mut tree = Tree::new();
+        let first = tree.add(2, 0, "", 1).unwrap();
+        tree.remove(first);
+        let second = tree.add(2, 0, "", 2).unwrap();
+
+        assert!(second > first);
+        assert!(tree.comments.get(POST_ID, first).is_none());
+        assert!(!tree.remove(first));
+    }
+
+    #[test]
+    fn random_adds_and_removes_keep_state_valid() {
+        for seed in 0..50 {
+            let mut rng = StdRng::seed_from_u64(seed);
+            let mut tree = Tree::new();
+            let mut max_id = 0;
+
+            for _ in 0..200 {
+                let comment_id = rng.gen_range(0..tree.next_comment_id);
+                if rng.gen_bool(0.6) {
+                    if let Some(id) = tree.add(2, comment_id, "", 0) {
+                        assert!(id > max_id);
+                        max_id = id;
+                    }
+                } else {
+                    tree.remove(comment_id);
+                }
+                assert!(tree.is_valid(), "seed {seed}");
+            }
+
+            let visible = (1..tree.next_comment_id).filter(|id| tree.comments.get(POST_ID, *id).is_some()).count() as u32;
+            assert_eq!(tree.comments.comments_count(POST_ID), visible);
+        }
+    }
+
+    #[test]
+    fn migration_repairs_counts_and_drops_unlinked_slots() {
+        let mut comments = vec![Comment::new(1, String::new(), 0)];
+        for _ in 0..3 {
+            let mut comment = Comment::new(2, String::new(), 1);
+            comment.parent = Some(0);
+            comments.push(comment);
+        }
+        // 1 and 3 are linked, 2 was unlinked but kept a like.
+        comments[0].first_child = Some(1);
+        comments[0].last_child = Some(3);
+        comments[0].comments_count = 7;
+        comments[1].next_sibling = Some(3);
+        comments[3].prev_sibling = Some(1);
+        comments[3].children_count = 2;
+        comments[2].is_alive = false;
+        comments[2].liked_users.insert(3);
+
+        let mut tree = Tree { comments: CommentMap::default(), next_comment_id: 4 };
+        tree.comments.migrate(POST_ID, comments);
+
+        assert!(tree.is_valid());
+        assert!(tree.raw(2).is_none());
+        assert_eq!(tree.comments.comments_count(POST_ID), 2);
+    }
+
+    #[test]
+    fn comment_edits_are_kept_as_revisions() {
+        let mut tree = Tree::new();
+        let comment_id = tree.add(2, 0, "reply", 1).unwrap();
+        for i in 0..MAX_REVISIONS as u64 + 5 {
+            let mut comment = tree.comments.get(POST_ID, comment_id).unwrap();
+            comment.edit(2, i.to_string(), 10 + i);
+            assert!(tree.comments.update(POST_ID, comment_id, &comment));
+        }
+        let revisions = tree.comments.get(POST_ID, comment_id).unwrap().get_revisions();
+        assert_eq!(revisions.len(), MAX_REVISIONS);
+        assert_eq!(revisions[0].description, "reply");
+        assert_eq!(revisions.last().unwrap().description, (MAX_REVISIONS + 4).to_string());
+
+        let detail = tree.sub_comments(0, &SuspendedUsers::default(), 0).remove(0);
+        assert!(detail.edited);
+        assert_eq!(detail.date_updated, 10 + MAX_REVISIONS as u64 + 4);
+
+        // Deleting a comment with replies drops its history along with the text.
+        tree.add(3, comment_id, "", 2);
+        tree.remove(comment_id);
+        assert!(tree.raw(comment_id).unwrap().revisions.is_empty());
+    }
+
+    #[test]
+    fn suspended_users_comments_can_be_hidden() {
+        let mut tree = Tree::new();
+        tree.add(2, 0, "first", 1).unwrap();
+        tree.add(3, 0, "second", 2).unwrap();
+        let mut suspended_users = SuspendedUsers::default();
+        suspended_users.suspend(2, Suspension { until: Some(10), hide_content: true });
+
+        let authors = |noble_id, now| -> Vec<NobleId> {
+            let (comments, _) = tree.comments.get_sub_comments(POST_ID, 0, noble_id, 0, 10, &SocialGraph::default(), &suspended_users, now);
+            comments.iter().map(|comment| comment.noble_id).collect()
+        };
+        assert_eq!(authors(1, 5), vec![3]);
+        assert_eq!(authors(2, 5), vec![2, 3]);
+        assert_eq!(authors(1, 10), vec![2, 3]);
+    }
+
+    #[test]
+    fn removing_a_post_removes_its_comments() {
+        let mut tree = Tree::new();
+        tree.add(2, 0, "", 1).unwrap();
+        tree.comments.add_root(2, 1, 0);
+
+        tree.comments.remove_post(POST_ID, tree.next_comment_id);
+
+        assert!(tree.raw(0).is_none() && tree.raw(1).is_none());
+        assert!(tree.comments.get(2, 0).is_some());
+    }
+}
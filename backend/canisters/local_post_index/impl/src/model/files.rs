@@ -0,0 +1,331 @@
+use crate::memory::{get_file_chunks_memory, Memory};
+use ic_stable_structures::{BoundedStorable, StableBTreeMap, Storable};
+use serde::{Serialize, Deserialize};
+use sha256::sha256_of_chunks;
+use std::borrow::Cow;
+use std::collections::{BTreeSet, HashMap};
+use types::{FileId, NobleId, PostId, TimestampMillis};
+use utils::mime::detect_mime_type;
+
+// Uploads are sent, and downloads streamed, this many bytes at a time.
+pub const CHUNK_SIZE: u32 = 512 * 1_024; // 512 KB
+// Unfinished uploads are abandoned, and their space given back, after this long.
+pub const UPLOAD_EXPIRY: TimestampMillis = 24 * 60 * 60 * 1000; // 1 day.
+
+// How many bytes of the file are looked at to work out its type.
+const MIME_SAMPLE_SIZE: usize = 512;
+
+// Files attached to posts. Only what describes them is on the heap, their content is kept in
+// stable memory so it neither counts against the heap nor has to be copied on upgrade.
+#[derive(Serialize, Deserialize)]
+pub struct Files {
+    files: HashMap<FileId, File>,
+    uploads: HashMap<FileId, Upload>,
+    // Bytes each user has stored or reserved for their unfinished uploads.
+    usage: HashMap<NobleId, u64>,
+    #[serde(skip, default = "init_chunks")]
+    chunks: StableBTreeMap<ChunkKey, Chunk, Memory>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct File {
+    pub owner: NobleId,
+    pub post_id: PostId,
+    pub name: String,
+    pub mime_type: String,
+    pub size: u64,
+    pub hash: [u8; 32],
+    pub date_created: TimestampMillis,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Upload {
+    pub owner: NobleId,
+    pub post_id: PostId,
+    pub name: String,
+    pub size: u64,
+    pub hash: [u8; 32],
+    pub received: BTreeSet<u32>,
+    pub date_started: TimestampMillis,
+}
+
+pub enum PutChunkResult {
+    Success,
+    NotFound,
+    InvalidChunk,
+}
+
+pub enum FinishUploadResult {
+    Success(File),
+    NotFound,
+    ChunksMissing(Vec<u32>),
+    HashMismatch,
+}
+
+impl Files {
+    pub fn get(&self, file_id: FileId) -> Option<&File> {
+        self.files.get(&file_id)
+    }
+
+    pub fn upload(&self, file_id: FileId) -> Option<&Upload> {
+        self.uploads.get(&file_id)
+    }
+
+    pub fn contains(&self, file_id: FileId) -> bool {
+        self.files.contains_key(&file_id) || self.uploads.contains_key(&file_id)
+    }
+
+    pub fn len(&self) -> usize {
+        self.files.len()
+    }
+
+    pub fn used(&self, noble_id: NobleId) -> u64 {
+        self.usage.get(&noble_id).copied().unwrap_or_default()
+    }
+
+    // The caller checks the user has room for it.
+    pub fn start_upload(&mut self, file_id: FileId, upload: Upload) {
+        *self.usage.entry(upload.owner).or_default() += upload.size;
+        self.uploads.insert(file_id, upload);
+    }
+
+    pub fn put_chunk(&mut self, file_id: FileId, index: u32, bytes: Vec<u8>) -> PutChunkResult {
+        let upload = match self.uploads.get_mut(&file_id) {
+            Some(upload) => upload,
+            None => return PutChunkResult::NotFound,
+        };
+        // Every chunk but the last is full, so the file ends up exactly as big as was said.
+        if index >= chunk_count(upload.size) || bytes.len() as u64 != chunk_len(upload.size, index) {
+            return PutChunkResult::InvalidChunk;
+        }
+        upload.received.insert(index);
+        self.chunks.insert(ChunkKey { file_id, index }, Chunk(bytes));
+        PutChunkResult::Success
+    }
+
+    pub fn finish_upload(&mut self, file_id: FileId, now: TimestampMillis) -> FinishUploadResult {
+        let upload = match self.uploads.get(&file_id) {
+            Some(upload) => upload,
+            None => return FinishUploadResult::NotFound,
+        };
+        let chunk_count = chunk_count(upload.size);
+        if upload.received.len() as u32 != chunk_count {
+            return FinishUploadResult::ChunksMissing((0..chunk_count).filter(|index| !upload.received.contains(index)).collect());
+        }
+
+        let hash = sha256_of_chunks((0..chunk_count).filter_map(|index| self.chunk(file_id, index)));
+        if hash != upload.hash {
+            // There's no telling which chunk was corrupted, so the upload has to start over.
+            self.remove_upload(file_id);
+            return FinishUploadResult::HashMismatch;
+        }
+
+        let upload = self.uploads.remove(&file_id).unwrap();
+        let sample = self.read(file_id, 0, upload.size.min(MIME_SAMPLE_SIZE as u64));
+        let file = File {
+            owner: upload.owner,
+            post_id: upload.post_id,
+            mime_type: detect_mime_type(&upload.name, &sample).to_string(),
+            name: upload.name,
+            size: upload.size,
+            hash,
+            date_created: now,
+        };
+        self.files.insert(file_id, file.clone());
+        FinishUploadResult::Success(file)
+    }
+
+    pub fn chunk(&self, file_id: FileId, index: u32) -> Option<Vec<u8>> {
+        self.chunks.get(&ChunkKey { file_id, index }).map(|chunk| chunk.0)
+    }
+
+    // The bytes from `start` up to, but not including, `end`.
+    pub fn read(&self, file_id: FileId, start: u64, end: u64) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(end.saturating_sub(start) as usize);
+        if start >= end {
+            return bytes;
+        }
+        let chunk_size = CHUNK_SIZE as u64;
+        for index in start / chunk_size..=(end - 1) / chunk_size {
+            let chunk = match self.chunk(file_id, index as u32) {
+                Some(chunk) => chunk,
+                None => break,
+            };
+            let offset = index * chunk_size;
+            let from = start.saturating_sub(offset) as usize;
+            let to = ((end - offset) as usize).min(chunk.len());
+            bytes.extend_from_slice(&chunk[from..to]);
+        }
+        bytes
+    }
+
+    pub fn remove(&mut self, file_id: FileId) -> Option<File> {
+        let file = self.files.remove(&file_id)?;
+        self.release(file.owner, file.size);
+        self.remove_chunks(file_id, file.size);
+        Some(file)
+    }
+
+    pub fn remove_upload(&mut self, file_id: FileId) -> Option<Upload> {
+        let upload = self.uploads.remove(&file_id)?;
+        self.release(upload.owner, upload.size);
+        self.remove_chunks(file_id, upload.size);
+        Some(upload)
+    }
+
+    // Removes the files and unfinished uploads of a post that's been removed.
+    pub fn remove_post_files(&mut self, post_id: PostId) {
+        let file_ids: Vec<_> = self.files.iter().filter(|(_, file)| file.post_id == post_id).map(|(file_id, _)| *file_id).collect();
+        for file_id in file_ids {
+            self.remove(file_id);
+        }
+        let upload_ids: Vec<_> = self.uploads.iter().filter(|(_, upload)| upload.post_id == post_id).map(|(file_id, _)| *file_id).collect();
+        for file_id in upload_ids {
+            self.remove_upload(file_id);
+        }
+    }
+
+    pub fn remove_expired_uploads(&mut self, now: TimestampMillis) -> usize {
+        let expired: Vec<_> = self
+            .uploads
+            .iter()
+            .filter(|(_, upload)| now >= upload.date_started.saturating_add(UPLOAD_EXPIRY))
+            .map(|(file_id, _)| *file_id)
+            .collect();
+        for file_id in expired.iter() {
+            self.remove_upload(*file_id);
+        }
+        expired.len()
+    }
+
+    fn release(&mut self, noble_id: NobleId, size: u64) {
+        if let Some(used) = self.usage.get_mut(&noble_id) {
+            *used = used.saturating_sub(size);
+            if *used == 0 {
+                self.usage.remove(&noble_id);
+            }
+        }
+    }
+
+    fn remove_chunks(&mut self, file_id: FileId, size: u64) {
+        for index in 0..chunk_count(size) {
+            self.chunks.remove(&ChunkKey { file_id, index });
+        }
+    }
+}
+
+impl Default for Files {
+    fn default() -> Self {
+        Files {
+            files: HashMap::default(),
+            uploads: HashMap::default(),
+            usage: HashMap::default(),
+            chunks: init_chunks(),
+        }
+    }
+}
+
+pub fn chunk_count(size: u64) -> u32 {
+    ((size + CHUNK_SIZE as u64 - 1) / CHUNK_SIZE as u64) as u32
+}
+
+fn chunk_len(size: u64, index: u32) -> u64 {
+    (size - index as u64 * CHUNK_SIZE as u64).min(CHUNK_SIZE as u64)
+}
+
+fn init_chunks() -> StableBTreeMap<ChunkKey, Chunk, Memory> {
+    StableBTreeMap::init(get_file_chunks_memory())
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+struct ChunkKey {
+    file_id: FileId,
+    index: u32,
+}
+
+impl Storable for ChunkKey {
+    fn to_bytes(&self) -> Cow<[u8]> {
+        let mut bytes = Vec::with_capacity(Self::MAX_SIZE as usize);
+        bytes.extend_from_slice(&self.file_id.to_be_bytes());
+        bytes.extend_from_slice(&self.index.to_be_bytes());
+        Cow::Owned(bytes)
+    }
+
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        ChunkKey {
+            file_id: FileId::from_be_bytes(bytes[..16].try_into().unwrap()),
+            index: u32::from_be_bytes(bytes[16..].try_into().unwrap()),
+        }
+    }
+}
+
+impl BoundedStorable for ChunkKey {
+    const MAX_SIZE: u32 = 20;
+    const IS_FIXED_SIZE: bool = true;
+}
+
+struct Chunk(Vec<u8>);
+
+impl Storable for Chunk {
+    fn to_bytes(&self) -> Cow<[u8]> {
+        Cow::Borrowed(&self.0)
+    }
+
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        Chunk(bytes.into_owned())
+    }
+}
+
+impl BoundedStorable for Chunk {
+    const MAX_SIZE: u32 = CHUNK_SIZE;
+    const IS_FIXED_SIZE: bool = false;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sha256::sha256;
+
+    #[test]
+    fn upload_is_checked_against_its_hash() {
+        let mut files = Files::default();
+        let content: Vec<u8> = (0..CHUNK_SIZE as usize + 100).map(|i| i as u8).collect();
+
+        files.start_upload(1, upload(content.len() as u64, sha256(&content)));
+        assert_eq!(files.used(7), content.len() as u64);
+        assert!(matches!(files.put_chunk(1, 1, vec![0; 99]), PutChunkResult::InvalidChunk));
+        assert!(matches!(files.put_chunk(1, 1, content[CHUNK_SIZE as usize..].to_vec()), PutChunkResult::Success));
+        assert!(matches!(files.finish_upload(1, 20), FinishUploadResult::ChunksMissing(missing) if missing == vec![0]));
+
+        assert!(matches!(files.put_chunk(1, 0, content[..CHUNK_SIZE as usize].to_vec()), PutChunkResult::Success));
+        match files.finish_upload(1, 20) {
+            FinishUploadResult::Success(file) => assert_eq!(file.mime_type, "application/octet-stream"),
+            _ => panic!(),
+        }
+        assert_eq!(files.read(1, CHUNK_SIZE as u64 - 2, CHUNK_SIZE as u64 + 2), content[CHUNK_SIZE as usize - 2..CHUNK_SIZE as usize + 2]);
+
+        // A corrupted upload is thrown away and its space given back.
+        files.start_upload(2, upload(3, sha256(b"abc")));
+        files.put_chunk(2, 0, b"abd".to_vec());
+        assert!(matches!(files.finish_upload(2, 20), FinishUploadResult::HashMismatch));
+        assert!(files.upload(2).is_none());
+        assert_eq!(files.used(7), content.len() as u64);
+
+        files.remove_post_files(3);
+        assert!(files.get(1).is_none());
+        assert!(files.chunk(1, 0).is_none());
+        assert_eq!(files.used(7), 0);
+    }
+
+    fn upload(size: u64, hash: [u8; 32]) -> Upload {
+        Upload {
+            owner: 7,
+            post_id: 3,
+            name: "data.bin".to_string(),
+            size,
+            hash,
+            received: BTreeSet::new(),
+            date_started: 10,
+        }
+    }
+}
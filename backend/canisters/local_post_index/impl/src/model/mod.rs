@@ -1,3 +1,6 @@
 pub mod comment;
+pub mod comment_map;
+pub mod files;
 pub mod post;
-pub mod post_map;
\ No newline at end of file
+pub mod post_map;
+pub mod revisions;
\ No newline at end of file
@@ -2,10 +2,11 @@ use candid::CandidType;
 use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
 use types::{
-    TimestampMillis, PostId, Category, NobleId, PostPrivacy, FileId, PostDetail, CommentId, CommentDetail
+    TimestampMillis, PostId, Category, NobleId, PostPrivacy, FileId, PostDetail, CommentId, PostRevision, SocialGraph,
 };
 
 use super::comment::Comment;
+use super::revisions::push_revision;
 
 #[derive(CandidType, Serialize, Deserialize, Clone, Debug, Default)]
 pub struct Post {
@@ -18,8 +19,13 @@ pub struct Post {
     pub video_url: String,
     pub attached_file_id: FileId,
 
+    // Comments are kept apart, see `CommentMap`. This is only set when reading posts saved before that.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub comments: Vec<Comment>,
-    
+    // Ids are given out in order and never reused, so an id always means the same comment.
+    #[serde(default)]
+    pub next_comment_id: CommentId,
+
     pub liked_users: HashSet<NobleId>,
     pub contributed_users: HashSet<NobleId>,
     pub post_privacy: PostPrivacy,
@@ -28,6 +34,15 @@ pub struct Post {
     pub date_created: TimestampMillis,
     pub date_updated: TimestampMillis,
     pub date_last_commented: TimestampMillis,
+    // Set while the post is waiting to be removed, see `PostMap::delete_post`.
+    #[serde(default)]
+    pub date_deleted: Option<TimestampMillis>,
+    // Deleted by a moderator, only they can restore it.
+    #[serde(default)]
+    pub hidden_by_moderator: bool,
+    // Every version of the title and description, oldest first, once the post has been edited.
+    #[serde(default)]
+    pub revisions: Vec<PostRevision>,
 }
 
 impl Post {
@@ -55,7 +70,8 @@ impl Post {
             video_url,
             attached_file_id,
 
-            comments: vec![Comment::new(noble_id, String::new(), now)],
+            comments: Vec::new(),
+            next_comment_id: 1,
 
             post_privacy,
             invited_users,
@@ -65,60 +81,27 @@ impl Post {
             date_created: now,
             date_updated: now,
             date_last_commented: now,
+            date_deleted: None,
+            hidden_by_moderator: false,
+            revisions: Vec::new(),
         }
     }
 
-    pub fn can_show(
-        &self,
-        noble_id: NobleId,
-        following_list: &Vec<NobleId>,
-        block_me_users: &Vec<NobleId>,
-    ) -> bool {
+    pub fn can_show(&self, noble_id: NobleId, social_graph: &SocialGraph) -> bool {
         if self.noble_id == noble_id {
             return true;
         }
 
-        if block_me_users.contains(&self.noble_id) {
+        if social_graph.has_blocked(self.noble_id, noble_id) {
             return false;
         }
     
         self.post_privacy == PostPrivacy::Everyone ||
-        (self.post_privacy == PostPrivacy::Followers && following_list.contains(&self.noble_id)) ||
+        (self.post_privacy == PostPrivacy::Followers && social_graph.is_following(noble_id, self.noble_id)) ||
         (self.post_privacy == PostPrivacy::SpecificUsers && self.invited_users.contains(&noble_id))
     }
 
-    pub fn get_sub_comments(&self, comment_id: CommentId, noble_id: NobleId, from: u32, limit: u32, block_me_users: &Vec<NobleId>) -> (Vec<CommentDetail>, bool) {
-        if let Some(comment) = self.comments.get(comment_id as usize) {
-            let mut comments = vec![];
-            let mut cnt = 0;
-            let mut id = comment.first_child;
-
-            loop {
-                if id.is_none() {
-                    break;
-                }
-                if let Some(comment) = self.comments.get(id.unwrap() as usize) {
-                    if !comment.can_show(&block_me_users) {
-                        id = comment.next_sibling;
-                        continue;
-                    }
-                    if cnt >= from + limit {
-                        return (comments, true);
-                    }
-                    if from <= cnt && cnt < from + limit {
-                        comments.push(comment.to_detail(id.unwrap(), noble_id));
-                    }
-                    cnt += 1;
-                    id = comment.next_sibling;
-                }
-            }
-            (comments, false)
-        } else {
-            (vec![], false)
-        }
-    }
-
-    pub fn to_detail(&self, like_state: bool, bookmark_state: bool) -> PostDetail {
+    pub fn to_detail(&self, comments_count: u32, like_state: bool, bookmark_state: bool) -> PostDetail {
         PostDetail {
             post_id: self.post_id,
             noble_id: self.noble_id,
@@ -129,7 +112,7 @@ impl Post {
             video_url: self.video_url.clone(),
             attached_file_id: self.attached_file_id,
             liked_users_count: self.liked_users.len() as u32,
-            comments_count: self.comments.get(0).unwrap().comments_count,
+            comments_count,
             date_created: self.date_created,
             date_updated: self.date_updated,
             date_last_commented: self.date_last_commented,
@@ -138,218 +121,75 @@ impl Post {
         }
     }
 
-    fn get_comment_id_for_new(&mut self) -> Option<CommentId> {
-        match self.comments.iter().position(|item| item.is_alive == false) {
-            Some(id) => Some(id as CommentId),
-            None => {
-                self.comments.push(Comment::default());
-                Some((self.comments.len() - 1) as CommentId)
-            }
-        }
+    pub fn edit(&mut self, editor: NobleId, title: String, description: String, now: TimestampMillis) {
+        if title != self.title || description != self.description {
+            let original = PostRevision {
+                title: self.title.clone(),
+                description: self.description.clone(),
+                editor: self.noble_id,
+                date_updated: self.date_created,
+            };
+            let revision = PostRevision { title: title.clone(), description: description.clone(), editor, date_updated: now };
+            push_revision(&mut self.revisions, original, revision);
+            self.title = title;
+            self.description = description;
+        }
+        self.date_updated = now;
     }
 
-    fn update_parent(&mut self, comment_id: Option<CommentId>, value: u32) {
-        if comment_id.is_none() {
-            return;
-        }
-        if let Some(comment) = self.comments.get_mut(comment_id.unwrap() as usize) {
-            comment.comments_count += value;
-            let parent = comment.parent;
-            self.update_parent(parent, value);
-        }
-    }
-
-    pub fn add_comment(
-        &mut self,
-        noble_id: NobleId,
-        comment_id: CommentId,
-        description: String,
-        now: TimestampMillis,
-    ) -> Option<CommentId> {
-        let new_comment_id = self.get_comment_id_for_new();
-
-        if let Some(comment) = self.comments.get_mut(comment_id as usize) {
-            let last_child_id = comment.last_child;
-            comment.last_child = new_comment_id;
-            comment.children_count += 1;
-            
-            match last_child_id {
-                Some(id) => {
-                    if let Some(last_child) = self.comments.get_mut(id as usize) {
-                        last_child.next_sibling = new_comment_id;
-                    }
-                },
-                None => comment.first_child = new_comment_id,
-            }
-            if let Some(new_comment) = self.comments.get_mut(new_comment_id.unwrap() as usize) {
-                new_comment.noble_id = noble_id;
-                new_comment.description = description;
-                new_comment.parent = Some(comment_id);
-                new_comment.prev_sibling = last_child_id;
-                new_comment.date_created = now;
-                new_comment.is_alive = true;
-            }
-            self.date_last_commented = now;
-            self.update_parent(Some(comment_id), 1);
-            return new_comment_id;
+    // Oldest first, just the current version if the post was never edited.
+    pub fn get_revisions(&self) -> Vec<PostRevision> {
+        if self.revisions.is_empty() {
+            vec![PostRevision {
+                title: self.title.clone(),
+                description: self.description.clone(),
+                editor: self.noble_id,
+                date_updated: self.date_created,
+            }]
+        } else {
+            self.revisions.clone()
         }
-        None
     }
+}
 
-    pub fn remove_comment(&mut self, comment_id: CommentId) -> bool {
-        if let Some(comment) = self.comments.get_mut(comment_id as usize) {
-            if comment.is_alive == false {
-                return false;
-            }
-            let parent_id = comment.parent;
-            let prev_sibling_id = comment.prev_sibling;
-            let next_sibling_id = comment.next_sibling;
-            let first_child_id = comment.first_child;
-            comment.clear();
-
-            match prev_sibling_id {
-                Some(id) => {
-                    if let Some(prev_sibling) = self.comments.get_mut(id as usize) {
-                        prev_sibling.next_sibling = next_sibling_id;
-                    }
-                },
-                None => {
-                    if let Some(parent) = self.comments.get_mut(parent_id.unwrap() as usize) {
-                        parent.first_child = next_sibling_id;
-                    }
-                }
-            }
-
-            match next_sibling_id {
-                Some(id) => {
-                    if let Some(next_sibling) = self.comments.get_mut(id as usize) {
-                        next_sibling.prev_sibling = prev_sibling_id;
-                    }
-                },
-                None => {
-                    if let Some(parent) = self.comments.get_mut(parent_id.unwrap() as usize) {
-                        parent.last_child = prev_sibling_id;
-                    }
-                }
-            }
-
-            let value = self.remove_all_sub_comments(first_child_id) + 1;
+#[cfg(test)]
+mod tests {
+    use super::*;
 
-            self.update_parent(parent_id, 0-value);
-            if let Some(parent) = self.comments.get_mut(parent_id.unwrap() as usize) {
-                parent.children_count -= 1;
-            }
-            return true;
-        }
-        return false;
-    }
+    #[test]
+    fn edits_are_kept_as_revisions() {
+        let mut post = new_post();
+        assert_eq!(post.get_revisions().len(), 1);
 
-    fn remove_all_sub_comments(&mut self, comment_id: Option<CommentId>) -> u32 {
-        if comment_id.is_none() {
-            return 0;
-        }
-        let mut result = 1;
-        if let Some(comment) = self.comments.get_mut(comment_id.unwrap() as usize) {
-            if comment.is_alive == false {
-                assert!(false);
-            }
-            let first_child = comment.first_child;
-            let next_sibling = comment.next_sibling;
-            comment.clear();
-            result += self.remove_all_sub_comments(first_child);
-            result += self.remove_all_sub_comments(next_sibling);
-        }
-        return result;
-    }
+        post.edit(1, "title".to_string(), "first".to_string(), 5);
+        post.edit(1, "title".to_string(), "first".to_string(), 6);
+        post.edit(9, "title".to_string(), "second".to_string(), 7);
 
-    pub fn edit_comment(&mut self, noble_id: NobleId, comment_id: CommentId, description: String) -> bool {
-        if let Some(comment) = self.comments.get_mut(comment_id as usize) {
-            if comment.noble_id == noble_id {
-                comment.description = description;
-                return true;
-            }
-        }
-        false
+        let revisions = post.get_revisions();
+        assert_eq!(revisions.len(), 3);
+        assert_eq!((revisions[0].editor, revisions[0].date_updated), (1, 0));
+        assert_eq!((revisions[2].editor, revisions[2].description.as_str()), (9, "second"));
+        assert_eq!(post.date_updated, 7);
     }
 
-    #[cfg(test)]
-    pub fn validate_state(&self) -> bool {
-
-        #[derive(Debug, Default)]
-        struct State {
-            pub comments_count: u32,
-            pub children_count: u32,
-            pub is_visited: bool,
-        }
-        let mut states = Vec::with_capacity(self.comments.len());
-        for _ in 0..self.comments.len() {
-            states.push(State::default());
-        }
-
-        fn dfs(post: &Post, comment_id: Option<CommentId>, states: &mut Vec<State>) {
-            if comment_id.is_none() {
-                return;
-            }
-            if let Some(comment) = post.comments.get(comment_id.unwrap() as usize) {
-                dfs(post, comment.first_child, states);
-                dfs(post, comment.next_sibling, states);
-                let state = states.get_mut(comment_id.unwrap() as usize).unwrap();
-                state.is_visited = true;
-                let comments_count = state.comments_count;
-
-                if comment.parent.is_some() {
-                    if let Some(parent_state) = states.get_mut(comment.parent.unwrap() as usize) {
-                        parent_state.children_count += 1;
-                        parent_state.comments_count += 1 + comments_count;
-                    }
-                }
-            }
-        }
+    #[test]
+    fn visibility_follows_the_social_graph() {
+        let mut post = new_post();
+        post.post_privacy = PostPrivacy::Followers;
+        let mut social_graph = SocialGraph::default();
 
-        dfs(self, Some(0), &mut states);
+        assert!(post.can_show(1, &social_graph));
+        assert!(!post.can_show(2, &social_graph));
 
-        for i in 0..self.comments.len() {
-            let comment = self.comments.get(i).unwrap();
-            let state = states.get(i).unwrap();
+        social_graph.follow(2, 1);
+        assert!(post.can_show(2, &social_graph));
 
-            if comment.comments_count != state.comments_count ||
-               comment.children_count != state.children_count ||
-               comment.is_alive != state.is_visited {
-                return false;
-            }
-        }
-        true
+        post.post_privacy = PostPrivacy::Everyone;
+        social_graph.block(1, 3);
+        assert!(!post.can_show(3, &social_graph));
     }
-}
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    // #[test]
-    #[allow(dead_code)]
-    fn total_test() {
-        let mut post = Post::default();
-        post.comments.push(Comment::new(1, "".to_string(), 0));
-        
-        post.add_comment(1, 0, "".to_string(), 0);
-        post.add_comment(1, 1, "".to_string(), 0);
-        post.add_comment(1, 0, "".to_string(), 0);
-        post.add_comment(1, 0, "".to_string(), 0);
-        post.add_comment(1, 0, "".to_string(), 0);
-        post.add_comment(1, 4, "".to_string(), 0);
-        post.add_comment(1, 4, "".to_string(), 0);
-        post.add_comment(1, 4, "".to_string(), 0);
-        post.add_comment(1, 7, "".to_string(), 0);
-        post.add_comment(1, 7, "".to_string(), 0);
-        assert_eq!(post.validate_state(), true);
-        post.remove_comment(2);
-        assert_eq!(post.validate_state(), true);
-        post.remove_comment(1);
-        assert_eq!(post.validate_state(), true);
-        post.remove_comment(4);
-        assert_eq!(post.validate_state(), true);
-        post.remove_comment(5);
-        assert_eq!(post.validate_state(), true);
+    fn new_post() -> Post {
+        Post::new(1, 1, "".to_string(), "".to_string(), Category::GeneralDiscussion, "".to_string(), "".to_string(), 0, PostPrivacy::Everyone, HashSet::new(), 0)
     }
-}
\ No newline at end of file
+}
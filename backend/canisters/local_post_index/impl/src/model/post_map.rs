@@ -1,20 +1,111 @@
+use crate::memory::{get_posts_memories, Memory};
+use crate::model::comment::Comment;
+use crate::model::comment_map::CommentMap;
 use crate::model::post::Post;
 use serde::{Serialize, Deserialize};
+use stable_map::StableMap;
 use std::collections::{HashMap, HashSet};
-use types::{TimestampMillis, PostId, Category, NobleId, PostPrivacy, FileId};
+use types::{TimestampMillis, PostId, Category, NobleId, PostPrivacy, FileId, CommentId};
 
-#[derive(Serialize, Deserialize, Default)]
+// Deleted posts stay restorable, and hidden from everything else, for this long.
+pub const POST_DELETION_GRACE_PERIOD: TimestampMillis = 30 * 24 * 60 * 60 * 1000; // 30 days.
+
+// Posts and their comments are kept in stable memory, so upgrading doesn't copy them. Reads
+// return a copy of the post, changes are saved with `update`.
+#[derive(Serialize, Deserialize)]
+#[serde(from = "PostMapTrimmed")]
 pub struct PostMap {
+    #[serde(skip)]
+    posts: StableMap<PostId, Post, Memory>,
+    #[serde(skip)]
+    comments: CommentMap,
+    // Post -> when it was deleted, so expired posts are found without reading every post.
+    deleted: HashMap<PostId, TimestampMillis>,
+    // The deleted posts a moderator hid. They don't expire, they're kept until a moderator restores
+    // or removes them.
+    hidden: HashSet<PostId>,
+}
+
+#[derive(Deserialize)]
+struct PostMapTrimmed {
+    // Only set when upgrading from the version that kept posts on the heap.
+    #[serde(default)]
     posts: HashMap<PostId, Post>,
+    #[serde(default)]
+    deleted: HashMap<PostId, TimestampMillis>,
+    // Not set when reading a PostMap saved before hidden posts were kept apart.
+    #[serde(default)]
+    hidden: Option<HashSet<PostId>>,
+}
+
+impl From<PostMapTrimmed> for PostMap {
+    fn from(value: PostMapTrimmed) -> Self {
+        let mut post_map = PostMap {
+            deleted: value.deleted,
+            ..Default::default()
+        };
+        post_map.hidden = value.hidden.unwrap_or_else(|| {
+            post_map.deleted
+                .keys()
+                .copied()
+                .filter(|post_id| post_map.posts.get(*post_id).map_or(false, |post| post.hidden_by_moderator))
+                .collect()
+        });
+
+        for (post_id, mut post) in value.posts {
+            let comments = std::mem::take(&mut post.comments);
+            post.next_comment_id = comments.len() as CommentId;
+            post_map.comments.migrate(post_id, comments);
+            if let Some(date_deleted) = post.date_deleted {
+                post_map.deleted.insert(post_id, date_deleted);
+                if post.hidden_by_moderator {
+                    post_map.hidden.insert(post_id);
+                }
+            }
+            post_map.posts.insert(post_id, &post);
+        }
+
+        post_map
+    }
+}
+
+impl Default for PostMap {
+    fn default() -> Self {
+        let (posts_index_memory, posts_memory) = get_posts_memories();
+        PostMap {
+            posts: StableMap::init(posts_index_memory, posts_memory),
+            comments: CommentMap::default(),
+            deleted: HashMap::default(),
+            hidden: HashSet::default(),
+        }
+    }
 }
 
 impl PostMap {
-    pub fn get(&self, post_id: PostId) -> Option<&Post> {
-        self.posts.get(&post_id)
+    pub fn get(&self, post_id: PostId) -> Option<Post> {
+        if self.deleted.contains_key(&post_id) {
+            None
+        } else {
+            self.posts.get(post_id)
+        }
+    }
+
+    pub fn get_deleted(&self, post_id: PostId) -> Option<Post> {
+        if self.deleted.contains_key(&post_id) {
+            self.posts.get(post_id)
+        } else {
+            None
+        }
     }
 
-    pub fn get_mut(&mut self, post_id: PostId) -> Option<&mut Post> {
-        self.posts.get_mut(&post_id)
+    // Saves the changes made to a post read with `get`.
+    pub fn update(&mut self, post: &Post) -> bool {
+        if self.posts.contains_key(post.post_id) && !self.deleted.contains_key(&post.post_id) {
+            self.posts.insert(post.post_id, post);
+            true
+        } else {
+            false
+        }
     }
 
     pub fn add_post(
@@ -32,22 +123,261 @@ impl PostMap {
         now: TimestampMillis,
     ) {
         let post = Post::new(post_id, noble_id, title, description, category, link_url, video_url, attached_file_id, post_privacy, invited_users, now);
-        self.posts.insert(post_id, post);
+        self.posts.insert(post_id, &post);
+        self.comments.add_root(post_id, noble_id, now);
+    }
+
+    pub fn comments(&self) -> &CommentMap {
+        &self.comments
     }
 
-    pub fn remove_post(
+    // Replies to a comment of a post which isn't deleted.
+    pub fn add_comment(
         &mut self,
         post_id: PostId,
-    ) {
-        self.posts.remove(&post_id);
+        noble_id: NobleId,
+        parent_id: CommentId,
+        description: String,
+        now: TimestampMillis,
+    ) -> Option<CommentId> {
+        let mut post = self.get(post_id)?;
+        let comment_id = self.comments.add(post_id, &mut post.next_comment_id, noble_id, parent_id, description, now)?;
+        post.date_last_commented = now;
+        self.posts.insert(post_id, &post);
+        Some(comment_id)
+    }
+
+    // Saves the changes made to a comment read with `comments().get`.
+    pub fn update_comment(&mut self, post_id: PostId, comment_id: CommentId, comment: &Comment) -> bool {
+        self.comments.update(post_id, comment_id, comment)
+    }
+
+    // Deleted posts included, moderators can remove comments from them.
+    pub fn remove_comment(&mut self, post_id: PostId, comment_id: CommentId) -> bool {
+        self.comments.remove(post_id, comment_id)
+    }
+
+    pub fn delete_post(&mut self, post_id: PostId, now: TimestampMillis) -> bool {
+        match self.get(post_id) {
+            Some(mut post) => {
+                post.date_deleted = Some(now);
+                self.posts.insert(post_id, &post);
+                self.deleted.insert(post_id, now);
+                true
+            }
+            None => false,
+        }
+    }
+
+    // Deletes the post, even if its author already had, so that only moderators can restore it. It
+    // stays until they do or remove it.
+    pub fn hide_post(&mut self, post_id: PostId, now: TimestampMillis) -> bool {
+        match self.posts.get(post_id) {
+            Some(mut post) => {
+                let date_deleted = *self.deleted.entry(post_id).or_insert(now);
+                post.date_deleted = Some(date_deleted);
+                post.hidden_by_moderator = true;
+                self.posts.insert(post_id, &post);
+                self.hidden.insert(post_id);
+                true
+            }
+            None => false,
+        }
+    }
+
+    pub fn restore_post(&mut self, post_id: PostId, now: TimestampMillis) -> bool {
+        match self.deleted.get(&post_id) {
+            Some(date_deleted) if self.hidden.contains(&post_id) || now < date_deleted + POST_DELETION_GRACE_PERIOD => {
+                self.deleted.remove(&post_id);
+                self.hidden.remove(&post_id);
+                if let Some(mut post) = self.posts.get(post_id) {
+                    post.date_deleted = None;
+                    post.hidden_by_moderator = false;
+                    self.posts.insert(post_id, &post);
+                }
+                true
+            }
+            _ => false,
+        }
+    }
+
+    // Removes the post for good, deleted or not, along with its comments.
+    pub fn remove(&mut self, post_id: PostId) -> Option<Post> {
+        self.deleted.remove(&post_id);
+        self.hidden.remove(&post_id);
+        let post = self.posts.remove(post_id)?;
+        self.comments.remove_post(post_id, post.next_comment_id);
+        Some(post)
+    }
+
+    // Removes the posts whose grace period is over, apart from hidden ones.
+    pub fn remove_expired(&mut self, now: TimestampMillis) -> Vec<Post> {
+        let expired: Vec<PostId> = self.deleted
+            .iter()
+            .filter(|(post_id, _)| !self.hidden.contains(post_id))
+            .filter(|(_, date_deleted)| now >= *date_deleted + POST_DELETION_GRACE_PERIOD)
+            .map(|(post_id, _)| *post_id)
+            .collect();
+
+        expired.iter().filter_map(|post_id| self.remove(*post_id)).collect()
     }
     
     pub fn len(&self) -> usize {
         self.posts.len()
     }
 
+    // Loads every post, only for when all of them are needed.
     #[allow(dead_code)]
-    pub fn iter(&self) -> impl Iterator<Item = &Post> {
-        self.posts.values()
+    pub fn iter(&self) -> impl Iterator<Item = Post> + '_ {
+        self.posts.iter().map(|(_, post)| post).filter(|post| post.date_deleted.is_none())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn deleted_post_is_hidden_until_restored_or_removed() {
+        let mut posts = PostMap::default();
+        for post_id in [1, 2] {
+            posts.add_post(post_id, 1, String::new(), String::new(), Category::GeneralDiscussion, String::new(), String::new(), 0, PostPrivacy::Everyone, HashSet::new(), 0);
+        }
+
+        assert_eq!(posts.add_comment(2, 2, 0, String::new(), 50), Some(1));
+        assert!(posts.delete_post(1, 100));
+        assert!(posts.delete_post(2, 100));
+        assert!(!posts.delete_post(1, 200));
+        assert!(posts.get(1).is_none());
+        assert!(posts.get_deleted(1).is_some());
+        assert!(posts.add_comment(1, 2, 0, String::new(), 150).is_none());
+
+        assert!(posts.restore_post(1, 100 + POST_DELETION_GRACE_PERIOD - 1));
+        assert!(posts.get(1).is_some());
+
+        assert!(posts.remove_expired(100 + POST_DELETION_GRACE_PERIOD - 1).is_empty());
+        let removed = posts.remove_expired(100 + POST_DELETION_GRACE_PERIOD);
+        assert_eq!(removed.iter().map(|post| post.post_id).collect::<Vec<_>>(), vec![2]);
+        assert!(!posts.restore_post(2, 100 + POST_DELETION_GRACE_PERIOD));
+        assert!(posts.comments().get(2, 1).is_none());
+        assert_eq!(posts.len(), 1);
+    }
+
+    #[test]
+    fn hidden_post_keeps_when_it_was_deleted() {
+        let mut posts = PostMap::default();
+        for post_id in [1, 2] {
+            posts.add_post(post_id, 1, String::new(), String::new(), Category::GeneralDiscussion, String::new(), String::new(), 0, PostPrivacy::Everyone, HashSet::new(), 0);
+        }
+
+        assert!(posts.delete_post(1, 100));
+        assert!(posts.hide_post(1, 200));
+        assert!(posts.hide_post(2, 200));
+        assert!(!posts.hide_post(3, 200));
+
+        let post = posts.get_deleted(1).unwrap();
+        assert!(post.hidden_by_moderator);
+        assert_eq!(post.date_deleted, Some(100));
+        assert!(posts.get(2).is_none());
+
+        assert!(posts.restore_post(2, 300));
+        assert!(!posts.get(2).unwrap().hidden_by_moderator);
+
+        // Hidden posts are kept past the grace period, until a moderator restores or removes them.
+        assert!(posts.remove_expired(100 + POST_DELETION_GRACE_PERIOD).is_empty());
+        assert!(posts.get_deleted(1).is_some());
+        assert!(posts.hide_post(2, 400));
+        assert!(posts.restore_post(2, 400 + 2 * POST_DELETION_GRACE_PERIOD));
+
+        assert!(posts.remove(1).is_some());
+        assert!(posts.remove_expired(100 + POST_DELETION_GRACE_PERIOD).is_empty());
+    }
+
+    #[test]
+    fn posts_hidden_before_upgrade_are_kept_apart() {
+        let mut posts = PostMap::default();
+        for post_id in [1, 2] {
+            posts.add_post(post_id, 1, String::new(), String::new(), Category::GeneralDiscussion, String::new(), String::new(), 0, PostPrivacy::Everyone, HashSet::new(), 0);
+        }
+        assert!(posts.delete_post(1, 100));
+        assert!(posts.hide_post(2, 100));
+
+        #[derive(Serialize)]
+        struct PreviousPostMap {
+            deleted: HashMap<PostId, TimestampMillis>,
+        }
+        let bytes = upgrade(&PreviousPostMap { deleted: posts.deleted.clone() });
+
+        let mut posts: PostMap = serializer::deserialize(bytes.as_slice()).unwrap();
+        assert_eq!(posts.hidden, HashSet::from([2]));
+        let removed = posts.remove_expired(100 + POST_DELETION_GRACE_PERIOD);
+        assert_eq!(removed.iter().map(|post| post.post_id).collect::<Vec<_>>(), vec![1]);
+    }
+
+    #[test]
+    fn upgrade_moves_posts_out_of_the_heap() {
+        #[derive(Serialize)]
+        struct LegacyPostMap {
+            posts: HashMap<PostId, Post>,
+        }
+
+        let posts: HashMap<PostId, Post> = (1..=2_000)
+            .map(|post_id| {
+                let mut post = Post::new(post_id, 1, "title".to_string(), "d".repeat(1_000), Category::GeneralDiscussion, String::new(), String::new(), 0, PostPrivacy::Everyone, HashSet::new(), 0);
+                post.comments = legacy_comments(20);
+                if post_id % 100 == 0 {
+                    post.date_deleted = Some(100);
+                }
+                (post_id, post)
+            })
+            .collect();
+        let legacy_bytes = upgrade(&LegacyPostMap { posts });
+
+        let posts: PostMap = serializer::deserialize(legacy_bytes.as_slice()).unwrap();
+        check(&posts);
+
+        // From now on only the deleted posts are copied.
+        let bytes = upgrade(&posts);
+        assert!(bytes.len() * 1_000 < legacy_bytes.len());
+
+        let mut posts: PostMap = serializer::deserialize(bytes.as_slice()).unwrap();
+        check(&posts);
+        assert_eq!(posts.remove_expired(100 + POST_DELETION_GRACE_PERIOD).len(), 20);
+        assert!(posts.comments().get(100, 1).is_none());
+    }
+
+    // Replies to the post, kept in the post and indexed by their ids.
+    fn legacy_comments(count: CommentId) -> Vec<Comment> {
+        let mut comments = vec![Comment::new(1, String::new(), 0)];
+        for comment_id in 1..=count {
+            let mut comment = Comment::new(comment_id as NobleId, "c".repeat(200), 10);
+            comment.parent = Some(0);
+            comment.prev_sibling = (comment_id > 1).then_some(comment_id - 1);
+            comment.next_sibling = (comment_id < count).then_some(comment_id + 1);
+            comments.push(comment);
+        }
+        comments[0].first_child = Some(1);
+        comments[0].last_child = Some(count);
+        comments
+    }
+
+    fn upgrade<T: Serialize>(value: &T) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        serializer::serialize(value, &mut bytes).unwrap();
+        bytes
+    }
+
+    fn check(posts: &PostMap) {
+        assert_eq!(posts.len(), 2_000);
+        assert_eq!(posts.deleted.len(), 20);
+        assert!(posts.get(100).is_none());
+        assert!(posts.get_deleted(100).is_some());
+        let post = posts.get(1_999).unwrap();
+        assert_eq!(post.description.len(), 1_000);
+        assert!(post.comments.is_empty());
+        assert_eq!(post.next_comment_id, 21);
+        assert_eq!(posts.comments().comments_count(1_999), 20);
+        assert_eq!(posts.comments().get(1_999, 20).unwrap().description.len(), 200);
+        assert!(posts.comments().validate_state(1_999, 21));
     }
 }
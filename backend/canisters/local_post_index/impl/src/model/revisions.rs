@@ -0,0 +1,13 @@
+// Past this the oldest edits are dropped, the original version is always kept.
+pub const MAX_REVISIONS: usize = 20;
+
+// Nothing is stored until the first edit, which records the original version along with the new one.
+pub fn push_revision<T>(revisions: &mut Vec<T>, original: T, revision: T) {
+    if revisions.is_empty() {
+        revisions.push(original);
+    }
+    revisions.push(revision);
+    if revisions.len() > MAX_REVISIONS {
+        revisions.remove(1);
+    }
+}
@@ -0,0 +1,19 @@
+use crate::{read_state, RuntimeState};
+use canister_api_macros::query_msgpack;
+use local_post_index_canister::c2c_is_nobleblocks_comment::{Response::*, *};
+
+#[query_msgpack]
+fn c2c_is_nobleblocks_comment(args: Args) -> Response {
+    read_state(|state| c2c_is_nobleblocks_comment_impl(args, state))
+}
+
+// Comment 0 is the post itself.
+fn c2c_is_nobleblocks_comment_impl(args: Args, state: &RuntimeState) -> Response {
+    if args.comment_id != 0
+        && state.data.posts.get(args.post_id).is_some()
+        && state.data.posts.comments().get(args.post_id, args.comment_id).is_some()
+    {
+        return Yes;
+    }
+    No
+}
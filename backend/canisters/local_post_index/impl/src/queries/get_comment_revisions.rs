@@ -0,0 +1,32 @@
+use crate::{read_state, RuntimeState};
+use ic_cdk_macros::query;
+use local_post_index_canister::get_comment_revisions::{Response::*, *};
+use types::check_jwt;
+
+// Oldest first, the last revision is the current version.
+#[query]
+fn get_comment_revisions(args: Args) -> Response {
+    read_state(|state| get_comment_revisions_impl(&args, state))
+}
+
+fn get_comment_revisions_impl(args: &Args, state: &RuntimeState) -> Response {
+    let now = state.env.now();
+
+    if let Some(jwt) = check_jwt(&args.jwt, now) {
+        let post = state.data.posts.get(args.post_id)
+            .filter(|post| !state.data.suspended_users.hides_content_from(post.noble_id, jwt.noble_id, now));
+        if let Some(post) = post {
+            if !post.can_show(jwt.noble_id, &state.data.social_graph) {
+                return PermissionDenied;
+            }
+            match state.data.posts.comments().get(args.post_id, args.comment_id) {
+                Some(comment) if args.comment_id != 0 && comment.can_show(jwt.noble_id, &state.data.social_graph) => Success(comment.get_revisions()),
+                _ => CommentNotFound,
+            }
+        } else {
+            PostNotFound
+        }
+    } else {
+        PermissionDenied
+    }
+}
@@ -15,9 +15,11 @@ fn get_comments_impl(
     let now = state.env.now();
 
     if let Some(jwt) = check_jwt(&args.jwt, now) {
-        if let Some(post) = state.data.posts.get(args.post_id) {
-            if post.can_show(jwt.noble_id, &args.following_list, &args.block_me_users) {
-                let (comments, more_exist) = post.get_sub_comments(args.comment_id, jwt.noble_id, args.from - 1, args.limit, &args.block_me_users);
+        let post = state.data.posts.get(args.post_id)
+            .filter(|post| !state.data.suspended_users.hides_content_from(post.noble_id, jwt.noble_id, now));
+        if let Some(post) = post {
+            if post.can_show(jwt.noble_id, &state.data.social_graph) {
+                let (comments, more_exist) = state.data.posts.comments().get_sub_comments(post.post_id, args.comment_id, jwt.noble_id, args.from - 1, args.limit, &state.data.social_graph, &state.data.suspended_users, now);
                 Success(ScucessResult { comments, more_exist })
             } else {
                 PermissionDenied
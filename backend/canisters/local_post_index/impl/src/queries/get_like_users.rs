@@ -1,7 +1,8 @@
 use crate::{read_state, RuntimeState};
 use ic_cdk_macros::query;
 use local_post_index_canister::get_like_users::{Response::*, *};
-use types::check_jwt;
+use std::collections::HashSet;
+use types::{check_jwt, NobleId};
 
 #[query]
 fn get_like_users(args: Args) -> Response {
@@ -11,15 +12,24 @@ fn get_like_users(args: Args) -> Response {
 fn get_like_users_impl(args: &Args, state: &RuntimeState) -> Response {
     let now = state.env.now();
 
-    if let Some(_jwt) = check_jwt(&args.jwt, now) {
-        if let Some(post) = state.data.posts.get(args.post_id) {
+    if let Some(jwt) = check_jwt(&args.jwt, now) {
+        let post = state.data.posts.get(args.post_id)
+            .filter(|post| !state.data.suspended_users.hides_content_from(post.noble_id, jwt.noble_id, now));
+        if let Some(post) = post {
+            let social_graph = &state.data.social_graph;
+            if !post.can_show(jwt.noble_id, social_graph) {
+                return PermissionDenied;
+            }
+            // Users who have blocked the caller are left out.
+            let visible = |liked_users: &HashSet<NobleId>| -> Vec<NobleId> {
+                liked_users.iter().copied().filter(|noble_id| !social_graph.has_blocked(*noble_id, jwt.noble_id)).collect()
+            };
             if args.comment_id == 0 {
-                Success(post.liked_users.clone().into_iter().collect())
+                Success(visible(&post.liked_users))
             } else {
-                if let Some(comment) = post.comments.get(args.comment_id as usize) {
-                    Success(comment.liked_users.clone().into_iter().collect())
-                } else {
-                    CommentNotFound
+                match state.data.posts.comments().get(args.post_id, args.comment_id) {
+                    Some(comment) if comment.can_show(jwt.noble_id, social_graph) => Success(visible(&comment.liked_users)),
+                    _ => CommentNotFound,
                 }
             }
         } else {
@@ -15,11 +15,15 @@ fn get_post_impl(
     let now = state.env.now();
 
     if let Some(jwt) = check_jwt(&args.jwt, now) {
-        if let Some(post) = state.data.posts.get(args.post_id) {
-            if post.can_show(jwt.noble_id, &args.following_list, &args.block_me_users) {
-                let (comments, more_exist) = post.get_sub_comments(0, jwt.noble_id, 0, args.limit, &args.block_me_users);
+        // The posts of suspended users whose content is hidden are treated as gone.
+        let post = state.data.posts.get(args.post_id)
+            .filter(|post| !state.data.suspended_users.hides_content_from(post.noble_id, jwt.noble_id, now));
+        if let Some(post) = post {
+            if post.can_show(jwt.noble_id, &state.data.social_graph) {
+                let post_comments = state.data.posts.comments();
+                let (comments, more_exist) = post_comments.get_sub_comments(post.post_id, 0, jwt.noble_id, 0, args.limit, &state.data.social_graph, &state.data.suspended_users, now);
                 Success(SuccessResult {
-                    post: post.to_detail(post.liked_users.contains(&jwt.noble_id), args.bookmarks.contains(&post.post_id)),
+                    post: post.to_detail(post_comments.comments_count(post.post_id), post.liked_users.contains(&jwt.noble_id), args.bookmarks.contains(&post.post_id)),
                     comments, more_exist
                 })
             } else {
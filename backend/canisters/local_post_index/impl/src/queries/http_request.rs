@@ -1,9 +1,16 @@
 use ic_cdk_macros::query;
-use types::{HttpRequest, HttpResponse, PostId, TimestampMillis};
-use http_request::{extract_route, Route, build_json_response, encode_logs};
+use serde_bytes::ByteBuf;
+use types::{CallbackFunc, FileId, HeaderField, HttpRequest, HttpResponse, PostId, StreamingStrategy, TimestampMillis, Token};
+use http_request::{extract_route, parse_range, ByteRange, Route, build_json_response, encode_logs};
 
+use crate::model::files::{chunk_count, File};
 use crate::{read_state, RuntimeState};
 
+// A range is cut short after this many bytes, the client asks again for the rest.
+const MAX_RANGE_LENGTH: u64 = 2 * 1_024 * 1_024; // 2 MB
+// Only images that can't carry scripts are shown in the browser, anything else is downloaded.
+const INLINE_MIME_TYPES: &[&str] = &["image/png", "image/jpeg", "image/gif"];
+
 #[query]
 fn http_request(request: HttpRequest) -> HttpResponse {
     fn get_metrics(state: &RuntimeState) -> HttpResponse {
@@ -28,11 +35,144 @@ fn http_request(request: HttpRequest) -> HttpResponse {
         }
     }
 
+    fn get_file(file_id: Option<FileId>, range: Option<&String>, state: &RuntimeState) -> HttpResponse {
+        let file_id = file_id.unwrap_or_default();
+
+        let file = match state.servable_file(file_id) {
+            Some(file) => file,
+            None => return HttpResponse::not_found(),
+        };
+
+        match range.and_then(|range| parse_range(range, file.size)) {
+            Some(ByteRange::Satisfiable { start, end }) => {
+                let end = end.min(start + MAX_RANGE_LENGTH - 1);
+                let mut headers = file_headers(file, end - start + 1);
+                headers.push(HeaderField("Content-Range".to_string(), format!("bytes {start}-{end}/{}", file.size)));
+                HttpResponse {
+                    status_code: 206,
+                    headers,
+                    body: ByteBuf::from(state.data.files.read(file_id, start, end + 1)),
+                    streaming_strategy: None,
+                }
+            }
+            Some(ByteRange::Unsatisfiable) => {
+                let mut response = HttpResponse::status_code(416);
+                response.headers.push(HeaderField("Content-Range".to_string(), format!("bytes */{}", file.size)));
+                response
+            }
+            // The first chunk now, the rest through `http_request_streaming_callback`.
+            None => HttpResponse {
+                status_code: 200,
+                headers: file_headers(file, file.size),
+                body: ByteBuf::from(state.data.files.chunk(file_id, 0).unwrap_or_default()),
+                streaming_strategy: (chunk_count(file.size) > 1).then(|| StreamingStrategy::Callback {
+                    callback: CallbackFunc::new(state.env.canister_id(), "http_request_streaming_callback".to_string()),
+                    token: Token { key: file_id.to_string(), index: 1 },
+                }),
+            },
+        }
+    }
+
     match extract_route(&request.url) {
         Route::Logs(since) => get_logs(since),
         Route::Traces(since) => get_traces(since),
         Route::Metrics => read_state(get_metrics),
         Route::Post(post_id) => read_state(|state| get_post(post_id, state)),
+        Route::File(file_id) => read_state(|state| get_file(file_id, request.header("Range"), state)),
         _ => HttpResponse::not_found(),
     }
-}
\ No newline at end of file
+}
+
+fn file_headers(file: &File, content_length: u64) -> Vec<HeaderField> {
+    // Keeps the name safe to put in a header.
+    let file_name: String = file
+        .name
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || ".-_ ".contains(c) { c } else { '_' })
+        .collect();
+
+    let disposition = if INLINE_MIME_TYPES.contains(&file.mime_type.as_str()) { "inline" } else { "attachment" };
+
+    vec![
+        HeaderField("Content-Type".to_string(), file.mime_type.clone()),
+        HeaderField("Content-Length".to_string(), content_length.to_string()),
+        HeaderField("Content-Disposition".to_string(), format!("{disposition}; filename=\"{file_name}\"")),
+        HeaderField("X-Content-Type-Options".to_string(), "nosniff".to_string()),
+        HeaderField("Accept-Ranges".to_string(), "bytes".to_string()),
+        // Kept short so a file stops being served soon after its post is deleted or made private.
+        HeaderField("Cache-Control".to_string(), "public, max-age=300".to_string()),
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::files::Upload;
+    use crate::Data;
+    use sha256::sha256;
+    use std::collections::{BTreeSet, HashSet};
+    use types::{Category, PostPrivacy, Suspension};
+    use utils::env::test::TestEnv;
+
+    #[test]
+    fn only_files_of_public_posts_are_served() {
+        let mut state = setup_runtime_state();
+        assert!(state.servable_file(1).is_some());
+        assert!(state.servable_file(2).is_none());
+
+        let mut post = state.data.posts.get(1).unwrap();
+        post.post_privacy = PostPrivacy::SpecificUsers;
+        state.data.posts.update(&post);
+        assert!(state.servable_file(1).is_none());
+    }
+
+    #[test]
+    fn files_of_hidden_authors_are_not_served() {
+        let mut state = setup_runtime_state();
+        let now = state.env.now();
+        state.data.suspended_users.suspend(1, Suspension { until: Some(now + 1_000), hide_content: false });
+        assert!(state.servable_file(1).is_some());
+
+        state.data.suspended_users.suspend(1, Suspension { until: Some(now + 1_000), hide_content: true });
+        assert!(state.servable_file(1).is_none());
+    }
+
+    #[test]
+    fn only_raster_images_are_shown_inline() {
+        let state = setup_runtime_state();
+        let disposition = |file: &File| {
+            file_headers(file, file.size).into_iter().find(|header| header.0 == "Content-Disposition").unwrap().1
+        };
+
+        let png = state.data.files.get(1).unwrap();
+        assert_eq!(disposition(png), "inline; filename=\"figure.png\"");
+        let svg = state.data.files.get(2).unwrap();
+        assert_eq!(disposition(svg), "attachment; filename=\"figure.svg\"");
+        assert!(file_headers(svg, svg.size).iter().any(|header| header.0 == "X-Content-Type-Options" && header.1 == "nosniff"));
+    }
+
+    fn setup_runtime_state() -> RuntimeState {
+        let env = TestEnv::default();
+        let mut data = Data::default();
+        let files: [(&str, &[u8], PostPrivacy); 2] = [
+            ("figure.png", b"\x89PNG\r\n\x1a\n", PostPrivacy::Everyone),
+            ("figure.svg", b"<svg xmlns=\"http://www.w3.org/2000/svg\"/>", PostPrivacy::Followers),
+        ];
+        for (post_id, (name, content, post_privacy)) in (1..).zip(files) {
+            let file_id = post_id as FileId;
+            data.posts.add_post(post_id, 1, "title".to_string(), "description".to_string(), Category::GeneralDiscussion, String::new(), String::new(), file_id, post_privacy, HashSet::new(), env.now);
+            data.files.start_upload(file_id, Upload {
+                owner: 1,
+                post_id,
+                name: name.to_string(),
+                size: content.len() as u64,
+                hash: sha256(content),
+                received: BTreeSet::new(),
+                date_started: env.now,
+            });
+            data.files.put_chunk(file_id, 0, content.to_vec());
+            data.files.finish_upload(file_id, env.now);
+        }
+        RuntimeState::new(Box::new(env), data)
+    }
+}
@@ -0,0 +1,29 @@
+use ic_cdk_macros::query;
+use serde_bytes::ByteBuf;
+use types::{FileId, StreamingCallbackHttpResponse, Token};
+
+use crate::model::files::chunk_count;
+use crate::{read_state, RuntimeState};
+
+#[query]
+fn http_request_streaming_callback(token: Token) -> StreamingCallbackHttpResponse {
+    read_state(|state| http_request_streaming_callback_impl(token, state))
+}
+
+fn http_request_streaming_callback_impl(token: Token, state: &RuntimeState) -> StreamingCallbackHttpResponse {
+    let file_id: FileId = token.key.parse().unwrap_or_default();
+
+    // Anyone can call this with any token, so the file is checked like in `http_request`.
+    let file = match state.servable_file(file_id) {
+        Some(file) => file,
+        None => return StreamingCallbackHttpResponse { body: ByteBuf::default(), token: None },
+    };
+
+    match state.data.files.chunk(file_id, token.index) {
+        Some(chunk) => StreamingCallbackHttpResponse {
+            body: ByteBuf::from(chunk),
+            token: (token.index + 1 < chunk_count(file.size)).then(|| Token { key: token.key, index: token.index + 1 }),
+        },
+        None => StreamingCallbackHttpResponse { body: ByteBuf::default(), token: None },
+    }
+}
@@ -1,4 +1,8 @@
+pub mod c2c_is_nobleblocks_comment;
+pub mod get_comment_revisions;
 pub mod get_comments;
 pub mod get_like_users;
 pub mod get_post;
-pub mod http_request;
\ No newline at end of file
+pub mod get_post_revisions;
+pub mod http_request;
+pub mod http_request_streaming_callback;
\ No newline at end of file
@@ -2,7 +2,12 @@ use crate::guards::caller_is_post_index_canister;
 use crate::{mutate_state, RuntimeState};
 use canister_api_macros::update_msgpack;
 use local_post_index_canister::c2c_notify_events::{Response::*, *};
-use local_post_index_canister::Event;
+use local_post_index_canister::{ContentModerated, Event};
+use post_index_canister::{CommentDeleted, Event as PostIndexEvent};
+use tracing::info;
+use types::{JwtVerificationKey, ModerationAction, ReportTarget, SessionRevocation};
+use user_index_canister::{Event as UserIndexEvent, UserWarned};
+use utils::rbac::Roles;
 
 #[update_msgpack(guard = "caller_is_post_index_canister")]
 fn c2c_notify_events(args: Args) -> Response {
@@ -21,5 +26,79 @@ fn handle_event(event: Event, state: &mut RuntimeState) {
         Event::LocalUserIndexCanisterAdded(ev) => {
             state.data.local_user_index_canister_ids.insert(ev.canister_id);
         },
+        Event::JwtKeysUpdated(ev) => jwt_keys_updated(ev.keys, state),
+        Event::SessionRevoked(ev) => session_revoked(ev.revocation, state),
+        Event::EditWindowUpdated(ev) => state.data.edit_window = ev.edit_window,
+        Event::UserFollowed(ev) => state.data.social_graph.follow(ev.sender_id, ev.receiver_id),
+        Event::UserUnfollowed(ev) => state.data.social_graph.unfollow(ev.sender_id, ev.receiver_id),
+        Event::UserBlocked(ev) => state.data.social_graph.block(ev.sender_id, ev.receiver_id),
+        Event::UserUnblocked(ev) => state.data.social_graph.unblock(ev.sender_id, ev.receiver_id),
+        Event::ContentModerated(ev) => content_moderated(*ev, state),
+        Event::RolesUpdated(ev) => state.data.roles = Roles::from_grants(ev.grants),
+        Event::UserSuspended(ev) => state.data.suspended_users.suspend(ev.noble_id, ev.suspension),
+        Event::SuspensionLifted(ev) => {
+            state.data.suspended_users.lift(ev.noble_id);
+        },
     }
 }
+
+// post_index has already taken a hidden or removed post out of the listings.
+fn content_moderated(ev: ContentModerated, state: &mut RuntimeState) {
+    let post_id = ev.post_id;
+    // The author may have deleted the post since it was reported.
+    let post = match state.data.posts.get(post_id).or_else(|| state.data.posts.get_deleted(post_id)) {
+        Some(post) => post,
+        None => return,
+    };
+
+    match (ev.comment_id, ev.action) {
+        (_, ModerationAction::Dismiss) => {},
+        (None, ModerationAction::Hide) => {
+            state.data.posts.hide_post(post_id, state.env.now());
+        },
+        (None, ModerationAction::Remove) => {
+            if state.data.posts.remove(post_id).is_some() {
+                state.on_post_removed(post_id);
+            }
+        },
+        (Some(comment_id), ModerationAction::Hide | ModerationAction::Remove) => {
+            if let Some(comment) = state.data.posts.comments().get(post_id, comment_id) {
+                if state.data.posts.remove_comment(post_id, comment_id) {
+                    let comments_count = state.data.posts.comments().comments_count(post_id);
+                    state.push_event_to_post_index(PostIndexEvent::CommentDeleted(Box::new(CommentDeleted {
+                        post_id,
+                        noble_id: comment.noble_id,
+                        comments_count,
+                    })));
+                }
+            }
+        },
+        (comment_id, ModerationAction::Warn) => {
+            let (noble_id, target) = match comment_id {
+                None => (Some(post.noble_id), ReportTarget::Post { post_id }),
+                Some(comment_id) => (
+                    state.data.posts.comments().get(post_id, comment_id).map(|comment| comment.noble_id),
+                    ReportTarget::Comment { post_id, comment_id },
+                ),
+            };
+            if let Some(noble_id) = noble_id {
+                state.push_event_to_user_index(UserIndexEvent::UserWarned(Box::new(UserWarned {
+                    noble_id,
+                    target,
+                    reason: ev.reason,
+                })));
+            }
+        },
+    }
+    info!(post_id, comment_id = ?ev.comment_id, action = ?ev.action, "Content moderated");
+}
+
+fn jwt_keys_updated(keys: Vec<JwtVerificationKey>, state: &mut RuntimeState) {
+    types::set_jwt_verification_keys(&keys);
+    state.data.jwt_verification_keys = keys;
+}
+
+fn session_revoked(revocation: SessionRevocation, state: &mut RuntimeState) {
+    state.data.session_revocations.apply(revocation, state.env.now());
+    types::set_session_revocations(&state.data.session_revocations);
+}
@@ -0,0 +1,70 @@
+use crate::{mutate_state, RuntimeState};
+use ic_cdk_macros::update;
+use types::check_jwt;
+use local_post_index_canister::delete_post::{Response::*, *};
+
+// The post can be brought back with `restore_post` during the grace period.
+#[update]
+fn delete_post(args: Args) -> Response {
+    mutate_state(|state| delete_post_impl(args, state))
+}
+
+fn delete_post_impl(args: Args, state: &mut RuntimeState) -> Response {
+    if let Some(jwt) = check_jwt(&args.jwt, state.env.now()) {
+        let moderator = state.can_moderate(jwt.noble_id);
+        if let Some(post) = state.data.posts.get(args.post_id) {
+            if post.noble_id == jwt.noble_id || moderator {
+                state.delete_post(args.post_id);
+                Success
+            } else {
+                PermissionDenied
+            }
+        } else {
+            PostNotFound
+        }
+    } else {
+        PermissionDenied
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Data;
+    use std::collections::HashSet;
+    use types::{Category, PostPrivacy, Role, RoleMember, JwtSigningKey, JWT};
+    use utils::env::test::TestEnv;
+
+    #[test]
+    fn only_author_can_delete() {
+        let mut state = setup_runtime_state();
+        let now = state.env.now();
+
+        let response = delete_post_impl(Args { jwt: JWT::new_for_test(2, now).sign(&JwtSigningKey::for_test()).unwrap(), post_id: 1 }, &mut state);
+        assert_eq!(response, PermissionDenied);
+
+        let response = delete_post_impl(Args { jwt: JWT::new_for_test(1, now).sign(&JwtSigningKey::for_test()).unwrap(), post_id: 1 }, &mut state);
+        assert_eq!(response, Success);
+        assert!(state.data.posts.get(1).is_none());
+
+        let response = delete_post_impl(Args { jwt: JWT::new_for_test(1, now).sign(&JwtSigningKey::for_test()).unwrap(), post_id: 1 }, &mut state);
+        assert_eq!(response, PostNotFound);
+    }
+
+    #[test]
+    fn moderators_can_delete_any_post() {
+        let mut state = setup_runtime_state();
+        let now = state.env.now();
+        state.data.roles.add(RoleMember::User(3), Role::Moderator, now);
+
+        let response = delete_post_impl(Args { jwt: JWT::new_for_test(3, now).sign(&JwtSigningKey::for_test()).unwrap(), post_id: 1 }, &mut state);
+        assert_eq!(response, Success);
+    }
+
+    fn setup_runtime_state() -> RuntimeState {
+        let env = TestEnv::default();
+        let mut data = Data::default();
+        data.posts.add_post(1, 1, String::new(), String::new(), Category::GeneralDiscussion, String::new(), String::new(), 0, PostPrivacy::Everyone, HashSet::new(), env.now);
+        RuntimeState::new(Box::new(env), data)
+    }
+}
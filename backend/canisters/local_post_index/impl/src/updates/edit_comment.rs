@@ -15,16 +15,27 @@ fn edit_comment(args: Args) -> Response {
 }
 
 fn edit_comment_impl(noble_id: NobleId, args: Args, state: &mut RuntimeState) -> Response {
-    if let Some(post) = state.data.posts.get_mut(args.post_id) {
+    let now = state.env.now();
+    if let Some(suspension) = state.data.suspended_users.get(noble_id, now) {
+        return Suspended { until: suspension.until };
+    }
+    let moderator = state.can_moderate(noble_id);
+    if state.data.posts.get(args.post_id).is_some() {
         if let Err(err) = validate_field_value("Description", true, MAX_COMMENT_LENGTH, &args.description, utils::field_validation::FieldType::Text) {
             return Error(ErrorResult { description: err });
         }
-        if post.comments.len() > args.comment_id as usize {
-            if post.edit_comment(noble_id, args.comment_id, args.description) {
-                Success
-            } else {
-                PermissionDenied
+        if let Some(mut comment) = state.data.posts.comments().get(args.post_id, args.comment_id) {
+            if !moderator {
+                if comment.noble_id != noble_id {
+                    return PermissionDenied;
+                }
+                if !state.is_edit_window_open(comment.date_created) {
+                    return EditWindowClosed;
+                }
             }
+            comment.edit(noble_id, args.description, now);
+            state.data.posts.update_comment(args.post_id, args.comment_id, &comment);
+            Success
         } else {
             CommentNotFound
         }
@@ -32,3 +43,33 @@ fn edit_comment_impl(noble_id: NobleId, args: Args, state: &mut RuntimeState) ->
         PostNotFound
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Data;
+    use std::collections::HashSet;
+    use types::{Category, PostPrivacy, Suspension};
+    use utils::env::test::TestEnv;
+
+    #[test]
+    fn suspended_authors_cannot_edit() {
+        let mut state = setup_runtime_state();
+        let now = state.env.now();
+        let args = || Args { jwt: String::new(), post_id: 1, comment_id: 1, description: "edited".to_string() };
+
+        state.data.suspended_users.suspend(2, Suspension { until: Some(now + 1_000), hide_content: false });
+        assert_eq!(edit_comment_impl(2, args(), &mut state), Suspended { until: Some(now + 1_000) });
+
+        state.data.suspended_users.lift(2);
+        assert_eq!(edit_comment_impl(2, args(), &mut state), Success);
+    }
+
+    fn setup_runtime_state() -> RuntimeState {
+        let env = TestEnv::default();
+        let mut data = Data::default();
+        data.posts.add_post(1, 1, "title".to_string(), "description".to_string(), Category::GeneralDiscussion, String::new(), String::new(), 0, PostPrivacy::Everyone, HashSet::new(), env.now);
+        data.posts.add_comment(1, 2, 0, "comment".to_string(), env.now);
+        RuntimeState::new(Box::new(env), data)
+    }
+}
@@ -11,30 +11,40 @@ fn edit_post(args: Args) -> Response {
 }
 
 fn edit_post_impl(args: Args, state: &mut RuntimeState) -> Response {
-    if let Some(jwt) = check_jwt(&args.jwt, state.env.now()) {
+    let now = state.env.now();
+    if let Some(jwt) = check_jwt(&args.jwt, now) {
+        if let Some(suspension) = state.data.suspended_users.get(jwt.noble_id, now) {
+            return Suspended { until: suspension.until };
+        }
+        let moderator = state.can_moderate(jwt.noble_id);
         match prepare(&args) {
             Ok(()) => {},
             Err(response) => return response,
         };
-        if let Some(post) = state.data.posts.get_mut(args.post_id) {
-            if post.noble_id == jwt.noble_id {
-                post.title = args.title.clone();
-                post.description = args.description.clone();
-                post.post_privacy = args.post_privacy;
-                post.invited_users = args.invited_users.clone();
-
-                state.push_event_to_post_index(PostIndexEvent::PostEdited(Box::new(PostEdited {
-                    post_id: args.post_id,
-                    title: args.title,
-                    description: truncate_string(args.description, 100),
-                    post_privacy: args.post_privacy,
-                    invited_users: args.invited_users,
-                })));
-
-                Success
-            } else {
-                PermissionDenied
+        if let Some(mut post) = state.data.posts.get(args.post_id) {
+            if !moderator {
+                if post.noble_id != jwt.noble_id {
+                    return PermissionDenied;
+                }
+                if !state.is_edit_window_open(post.date_created) {
+                    return EditWindowClosed;
+                }
             }
+
+            post.edit(jwt.noble_id, args.title.clone(), args.description.clone(), now);
+            post.post_privacy = args.post_privacy;
+            post.invited_users = args.invited_users.clone();
+            state.data.posts.update(&post);
+
+            state.push_event_to_post_index(PostIndexEvent::PostEdited(Box::new(PostEdited {
+                post_id: args.post_id,
+                title: args.title,
+                description: truncate_string(args.description, 300),
+                post_privacy: args.post_privacy,
+                invited_users: args.invited_users,
+            })));
+
+            Success
         } else {
             PostNotFound
         }
@@ -60,3 +70,63 @@ fn prepare(args: &Args) -> Result<(), Response> {
 
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Data;
+    use std::collections::HashSet;
+    use types::{Category, PostPrivacy, Suspension, JwtSigningKey, JWT};
+    use utils::env::test::TestEnv;
+
+    #[test]
+    fn only_moderators_can_edit_after_window() {
+        let mut state = setup_runtime_state();
+        state.data.edit_window = Some(1_000);
+        let now = state.env.now();
+
+        let args = |noble_id: u64, description: &str| Args {
+            jwt: JWT::new_for_test(noble_id, now).sign(&JwtSigningKey::for_test()).unwrap(),
+            post_id: 1,
+            title: "title".to_string(),
+            description: description.to_string(),
+            ..Default::default()
+        };
+
+        assert_eq!(edit_post_impl(args(2, "edited"), &mut state), PermissionDenied);
+        assert_eq!(edit_post_impl(args(1, "edited"), &mut state), Success);
+
+        let mut post = state.data.posts.get(1).unwrap();
+        post.date_created = now - 1_000;
+        state.data.posts.update(&post);
+        assert_eq!(edit_post_impl(args(1, "too late"), &mut state), EditWindowClosed);
+
+        let revisions = state.data.posts.get(1).unwrap().get_revisions();
+        assert_eq!(revisions.len(), 2);
+        assert_eq!(revisions[1].description, "edited");
+    }
+
+    #[test]
+    fn suspended_authors_cannot_edit() {
+        let mut state = setup_runtime_state();
+        let now = state.env.now();
+        state.data.suspended_users.suspend(1, Suspension { until: None, hide_content: false });
+
+        let args = Args {
+            jwt: JWT::new_for_test(1, now).sign(&JwtSigningKey::for_test()).unwrap(),
+            post_id: 1,
+            title: "title".to_string(),
+            description: "edited".to_string(),
+            ..Default::default()
+        };
+        assert_eq!(edit_post_impl(args, &mut state), Suspended { until: None });
+        assert_eq!(state.data.posts.get(1).unwrap().description, "description");
+    }
+
+    fn setup_runtime_state() -> RuntimeState {
+        let env = TestEnv::default();
+        let mut data = Data::default();
+        data.posts.add_post(1, 1, "title".to_string(), "description".to_string(), Category::GeneralDiscussion, String::new(), String::new(), 0, PostPrivacy::Everyone, HashSet::new(), env.now);
+        RuntimeState::new(Box::new(env), data)
+    }
+}
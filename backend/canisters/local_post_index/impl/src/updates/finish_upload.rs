@@ -0,0 +1,96 @@
+use crate::{mutate_state, RuntimeState};
+use crate::model::files::FinishUploadResult;
+use ic_cdk_macros::update;
+use local_post_index_canister::finish_upload::{Response::*, *};
+use post_index_canister::{Event as PostIndexEvent, PostFileAttached};
+use tracing::info;
+use types::check_jwt;
+
+#[update]
+fn finish_upload(args: Args) -> Response {
+    mutate_state(|state| finish_upload_impl(args, state))
+}
+
+fn finish_upload_impl(args: Args, state: &mut RuntimeState) -> Response {
+    let now = state.env.now();
+
+    let noble_id = match check_jwt(&args.jwt, now) {
+        Some(jwt) => jwt.noble_id,
+        None => return PermissionDenied,
+    };
+    if let Some(suspension) = state.data.suspended_users.get(noble_id, now) {
+        return Suspended { until: suspension.until };
+    }
+    let post_id = match state.data.files.upload(args.file_id) {
+        Some(upload) if upload.owner == noble_id => upload.post_id,
+        Some(_) => return PermissionDenied,
+        None => return UploadNotFound,
+    };
+    // The post may have been deleted while the file was being uploaded.
+    let mut post = match state.data.posts.get(post_id) {
+        Some(post) => post,
+        None => {
+            state.data.files.remove_upload(args.file_id);
+            return PostNotFound;
+        }
+    };
+
+    let file = match state.data.files.finish_upload(args.file_id, now) {
+        FinishUploadResult::Success(file) => file,
+        FinishUploadResult::NotFound => return UploadNotFound,
+        FinishUploadResult::ChunksMissing(missing) => return ChunksMissing(missing),
+        FinishUploadResult::HashMismatch => return HashMismatch,
+    };
+
+    let replaced = std::mem::replace(&mut post.attached_file_id, args.file_id);
+    state.data.posts.update(&post);
+    // Only a file that was uploaded for this post is removed along with it.
+    if state.data.files.get(replaced).map_or(false, |replaced_file| replaced_file.post_id == post_id) {
+        state.data.files.remove(replaced);
+    }
+    state.push_event_to_post_index(PostIndexEvent::PostFileAttached(Box::new(PostFileAttached {
+        post_id,
+        attached_file_id: args.file_id,
+    })));
+    info!(post_id, file_id = %args.file_id, size = file.size, "File attached to post");
+
+    Success(SuccessResult { file_id: args.file_id, mime_type: file.mime_type, size: file.size })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::files::Upload;
+    use crate::Data;
+    use std::collections::{BTreeSet, HashSet};
+    use types::{Category, JwtSigningKey, PostPrivacy, Suspension, JWT};
+    use utils::env::test::TestEnv;
+
+    #[test]
+    fn suspended_authors_cannot_attach_files() {
+        let mut state = setup_runtime_state();
+        let now = state.env.now();
+        state.data.suspended_users.suspend(1, Suspension { until: Some(now + 1_000), hide_content: false });
+
+        let args = Args { jwt: JWT::new_for_test(1, now).sign(&JwtSigningKey::for_test()).unwrap(), file_id: 5 };
+        assert_eq!(finish_upload_impl(args, &mut state), Suspended { until: Some(now + 1_000) });
+        assert_eq!(state.data.posts.get(1).unwrap().attached_file_id, 0);
+        assert!(state.data.files.upload(5).is_some());
+    }
+
+    fn setup_runtime_state() -> RuntimeState {
+        let env = TestEnv::default();
+        let mut data = Data::default();
+        data.posts.add_post(1, 1, "title".to_string(), "description".to_string(), Category::GeneralDiscussion, String::new(), String::new(), 0, PostPrivacy::Everyone, HashSet::new(), env.now);
+        data.files.start_upload(5, Upload {
+            owner: 1,
+            post_id: 1,
+            name: "paper.pdf".to_string(),
+            size: 3,
+            hash: [0; 32],
+            received: BTreeSet::new(),
+            date_started: env.now,
+        });
+        RuntimeState::new(Box::new(env), data)
+    }
+}
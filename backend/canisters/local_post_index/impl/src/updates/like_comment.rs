@@ -12,19 +12,25 @@ fn like_comment(args: Args) -> Response {
 
 fn like_comment_impl(args: Args, state: &mut RuntimeState) -> Response {
     if let Some(jwt) = check_jwt(&args.jwt, state.env.now()) {
-        if let Some(post) = state.data.posts.get_mut(args.post_id) {
+        if let Some(suspension) = state.data.suspended_users.get(jwt.noble_id, state.env.now()) {
+            return Suspended { until: suspension.until };
+        }
+        if let Some(mut post) = state.data.posts.get(args.post_id) {
             if args.comment_id == 0 {
                 if post.liked_users.insert(jwt.noble_id) {
+                    state.data.posts.update(&post);
                     state.push_event_to_post_index(PostIndexEvent::PostLiked(Box::new(PostLiked { noble_id: jwt.noble_id, post_id: args.post_id })));
-                    state.push_event_to_user_index(UserIndexEvent::CommentLiked(Box::new(CommentLiked { noble_id: jwt.noble_id, post_id: args.post_id, comment_id: 0 })));
+                    state.push_event_to_user_index(UserIndexEvent::CommentLiked(Box::new(CommentLiked { noble_id: jwt.noble_id, post_id: args.post_id, comment_id: 0, author_id: Some(post.noble_id) })));
                     Success
                 } else {
                     AlreadyLiked
                 }
             } else {
-                if let Some(comment) = post.comments.get_mut(args.comment_id as usize) {
+                if let Some(mut comment) = state.data.posts.comments().get(args.post_id, args.comment_id) {
                     if comment.liked_users.insert(jwt.noble_id) {
-                        state.push_event_to_user_index(UserIndexEvent::CommentLiked(Box::new(CommentLiked { noble_id: jwt.noble_id, post_id: args.post_id, comment_id: args.comment_id })));
+                        let author_id = comment.noble_id;
+                        state.data.posts.update_comment(args.post_id, args.comment_id, &comment);
+                        state.push_event_to_user_index(UserIndexEvent::CommentLiked(Box::new(CommentLiked { noble_id: jwt.noble_id, post_id: args.post_id, comment_id: args.comment_id, author_id: Some(author_id) })));
                         Success
                     } else {
                         AlreadyLiked
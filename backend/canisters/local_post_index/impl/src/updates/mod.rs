@@ -1,8 +1,13 @@
 pub mod c2c_notify_events;
 pub mod delete_comment;
+pub mod delete_post;
 pub mod edit_comment;
 pub mod edit_post;
+pub mod finish_upload;
 pub mod like_comment;
 pub mod new_comment;
 pub mod new_post;
-pub mod unlike_comment;
\ No newline at end of file
+pub mod restore_post;
+pub mod start_upload;
+pub mod unlike_comment;
+pub mod upload_chunk;
\ No newline at end of file
@@ -3,7 +3,12 @@ use ic_cdk_macros::update;
 use local_post_index_canister::new_comment::{Response::*, *};
 use types::{check_jwt, NobleId, TimestampMillis};
 use utils::field_validation::validate_field_value;
+use utils::username_validation::extract_mentions;
 use post_index_canister::{Event as PostIndexEvent, NewComment};
+use user_index_canister::{Event as UserIndexEvent, CommentReplied, UsersMentioned};
+
+// Mentions beyond this many in a single comment don't notify anyone.
+const MAX_MENTIONS_PER_COMMENT: usize = 10;
 
 #[update]
 fn new_comment(args: Args) -> Response {
@@ -16,17 +21,37 @@ fn new_comment(args: Args) -> Response {
 }
 
 fn new_comment_impl(noble_id: NobleId, args: Args, state: &mut RuntimeState, now: TimestampMillis) -> Response {
-    if let Some(post) = state.data.posts.get_mut(args.post_id) {
+    if let Some(suspension) = state.data.suspended_users.get(noble_id, now) {
+        return Suspended { until: suspension.until };
+    }
+    if state.data.posts.get(args.post_id).is_some() {
         if let Err(err) = validate_field_value("Description", true, MAX_COMMENT_LENGTH, &args.description, utils::field_validation::FieldType::Text) {
             return Error(ErrorResult { description: err });
         }
-        if post.comments.len() > args.comment_id as usize {
-            if let Some(comment_id) = post.add_comment(noble_id, args.comment_id, args.description, now) {
+        if let Some(parent) = state.data.posts.comments().get(args.post_id, args.comment_id) {
+            let parent_author_id = parent.noble_id;
+            let mentions = extract_mentions(&args.description, MAX_MENTIONS_PER_COMMENT);
+            if let Some(comment_id) = state.data.posts.add_comment(args.post_id, noble_id, args.comment_id, args.description, now) {
                 state.push_event_to_post_index(PostIndexEvent::NewComment(Box::new(NewComment{
                     noble_id,
                     post_id: args.post_id,
                     date_create: now
                 })));
+                state.push_event_to_user_index(UserIndexEvent::CommentReplied(Box::new(CommentReplied {
+                    noble_id,
+                    recipient_id: parent_author_id,
+                    post_id: args.post_id,
+                    comment_id,
+                    parent_comment_id: args.comment_id,
+                })));
+                if !mentions.is_empty() {
+                    state.push_event_to_user_index(UserIndexEvent::UsersMentioned(Box::new(UsersMentioned {
+                        noble_id,
+                        usernames: mentions,
+                        post_id: args.post_id,
+                        comment_id,
+                    })));
+                }
                 Success(SuccessResult { comment_id, parent_comment_id: args.comment_id })
             } else {
                 CommentNotFound
@@ -0,0 +1,35 @@
+use crate::{mutate_state, RuntimeState};
+use ic_cdk_macros::update;
+use types::check_jwt;
+use local_post_index_canister::restore_post::{Response::*, *};
+use post_index_canister::{Event as PostIndexEvent, PostRestored};
+
+#[update]
+fn restore_post(args: Args) -> Response {
+    mutate_state(|state| restore_post_impl(args, state))
+}
+
+fn restore_post_impl(args: Args, state: &mut RuntimeState) -> Response {
+    let now = state.env.now();
+    if let Some(jwt) = check_jwt(&args.jwt, now) {
+        let moderator = state.can_moderate(jwt.noble_id);
+        if let Some(post) = state.data.posts.get_deleted(args.post_id) {
+            if (post.noble_id == jwt.noble_id && !post.hidden_by_moderator) || moderator {
+                if state.data.posts.restore_post(args.post_id, now) {
+                    state.push_event_to_post_index(PostIndexEvent::PostRestored(Box::new(PostRestored {
+                        post_id: args.post_id,
+                    })));
+                    Success
+                } else {
+                    PostNotFound
+                }
+            } else {
+                PermissionDenied
+            }
+        } else {
+            PostNotFound
+        }
+    } else {
+        PermissionDenied
+    }
+}
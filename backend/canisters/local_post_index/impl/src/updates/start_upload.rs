@@ -0,0 +1,114 @@
+use crate::{mutate_state, RuntimeState, FILE_QUOTA_PER_USER_PER_CANISTER, MAX_FILE_NAME_LENGTH, MAX_FILE_SIZE};
+use crate::model::files::{chunk_count, Upload, CHUNK_SIZE};
+use ic_cdk_macros::update;
+use local_post_index_canister::start_upload::{Response::*, *};
+use rand::Rng;
+use std::collections::BTreeSet;
+use types::{check_jwt, FileId};
+
+#[update]
+fn start_upload(args: Args) -> Response {
+    mutate_state(|state| start_upload_impl(args, state))
+}
+
+fn start_upload_impl(args: Args, state: &mut RuntimeState) -> Response {
+    let now = state.env.now();
+
+    let noble_id = match check_jwt(&args.jwt, now) {
+        Some(jwt) => jwt.noble_id,
+        None => return PermissionDenied,
+    };
+    if let Some(suspension) = state.data.suspended_users.get(noble_id, now) {
+        return Suspended { until: suspension.until };
+    }
+    match state.data.posts.get(args.post_id) {
+        Some(post) if post.noble_id == noble_id => (),
+        Some(_) => return PermissionDenied,
+        None => return PostNotFound,
+    }
+
+    let file_name = args.file_name.trim();
+    if file_name.is_empty() || file_name.len() > MAX_FILE_NAME_LENGTH || file_name.contains(['/', '\\']) || file_name.chars().any(char::is_control) {
+        return InvalidFileName;
+    }
+    let hash = match parse_hash(&args.hash) {
+        Some(hash) => hash,
+        None => return InvalidHash,
+    };
+    if args.size == 0 {
+        return FileEmpty;
+    }
+    if args.size > MAX_FILE_SIZE {
+        return FileTooLarge(MAX_FILE_SIZE);
+    }
+    let used = state.data.files.used(noble_id);
+    if used + args.size > FILE_QUOTA_PER_USER_PER_CANISTER {
+        return QuotaExceeded(QuotaExceededResult { used, quota: FILE_QUOTA_PER_USER_PER_CANISTER });
+    }
+
+    // 0 means a post has no file.
+    let file_id = loop {
+        let file_id: FileId = state.env.rng().gen();
+        if file_id != 0 && !state.data.files.contains(file_id) {
+            break file_id;
+        }
+    };
+    state.data.files.start_upload(file_id, Upload {
+        owner: noble_id,
+        post_id: args.post_id,
+        name: file_name.to_string(),
+        size: args.size,
+        hash,
+        received: BTreeSet::new(),
+        date_started: now,
+    });
+
+    Success(SuccessResult { file_id, chunk_size: CHUNK_SIZE, chunk_count: chunk_count(args.size) })
+}
+
+fn parse_hash(hex: &str) -> Option<[u8; 32]> {
+    if hex.len() != 64 || !hex.is_ascii() {
+        return None;
+    }
+    let mut hash = [0; 32];
+    for (i, byte) in hash.iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&hex[i * 2..i * 2 + 2], 16).ok()?;
+    }
+    Some(hash)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Data;
+    use std::collections::HashSet;
+    use types::{Category, JwtSigningKey, PostPrivacy, Suspension, JWT};
+    use utils::env::test::TestEnv;
+
+    #[test]
+    fn suspended_authors_cannot_upload() {
+        let mut state = setup_runtime_state();
+        let now = state.env.now();
+        let args = || Args {
+            jwt: JWT::new_for_test(1, now).sign(&JwtSigningKey::for_test()).unwrap(),
+            post_id: 1,
+            file_name: "paper.pdf".to_string(),
+            size: 100,
+            hash: "ab".repeat(32),
+        };
+
+        state.data.suspended_users.suspend(1, Suspension { until: None, hide_content: false });
+        assert_eq!(start_upload_impl(args(), &mut state), Suspended { until: None });
+        assert_eq!(state.data.files.used(1), 0);
+
+        state.data.suspended_users.lift(1);
+        assert!(matches!(start_upload_impl(args(), &mut state), Success(_)));
+    }
+
+    fn setup_runtime_state() -> RuntimeState {
+        let env = TestEnv::default();
+        let mut data = Data::default();
+        data.posts.add_post(1, 1, "title".to_string(), "description".to_string(), Category::GeneralDiscussion, String::new(), String::new(), 0, PostPrivacy::Everyone, HashSet::new(), env.now);
+        RuntimeState::new(Box::new(env), data)
+    }
+}
@@ -12,9 +12,10 @@ fn unlike_comment(args: Args) -> Response {
 
 fn unlike_comment_impl(args: Args, state: &mut RuntimeState) -> Response {
     if let Some(jwt) = check_jwt(&args.jwt, state.env.now()) {
-        if let Some(post) = state.data.posts.get_mut(args.post_id) {
+        if let Some(mut post) = state.data.posts.get(args.post_id) {
             if args.comment_id == 0 {
                 if post.liked_users.remove(&jwt.noble_id) {
+                    state.data.posts.update(&post);
                     state.push_event_to_post_index(PostIndexEvent::PostUnliked(Box::new(PostUnliked { noble_id: jwt.noble_id, post_id: args.post_id })));
                     state.push_event_to_user_index(UserIndexEvent::CommentUnliked(Box::new(CommentUnliked { noble_id: jwt.noble_id, post_id: args.post_id, comment_id: 0 })));
                     Success
@@ -22,8 +23,9 @@ fn unlike_comment_impl(args: Args, state: &mut RuntimeState) -> Response {
                     UserNotFound
                 }
             } else {
-                if let Some(comment) = post.comments.get_mut(args.comment_id as usize) {
+                if let Some(mut comment) = state.data.posts.comments().get(args.post_id, args.comment_id) {
                     if comment.liked_users.remove(&jwt.noble_id) {
+                        state.data.posts.update_comment(args.post_id, args.comment_id, &comment);
                         state.push_event_to_user_index(UserIndexEvent::CommentUnliked(Box::new(CommentUnliked { noble_id: jwt.noble_id, post_id: args.post_id, comment_id: args.comment_id })));
                         Success
                     } else {
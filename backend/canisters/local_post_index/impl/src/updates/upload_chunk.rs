@@ -0,0 +1,28 @@
+use crate::{mutate_state, RuntimeState};
+use crate::model::files::PutChunkResult;
+use ic_cdk_macros::update;
+use local_post_index_canister::upload_chunk::{Response::*, *};
+use types::check_jwt;
+
+#[update]
+fn upload_chunk(args: Args) -> Response {
+    mutate_state(|state| upload_chunk_impl(args, state))
+}
+
+fn upload_chunk_impl(args: Args, state: &mut RuntimeState) -> Response {
+    let noble_id = match check_jwt(&args.jwt, state.env.now()) {
+        Some(jwt) => jwt.noble_id,
+        None => return PermissionDenied,
+    };
+    match state.data.files.upload(args.file_id) {
+        Some(upload) if upload.owner == noble_id => (),
+        Some(_) => return PermissionDenied,
+        None => return UploadNotFound,
+    }
+
+    match state.data.files.put_chunk(args.file_id, args.index, args.bytes.into_vec()) {
+        PutChunkResult::Success => Success,
+        PutChunkResult::NotFound => UploadNotFound,
+        PutChunkResult::InvalidChunk => InvalidChunk,
+    }
+}
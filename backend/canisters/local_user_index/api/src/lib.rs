@@ -1,3 +1,4 @@
+use candid::CandidType;
 use serde::{Serialize, Deserialize};
 mod lifecycle;
 mod queries;
@@ -5,7 +6,7 @@ mod updates;
 
 pub use lifecycle::*;
 pub use queries::*;
-use types::{NobleId, PostId, CommentId, CanisterId};
+use types::{NobleId, PostId, CommentId, CanisterId, JwtVerificationKey, SessionRevocation, TimestampMillis, ReportReason, ReportTarget, Suspension};
 pub use updates::*;
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -15,9 +16,72 @@ pub enum Event {
     UserBlocked(Box<BlockUser>),
     UserUnblocked(Box<BlockUser>),
     UsernameChanged(Box<UsernameChanged>),
+    EmailChanged(Box<EmailChanged>),
     CommentLiked(Box<CommentLiked>),
     CommentUnliked(Box<CommentUnliked>),
+    PostDeleted(Box<PostDeleted>),
     LocalPostIndexCanisterAdded(Box<LocalPostIndexCanisterAdded>),
+    JwtKeysUpdated(Box<JwtKeysUpdated>),
+    SessionRevoked(Box<SessionRevoked>),
+    NotificationAdded(Box<NotificationAdded>),
+    UserSuspended(Box<UserSuspended>),
+    SuspensionLifted(Box<SuspensionLifted>),
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct NotificationAdded {
+    pub recipient_id: NobleId,
+    pub actor_id: NobleId,
+    pub kind: NotificationKind,
+}
+
+pub type NotificationId = u64;
+
+// What happened and to what. Unread notifications of an equal kind are aggregated.
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub enum NotificationKind {
+    Followed,
+    FollowRequested,
+    PostLiked { post_id: PostId },
+    CommentLiked { post_id: PostId, comment_id: CommentId },
+    // `comment_id` is the recipient's comment which was replied to, 0 for the post itself.
+    CommentReplied { post_id: PostId, comment_id: CommentId },
+    Mentioned { post_id: PostId, comment_id: CommentId },
+    // Sent by the moderators rather than a user, the actor is 0.
+    Warned { target: ReportTarget, reason: ReportReason },
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Notification {
+    pub notification_id: NotificationId,
+    pub kind: NotificationKind,
+    // Most recent first, at most a few of them, eg. "Alice and 4 others liked your post".
+    pub actors: Vec<NobleId>,
+    pub actor_count: u32,
+    pub read: bool,
+    pub date_updated: TimestampMillis,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct JwtKeysUpdated {
+    pub keys: Vec<JwtVerificationKey>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct UserSuspended {
+    pub noble_id: NobleId,
+    pub suspension: Suspension,
+}
+
+// Lifted by a moderator or because it's over.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SuspensionLifted {
+    pub noble_id: NobleId,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SessionRevoked {
+    pub revocation: SessionRevocation,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -45,6 +109,18 @@ pub struct UsernameChanged {
     pub username: String,
 }
 
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct PostDeleted {
+    pub post_id: PostId,
+}
+
+// Sent once the new address has been verified in user_index.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct EmailChanged {
+    pub noble_id: NobleId,
+    pub email: String,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct FollowUser {
     pub sender_id: NobleId,
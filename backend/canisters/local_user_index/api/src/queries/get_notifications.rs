@@ -0,0 +1,18 @@
+use candid::CandidType;
+use serde::Deserialize;
+use crate::{Notification, NotificationId};
+
+#[derive(CandidType, Deserialize, Debug)]
+pub struct Args {
+    pub jwt: String,
+    // Only notifications older than this one, None for the newest.
+    pub before: Option<NotificationId>,
+    pub limit: u32,
+}
+
+#[derive(CandidType, Deserialize, Debug, PartialEq, Eq)]
+pub enum Response {
+    Success(Vec<Notification>),
+    PermissionDenied,
+    UserNotFound,
+}
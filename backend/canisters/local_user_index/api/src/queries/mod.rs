@@ -6,6 +6,8 @@ pub mod get_bookmarks;
 pub mod get_followers;
 pub mod get_following_list;
 pub mod get_liked_posts;
+pub mod get_notifications;
 pub mod get_profile;
 pub mod get_user_data;
-pub mod get_user;
\ No newline at end of file
+pub mod get_user;
+pub mod unread_count;
\ No newline at end of file
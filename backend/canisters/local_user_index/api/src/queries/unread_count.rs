@@ -0,0 +1,14 @@
+use candid::CandidType;
+use serde::Deserialize;
+
+#[derive(CandidType, Deserialize, Debug)]
+pub struct Args {
+    pub jwt: String,
+}
+
+#[derive(CandidType, Deserialize, Debug, PartialEq, Eq)]
+pub enum Response {
+    Success(u32),
+    PermissionDenied,
+    UserNotFound,
+}
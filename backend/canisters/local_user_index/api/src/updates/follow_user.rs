@@ -1,6 +1,6 @@
 use candid::CandidType;
 use serde::{Deserialize, Serialize};
-use types::NobleId;
+use types::{NobleId, TimestampMillis};
 
 #[derive(CandidType, Serialize, Deserialize, Debug)]
 pub struct Args {
@@ -14,5 +14,6 @@ pub enum Response {
     PermissionDenied,
     AlreadyFollowing,
     UserNotFound,
+    Suspended { until: Option<TimestampMillis> },
     InternalError(String),
 }
@@ -0,0 +1,17 @@
+use candid::CandidType;
+use serde::Deserialize;
+use crate::NotificationId;
+
+#[derive(CandidType, Deserialize, Debug)]
+pub struct Args {
+    pub jwt: String,
+    // Marks this notification and every older one as read, None for all of them.
+    pub up_to: Option<NotificationId>,
+}
+
+#[derive(CandidType, Deserialize, Debug, PartialEq, Eq)]
+pub enum Response {
+    Success,
+    PermissionDenied,
+    UserNotFound,
+}
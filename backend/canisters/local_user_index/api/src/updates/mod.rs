@@ -3,6 +3,7 @@ pub mod add_bookmark;
 pub mod c2c_notify_events;
 pub mod delete_account;
 pub mod follow_user;
+pub mod mark_notifications_read;
 pub mod mute_user;
 pub mod register_user_with_google;
 pub mod register_user_with_internet_identity;
@@ -1,7 +1,9 @@
 use crate::RuntimeState;
 
+pub mod process_legacy_photos;
 pub mod sync_events_to_user_index_canister;
 
 pub(crate) fn start(state: &RuntimeState) {
+    process_legacy_photos::start_job_if_required(state);
     sync_events_to_user_index_canister::start_job_if_required(state);
 }
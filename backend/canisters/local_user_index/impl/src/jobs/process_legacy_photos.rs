@@ -0,0 +1,47 @@
+use crate::{mutate_state, RuntimeState};
+use avatar::process_avatar;
+use ic_cdk_timers::TimerId;
+use std::cell::Cell;
+use std::time::Duration;
+use tracing::{info, trace};
+
+thread_local! {
+    static TIMER_ID: Cell<Option<TimerId>> = Cell::default();
+}
+
+pub(crate) fn start_job_if_required(state: &RuntimeState) -> bool {
+    if TIMER_ID.with(|t| t.get().is_none()) && state.data.users.next_legacy_photo().is_some() {
+        let timer_id = ic_cdk_timers::set_timer_interval(Duration::ZERO, run);
+        TIMER_ID.with(|t| t.set(Some(timer_id)));
+        trace!("'process_legacy_photos' job started");
+        true
+    } else {
+        false
+    }
+}
+
+// One photo at a time, decoding a large one takes up most of what a single message may use.
+fn run() {
+    if !mutate_state(process_next_photo) {
+        if let Some(timer_id) = TIMER_ID.with(|t| t.take()) {
+            ic_cdk_timers::clear_timer(timer_id);
+            trace!("'process_legacy_photos' job stopped");
+        }
+    }
+}
+
+// Turns a photo uploaded before avatars were processed into one. The avatar id stays the same, so
+// links to it keep working.
+fn process_next_photo(state: &mut RuntimeState) -> bool {
+    let (noble_id, photo) = match state.data.users.next_legacy_photo() {
+        Some(next) => next,
+        None => return false,
+    };
+    match process_avatar(&photo) {
+        Ok(avatar) => state.data.users.set_avatar(noble_id, &avatar),
+        // The default avatar is shown instead.
+        Err(error) => info!(noble_id, ?error, "Legacy photo could not be processed"),
+    }
+    state.data.users.remove_legacy_photo(noble_id);
+    true
+}
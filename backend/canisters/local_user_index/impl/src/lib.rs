@@ -1,12 +1,14 @@
 use std::cell::RefCell;
 use std::collections::HashSet;
 
+use crate::model::notifications::NotificationMap;
 use crate::model::user_map::UserMap;
 use canister_state_macros::canister_state;
 use candid::{Principal, CandidType};
+use local_user_index_canister::NotificationKind;
 use user_index_canister::Event as UserIndexEvent;
 use serde::{Deserialize, Serialize};
-use types::{CanisterId, TimestampMillis, Cycles, Version, Timestamped};
+use types::{CanisterId, NobleId, TimestampMillis, Cycles, Version, Timestamped, JwtVerificationKey, SessionRevocations, SuspendedUsers};
 use utils::env::Environment;
 use utils::canister_event_sync_queue::CanisterEventSyncQueue;
 
@@ -60,6 +62,18 @@ impl RuntimeState {
         jobs::sync_events_to_user_index_canister::start_job_if_required(self);
     }
 
+    // Drops notifications to oneself and from users the recipient has blocked.
+    pub fn notify(&mut self, recipient_id: NobleId, actor_id: NobleId, kind: NotificationKind) {
+        if recipient_id == actor_id {
+            return;
+        }
+        if let Some(recipient) = self.data.users.get(recipient_id) {
+            if !recipient.block_users.contains(&actor_id) {
+                self.data.notifications.add(recipient_id, actor_id, kind, self.env.now());
+            }
+        }
+    }
+
     pub fn metrics(&self) -> Metrics {
         Metrics {
             now: self.env.now(),
@@ -84,6 +98,19 @@ struct Data {
     pub super_admin: Principal,
     pub local_post_index_canister_ids: HashSet<CanisterId>,
     pub user_index_event_sync_queue: CanisterEventSyncQueue<UserIndexEvent>,
+    #[serde(default)]
+    pub jwt_verification_keys: Vec<JwtVerificationKey>,
+    #[serde(default)]
+    pub session_revocations: SessionRevocations,
+    #[serde(default)]
+    pub notifications: NotificationMap,
+    // Whether the follows and blocks made before the post canisters kept their own copy have
+    // been sent to them.
+    #[serde(default)]
+    pub social_graph_synced: bool,
+    // A copy of the suspensions kept by user_index.
+    #[serde(default)]
+    pub suspended_users: SuspendedUsers,
 }
 
 impl Data {
@@ -100,6 +127,11 @@ impl Data {
             local_post_index_canister_ids,
             super_admin,
             user_index_event_sync_queue: CanisterEventSyncQueue::default(),
+            jwt_verification_keys: Vec::default(),
+            session_revocations: SessionRevocations::default(),
+            notifications: NotificationMap::default(),
+            social_graph_synced: true,
+            suspended_users: SuspendedUsers::default(),
         }
     }
 }
@@ -114,6 +146,11 @@ impl Default for Data {
             super_admin: Principal::anonymous(),
             local_post_index_canister_ids: HashSet::default(),
             user_index_event_sync_queue: CanisterEventSyncQueue::default(),
+            jwt_verification_keys: Vec::default(),
+            session_revocations: SessionRevocations::default(),
+            notifications: NotificationMap::default(),
+            social_graph_synced: true,
+            suspended_users: SuspendedUsers::default(),
         }
     }
 }
@@ -1,11 +1,12 @@
 use crate::lifecycle::{init_env, init_state, UPGRADE_BUFFER_SIZE};
 use crate::memory::get_upgrades_memory;
-use crate::Data;
+use crate::{mutate_state, Data, RuntimeState};
 use canister_logger::LogEntry;
 use ic_cdk_macros::post_upgrade;
 use ic_stable_structures::reader::{BufferedReader, Reader};
 use local_user_index_canister::post_upgrade::Args;
 use tracing::info;
+use user_index_canister::{Event as UserIndexEvent, SocialGraphSynced};
 
 #[post_upgrade]
 fn post_upgrade(args: Args) {
@@ -20,5 +21,28 @@ fn post_upgrade(args: Args) {
 
     init_state(env, data, args.wasm_version);
 
+    mutate_state(sync_social_graph_if_required);
+
     info!(version = %args.wasm_version, "Post-upgrade complete");
 }
+
+fn sync_social_graph_if_required(state: &mut RuntimeState) {
+    if state.data.social_graph_synced {
+        return;
+    }
+    let events: Vec<_> = state.data.users
+        .iter()
+        .filter(|user| !user.following_list.is_empty() || !user.block_users.is_empty())
+        .map(|user| UserIndexEvent::SocialGraphSynced(Box::new(SocialGraphSynced {
+            noble_id: user.noble_id,
+            following: user.following_list.iter().map(|item| item.noble_id).collect(),
+            blocked: user.block_users.clone(),
+        })))
+        .collect();
+
+    info!(user_count = events.len(), "Syncing follows and blocks to the post canisters");
+    for event in events {
+        state.push_event_to_user_index(event);
+    }
+    state.data.social_graph_synced = true;
+}
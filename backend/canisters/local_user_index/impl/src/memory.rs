@@ -4,6 +4,12 @@ use ic_stable_structures::{
 };
 
 const UPGRADES: MemoryId = MemoryId::new(0);
+const USERS_INDEX: MemoryId = MemoryId::new(1);
+const USERS: MemoryId = MemoryId::new(2);
+const PHOTOS_INDEX: MemoryId = MemoryId::new(3);
+const PHOTOS: MemoryId = MemoryId::new(4);
+const AVATARS_INDEX: MemoryId = MemoryId::new(5);
+const AVATARS: MemoryId = MemoryId::new(6);
 
 pub type Memory = VirtualMemory<DefaultMemoryImpl>;
 
@@ -16,6 +22,18 @@ pub fn get_upgrades_memory() -> Memory {
     get_memory(UPGRADES)
 }
 
+pub fn get_users_memories() -> (Memory, Memory) {
+    (get_memory(USERS_INDEX), get_memory(USERS))
+}
+
+pub fn get_photos_memories() -> (Memory, Memory) {
+    (get_memory(PHOTOS_INDEX), get_memory(PHOTOS))
+}
+
+pub fn get_avatars_memories() -> (Memory, Memory) {
+    (get_memory(AVATARS_INDEX), get_memory(AVATARS))
+}
+
 fn get_memory(id: MemoryId) -> Memory {
     MEMORY_MANAGER.with(|m| m.get(id))
 }
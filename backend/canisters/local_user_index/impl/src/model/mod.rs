@@ -1,2 +1,3 @@
+pub mod notifications;
 pub mod user;
 pub mod user_map;
\ No newline at end of file
@@ -0,0 +1,157 @@
+use local_user_index_canister::{Notification, NotificationId, NotificationKind};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, VecDeque};
+use types::{NobleId, TimestampMillis};
+
+// Older notifications are dropped once a user has this many.
+const MAX_NOTIFICATIONS_PER_USER: usize = 200;
+// Actors kept per notification, for display and to drop repeats such as like / unlike / like.
+const MAX_ACTORS: usize = 10;
+
+#[derive(Serialize, Deserialize, Default)]
+pub struct NotificationMap {
+    inboxes: HashMap<NobleId, Inbox>,
+}
+
+#[derive(Serialize, Deserialize, Default)]
+struct Inbox {
+    next_id: NotificationId,
+    // Oldest first, ids increase along the queue.
+    notifications: VecDeque<Notification>,
+}
+
+impl NotificationMap {
+    // Returns false if the actor is already counted in an unread notification of this kind. Once it
+    // has been read, the same actor starts a new one.
+    pub fn add(&mut self, recipient_id: NobleId, actor_id: NobleId, kind: NotificationKind, now: TimestampMillis) -> bool {
+        let inbox = self.inboxes.entry(recipient_id).or_default();
+
+        let unread = inbox.notifications.iter().rposition(|n| n.kind == kind && !n.read);
+        let mut notification = match unread {
+            Some(index) if inbox.notifications[index].actors.contains(&actor_id) => return false,
+            // Aggregated notifications move to the top with a new id, so `mark_read(up_to)` leaves them unread.
+            Some(index) => inbox.notifications.remove(index).unwrap(),
+            None => Notification {
+                notification_id: 0,
+                kind,
+                actors: Vec::new(),
+                actor_count: 0,
+                read: false,
+                date_updated: now,
+            },
+        };
+
+        notification.notification_id = inbox.next_id;
+        notification.actors.insert(0, actor_id);
+        notification.actors.truncate(MAX_ACTORS);
+        notification.actor_count += 1;
+        notification.date_updated = now;
+
+        inbox.next_id += 1;
+        inbox.notifications.push_back(notification);
+        if inbox.notifications.len() > MAX_NOTIFICATIONS_PER_USER {
+            inbox.notifications.pop_front();
+        }
+        true
+    }
+
+    // Newest first.
+    pub fn get(&self, noble_id: NobleId, before: Option<NotificationId>, limit: usize) -> Vec<Notification> {
+        self.inboxes
+            .get(&noble_id)
+            .map(|inbox| {
+                inbox
+                    .notifications
+                    .iter()
+                    .rev()
+                    .filter(|n| before.map_or(true, |b| n.notification_id < b))
+                    .take(limit)
+                    .cloned()
+                    .collect()
+            })
+            .unwrap_or_default()
+    }
+
+    pub fn unread_count(&self, noble_id: NobleId) -> u32 {
+        self.inboxes
+            .get(&noble_id)
+            .map_or(0, |inbox| inbox.notifications.iter().filter(|n| !n.read).count() as u32)
+    }
+
+    pub fn mark_read(&mut self, noble_id: NobleId, up_to: Option<NotificationId>) {
+        if let Some(inbox) = self.inboxes.get_mut(&noble_id) {
+            inbox
+                .notifications
+                .iter_mut()
+                .filter(|n| up_to.map_or(true, |u| n.notification_id <= u))
+                .for_each(|n| n.read = true);
+        }
+    }
+
+    pub fn remove_user(&mut self, noble_id: NobleId) {
+        self.inboxes.remove(&noble_id);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: TimestampMillis = 1_000_000;
+
+    #[test]
+    fn likes_are_aggregated_and_deduplicated() {
+        let mut notifications = NotificationMap::default();
+        let liked = NotificationKind::PostLiked { post_id: 1 };
+
+        assert!(notifications.add(1, 2, liked.clone(), NOW));
+        assert!(notifications.add(1, 3, NotificationKind::Followed, NOW));
+        assert!(notifications.add(1, 4, liked.clone(), NOW + 1));
+        assert!(!notifications.add(1, 2, liked.clone(), NOW + 2));
+
+        let all = notifications.get(1, None, 10);
+        assert_eq!(all.len(), 2);
+        assert_eq!(all[0].kind, liked);
+        assert_eq!(all[0].actors, vec![4, 2]);
+        assert_eq!(all[0].actor_count, 2);
+        assert_eq!(all[0].date_updated, NOW + 1);
+        assert_eq!(notifications.unread_count(1), 2);
+    }
+
+    #[test]
+    fn read_notifications_are_not_aggregated_into() {
+        let mut notifications = NotificationMap::default();
+        let liked = NotificationKind::CommentLiked { post_id: 1, comment_id: 2 };
+
+        notifications.add(1, 2, liked.clone(), NOW);
+        let first_id = notifications.get(1, None, 1)[0].notification_id;
+        notifications.mark_read(1, Some(first_id));
+        assert_eq!(notifications.unread_count(1), 0);
+
+        notifications.add(1, 3, liked.clone(), NOW);
+        let all = notifications.get(1, None, 10);
+        assert_eq!(all.len(), 2);
+        assert_eq!(all[0].actors, vec![3]);
+        assert!(!all[0].read);
+        assert_eq!(notifications.get(1, Some(all[0].notification_id), 10), vec![all[1].clone()]);
+
+        // Liking again after the notification was read shows up as new.
+        notifications.mark_read(1, None);
+        assert!(notifications.add(1, 3, liked.clone(), NOW + 1));
+        assert!(!notifications.add(1, 3, liked.clone(), NOW + 2));
+        assert_eq!(notifications.get(1, None, 10).len(), 3);
+        assert_eq!(notifications.unread_count(1), 1);
+    }
+
+    #[test]
+    fn oldest_are_dropped() {
+        let mut notifications = NotificationMap::default();
+        for post_id in 0..MAX_NOTIFICATIONS_PER_USER as u64 + 5 {
+            notifications.add(1, 2, NotificationKind::PostLiked { post_id }, NOW);
+        }
+
+        let all = notifications.get(1, None, usize::MAX);
+        assert_eq!(all.len(), MAX_NOTIFICATIONS_PER_USER);
+        assert_eq!(all.last().unwrap().notification_id, 5);
+    }
+}
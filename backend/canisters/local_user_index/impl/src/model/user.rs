@@ -19,6 +19,8 @@ pub struct User {
     pub city: String,
 
     pub preferred_pronouns: Option<PreferredPronouns>,
+    // Photos are kept apart, see `UserMap`. This is only set when reading users saved before that.
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub photo: Vec<u8>,
     pub search_by_email: bool,
     pub bio: String,
@@ -178,6 +180,11 @@ impl User {
     pub fn is_bookmarked(&self, post_id: PostId) -> bool {
         self.bookmarks.contains(&post_id)
     }
+    // Drops the bookmark and likes of a post which no longer exists.
+    pub fn remove_post(&mut self, post_id: PostId) {
+        self.bookmarks.retain(|id| *id != post_id);
+        self.liked_posts.retain(|(id, _)| *id != post_id);
+    }
     pub fn add_bookmark(&mut self, post_id: PostId) {
         self.bookmarks.push(post_id);
     }
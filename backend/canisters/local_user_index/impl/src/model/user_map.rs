@@ -1,15 +1,27 @@
+use crate::memory::{get_avatars_memories, get_photos_memories, get_users_memories, Memory};
 use crate::model::user::User;
+use avatar::Avatar;
 use candid::Principal;
 use rand::rngs::StdRng;
 use serde::{Serialize, Deserialize};
+use serde_bytes::ByteBuf;
+use stable_map::StableMap;
 use std::collections::HashMap;
-use types::{TimestampMillis, NobleId, CanisterId, AvatarId};
+use types::{TimestampMillis, NobleId, CanisterId, AvatarId, PostId};
 
-#[derive(Serialize, Deserialize, Default)]
+// Users and their avatars are kept in stable memory, so upgrading doesn't copy them. Reads return
+// a copy of the user, changes are saved with `update`.
+#[derive(Serialize, Deserialize)]
 #[serde(from = "UserMapTrimmed")]
 pub struct UserMap {
-    users: HashMap<NobleId, User>,
     #[serde(skip)]
+    users: StableMap<NobleId, User, Memory>,
+    // Photos as they were uploaded, before avatars were processed. `process_legacy_photos` turns
+    // them into avatars.
+    #[serde(skip)]
+    photos: StableMap<NobleId, ByteBuf, Memory>,
+    #[serde(skip)]
+    avatars: StableMap<NobleId, Avatar, Memory>,
     pub avatar_id_to_noble_id: HashMap<AvatarId, NobleId>,
 }
 
@@ -20,12 +32,22 @@ pub enum UpdateUserResult {
 }
 
 impl UserMap {
-    pub fn get(&self, noble_id: NobleId) -> Option<&User> {
-        self.users.get(&noble_id)
+    pub fn get(&self, noble_id: NobleId) -> Option<User> {
+        self.users.get(noble_id)
     }
 
-    pub fn get_mut(&mut self, noble_id: NobleId) -> Option<&mut User> {
-        self.users.get_mut(&noble_id)
+    pub fn update(&mut self, user: &User) -> UpdateUserResult {
+        if self.users.contains_key(user.noble_id) {
+            self.users.insert(user.noble_id, user);
+            UpdateUserResult::Success
+        } else {
+            UpdateUserResult::UserNotFound
+        }
+    }
+
+    // Loads every user, only for when all of them are needed.
+    pub fn iter(&self) -> impl Iterator<Item = User> + '_ {
+        self.users.iter().map(|(_, user)| user)
     }
 
     pub fn len(&self) -> usize {
@@ -42,27 +64,63 @@ impl UserMap {
         now: TimestampMillis,
     ) {
         let user = User::new(principal, noble_id, canister_id, email, username, now);
-        self.users.insert(noble_id, user);
+        self.users.insert(noble_id, &user);
     }
 
     pub fn remove(&mut self, noble_id: NobleId) -> UpdateUserResult {
-        if self.users.get(&noble_id).is_some() {
-            self.users.remove(&noble_id);
+        if let Some(user) = self.users.remove(noble_id) {
+            self.photos.remove(noble_id);
+            self.avatars.remove(noble_id);
+            self.avatar_id_to_noble_id.remove(&user.avatar_id);
             UpdateUserResult::Success
         } else {
             UpdateUserResult::UserNotFound
         }
     }
 
+    pub fn avatar(&self, noble_id: NobleId) -> Option<Avatar> {
+        self.avatars.get(noble_id)
+    }
+
+    pub fn set_avatar(&mut self, noble_id: NobleId, avatar: &Avatar) {
+        if self.users.contains_key(noble_id) {
+            self.photos.remove(noble_id);
+            self.avatars.insert(noble_id, avatar);
+        }
+    }
+
+    // The largest thumbnail, or the photo as uploaded if it hasn't been processed yet. Empty if the
+    // user never set one.
+    pub fn photo(&self, noble_id: NobleId) -> Vec<u8> {
+        self.avatar(noble_id)
+            .and_then(|avatar| avatar.thumbnails.into_iter().last())
+            .map(|thumbnail| thumbnail.bytes.into_vec())
+            .or_else(|| self.legacy_photo(noble_id))
+            .unwrap_or_default()
+    }
+
+    pub fn legacy_photo(&self, noble_id: NobleId) -> Option<Vec<u8>> {
+        self.photos.get(noble_id).map(|photo| photo.into_vec())
+    }
+
+    pub fn next_legacy_photo(&self) -> Option<(NobleId, Vec<u8>)> {
+        self.photos.iter().next().map(|(noble_id, photo)| (noble_id, photo.into_vec()))
+    }
+
+    pub fn remove_legacy_photo(&mut self, noble_id: NobleId) {
+        self.photos.remove(noble_id);
+    }
+
     pub fn update_avatar_id(&mut self, noble_id: NobleId, rng: &mut StdRng) -> AvatarId {
         let mut avatar_id = utils::env::get_random_id(rng);
         while self.avatar_id_to_noble_id.contains_key(&avatar_id) {
             avatar_id = utils::env::get_random_id(rng);
         }
 
-        if let Some(user) = self.get_mut(noble_id) {
+        if let Some(mut user) = self.get(noble_id) {
             let older_avatar_id = user.avatar_id;
             user.avatar_id = avatar_id;
+            self.update(&user);
 
             self.avatar_id_to_noble_id.remove(&older_avatar_id);
             self.avatar_id_to_noble_id.insert(avatar_id, noble_id);
@@ -73,6 +131,19 @@ impl UserMap {
         }
     }
 
+    // Drops a removed post from every user's bookmarks and liked posts.
+    pub fn remove_post(&mut self, post_id: PostId) {
+        let noble_ids: Vec<_> = self.users.keys().collect();
+        for noble_id in noble_ids {
+            if let Some(mut user) = self.get(noble_id) {
+                if user.bookmarks.contains(&post_id) || user.liked_posts.iter().any(|(id, _)| *id == post_id) {
+                    user.remove_post(post_id);
+                    self.update(&user);
+                }
+            }
+        }
+    }
+
     #[cfg(test)]
     pub fn add_test_user(&mut self, user: User) {
         self.register(
@@ -83,42 +154,124 @@ impl UserMap {
             user.username.clone(),
             user.date_created,
         );
-        self.update(user);
+        self.update(&user);
     }
+}
 
-    #[cfg(test)]
-    pub fn update(&mut self, user: User) -> UpdateUserResult {
-        let noble_id = user.noble_id;
-
-        if let Some(_) = self.users.get(&noble_id) {
-            self.users.insert(noble_id, user);
-            UpdateUserResult::Success
-        } else {
-            UpdateUserResult::UserNotFound
+impl Default for UserMap {
+    fn default() -> Self {
+        let (users_index_memory, users_memory) = get_users_memories();
+        let (photos_index_memory, photos_memory) = get_photos_memories();
+        let (avatars_index_memory, avatars_memory) = get_avatars_memories();
+        UserMap {
+            users: StableMap::init(users_index_memory, users_memory),
+            photos: StableMap::init(photos_index_memory, photos_memory),
+            avatars: StableMap::init(avatars_index_memory, avatars_memory),
+            avatar_id_to_noble_id: HashMap::default(),
         }
     }
 }
 
 #[derive(Deserialize)]
 struct UserMapTrimmed {
+    // Only set when upgrading from the version that kept users, photos included, on the heap.
+    #[serde(default)]
     users: HashMap<NobleId, User>,
+    #[serde(default)]
+    avatar_id_to_noble_id: HashMap<AvatarId, NobleId>,
 }
 
 impl From<UserMapTrimmed> for UserMap {
     fn from(value: UserMapTrimmed) -> Self {
         let mut user_map = UserMap {
-            users: value.users,
+            avatar_id_to_noble_id: value.avatar_id_to_noble_id,
             ..Default::default()
         };
 
-        for (noble_id, user) in user_map.users.iter_mut() {
-            let avatar_id = user.avatar_id;
-            if avatar_id != 0 {
-                user_map.avatar_id_to_noble_id.insert(avatar_id, *noble_id);
-                user.avatar_id = avatar_id;
+        for (noble_id, mut user) in value.users {
+            let photo = std::mem::take(&mut user.photo);
+            if !photo.is_empty() {
+                user_map.photos.insert(noble_id, &ByteBuf::from(photo));
             }
+            if user.avatar_id != 0 {
+                user_map.avatar_id_to_noble_id.insert(user.avatar_id, noble_id);
+            }
+            user_map.users.insert(noble_id, &user);
         }
 
         user_map
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{MAX_PHOTO_SIZE, USER_LIMIT};
+    use types::{Follower, FollowingUser};
+
+    #[test]
+    fn upgrade_moves_users_and_photos_out_of_the_heap() {
+        #[derive(Serialize)]
+        struct LegacyUserMap {
+            users: HashMap<NobleId, User>,
+        }
+
+        let users: HashMap<NobleId, User> = (1..=USER_LIMIT as NobleId)
+            .map(|noble_id| (noble_id, User {
+                noble_id,
+                username: format!("user{noble_id}"),
+                bio: "b".repeat(250),
+                photo: vec![noble_id as u8; MAX_PHOTO_SIZE / 16],
+                avatar_id: noble_id * 10,
+                followers: (1..=USER_LIMIT as NobleId).map(|id| Follower { noble_id: id, is_approved: true }).collect(),
+                following_list: (1..=USER_LIMIT as NobleId).map(|id| FollowingUser { noble_id: id, is_muted: false }).collect(),
+                bookmarks: (0..500).collect(),
+                ..Default::default()
+            }))
+            .collect();
+        let legacy_bytes = upgrade(&LegacyUserMap { users });
+
+        let user_map: UserMap = serializer::deserialize(legacy_bytes.as_slice()).unwrap();
+        check(&user_map);
+
+        // From now on only the avatar ids are copied.
+        let bytes = upgrade(&user_map);
+        assert!(bytes.len() < 16 * 1_024);
+        assert!(bytes.len() * 1_000 < legacy_bytes.len());
+
+        let user_map: UserMap = serializer::deserialize(bytes.as_slice()).unwrap();
+        check(&user_map);
+    }
+
+    #[test]
+    fn photo_is_the_largest_thumbnail() {
+        let mut user_map = UserMap::default();
+        user_map.add_test_user(User { noble_id: 1, ..Default::default() });
+        assert!(user_map.photo(1).is_empty());
+
+        let thumbnail = |size: u32| avatar::Thumbnail { size, mime_type: "image/webp".to_string(), bytes: ByteBuf::from(vec![size as u8]) };
+        user_map.set_avatar(1, &Avatar { thumbnails: vec![thumbnail(64), thumbnail(128)] });
+        assert_eq!(user_map.photo(1), vec![128]);
+    }
+
+    fn upgrade<T: Serialize>(value: &T) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        serializer::serialize(value, &mut bytes).unwrap();
+        bytes
+    }
+
+    fn check(user_map: &UserMap) {
+        assert_eq!(user_map.len(), USER_LIMIT);
+        assert_eq!(user_map.avatar_id_to_noble_id.len(), USER_LIMIT);
+        for noble_id in [1, 100, USER_LIMIT as NobleId] {
+            let user = user_map.get(noble_id).unwrap();
+            assert_eq!(user.username, format!("user{noble_id}"));
+            assert!(user.photo.is_empty());
+            assert_eq!(user.followers.len(), USER_LIMIT);
+            assert_eq!(user.bookmarks.len(), 500);
+            assert_eq!(user_map.legacy_photo(noble_id), Some(vec![noble_id as u8; MAX_PHOTO_SIZE / 16]));
+            assert_eq!(user_map.photo(noble_id), vec![noble_id as u8; MAX_PHOTO_SIZE / 16]);
+            assert_eq!(user_map.avatar_id_to_noble_id.get(&(noble_id * 10)), Some(&noble_id));
+        }
+    }
+}
@@ -20,7 +20,7 @@ fn current_user_impl(args: Args, state: &RuntimeState) -> Response {
                 city: user.city.clone(),
             
                 preferred_pronouns: user.preferred_pronouns.clone(),
-                photo: user.photo.clone(),
+                photo: state.data.users.photo(user.noble_id),
                 email: user.email.clone(),
                 search_by_email: user.search_by_email,
                 bio: user.bio.clone(),
@@ -0,0 +1,26 @@
+use crate::{read_state, RuntimeState};
+use ic_cdk_macros::query;
+use local_user_index_canister::get_notifications::{Response::*, *};
+use types::check_jwt;
+
+const MAX_LIMIT: u32 = 50;
+
+#[query]
+fn get_notifications(args: Args) -> Response {
+    read_state(|state| get_notifications_impl(&args, state))
+}
+
+fn get_notifications_impl(args: &Args, state: &RuntimeState) -> Response {
+    let now = state.env.now();
+
+    if let Some(jwt) = check_jwt(&args.jwt, now) {
+        if state.data.users.get(jwt.noble_id).is_some() {
+            let limit = args.limit.min(MAX_LIMIT) as usize;
+            Success(state.data.notifications.get(jwt.noble_id, args.before, limit))
+        } else {
+            UserNotFound
+        }
+    } else {
+        PermissionDenied
+    }
+}
@@ -1,21 +1,57 @@
 use ic_cdk_macros::query;
-use types::{HttpRequest, HttpResponse, NobleId, TimestampMillis, AvatarId};
-use http_request::{extract_route, Route, build_response, build_json_response, encode_logs};
+use types::{HeaderField, HttpRequest, HttpResponse, NobleId, TimestampMillis, AvatarId, Gender};
+use http_request::{extract_route, images, Route, build_response, build_json_response, encode_logs};
+use utils::mime::detect_mime_type;
 
 use crate::{read_state, RuntimeState};
 
 #[query]
 fn http_request(request: HttpRequest) -> HttpResponse {
-    fn get_avatar(avatar_id: Option<AvatarId>, state: &RuntimeState) -> HttpResponse {
-        if avatar_id.is_none() {
-            return HttpResponse::not_found();
-        }
-        if let Some(noble_id) = state.data.users.avatar_id_to_noble_id.get(&avatar_id.unwrap()) {
-            if let Some(user) = state.data.users.get(*noble_id) {
-                return build_response(user.photo.clone(), "image/jpeg");
+    fn get_avatar(avatar_id: Option<AvatarId>, size: Option<u32>, if_none_match: Option<&String>, state: &RuntimeState) -> HttpResponse {
+        let avatar_id = match avatar_id {
+            Some(avatar_id) => avatar_id,
+            None => return HttpResponse::not_found(),
+        };
+        let noble_id = match state.data.users.avatar_id_to_noble_id.get(&avatar_id) {
+            Some(noble_id) => *noble_id,
+            None => return default_avatar(None),
+        };
+
+        if let Some(avatar) = state.data.users.avatar(noble_id) {
+            if let Some(thumbnail) = avatar.thumbnail(size) {
+                // Setting a photo gives it a new id, so what's behind an id never changes.
+                let etag = format!("\"{avatar_id}-{}\"", thumbnail.size);
+                let cache_headers = vec![
+                    HeaderField("ETag".to_string(), etag.clone()),
+                    HeaderField("Cache-Control".to_string(), "public, max-age=31536000, immutable".to_string()),
+                ];
+                if if_none_match == Some(&etag) {
+                    let mut response = HttpResponse::status_code(304);
+                    response.headers = cache_headers;
+                    return response;
+                }
+                let mut response = build_response(thumbnail.bytes.to_vec(), thumbnail.mime_type.clone());
+                response.headers.extend(cache_headers);
+                return response;
             }
         }
-        HttpResponse::not_found()
+        // Set before avatars were processed and not yet turned into one.
+        if let Some(photo) = state.data.users.legacy_photo(noble_id) {
+            let mime_type = detect_mime_type("", &photo);
+            return build_response(photo, mime_type);
+        }
+        default_avatar(state.data.users.get(noble_id).and_then(|user| user.gender))
+    }
+
+    // Not cached for long, the user may set a photo.
+    fn default_avatar(gender: Option<Gender>) -> HttpResponse {
+        let photo = match gender {
+            Some(Gender::Female) => images::get_default_female_avatar(),
+            _ => images::get_default_male_avatar(),
+        };
+        let mut response = build_response(photo, "image/png");
+        response.headers.push(HeaderField("Cache-Control".to_string(), "public, max-age=3600".to_string()));
+        response
     }
 
     fn get_metrics(state: &RuntimeState) -> HttpResponse {
@@ -41,7 +77,7 @@ fn http_request(request: HttpRequest) -> HttpResponse {
     }
 
     match extract_route(&request.url) {
-        Route::Avatar(noble_id) => read_state(|state| get_avatar(noble_id, state)),
+        Route::Avatar(avatar_id, size) => read_state(|state| get_avatar(avatar_id, size, request.header("If-None-Match"), state)),
         Route::Logs(since) => get_logs(since),
         Route::Traces(since) => get_traces(since),
         Route::Metrics => read_state(get_metrics),
@@ -0,0 +1,23 @@
+use crate::{read_state, RuntimeState};
+use ic_cdk_macros::query;
+use local_user_index_canister::unread_count::{Response::*, *};
+use types::check_jwt;
+
+#[query]
+fn unread_count(args: Args) -> Response {
+    read_state(|state| unread_count_impl(&args, state))
+}
+
+fn unread_count_impl(args: &Args, state: &RuntimeState) -> Response {
+    let now = state.env.now();
+
+    if let Some(jwt) = check_jwt(&args.jwt, now) {
+        if state.data.users.get(jwt.noble_id).is_some() {
+            Success(state.data.notifications.unread_count(jwt.noble_id))
+        } else {
+            UserNotFound
+        }
+    } else {
+        PermissionDenied
+    }
+}
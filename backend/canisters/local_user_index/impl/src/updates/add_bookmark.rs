@@ -28,11 +28,12 @@ async fn add_bookmark(args: Args) -> Response {
 }
 
 fn add_bookmark_impl(noble_id: NobleId, args: Args, state: &mut RuntimeState) -> Response {
-    if let Some(user) = state.data.users.get_mut(noble_id) {
+    if let Some(mut user) = state.data.users.get(noble_id) {
         if user.is_bookmarked(args.post_id) {
             AlreadyBookmarked
         } else {
             user.add_bookmark(args.post_id);
+            state.data.users.update(&user);
             Success
         }
     } else {
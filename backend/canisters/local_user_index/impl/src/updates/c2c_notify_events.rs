@@ -2,8 +2,8 @@ use crate::guards::caller_is_user_index_canister;
 use crate::{mutate_state, RuntimeState};
 use canister_api_macros::update_msgpack;
 use local_user_index_canister::c2c_notify_events::{Response::*, *};
-use local_user_index_canister::Event;
-use types::{NobleId, PostId, CommentId};
+use local_user_index_canister::{Event, NotificationKind};
+use types::{NobleId, PostId, CommentId, JwtVerificationKey, SessionRevocation};
 
 #[update_msgpack(guard = "caller_is_user_index_canister")]
 fn c2c_notify_events(args: Args) -> Response {
@@ -24,52 +24,91 @@ fn handle_event(event: Event, state: &mut RuntimeState) {
         Event::UserBlocked(ev) => block_user(ev.sender_id, ev.receiver_id, state),
         Event::UserUnblocked(ev) => unblock_user(ev.sender_id, ev.receiver_id, state),
         Event::UsernameChanged(ev) => username_changed(ev.noble_id, ev.username, state),
+        Event::EmailChanged(ev) => email_changed(ev.noble_id, ev.email, state),
         Event::CommentLiked(ev) => comment_liked(ev.noble_id, ev.post_id, ev.comment_id, state),
         Event::CommentUnliked(ev) => comment_unliked(ev.noble_id, ev.post_id, ev.comment_id, state),
+        Event::PostDeleted(ev) => post_deleted(ev.post_id, state),
         Event::LocalPostIndexCanisterAdded(ev) => {
             state.data.local_post_index_canister_ids.insert(ev.canister_id);
         }
+        Event::JwtKeysUpdated(ev) => jwt_keys_updated(ev.keys, state),
+        Event::SessionRevoked(ev) => session_revoked(ev.revocation, state),
+        Event::NotificationAdded(ev) => state.notify(ev.recipient_id, ev.actor_id, ev.kind),
+        Event::UserSuspended(ev) => state.data.suspended_users.suspend(ev.noble_id, ev.suspension),
+        Event::SuspensionLifted(ev) => {
+            state.data.suspended_users.lift(ev.noble_id);
+        }
     }
 }
 
+fn jwt_keys_updated(keys: Vec<JwtVerificationKey>, state: &mut RuntimeState) {
+    types::set_jwt_verification_keys(&keys);
+    state.data.jwt_verification_keys = keys;
+}
+
+fn session_revoked(revocation: SessionRevocation, state: &mut RuntimeState) {
+    state.data.session_revocations.apply(revocation, state.env.now());
+    types::set_session_revocations(&state.data.session_revocations);
+}
+
 fn follow_user(sender_id: NobleId, receiver_id: NobleId, state: &mut RuntimeState) {
-    if let Some(receiver) = state.data.users.get_mut(receiver_id) {
-        receiver.add_follower(sender_id);
+    if let Some(mut receiver) = state.data.users.get(receiver_id) {
+        if receiver.add_follower(sender_id) {
+            state.data.users.update(&receiver);
+            state.notify(receiver_id, sender_id, NotificationKind::Followed);
+        }
     }
 }
 
 fn unfollow_user(sender_id: NobleId, receiver_id: NobleId, state: &mut RuntimeState) {
-    if let Some(receiver) = state.data.users.get_mut(receiver_id) {
+    if let Some(mut receiver) = state.data.users.get(receiver_id) {
         receiver.remove_follower(sender_id);
+        state.data.users.update(&receiver);
     }
 }
 
 fn block_user(sender_id: NobleId, receiver_id: NobleId, state: &mut RuntimeState) {
-    if let Some(receiver) = state.data.users.get_mut(receiver_id) {
+    if let Some(mut receiver) = state.data.users.get(receiver_id) {
         receiver.add_block_me_user(sender_id);
+        state.data.users.update(&receiver);
     }
 }
 
 fn unblock_user(sender_id: NobleId, receiver_id: NobleId, state: &mut RuntimeState) {
-    if let Some(receiver) = state.data.users.get_mut(receiver_id) {
+    if let Some(mut receiver) = state.data.users.get(receiver_id) {
         receiver.remove_block_me_user(sender_id);
+        state.data.users.update(&receiver);
     }
 }
 
 fn username_changed(noble_id: NobleId, username: String, state: &mut RuntimeState) {
-    if let Some(user) = state.data.users.get_mut(noble_id) {
+    if let Some(mut user) = state.data.users.get(noble_id) {
         user.username = username;
+        state.data.users.update(&user);
     }
 }
 
+fn email_changed(noble_id: NobleId, email: String, state: &mut RuntimeState) {
+    if let Some(mut user) = state.data.users.get(noble_id) {
+        user.email = email;
+        state.data.users.update(&user);
+    }
+}
+
+fn post_deleted(post_id: PostId, state: &mut RuntimeState) {
+    state.data.users.remove_post(post_id);
+}
+
 fn comment_liked(noble_id: NobleId, post_id: PostId, comment_id: CommentId, state: &mut RuntimeState) {
-    if let Some(user) = state.data.users.get_mut(noble_id) {
+    if let Some(mut user) = state.data.users.get(noble_id) {
         user.like_post(post_id, comment_id);
+        state.data.users.update(&user);
     }
 }
 
 fn comment_unliked(noble_id: NobleId, post_id: PostId, comment_id: CommentId, state: &mut RuntimeState) {
-    if let Some(user) = state.data.users.get_mut(noble_id) {
+    if let Some(mut user) = state.data.users.get(noble_id) {
         user.unlike_post(post_id, comment_id);
+        state.data.users.update(&user);
     }
 }
@@ -17,6 +17,7 @@ fn delete_account_impl(args: Args, state: &mut RuntimeState) -> Response {
     
             match state.data.users.remove(noble_id) {
                 UpdateUserResult::Success => {
+                    state.data.notifications.remove_user(noble_id);
                     state.push_event_to_user_index(UserIndexEvent::AccountDeleted(Box::new(
                         AccountDeleted { noble_id }
                     )));
@@ -38,7 +39,7 @@ mod tests {
     use crate::Data;
     use crate::model::user::User;
     use candid::Principal;
-    use types::{NobleId, JWT};
+    use types::{NobleId, JwtSigningKey, JWT};
     use utils::env::test::TestEnv;
 
     #[test]
@@ -49,7 +50,7 @@ mod tests {
 
         let jwt = JWT::new_for_test(1, runtime_state.env.now());
         let args = Args {
-            jwt: jwt.to_string().unwrap(),
+            jwt: jwt.sign(&JwtSigningKey::for_test()).unwrap(),
         };
         let result = delete_account_impl(args, &mut runtime_state);
         assert_eq!(result, Response::Success);
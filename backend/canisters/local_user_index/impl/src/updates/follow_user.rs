@@ -1,6 +1,7 @@
 use crate::{mutate_state, RuntimeState, read_state};
 use ic_cdk_macros::update;
 use local_user_index_canister::follow_user::{Response::*, *};
+use local_user_index_canister::NotificationKind;
 use types::{check_jwt, NobleId};
 use user_index_canister::{Event as UserIndexEvent, FollowUser};
 
@@ -29,7 +30,10 @@ async fn follow_user(args: Args) -> Response {
 }
 
 fn follow_user_impl(noble_id: NobleId, args: Args, state: &mut RuntimeState) -> Response {
-    if let Some(sender) = state.data.users.get_mut(noble_id) {
+    if let Some(suspension) = state.data.suspended_users.get(noble_id, state.env.now()) {
+        return Suspended { until: suspension.until };
+    }
+    if let Some(mut sender) = state.data.users.get(noble_id) {
         let sender_id = noble_id;
         let receiver_id = args.noble_id;
         if sender.is_following(receiver_id) {
@@ -41,14 +45,19 @@ fn follow_user_impl(noble_id: NobleId, args: Args, state: &mut RuntimeState) ->
         }
 
         sender.add_following_user(args.noble_id);
+        state.data.users.update(&sender);
 
-        if let Some(receiver) = state.data.users.get_mut(receiver_id) {
-            receiver.add_follower(sender_id);
-        } else {
-            state.push_event_to_user_index(UserIndexEvent::UserFollowed(Box::new(
-                FollowUser { sender_id, receiver_id }
-            )));
+        if let Some(mut receiver) = state.data.users.get(receiver_id) {
+            if receiver.add_follower(sender_id) {
+                state.data.users.update(&receiver);
+                state.notify(receiver_id, sender_id, NotificationKind::Followed);
+            }
         }
+        // user_index passes it on to the receiver's canister, when that's another one, and to the
+        // post canisters, which check follows and blocks themselves.
+        state.push_event_to_user_index(UserIndexEvent::UserFollowed(Box::new(
+            FollowUser { sender_id, receiver_id }
+        )));
         Success
     } else {
         UserNotFound
@@ -61,7 +70,7 @@ mod tests {
     use crate::Data;
     use crate::model::user::User;
     use candid::Principal;
-    use types::{NobleId, JWT};
+    use types::{NobleId, Suspension, JwtSigningKey, JWT};
     use utils::env::test::TestEnv;
 
     #[test]
@@ -70,7 +79,7 @@ mod tests {
 
         let jwt = JWT::new_for_test(1, runtime_state.env.now());
         let args = Args {
-            jwt: jwt.to_string().unwrap(),
+            jwt: jwt.sign(&JwtSigningKey::for_test()).unwrap(),
             noble_id: 3,
         };
         assert_eq!(runtime_state.data.users.get(1).unwrap().is_following(3), false);
@@ -79,6 +88,7 @@ mod tests {
         assert_eq!(result, Response::Success);
         assert_eq!(runtime_state.data.users.get(1).unwrap().is_following(3), true);
         assert_eq!(runtime_state.data.users.get(3).unwrap().is_follower(1), true);
+        assert_eq!(runtime_state.data.notifications.unread_count(3), 1);
     }
 
     #[test]
@@ -87,7 +97,7 @@ mod tests {
 
         let jwt = JWT::new_for_test(1, runtime_state.env.now());
         let args = Args {
-            jwt: jwt.to_string().unwrap(),
+            jwt: jwt.sign(&JwtSigningKey::for_test()).unwrap(),
             noble_id: 4,
         };
         let result = follow_user_impl(jwt.noble_id, args, &mut runtime_state);
@@ -100,13 +110,29 @@ mod tests {
             ..Default::default()
         };
         let args = Args {
-            jwt: jwt.to_string().unwrap(),
+            jwt: jwt.sign(&JwtSigningKey::for_test()).unwrap(),
             noble_id: 4,
         };
         let result = follow_user_impl(jwt.noble_id, args, &mut runtime_state);
         assert_eq!(result, Response::AlreadyFollowing);
     }
 
+    #[test]
+    fn suspended_users_cannot_follow() {
+        let mut runtime_state = setup_runtime_state();
+        let now = runtime_state.env.now();
+        runtime_state.data.suspended_users.suspend(1, Suspension { until: Some(now + 1000), hide_content: false });
+
+        let jwt = JWT::new_for_test(1, now);
+        let args = Args {
+            jwt: jwt.sign(&JwtSigningKey::for_test()).unwrap(),
+            noble_id: 3,
+        };
+        let result = follow_user_impl(jwt.noble_id, args, &mut runtime_state);
+        assert_eq!(result, Response::Suspended { until: Some(now + 1000) });
+        assert_eq!(runtime_state.data.users.get(1).unwrap().is_following(3), false);
+    }
+
     fn setup_runtime_state() -> RuntimeState {
         let mut env = TestEnv::default();
         let mut data = Data::default();
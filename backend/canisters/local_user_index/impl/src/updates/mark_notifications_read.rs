@@ -0,0 +1,22 @@
+use crate::{mutate_state, RuntimeState};
+use ic_cdk_macros::update;
+use local_user_index_canister::mark_notifications_read::{Response::*, *};
+use types::check_jwt;
+
+#[update]
+fn mark_notifications_read(args: Args) -> Response {
+    mutate_state(|state| mark_notifications_read_impl(&args, state))
+}
+
+fn mark_notifications_read_impl(args: &Args, state: &mut RuntimeState) -> Response {
+    if let Some(jwt) = check_jwt(&args.jwt, state.env.now()) {
+        if state.data.users.get(jwt.noble_id).is_some() {
+            state.data.notifications.mark_read(jwt.noble_id, args.up_to);
+            Success
+        } else {
+            UserNotFound
+        }
+    } else {
+        PermissionDenied
+    }
+}
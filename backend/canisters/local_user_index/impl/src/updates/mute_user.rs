@@ -13,7 +13,7 @@ fn mute_user_impl(args: Args, state: &mut RuntimeState) -> Response {
         if state.data.users.get(args.noble_id).is_none() {
             return UserNotFound;
         }
-        if let Some(user) = state.data.users.get_mut(jwt.noble_id) {
+        if let Some(mut user) = state.data.users.get(jwt.noble_id) {
             if user.is_muted(args.noble_id) {
                 return AlreadyMuted;
             }
@@ -23,6 +23,7 @@ fn mute_user_impl(args: Args, state: &mut RuntimeState) -> Response {
             }
     
             user.mute_user(args.noble_id);
+            state.data.users.update(&user);
 
             Success
         } else {
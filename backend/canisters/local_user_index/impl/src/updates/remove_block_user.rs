@@ -29,7 +29,7 @@ async fn remove_block_user(args: Args) -> Response {
 }
 
 fn remove_block_user_impl(noble_id: NobleId, args: Args, state: &mut RuntimeState) -> Response {
-    if let Some(sender) = state.data.users.get_mut(noble_id) {
+    if let Some(mut sender) = state.data.users.get(noble_id) {
         let sender_id = noble_id;
         let receiver_id = args.noble_id;
 
@@ -38,14 +38,15 @@ fn remove_block_user_impl(noble_id: NobleId, args: Args, state: &mut RuntimeStat
         }
 
         sender.remove_block_user(receiver_id);
+        state.data.users.update(&sender);
 
-        if let Some(receiver) = state.data.users.get_mut(receiver_id) {
+        if let Some(mut receiver) = state.data.users.get(receiver_id) {
             receiver.remove_block_me_user(sender_id);
-        } else {
-            state.push_event_to_user_index(UserIndexEvent::UserUnblocked(Box::new(
-                BlockUser { sender_id, receiver_id }
-            )));
+            state.data.users.update(&receiver);
         }
+        state.push_event_to_user_index(UserIndexEvent::UserUnblocked(Box::new(
+            BlockUser { sender_id, receiver_id }
+        )));
         Success
     } else {
         UserNotFound
@@ -58,7 +59,7 @@ mod tests {
     use crate::Data;
     use crate::model::user::User;
     use candid::Principal;
-    use types::{NobleId, JWT};
+    use types::{NobleId, JwtSigningKey, JWT};
     use utils::env::test::TestEnv;
 
     #[test]
@@ -67,7 +68,7 @@ mod tests {
 
         let jwt = JWT::new_for_test(1, runtime_state.env.now());
         let args = Args {
-            jwt: jwt.to_string().unwrap(),
+            jwt: jwt.sign(&JwtSigningKey::for_test()).unwrap(),
             noble_id: 4,
         };
         let result = remove_block_user_impl(jwt.noble_id, args, &mut runtime_state);
@@ -10,8 +10,9 @@ fn remove_bookmark(args: Args) -> Response {
 
 fn remove_bookmark_impl(args: Args, state: &mut RuntimeState) -> Response {
     if let Some(jwt) = check_jwt(&args.jwt, state.env.now()) {
-        if let Some(user) = state.data.users.get_mut(jwt.noble_id) {
+        if let Some(mut user) = state.data.users.get(jwt.noble_id) {
             if user.remove_bookmark(args.post_id) {
+                state.data.users.update(&user);
                 Success
             } else {
                 BookmarkNotFound
@@ -9,7 +9,7 @@ use utils::username_validation::{validate_username, UsernameValidationError};
 #[update]
 async fn set_account(args: Args) -> Response {
     if let Some(jwt) = check_jwt(&args.jwt, read_state(|state| state.env.now())) {
-        let (username_case_insensitive_changed, email_changed, user_index_canister_id) = match read_state(|state| prepare(jwt.noble_id, &args, state)) {
+        let (username_case_insensitive_changed, user_index_canister_id) = match read_state(|state| prepare(jwt.noble_id, &args, state)) {
             Ok(ok) => ok,
             Err(error) => return error,
         };
@@ -34,22 +34,6 @@ async fn set_account(args: Args) -> Response {
             }
         }
 
-        if email_changed {
-            match user_index_canister_c2c_client::check_email(
-                user_index_canister_id,
-                &user_index_canister::check_email::Args{email: args.email.clone()}
-            ).await {
-                Ok(response) => {
-                    match response {
-                        user_index_canister::check_email::Response::Success => {},
-                        user_index_canister::check_email::Response::EmailTaken => error.email = format!("Email already exists."),
-                        user_index_canister::check_email::Response::EmailIsInvalid => error.email = format!("Email is invalid."),
-                    }
-                },
-                Err(error) => return InternalError(format!("{:?}", error)),
-            }
-        }
-
         if error.is_error() {
             return Error(error);
         }
@@ -60,7 +44,7 @@ async fn set_account(args: Args) -> Response {
     }
 }
 
-fn prepare(noble_id: NobleId, args: &Args, state: &RuntimeState) -> Result<(bool, bool, Principal), Response> {
+fn prepare(noble_id: NobleId, args: &Args, state: &RuntimeState) -> Result<(bool, Principal), Response> {
     let username = &args.username;
 
     let mut error = ErrorResult::new();
@@ -72,36 +56,34 @@ fn prepare(noble_id: NobleId, args: &Args, state: &RuntimeState) -> Result<(bool
         Err(UsernameValidationError::Invalid) => error.username = format!("Username is invalid."),
     };
 
-    if !email_address::EmailAddress::is_valid(&args.email) {
-        error.email = format!("Email is invalid.");
-    }
+    if let Some(user) = state.data.users.get(noble_id) {
+        // A new address has to be verified first, see user_index's `change_email`.
+        if args.email != user.email {
+            error.email = format!("Email can only be changed once the new address is verified.");
+        }
 
-    if error.is_error() {
-        return Err(Error(error));
-    }
+        if error.is_error() {
+            return Err(Error(error));
+        }
 
-    if let Some(user) = state.data.users.get(noble_id) {
         let username_case_insensitive_changed = username.to_uppercase() != user.username.to_uppercase();
 
-        let email_changed = args.email != user.email;
-
-        Ok((username_case_insensitive_changed, email_changed, state.data.user_index_canister_id))
+        Ok((username_case_insensitive_changed, state.data.user_index_canister_id))
     } else {
         Err(UserNotFound)
     }
 }
 
 fn set_account_impl(noble_id: NobleId, args: Args, state: &mut RuntimeState) -> Response {
-    if let Some(user) = state.data.users.get_mut(noble_id) {
+    if let Some(mut user) = state.data.users.get(noble_id) {
         user.username = args.username.clone();
-        user.email = args.email.clone();
         user.search_by_email = args.search_by_email;
         user.account_privacy = args.account_privacy;
+        state.data.users.update(&user);
         state.push_event_to_user_index(UserIndexEvent::AccountChanged(Box::new(
             AccountChanged {
                 noble_id,
                 username: args.username,
-                email: args.email,
                 search_by_email: args.search_by_email,
             }
         )));
@@ -37,15 +37,15 @@ mod tests {
     use crate::model::user::User;
     use candid::Principal;
     use utils::env::test::TestEnv;
-    use types::{AccountPrivacy, NobleId, JWT};
+    use types::{AccountPrivacy, NobleId, JwtSigningKey, JWT};
 
     #[test]
     fn set_account_privacy_test() {
         let mut runtime_state = setup_runtime_state();
 
-        let jwt = JWT::new(1, "".to_string(), "".to_string(), runtime_state.env.now());
+        let jwt = JWT::new_for_test(1, runtime_state.env.now());
         let args = Args {
-            jwt: jwt.to_string().unwrap(),
+            jwt: jwt.sign(&JwtSigningKey::for_test()).unwrap(),
             account_privacy: AccountPrivacy::AnyBodyCanView,
         };
         let result = set_account_privacy_impl(args, &mut runtime_state);
@@ -54,9 +54,9 @@ mod tests {
         let user = runtime_state.data.users.get(jwt.noble_id).unwrap();
         assert_eq!(user.account_privacy, AccountPrivacy::AnyBodyCanView);
 
-        let jwt = JWT::new(1, "".to_string(), "".to_string(), runtime_state.env.now());
+        let jwt = JWT::new_for_test(1, runtime_state.env.now());
         let args = Args {
-            jwt: jwt.to_string().unwrap(),
+            jwt: jwt.sign(&JwtSigningKey::for_test()).unwrap(),
             account_privacy: AccountPrivacy::ApprovedFollowersCanView,
         };
         let result = set_account_privacy_impl(args, &mut runtime_state);
@@ -43,16 +43,16 @@ mod tests {
     use crate::Data;
     use crate::model::user::User;
     use candid::Principal;
-    use types::{NobleId, JWT};
+    use types::{NobleId, JwtSigningKey, JWT};
     use utils::env::test::TestEnv;
 
     #[test]
     fn set_location_test() {
         let mut runtime_state = setup_runtime_state();
 
-        let jwt = JWT::new(1, "".to_string(), "".to_string(), runtime_state.env.now());
+        let jwt = JWT::new_for_test(1, runtime_state.env.now());
         let args = Args {
-            jwt: jwt.to_string().unwrap(),
+            jwt: jwt.sign(&JwtSigningKey::for_test()).unwrap(),
             country: "UA".to_string(),
             city: "Kyiv".to_string(),
         };
@@ -1,4 +1,5 @@
 use crate::{mutate_state, RuntimeState, MAX_PHOTO_SIZE};
+use avatar::{process_avatar, AvatarError, MAX_DIMENSION};
 use ic_cdk_macros::update;
 use local_user_index_canister::set_photo::{Response::*, *};
 use user_index_canister::{Event as UserIndexEvent, PhotoChanged};
@@ -11,35 +12,38 @@ fn set_photo(args: Args) -> Response {
 
 fn set_photo_impl(args: Args, state: &mut RuntimeState) -> Response {
     if let Some(jwt) = check_jwt(&args.jwt, state.env.now()) {
-        match prepare(&args) {
-            Ok(()) => {},
+        let avatar = match prepare(&args) {
+            Ok(avatar) => avatar,
             Err(error) => return error,
+        };
+
+        if state.data.users.get(jwt.noble_id).is_none() {
+            return UserNotFound;
         }
 
-        state.data.users.update_avatar_id(jwt.noble_id, state.env.rng());
+        // A new id for every photo, so each one can be cached for good.
+        let avatar_id = state.data.users.update_avatar_id(jwt.noble_id, state.env.rng());
+        state.data.users.set_avatar(jwt.noble_id, &avatar);
 
-        if let Some(user) = state.data.users.get_mut(jwt.noble_id) {
-            user.photo = args.photo;
+        if let Some(mut user) = state.data.users.get(jwt.noble_id) {
             user.date_updated = state.env.now();
-            let avatar_id = user.avatar_id;
-
-            state.push_event_to_user_index(UserIndexEvent::PhotoChanged(Box::new(
-                PhotoChanged {
-                    noble_id: jwt.noble_id,
-                    avatar_id,
-                }
-            )));
-
-            Success(avatar_id)
-        } else {
-            UserNotFound
+            state.data.users.update(&user);
         }
+
+        state.push_event_to_user_index(UserIndexEvent::PhotoChanged(Box::new(
+            PhotoChanged {
+                noble_id: jwt.noble_id,
+                avatar_id,
+            }
+        )));
+
+        Success(avatar_id)
     } else {
         PermissionDenied
     }
 }
 
-fn prepare(args: &Args) -> Result<(), Response> {
+fn prepare(args: &Args) -> Result<Avatar, Response> {
     let mut error = ErrorResult::new();
 
     if args.photo.is_empty() {
@@ -51,8 +55,22 @@ fn prepare(args: &Args) -> Result<(), Response> {
     }
 
     if error.is_error() {
-        Err(Error(error))
-    } else {
-        Ok(())
+        return Err(Error(error));
+    }
+
+    match process_avatar(&args.photo) {
+        Ok(avatar) => Ok(avatar),
+        Err(AvatarError::UnsupportedFormat) => {
+            error.photo = format!("Photo should be a PNG, JPEG or WebP image.");
+            Err(Error(error))
+        }
+        Err(AvatarError::TooLarge) => {
+            error.photo = format!("Photo should be at most {MAX_DIMENSION} pixels wide and high.");
+            Err(Error(error))
+        }
+        Err(AvatarError::Invalid) => {
+            error.photo = format!("Photo could not be read.");
+            Err(Error(error))
+        }
     }
 }
@@ -17,7 +17,7 @@ fn set_profile_impl(args: Args, state: &mut RuntimeState) -> Response {
             Err(error) => return error,
         }
 
-        if let Some(user) = state.data.users.get_mut(jwt.noble_id) {
+        if let Some(mut user) = state.data.users.get(jwt.noble_id) {
             user.first_name = args.first_name.clone();
             user.last_name = args.last_name.clone();
             user.gender = args.gender;
@@ -36,6 +36,7 @@ fn set_profile_impl(args: Args, state: &mut RuntimeState) -> Response {
             let avatar_id = user.avatar_id;
 
             user.date_updated = state.env.now();
+            state.data.users.update(&user);
 
             state.push_event_to_user_index(UserIndexEvent::ProfileChanged(Box::new(
                 ProfileChanged {
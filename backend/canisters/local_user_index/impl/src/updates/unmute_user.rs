@@ -10,12 +10,13 @@ fn unmute_user(args: Args) -> Response {
 
 fn unmute_user_impl(args: Args, state: &mut RuntimeState) -> Response {
     if let Some(jwt) = check_jwt(&args.jwt, state.env.now()) {
-        if let Some(user) = state.data.users.get_mut(jwt.noble_id) {
+        if let Some(mut user) = state.data.users.get(jwt.noble_id) {
             if !user.is_muted(args.noble_id) {
                 return UserNotFound;
             }
     
             user.unmute_user(args.noble_id);
+            state.data.users.update(&user);
 
             Success
         } else {
@@ -8,7 +8,7 @@ mod updates;
 
 pub use lifecycle::*;
 pub use queries::*;
-use types::{TimestampMillis, NobleId, PostId, PostPrivacy, CanisterId};
+use types::{TimestampMillis, NobleId, PostId, PostPrivacy, CanisterId, JwtVerificationKey, SessionRevocation, FileId, CommentId, ModerationAction, ReportReason, RoleGrant, Suspension};
 pub use updates::*;
 
 
@@ -20,7 +20,60 @@ pub enum Event {
     PostUnliked(Box<PostUnliked>),
     PostEdited(Box<PostEdited>),
     PostDeleted(Box<PostDeleted>),
+    PostTrashed(Box<PostTrashed>),
+    PostRestored(Box<PostRestored>),
+    PostFileAttached(Box<PostFileAttached>),
     LocalUserIndexAdded(Box<LocalUserIndexAdded>),
+    JwtKeysUpdated(Box<JwtKeysUpdated>),
+    SessionRevoked(Box<SessionRevoked>),
+    UserFollowed(Box<FollowUser>),
+    UserUnfollowed(Box<FollowUser>),
+    UserBlocked(Box<BlockUser>),
+    UserUnblocked(Box<BlockUser>),
+    ContentModerated(Box<ContentModerated>),
+    RolesUpdated(Box<RolesUpdated>),
+    UserSuspended(Box<UserSuspended>),
+    SuspensionLifted(Box<SuspensionLifted>),
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct FollowUser {
+    pub sender_id: NobleId,
+    pub receiver_id: NobleId,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct BlockUser {
+    pub sender_id: NobleId,
+    pub receiver_id: NobleId,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct JwtKeysUpdated {
+    pub keys: Vec<JwtVerificationKey>,
+}
+
+// Every role grant, replacing the ones the canister had.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct RolesUpdated {
+    pub grants: Vec<RoleGrant>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct UserSuspended {
+    pub noble_id: NobleId,
+    pub suspension: Suspension,
+}
+
+// Lifted by a moderator or because it's over.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SuspensionLifted {
+    pub noble_id: NobleId,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SessionRevoked {
+    pub revocation: SessionRevocation,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -33,6 +86,33 @@ pub struct PostDeleted {
     pub post_id: PostId,
 }
 
+// The post was deleted but can still be restored, it's hidden until then.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct PostTrashed {
+    pub post_id: PostId,
+}
+
+// A moderator resolved a report about the post, or one of its comments when `comment_id` is set.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ContentModerated {
+    pub post_id: PostId,
+    pub comment_id: Option<CommentId>,
+    pub action: ModerationAction,
+    pub reason: ReportReason,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct PostRestored {
+    pub post_id: PostId,
+}
+
+// The author finished uploading a file for the post, replacing any it had.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct PostFileAttached {
+    pub post_id: PostId,
+    pub attached_file_id: FileId,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct PostEdited {
     pub post_id: PostId,
@@ -57,6 +137,9 @@ pub struct PostLiked {
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct CommentDeleted {
     pub post_id: PostId,
+    // Who wrote the comment. 0 in events sent before it was added.
+    #[serde(default)]
+    pub noble_id: NobleId,
     pub comments_count: u32,
 }
 
@@ -0,0 +1,14 @@
+use candid::CandidType;
+use serde::{Serialize, Deserialize};
+use types::{CanisterId, PostId};
+
+#[derive(CandidType, Serialize, Deserialize, Debug)]
+pub struct Args {
+    pub post_id: PostId,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Debug)]
+pub enum Response {
+    Success(CanisterId),
+    PostNotFound,
+}
@@ -0,0 +1,46 @@
+use candid::CandidType;
+use serde::{Serialize, Deserialize};
+use types::{TimestampMillis, PostSummary, NobleId, PostId};
+
+#[derive(CandidType, Serialize, Deserialize, Debug)]
+pub struct Args {
+    pub jwt: String,
+    // The `next_cursor` of the previous page, None for the first.
+    pub cursor: Option<String>,
+    pub limit: u32,
+    // Followed users the caller has muted, they're left out of the feed.
+    pub muted_users: Vec<NobleId>,
+    // Also show posts that followed users commented on.
+    pub include_comments: bool,
+    pub liked_posts: Vec<PostId>,
+    pub bookmarks: Vec<PostId>,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Debug)]
+pub enum Response {
+    Success(SuccessResult),
+    PermissionDenied,
+    InvalidCursor,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Debug)]
+pub struct SuccessResult {
+    pub items: Vec<FeedItem>,
+    // None once there are no more posts.
+    pub next_cursor: Option<String>,
+    pub timestamp: TimestampMillis,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Debug)]
+pub struct FeedItem {
+    pub post: PostSummary,
+    pub reason: FeedReason,
+    pub timestamp: TimestampMillis,
+}
+
+// Why the post is in the feed, each post is there once for its latest activity.
+#[derive(CandidType, Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FeedReason {
+    Posted,
+    Commented(NobleId),
+}
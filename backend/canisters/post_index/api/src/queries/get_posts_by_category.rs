@@ -2,21 +2,28 @@ use candid::CandidType;
 use serde::{Serialize, Deserialize};
 use types::{TimestampMillis, Category, PostSummary, NobleId, PostId};
 
-#[derive(CandidType, Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[derive(CandidType, Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum Sort {
     RecentActivity,
     NewestPost,
+    // Likes and comments, weighed down by age.
+    Trending,
+    // Most liked posts created in the last day / week / ever.
+    TopDay,
+    TopWeek,
+    TopAllTime,
+    // Most comments.
+    MostDiscussed,
 }
 
 #[derive(CandidType, Serialize, Deserialize, Debug)]
 pub struct Args {
     pub jwt: String,
-    pub from: u32,
+    // The `next_cursor` of the previous page, None for the first.
+    pub cursor: Option<String>,
     pub limit: u32,
     pub category: Option<Category>,
     pub sort: Sort,
-    pub following_list: Vec<NobleId>,
-    pub block_me_users: Vec<NobleId>,
     pub liked_posts: Vec<NobleId>,
     pub bookmarks: Vec<PostId>,
 }
@@ -25,11 +32,13 @@ pub struct Args {
 pub enum Response {
     Success(SuccessResult),
     PermissionDenied,
+    InvalidCursor,
 }
 
 #[derive(CandidType, Serialize, Deserialize, Debug)]
 pub struct SuccessResult {
-    pub total_posts_count: u32,
     pub posts: Vec<PostSummary>,
+    // None once there are no more posts.
+    pub next_cursor: Option<String>,
     pub timestamp: TimestampMillis,
 }
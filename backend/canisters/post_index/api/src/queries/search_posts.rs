@@ -0,0 +1,40 @@
+use candid::CandidType;
+use serde::{Serialize, Deserialize};
+use types::{TimestampMillis, Category, PostSummary, PostId};
+
+#[derive(CandidType, Serialize, Deserialize, Debug)]
+pub struct Args {
+    pub jwt: String,
+    pub query: String,
+    pub from: u32,
+    pub limit: u32,
+    pub category: Option<Category>,
+    pub liked_posts: Vec<PostId>,
+    pub bookmarks: Vec<PostId>,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Debug)]
+pub enum Response {
+    Success(SuccessResult),
+}
+
+#[derive(CandidType, Serialize, Deserialize, Debug)]
+pub struct SuccessResult {
+    pub total_posts_count: u32,
+    pub posts: Vec<SearchResult>,
+    pub timestamp: TimestampMillis,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Debug)]
+pub struct SearchResult {
+    pub post: PostSummary,
+    pub title_highlights: Vec<TextRange>,
+    pub description_highlights: Vec<TextRange>,
+}
+
+// Character offsets of a matched word, `end` is exclusive.
+#[derive(CandidType, Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub struct TextRange {
+    pub start: u32,
+    pub end: u32,
+}
@@ -2,7 +2,7 @@ use std::collections::HashSet;
 
 use candid::CandidType;
 use serde::{Deserialize, Serialize};
-use types::{NobleId, Category, PostPrivacy, FileId, CanisterId, PostId};
+use types::{NobleId, Category, PostPrivacy, FileId, CanisterId, PostId, TimestampMillis};
 
 #[derive(CandidType, Serialize, Deserialize, Debug)]
 pub struct Args {
@@ -22,6 +22,7 @@ pub enum Response {
     Success(CanisterId, PostId),
     PermissionDenied,
     PostLimitReached,
+    Suspended { until: Option<TimestampMillis> },
     InternalError(String),
     Error(ErrorResult),
 }
@@ -0,0 +1,30 @@
+use candid::CandidType;
+use human_readable::ToHumanReadable;
+use serde::{Deserialize, Serialize};
+use types::Milliseconds;
+
+#[derive(CandidType, Serialize, Deserialize, Debug)]
+pub struct Args {
+    // How long after creation authors can edit a post or comment, None for no limit.
+    pub edit_window: Option<Milliseconds>,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Debug)]
+pub enum Response {
+    Success,
+}
+
+#[derive(Serialize)]
+pub struct HumanReadableArgs {
+    edit_window: Option<Milliseconds>,
+}
+
+impl ToHumanReadable for Args {
+    type Target = HumanReadableArgs;
+
+    fn to_human_readable(&self) -> Self::Target {
+        HumanReadableArgs {
+            edit_window: self.edit_window,
+        }
+    }
+}
@@ -1,7 +1,8 @@
 use crate::read_state;
+use utils::rbac::Permission;
 
-pub fn caller_is_governance_principal() -> Result<(), String> {
-    if read_state(|state| state.caller_is_governance_principal()) {
+pub fn caller_has_permission(permission: Permission) -> Result<(), String> {
+    if read_state(|state| state.caller_has_permission(permission)) {
         Ok(())
     } else {
         Err("Permission Denied".to_owned())
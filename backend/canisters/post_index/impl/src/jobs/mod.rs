@@ -1,10 +1,12 @@
 use crate::RuntimeState;
 
+pub mod refresh_post_rankings;
 pub mod upgrade_canisters;
 pub mod sync_events_to_local_post_index_canisters;
 pub mod sync_events_to_user_index_canister;
 
 pub(crate) fn start(state: &RuntimeState) {
+    refresh_post_rankings::start_job_if_required(state);
     upgrade_canisters::start_job_if_required(state);
     sync_events_to_local_post_index_canisters::start_job_if_required(state);
     sync_events_to_user_index_canister::start_job_if_required(state);
@@ -0,0 +1,36 @@
+use crate::{mutate_state, RuntimeState};
+use ic_cdk_timers::TimerId;
+use std::cell::Cell;
+use std::time::Duration;
+use tracing::trace;
+
+// Likes and comments update a post's rankings as they come in, this takes posts out of the top of
+// the day and week as they age.
+const INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+thread_local! {
+    static TIMER_ID: Cell<Option<TimerId>> = Cell::default();
+}
+
+pub(crate) fn start_job_if_required(_state: &RuntimeState) -> bool {
+    if TIMER_ID.with(|t| t.get().is_none()) {
+        let timer_id = ic_cdk_timers::set_timer_interval(INTERVAL, run);
+        TIMER_ID.with(|t| t.set(Some(timer_id)));
+        // The rankings rebuilt after an upgrade don't know the time yet.
+        ic_cdk_timers::set_timer(Duration::ZERO, run);
+        trace!("'refresh_post_rankings' job started");
+        true
+    } else {
+        false
+    }
+}
+
+fn run() {
+    mutate_state(refresh_post_rankings);
+}
+
+fn refresh_post_rankings(state: &mut RuntimeState) {
+    let now = state.env.now();
+    let expired = state.data.posts.refresh_rankings(now);
+    trace!(expired, "Post rankings refreshed");
+}
@@ -1,12 +1,12 @@
-use std::{cell::RefCell, collections::HashSet};
+use std::{cell::RefCell, collections::{BTreeMap, HashSet}};
 
 use canister_state_macros::canister_state;
 use candid::{Principal, CandidType};
 use local_post_index_canister::Event as LocalPostIndexEvent;
 use model::{post_map::PostMap, local_post_index_map::{LocalPostIndexMap, LocalPostIndex}};
 use serde::{Deserialize, Serialize};
-use types::{CanisterId, TimestampMillis, Cycles, Timestamped, Version, CanisterWasm, NobleId};
-use utils::{env::Environment, canister::{CanistersRequiringUpgrade, FailedUpgradeCount}, consts::{DEV_TEAM_PRINCIPAL, CYCLES_REQUIRED_FOR_UPGRADE}, canister_event_sync_queue::CanisterEventSyncQueue};
+use types::{CanisterId, TimestampMillis, Cycles, Timestamped, Version, CanisterWasm, Milliseconds, JwtVerificationKey, SessionRevocations, SocialGraph, Role, SuspendedUsers};
+use utils::{env::Environment, canister::{CanistersRequiringUpgrade, FailedUpgradeCount}, consts::CYCLES_REQUIRED_FOR_UPGRADE, canister_event_sync_queue::CanisterEventSyncQueue, rbac::{Permission, Roles}};
 use user_index_canister::Event as UserIndexEvent;
 
 mod jobs;
@@ -42,10 +42,8 @@ impl RuntimeState {
         RuntimeState { env, data }
     }
 
-    pub fn caller_is_governance_principal(&self) -> bool {
-        let caller = ic_cdk::caller();
-        DEV_TEAM_PRINCIPAL == caller ||
-        self.data.super_admin == caller
+    pub fn caller_has_permission(&self, permission: Permission) -> bool {
+        self.data.roles.principal_has_permission(self.env.caller(), permission)
     }
 
     pub fn caller_is_known_canister(&self) -> bool {
@@ -63,6 +61,12 @@ impl RuntimeState {
         jobs::sync_events_to_local_post_index_canisters::start_job_if_required(self);
     }
 
+    pub fn push_event_to_local_post_index(&mut self, canister_id: CanisterId, event: LocalPostIndexEvent) {
+        self.data.post_index_event_sync_queue.push(canister_id, event);
+        #[cfg(not(test))]
+        jobs::sync_events_to_local_post_index_canisters::start_job_if_required(self);
+    }
+
     pub fn push_event_to_user_index(&mut self, event: UserIndexEvent) {
         self.data
         .user_index_event_sync_queue
@@ -84,8 +88,7 @@ impl RuntimeState {
             canister_upgrades_pending: canister_upgrades_metrics.pending as u64,
             canister_upgrades_in_progress: canister_upgrades_metrics.in_progress as u64,
             local_post_index_wasm_version: self.data.local_post_index_canister_wasm_for_new_canisters.version,
-            platform_moderators: self.data.platform_moderators.len() as u8,
-            platform_operators: self.data.platform_operators.len() as u8,
+            role_counts: self.data.roles.counts(),
             local_post_indexes: self.data.local_index_map.iter().map(|(c, i)| (*c, i.clone())).collect(),
             total_cycles_spent_on_canisters: self.data.total_cycles_spent_on_canisters,
             canister_ids: CanisterIds {
@@ -108,10 +111,9 @@ struct Data {
     pub local_post_index_canister_wasm_for_upgrades: CanisterWasm,
     #[serde(default)]
     pub canisters_requiring_upgrade: CanistersRequiringUpgrade,
+    // A copy of the grants kept by user_index, passed on to the local post indexes.
     #[serde(default)]
-    pub platform_moderators: HashSet<NobleId>,
-    #[serde(default)]
-    pub platform_operators: HashSet<NobleId>,
+    pub roles: Roles,
     #[serde(default)]
     pub total_cycles_spent_on_canisters: Cycles,
     #[serde(default = "default_local_user_index_canister_ids")]
@@ -120,6 +122,19 @@ struct Data {
     pub post_index_event_sync_queue: CanisterEventSyncQueue<LocalPostIndexEvent>,
     #[serde(default)]
     pub user_index_event_sync_queue: CanisterEventSyncQueue<UserIndexEvent>,
+    #[serde(default)]
+    pub jwt_verification_keys: Vec<JwtVerificationKey>,
+    #[serde(default)]
+    pub session_revocations: SessionRevocations,
+    // Passed on to every local post index, see `set_edit_window`.
+    #[serde(default)]
+    pub edit_window: Option<Milliseconds>,
+    // Follows and blocks from user_index, passed on to the local post indexes.
+    #[serde(default)]
+    pub social_graph: SocialGraph,
+    // Suspensions from user_index, passed on to the local post indexes.
+    #[serde(default)]
+    pub suspended_users: SuspendedUsers,
 }
 
 fn default_local_user_index_canister_ids() -> HashSet<CanisterId>{
@@ -133,6 +148,7 @@ impl Data {
         user_index_canister_id: CanisterId,
         local_user_index_canister_ids: HashSet<CanisterId>,
         super_admin: Principal,
+        now: TimestampMillis,
     ) -> Self {
         Data {
             posts: PostMap::default(),
@@ -143,11 +159,15 @@ impl Data {
             local_post_index_canister_wasm_for_new_canisters: CanisterWasm::default(),
             local_post_index_canister_wasm_for_upgrades: CanisterWasm::default(),
             canisters_requiring_upgrade: CanistersRequiringUpgrade::default(),
-            platform_moderators: HashSet::default(),
-            platform_operators: HashSet::default(),
+            roles: Roles::new(super_admin, now),
             total_cycles_spent_on_canisters: Cycles::default(),
             post_index_event_sync_queue: CanisterEventSyncQueue::default(),
             user_index_event_sync_queue: CanisterEventSyncQueue::default(),
+            jwt_verification_keys: Vec::default(),
+            session_revocations: SessionRevocations::default(),
+            edit_window: None,
+            social_graph: SocialGraph::default(),
+            suspended_users: SuspendedUsers::default(),
         }
     }
 }
@@ -164,11 +184,15 @@ impl Default for Data {
             local_post_index_canister_wasm_for_new_canisters: CanisterWasm::default(),
             local_post_index_canister_wasm_for_upgrades: CanisterWasm::default(),
             canisters_requiring_upgrade: CanistersRequiringUpgrade::default(),
-            platform_moderators: HashSet::default(),
-            platform_operators: HashSet::default(),
+            roles: Roles::default(),
             total_cycles_spent_on_canisters: Cycles::default(),
             post_index_event_sync_queue: CanisterEventSyncQueue::default(),
             user_index_event_sync_queue: CanisterEventSyncQueue::default(),
+            jwt_verification_keys: Vec::default(),
+            session_revocations: SessionRevocations::default(),
+            edit_window: None,
+            social_graph: SocialGraph::default(),
+            suspended_users: SuspendedUsers::default(),
         }
     }
 }
@@ -185,8 +209,7 @@ pub struct Metrics {
     pub canister_upgrades_in_progress: u64,
     pub wasm_version: Version,
     pub local_post_index_wasm_version: Version,
-    pub platform_moderators: u8,
-    pub platform_operators: u8,
+    pub role_counts: BTreeMap<Role, usize>,
     pub local_post_indexes: Vec<(CanisterId, LocalPostIndex)>,
     pub total_cycles_spent_on_canisters: Cycles,
     pub canister_ids: CanisterIds,
@@ -14,6 +14,7 @@ fn init(args: Args) {
         args.user_index_canister_id,
         args.local_user_index_canister_ids,
         args.super_admin,
+        env.now(),
     );
     for canister_id in args.local_post_index_canister_ids {
         data.local_index_map.add_index(canister_id, Version::default());
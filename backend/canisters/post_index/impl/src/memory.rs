@@ -4,6 +4,8 @@ use ic_stable_structures::{
 };
 
 const UPGRADES: MemoryId = MemoryId::new(0);
+const POSTS_INDEX: MemoryId = MemoryId::new(1);
+const POSTS: MemoryId = MemoryId::new(2);
 
 pub type Memory = VirtualMemory<DefaultMemoryImpl>;
 
@@ -16,6 +18,10 @@ pub fn get_upgrades_memory() -> Memory {
     get_memory(UPGRADES)
 }
 
+pub fn get_posts_memories() -> (Memory, Memory) {
+    (get_memory(POSTS_INDEX), get_memory(POSTS))
+}
+
 fn get_memory(id: MemoryId) -> Memory {
     MEMORY_MANAGER.with(|m| m.get(id))
 }
@@ -0,0 +1,144 @@
+use crate::model::post::Post;
+use crate::model::sorted_index::SortKey;
+use serde::{Serialize, Deserialize};
+use std::cmp::Reverse;
+use std::collections::btree_set::Range;
+use std::collections::{BTreeSet, BinaryHeap, HashMap, HashSet};
+use std::ops::Bound::{Excluded, Unbounded};
+use types::{NobleId, PostId, TimestampMillis};
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Activity {
+    Posted,
+    Commented(NobleId),
+}
+
+// Each user's posts and the posts they commented on, newest first, for merging into home feeds.
+// Only who commented where and when is saved, the rest is rebuilt from the posts after an upgrade.
+#[derive(Serialize, Deserialize, Default)]
+#[serde(from = "FeedIndexTrimmed")]
+pub struct FeedIndex {
+    // Post -> commenter -> when they last commented on it.
+    comments: HashMap<PostId, HashMap<NobleId, TimestampMillis>>,
+    #[serde(skip)]
+    posts_by_author: HashMap<NobleId, BTreeSet<SortKey>>,
+    #[serde(skip)]
+    comments_by_user: HashMap<NobleId, BTreeSet<SortKey>>,
+}
+
+#[derive(Deserialize)]
+struct FeedIndexTrimmed {
+    comments: HashMap<PostId, HashMap<NobleId, TimestampMillis>>,
+}
+
+impl From<FeedIndexTrimmed> for FeedIndex {
+    fn from(value: FeedIndexTrimmed) -> Self {
+        let mut feed_index = FeedIndex::default();
+        for (post_id, commenters) in value.comments {
+            for (noble_id, date) in commenters {
+                feed_index.add_comment(post_id, noble_id, date);
+            }
+        }
+        feed_index
+    }
+}
+
+impl FeedIndex {
+    pub fn add_post(&mut self, post: &Post) {
+        self.posts_by_author.entry(post.noble_id).or_default().insert((Reverse(post.date_created), post.post_id));
+    }
+
+    pub fn remove_post(&mut self, post: &Post) {
+        if let Some(keys) = self.posts_by_author.get_mut(&post.noble_id) {
+            keys.remove(&(Reverse(post.date_created), post.post_id));
+        }
+        for (noble_id, date) in self.comments.remove(&post.post_id).unwrap_or_default() {
+            if let Some(keys) = self.comments_by_user.get_mut(&noble_id) {
+                keys.remove(&(Reverse(date), post.post_id));
+            }
+        }
+    }
+
+    pub fn add_comment(&mut self, post_id: PostId, noble_id: NobleId, date: TimestampMillis) {
+        let keys = self.comments_by_user.entry(noble_id).or_default();
+        if let Some(previous) = self.comments.entry(post_id).or_default().insert(noble_id, date) {
+            keys.remove(&(Reverse(previous), post_id));
+        }
+        keys.insert((Reverse(date), post_id));
+    }
+
+    // Takes the post out of the user's comments. The post index only hears of the one comment being
+    // deleted, so any others they left on the post go with it.
+    pub fn remove_comment(&mut self, post_id: PostId, noble_id: NobleId) {
+        let date = match self.comments.get_mut(&post_id).and_then(|commenters| commenters.remove(&noble_id)) {
+            Some(date) => date,
+            None => return,
+        };
+        if let Some(keys) = self.comments_by_user.get_mut(&noble_id) {
+            keys.remove(&(Reverse(date), post_id));
+        }
+    }
+
+    // Everything the users did after `after`, newest first. A post shows up once per user who
+    // posted or commented on it, see `latest_activity`.
+    pub fn iter<'a>(&'a self, users: &HashSet<NobleId>, include_comments: bool, after: Option<SortKey>) -> FeedIter<'a> {
+        let start = after.map_or(Unbounded, Excluded);
+        let mut sources = Vec::new();
+        for noble_id in users {
+            if let Some(keys) = self.posts_by_author.get(noble_id) {
+                sources.push((Activity::Posted, keys.range((start, Unbounded))));
+            }
+            if include_comments {
+                if let Some(keys) = self.comments_by_user.get(noble_id) {
+                    sources.push((Activity::Commented(*noble_id), keys.range((start, Unbounded))));
+                }
+            }
+        }
+        FeedIter::new(sources)
+    }
+
+    // When the users last posted or commented on the post.
+    pub fn latest_activity(&self, post: &Post, users: &HashSet<NobleId>, include_comments: bool) -> Option<TimestampMillis> {
+        let posted = users.contains(&post.noble_id).then_some(post.date_created);
+        let commented = match (include_comments, self.comments.get(&post.post_id)) {
+            (true, Some(commenters)) => commenters
+                .iter()
+                .filter(|(noble_id, _)| users.contains(noble_id))
+                .map(|(_, date)| *date)
+                .max(),
+            _ => None,
+        };
+        posted.max(commented)
+    }
+}
+
+// Merges the users' activity in order, only ever holding the next item of each.
+pub struct FeedIter<'a> {
+    sources: Vec<(Activity, Range<'a, SortKey>)>,
+    heap: BinaryHeap<Reverse<(SortKey, usize)>>,
+}
+
+impl<'a> FeedIter<'a> {
+    fn new(mut sources: Vec<(Activity, Range<'a, SortKey>)>) -> Self {
+        let mut heap = BinaryHeap::with_capacity(sources.len());
+        for (index, (_, keys)) in sources.iter_mut().enumerate() {
+            if let Some(key) = keys.next() {
+                heap.push(Reverse((*key, index)));
+            }
+        }
+        FeedIter { sources, heap }
+    }
+}
+
+impl<'a> Iterator for FeedIter<'a> {
+    type Item = (SortKey, Activity);
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let Reverse((key, index)) = self.heap.pop()?;
+        let (activity, keys) = &mut self.sources[index];
+        if let Some(next) = keys.next() {
+            self.heap.push(Reverse((*next, index)));
+        }
+        Some((key, *activity))
+    }
+}
@@ -1,3 +1,6 @@
+pub mod feed_index;
 pub mod local_post_index_map;
 pub mod post_map;
-pub mod post;
\ No newline at end of file
+pub mod post;
+pub mod search_index;
+pub mod sorted_index;
\ No newline at end of file
@@ -2,7 +2,7 @@ use candid::CandidType;
 use serde::{Deserialize, Serialize};
 use std::collections::HashSet;
 use types::{
-    TimestampMillis, PostId, Category, NobleId, PostPrivacy, FileId, PostSummary, CanisterId
+    TimestampMillis, PostId, Category, NobleId, PostPrivacy, FileId, PostSummary, CanisterId, SocialGraph
 };
 
 #[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
@@ -27,6 +27,9 @@ pub struct Post {
 
     pub date_created: TimestampMillis,
     pub date_last_commented: TimestampMillis,
+    // Deleted in its local_post_index but still restorable.
+    #[serde(default)]
+    pub trashed: bool,
 }
 
 impl Post {
@@ -64,6 +67,7 @@ impl Post {
 
             date_created: now,
             date_last_commented: now,
+            trashed: false,
         }
     }
 
@@ -93,10 +97,9 @@ impl Post {
         &self,
         noble_id: NobleId,
         category: &Option<Category>,
-        following_list: &Vec<NobleId>,
-        block_me_users: &Vec<NobleId>,
+        social_graph: &SocialGraph,
     ) -> bool {
-        if block_me_users.contains(&self.noble_id) {
+        if self.trashed || social_graph.has_blocked(self.noble_id, noble_id) {
             return false;
         }
 
@@ -106,7 +109,7 @@ impl Post {
 
         if self.noble_id == noble_id ||
            self.post_privacy == PostPrivacy::Everyone ||
-          (self.post_privacy == PostPrivacy::Followers && following_list.contains(&self.noble_id)) ||
+          (self.post_privacy == PostPrivacy::Followers && social_graph.is_following(noble_id, self.noble_id)) ||
           (self.post_privacy == PostPrivacy::SpecificUsers && self.invited_users.contains(&noble_id)) {
             if let Some(cate) = category {
                 *cate == self.category
@@ -144,6 +147,7 @@ impl Default for Post {
 
             date_created: 0,
             date_last_commented: 0,
+            trashed: false,
         }
     }
 }
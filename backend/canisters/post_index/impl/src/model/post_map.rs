@@ -1,11 +1,64 @@
+use crate::memory::{get_posts_memories, Memory};
+use crate::model::feed_index::{FeedIndex, FeedIter};
 use crate::model::post::Post;
+use crate::model::search_index::SearchIndex;
+use crate::model::sorted_index::{Listings, SortKey};
+use post_index_canister::get_posts_by_category::Sort;
 use serde::{Serialize, Deserialize};
+use stable_map::StableMap;
 use std::collections::{HashMap, HashSet};
 use types::{TimestampMillis, PostId, Category, NobleId, PostPrivacy, FileId, CanisterId};
 
-#[derive(Serialize, Deserialize, Default)]
+// Posts are kept in stable memory, so upgrading doesn't copy them, only the feed index. Reads
+// return a copy of the post, changes are saved with `update`.
+#[derive(Serialize, Deserialize)]
+#[serde(from = "PostMapTrimmed")]
 pub struct PostMap {
+    #[serde(skip)]
+    posts: StableMap<PostId, Post, Memory>,
+    #[serde(default)]
+    feed_index: FeedIndex,
+    #[serde(skip)]
+    search_index: SearchIndex,
+    #[serde(skip)]
+    listings: Listings,
+}
+
+impl Default for PostMap {
+    fn default() -> Self {
+        let (posts_index_memory, posts_memory) = get_posts_memories();
+        PostMap {
+            posts: StableMap::init(posts_index_memory, posts_memory),
+            feed_index: FeedIndex::default(),
+            search_index: SearchIndex::default(),
+            listings: Listings::default(),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct PostMapTrimmed {
+    // Only set when upgrading from the version that kept posts on the heap.
+    #[serde(default)]
     posts: HashMap<PostId, Post>,
+    #[serde(default)]
+    feed_index: FeedIndex,
+}
+
+impl From<PostMapTrimmed> for PostMap {
+    fn from(value: PostMapTrimmed) -> Self {
+        let mut post_map = PostMap { feed_index: value.feed_index, ..Default::default() };
+        let stored: Vec<Post> = post_map.posts.iter().map(|(_, post)| post).collect();
+        // Until `refresh_rankings` runs with the real time, which it does right after an upgrade.
+        let now = stored.iter().chain(value.posts.values()).map(|post| post.date_last_commented).max().unwrap_or_default();
+        for post in stored {
+            post_map.index(&post, now);
+        }
+        for (_, post) in value.posts {
+            post_map.insert(post, now);
+        }
+        post_map
+    }
 }
 
 impl PostMap {
@@ -25,35 +78,174 @@ impl PostMap {
         now: TimestampMillis,
     ) {
         let post = Post::new(post_id, canister_id, noble_id, title, description, category, link_url, video_url, attached_file_id, post_privacy, invited_users, now);
-        self.posts.insert(post_id, post);
+        self.insert(post, now);
+    }
+
+    pub fn edit_post(
+        &mut self,
+        post_id: PostId,
+        title: String,
+        description: String,
+        post_privacy: PostPrivacy,
+        invited_users: HashSet<NobleId>,
+    ) {
+        if let Some(mut post) = self.posts.get(post_id) {
+            self.search_index.remove(post_id, &post.title, &post.description);
+            self.search_index.add(post_id, &title, &description);
+            post.title = title;
+            post.description = description;
+            post.post_privacy = post_privacy;
+            post.invited_users = invited_users;
+            self.posts.insert(post_id, &post);
+        }
+    }
+
+    // Saves the changes `f` makes to the post and re-sorts it.
+    pub fn update(&mut self, post_id: PostId, now: TimestampMillis, f: impl FnOnce(&mut Post)) {
+        if let Some(mut post) = self.posts.get(post_id) {
+            f(&mut post);
+            self.listings.update(&post, now);
+            self.posts.insert(post_id, &post);
+        }
+    }
+
+    // Posts age out of the top of the day and week, returns how many did.
+    pub fn refresh_rankings(&mut self, now: TimestampMillis) -> usize {
+        self.listings.remove_expired(now)
+    }
+
+    pub fn add_comment(&mut self, post_id: PostId, noble_id: NobleId, date: TimestampMillis) {
+        if self.posts.contains_key(post_id) {
+            self.feed_index.add_comment(post_id, noble_id, date);
+        }
+    }
+
+    pub fn remove_comment(&mut self, post_id: PostId, noble_id: NobleId) {
+        self.feed_index.remove_comment(post_id, noble_id);
     }
 
     pub fn remove_post(
         &mut self,
         post_id: PostId,
     ) {
-        self.posts.remove(&post_id);
+        if let Some(post) = self.posts.remove(post_id) {
+            self.search_index.remove(post_id, &post.title, &post.description);
+            self.listings.remove(&post);
+            self.feed_index.remove_post(&post);
+        }
     }
 
-    pub fn get(&self, post_id: PostId) -> Option<&Post> {
-        self.posts.get(&post_id)
+    // The posts after `after` in the given order, optionally only those in `category`.
+    pub fn iter_sorted(&self, sort: Sort, category: Option<Category>, after: Option<SortKey>) -> impl Iterator<Item = (SortKey, Post)> + '_ {
+        self.listings.iter(sort, category, after).filter_map(move |key| self.posts.get(key.1).map(|post| (key, post)))
     }
 
-    pub fn get_mut(&mut self, post_id: PostId) -> Option<&mut Post> {
-        self.posts.get_mut(&post_id)
+    // What the users posted and commented on after `after`, see `FeedIndex::iter`.
+    pub fn iter_feed(&self, users: &HashSet<NobleId>, include_comments: bool, after: Option<SortKey>) -> FeedIter<'_> {
+        self.feed_index.iter(users, include_comments, after)
+    }
+
+    pub fn latest_activity(&self, post: &Post, users: &HashSet<NobleId>, include_comments: bool) -> Option<TimestampMillis> {
+        self.feed_index.latest_activity(post, users, include_comments)
+    }
+
+    // Matching posts best first, see `SearchIndex::search`.
+    pub fn search(&self, terms: &[String]) -> Vec<(Post, f64)> {
+        self.search_index
+            .search(terms)
+            .into_iter()
+            .filter_map(|(post_id, score)| self.posts.get(post_id).map(|post| (post, score)))
+            .collect()
+    }
+
+    fn insert(&mut self, post: Post, now: TimestampMillis) {
+        self.remove_post(post.post_id);
+        self.index(&post, now);
+        self.posts.insert(post.post_id, &post);
+    }
+
+    fn index(&mut self, post: &Post, now: TimestampMillis) {
+        self.search_index.add(post.post_id, &post.title, &post.description);
+        self.listings.update(post, now);
+        self.feed_index.add_post(post);
+    }
+
+    pub fn get(&self, post_id: PostId) -> Option<Post> {
+        self.posts.get(post_id)
+    }
+
+    pub fn contains(&self, post_id: PostId) -> bool {
+        self.posts.contains_key(post_id)
     }
 
     pub fn len(&self) -> usize {
         self.posts.len()
     }
 
-    pub fn iter(&self) -> impl Iterator<Item = &Post> {
-        self.posts.values()
+    // Loads every post, only for when all of them are needed.
+    pub fn iter(&self) -> impl Iterator<Item = Post> + '_ {
+        self.posts.iter().map(|(_, post)| post)
     }
 
     #[cfg(test)]
     pub fn add_test_post(&mut self, post: Post) {
-        self.posts.insert(post.post_id, post);
+        let now = post.date_last_commented;
+        self.insert(post, now);
     }
 }
 
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use utils::text_search::query_terms;
+
+    const POST_COUNT: PostId = 10_000;
+
+    #[test]
+    fn upgrade_moves_posts_out_of_the_heap() {
+        #[derive(Serialize)]
+        struct LegacyPostMap {
+            posts: HashMap<PostId, Post>,
+        }
+
+        let posts: HashMap<PostId, Post> = (1..=POST_COUNT)
+            .map(|post_id| (post_id, Post {
+                post_id,
+                noble_id: post_id % 100,
+                title: if post_id == POST_COUNT { "Last post".to_string() } else { "A post".to_string() },
+                description: "d".repeat(300),
+                date_created: post_id,
+                date_last_commented: post_id,
+                ..Default::default()
+            }))
+            .collect();
+        let legacy_bytes = upgrade(&LegacyPostMap { posts });
+
+        let post_map: PostMap = serializer::deserialize(legacy_bytes.as_slice()).unwrap();
+        check(&post_map);
+
+        // From now on only the feed index is copied, the rest is rebuilt from the stored posts.
+        let bytes = upgrade(&post_map);
+        assert!(bytes.len() * 100 < legacy_bytes.len());
+
+        let post_map: PostMap = serializer::deserialize(bytes.as_slice()).unwrap();
+        check(&post_map);
+    }
+
+    fn upgrade<T: Serialize>(value: &T) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        serializer::serialize(value, &mut bytes).unwrap();
+        bytes
+    }
+
+    fn check(post_map: &PostMap) {
+        assert_eq!(post_map.len(), POST_COUNT as usize);
+        assert_eq!(post_map.get(1).unwrap().description.len(), 300);
+        assert_eq!(post_map.iter_sorted(Sort::NewestPost, None, None).next().map(|(_, post)| post.post_id), Some(POST_COUNT));
+        let found: Vec<_> = post_map.search(&query_terms("last", 10)).into_iter().map(|(post, _)| post.post_id).collect();
+        assert_eq!(found, vec![POST_COUNT]);
+        let users = HashSet::from([POST_COUNT % 100]);
+        assert_eq!(post_map.iter_feed(&users, false, None).next().map(|((_, post_id), _)| post_id), Some(POST_COUNT));
+    }
+}
@@ -0,0 +1,105 @@
+use std::collections::HashMap;
+use types::PostId;
+use utils::text_search::tokenize;
+
+// Title words count this many times, so a post named after the query ranks above one mentioning it.
+const TITLE_WEIGHT: u32 = 2;
+// The usual BM25 parameters, for term frequency saturation and length normalization.
+const K1: f64 = 1.2;
+const B: f64 = 0.75;
+
+// Inverted index over post titles and descriptions. It isn't saved with the canister's data,
+// `PostMap` rebuilds it after an upgrade.
+#[derive(Default)]
+pub struct SearchIndex {
+    // Term -> post -> weighted number of times the term appears.
+    postings: HashMap<String, HashMap<PostId, u32>>,
+    lengths: HashMap<PostId, u32>,
+    total_length: u64,
+}
+
+impl SearchIndex {
+    pub fn add(&mut self, post_id: PostId, title: &str, description: &str) {
+        let frequencies = term_frequencies(title, description);
+        let length = frequencies.values().sum();
+        for (term, frequency) in frequencies {
+            self.postings.entry(term).or_default().insert(post_id, frequency);
+        }
+        self.lengths.insert(post_id, length);
+        self.total_length += length as u64;
+    }
+
+    // Takes the text the post was indexed with.
+    pub fn remove(&mut self, post_id: PostId, title: &str, description: &str) {
+        for term in term_frequencies(title, description).keys() {
+            if let Some(posts) = self.postings.get_mut(term) {
+                posts.remove(&post_id);
+                if posts.is_empty() {
+                    self.postings.remove(term);
+                }
+            }
+        }
+        if let Some(length) = self.lengths.remove(&post_id) {
+            self.total_length -= length as u64;
+        }
+    }
+
+    // Posts matching any of the terms, best first.
+    pub fn search(&self, terms: &[String]) -> Vec<(PostId, f64)> {
+        if self.lengths.is_empty() {
+            return Vec::new();
+        }
+        let post_count = self.lengths.len() as f64;
+        let average_length = (self.total_length as f64 / post_count).max(1.0);
+
+        let mut scores: HashMap<PostId, f64> = HashMap::new();
+        for posts in terms.iter().filter_map(|term| self.postings.get(term)) {
+            let matching = posts.len() as f64;
+            let idf = (1.0 + (post_count - matching + 0.5) / (matching + 0.5)).ln();
+
+            for (post_id, frequency) in posts {
+                let frequency = *frequency as f64;
+                let length = self.lengths.get(post_id).copied().unwrap_or_default() as f64;
+                let normalization = K1 * (1.0 - B + B * length / average_length);
+                *scores.entry(*post_id).or_default() += idf * frequency * (K1 + 1.0) / (frequency + normalization);
+            }
+        }
+
+        let mut results: Vec<_> = scores.into_iter().collect();
+        results.sort_unstable_by(|(lhs_id, lhs), (rhs_id, rhs)| rhs.total_cmp(lhs).then(lhs_id.cmp(rhs_id)));
+        results
+    }
+}
+
+fn term_frequencies(title: &str, description: &str) -> HashMap<String, u32> {
+    let mut frequencies = HashMap::new();
+    for (text, weight) in [(title, TITLE_WEIGHT), (description, 1)] {
+        for token in tokenize(text) {
+            *frequencies.entry(token.term).or_default() += weight;
+        }
+    }
+    frequencies
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use utils::text_search::query_terms;
+
+    #[test]
+    fn ranks_by_relevance() {
+        let mut index = SearchIndex::default();
+        index.add(1, "", "A long post which mentions canisters once, among plenty of other words about nothing much");
+        index.add(2, "Canisters", "How canisters work");
+        index.add(3, "", "Upgrading a canister");
+        index.add(4, "", "Unrelated");
+
+        let results: Vec<PostId> = index.search(&query_terms("canister", 10)).into_iter().map(|(id, _)| id).collect();
+        assert_eq!(results, vec![2, 3, 1]);
+
+        index.remove(2, "Canisters", "How canisters work");
+        let results: Vec<PostId> = index.search(&query_terms("canisters upgrading", 10)).into_iter().map(|(id, _)| id).collect();
+        assert_eq!(results, vec![3, 1]);
+        assert!(index.search(&query_terms("work", 10)).is_empty());
+    }
+}
@@ -0,0 +1,226 @@
+use crate::model::post::Post;
+use post_index_canister::get_posts_by_category::Sort;
+use std::cmp::Reverse;
+use std::collections::{BTreeSet, HashMap};
+use std::mem;
+use std::ops::Bound::{Excluded, Unbounded};
+use types::{Category, PostId, TimestampMillis};
+
+const HOUR: TimestampMillis = 60 * 60 * 1000;
+const DAY: TimestampMillis = 24 * HOUR;
+const WEEK: TimestampMillis = 7 * DAY;
+
+// Trending is log2(likes + COMMENT_WEIGHT * comments + 1) + date created / TRENDING_HALF_LIFE, so
+// a post needs twice the activity to rank with one posted TRENDING_HALF_LIFE later. It doesn't
+// depend on the time it is worked out at, so scores never have to be refreshed.
+const COMMENT_WEIGHT: f64 = 2.0;
+const TRENDING_HALF_LIFE: TimestampMillis = 12 * HOUR;
+// Scores are kept as integers, scaled so close scores still order the posts.
+const TRENDING_SCALE: f64 = 1_000_000.0;
+
+const SORTS: [Sort; 7] = [
+    Sort::RecentActivity,
+    Sort::NewestPost,
+    Sort::Trending,
+    Sort::TopDay,
+    Sort::TopWeek,
+    Sort::TopAllTime,
+    Sort::MostDiscussed,
+];
+
+// Highest value first, posts with the same value in id order.
+pub type SortKey = (Reverse<u64>, PostId);
+
+// Posts ordered by a value, overall and per category, so a page is read from where the previous
+// one ended instead of sorting every post.
+#[derive(Default)]
+pub struct SortedIndex {
+    all: BTreeSet<SortKey>,
+    by_category: HashMap<Category, BTreeSet<SortKey>>,
+    values: HashMap<PostId, (Category, u64)>,
+}
+
+impl SortedIndex {
+    // None takes the post out of the index.
+    pub fn set(&mut self, post_id: PostId, category: Category, value: Option<u64>) {
+        if let Some((category, value)) = self.values.remove(&post_id) {
+            let key = (Reverse(value), post_id);
+            self.all.remove(&key);
+            if let Some(keys) = self.by_category.get_mut(&category) {
+                keys.remove(&key);
+            }
+        }
+        if let Some(value) = value {
+            let key = (Reverse(value), post_id);
+            self.all.insert(key);
+            self.by_category.entry(category).or_default().insert(key);
+            self.values.insert(post_id, (category, value));
+        }
+    }
+
+    // The keys after `after`, or from the start.
+    pub fn iter(&self, category: Option<Category>, after: Option<SortKey>) -> impl Iterator<Item = SortKey> + '_ {
+        let keys = match category {
+            Some(category) => self.by_category.get(&category),
+            None => Some(&self.all),
+        };
+        let start = after.map_or(Unbounded, Excluded);
+        keys.into_iter().flat_map(move |keys| keys.range((start, Unbounded)).copied())
+    }
+}
+
+// One index per sort. Rebuilt after an upgrade. Posts age out of the top of the day and week in
+// `remove_expired`, which `refresh_post_rankings` calls every hour.
+#[derive(Default)]
+pub struct Listings {
+    indexes: HashMap<Sort, SortedIndex>,
+    // The posts in the top of the day and week, oldest first, so only those aging out are looked at.
+    windows: HashMap<Sort, BTreeSet<(TimestampMillis, PostId)>>,
+}
+
+impl Listings {
+    pub fn update(&mut self, post: &Post, now: TimestampMillis) {
+        for sort in SORTS {
+            let value = sort_value(sort, post, now);
+            if window(sort).is_some() {
+                let posts = self.windows.entry(sort).or_default();
+                if value.is_some() {
+                    posts.insert((post.date_created, post.post_id));
+                } else {
+                    posts.remove(&(post.date_created, post.post_id));
+                }
+            }
+            self.indexes.entry(sort).or_default().set(post.post_id, post.category, value);
+        }
+    }
+
+    pub fn remove(&mut self, post: &Post) {
+        for posts in self.windows.values_mut() {
+            posts.remove(&(post.date_created, post.post_id));
+        }
+        for index in self.indexes.values_mut() {
+            index.set(post.post_id, Category::default(), None);
+        }
+    }
+
+    // Takes the posts created more than a day or a week before `now` out of the top of the day or
+    // week.
+    pub fn remove_expired(&mut self, now: TimestampMillis) -> usize {
+        let mut removed = 0;
+        for (sort, posts) in self.windows.iter_mut() {
+            let cutoff = match window(*sort).and_then(|window| now.checked_sub(window)) {
+                Some(cutoff) => cutoff,
+                None => continue,
+            };
+            // Everything created at or before the cutoff has expired.
+            let remaining = posts.split_off(&(cutoff + 1, 0));
+            let expired = mem::replace(posts, remaining);
+            removed += expired.len();
+            if let Some(index) = self.indexes.get_mut(sort) {
+                for (_, post_id) in expired {
+                    index.set(post_id, Category::default(), None);
+                }
+            }
+        }
+        removed
+    }
+
+    pub fn iter(&self, sort: Sort, category: Option<Category>, after: Option<SortKey>) -> impl Iterator<Item = SortKey> + '_ {
+        self.indexes.get(&sort).into_iter().flat_map(move |index| index.iter(category, after))
+    }
+}
+
+// How long posts stay in the sorts that only list recent posts.
+fn window(sort: Sort) -> Option<TimestampMillis> {
+    match sort {
+        Sort::TopDay => Some(DAY),
+        Sort::TopWeek => Some(WEEK),
+        _ => None,
+    }
+}
+
+fn sort_value(sort: Sort, post: &Post, now: TimestampMillis) -> Option<u64> {
+    let age = now.saturating_sub(post.date_created);
+    let likes = post.liked_users_count as u64;
+    match sort {
+        Sort::RecentActivity => Some(post.date_last_commented),
+        Sort::NewestPost => Some(post.date_created),
+        Sort::Trending => Some(trending_score(post)),
+        Sort::TopDay | Sort::TopWeek => window(sort).filter(|window| age < *window).map(|_| likes),
+        Sort::TopAllTime => Some(likes),
+        Sort::MostDiscussed => Some(post.comments_count as u64),
+    }
+}
+
+fn trending_score(post: &Post) -> u64 {
+    let points = post.liked_users_count as f64 + COMMENT_WEIGHT * post.comments_count as f64 + 1.0;
+    let age = post.date_created as f64 / TRENDING_HALF_LIFE as f64;
+    ((points.log2() + age) * TRENDING_SCALE) as u64
+}
+
+// Cursors are only meant to be passed back, their format may change.
+pub fn encode_cursor((Reverse(value), post_id): SortKey) -> String {
+    format!("{value:x}.{post_id:x}")
+}
+
+pub fn decode_cursor(cursor: &str) -> Option<SortKey> {
+    let (value, post_id) = cursor.split_once('.')?;
+    let value = u64::from_str_radix(value, 16).ok()?;
+    let post_id = PostId::from_str_radix(post_id, 16).ok()?;
+    Some((Reverse(value), post_id))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rankings() {
+        let now = 10 * DAY;
+        let mut listings = Listings::default();
+        let posts = [
+            // Popular but old.
+            Post { post_id: 1, liked_users_count: 50, comments_count: 1, date_created: now - 3 * DAY, ..Default::default() },
+            // A little activity, just posted.
+            Post { post_id: 2, liked_users_count: 3, comments_count: 2, date_created: now - HOUR, ..Default::default() },
+            Post { post_id: 3, liked_users_count: 1, comments_count: 9, date_created: now - 2 * HOUR, ..Default::default() },
+        ];
+        for post in &posts {
+            listings.update(post, now);
+        }
+
+        assert_eq!(ids(&listings, Sort::Trending), vec![3, 2, 1]);
+        assert_eq!(ids(&listings, Sort::TopDay), vec![2, 3]);
+        assert_eq!(ids(&listings, Sort::TopWeek), vec![1, 2, 3]);
+        assert_eq!(ids(&listings, Sort::MostDiscussed), vec![3, 2, 1]);
+
+        // The hourly refresh takes posts out of the top of the day once they are a day old, and
+        // only those.
+        assert_eq!(listings.remove_expired(now + DAY - 3 * HOUR / 2), 1);
+        assert_eq!(ids(&listings, Sort::TopDay), vec![2]);
+        assert_eq!(listings.remove_expired(now + DAY), 1);
+        assert!(ids(&listings, Sort::TopDay).is_empty());
+        assert_eq!(ids(&listings, Sort::TopWeek), vec![1, 2, 3]);
+        assert_eq!(listings.remove_expired(now + 4 * DAY), 1);
+        assert_eq!(ids(&listings, Sort::TopWeek), vec![2, 3]);
+
+        // Scores don't depend on when they were worked out.
+        listings.update(&posts[0], now + WEEK);
+        assert_eq!(ids(&listings, Sort::Trending), vec![3, 2, 1]);
+
+        listings.remove(&posts[1]);
+        assert_eq!(ids(&listings, Sort::TopAllTime), vec![1, 3]);
+    }
+
+    fn ids(listings: &Listings, sort: Sort) -> Vec<PostId> {
+        listings.iter(sort, None, None).map(|(_, post_id)| post_id).collect()
+    }
+
+    #[test]
+    fn cursors_round_trip() {
+        let key = (Reverse(1_700_000_000_000), 42);
+        assert_eq!(decode_cursor(&encode_cursor(key)), Some(key));
+        assert_eq!(decode_cursor("12"), None);
+        assert_eq!(decode_cursor("x.1"), None);
+    }
+}
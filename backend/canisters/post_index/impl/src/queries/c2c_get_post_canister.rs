@@ -0,0 +1,16 @@
+use crate::{read_state, RuntimeState};
+use canister_api_macros::query_msgpack;
+use post_index_canister::c2c_get_post_canister::{Response::*, *};
+
+// The local_post_index the post lives in, for checking its comments.
+#[query_msgpack]
+fn c2c_get_post_canister(args: Args) -> Response {
+    read_state(|state| c2c_get_post_canister_impl(args, state))
+}
+
+fn c2c_get_post_canister_impl(args: Args, state: &RuntimeState) -> Response {
+    match state.data.posts.get(args.post_id) {
+        Some(post) => Success(post.canister_id),
+        None => PostNotFound,
+    }
+}
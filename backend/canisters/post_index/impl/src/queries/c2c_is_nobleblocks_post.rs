@@ -8,7 +8,7 @@ fn c2c_is_nobleblocks_post(args: Args) -> Response {
 }
 
 fn c2c_is_nobleblocks_post_impl(args: Args, state: &RuntimeState) -> Response {
-    if state.data.posts.get(args.post_id).is_some() {
+    if state.data.posts.contains(args.post_id) {
         return Yes;
     }
     No
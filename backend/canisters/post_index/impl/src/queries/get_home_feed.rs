@@ -0,0 +1,204 @@
+use crate::{read_state, RuntimeState};
+use crate::model::feed_index::Activity;
+use crate::model::sorted_index::{decode_cursor, encode_cursor};
+use ic_cdk_macros::query;
+use post_index_canister::get_home_feed::{Response::*, *};
+use std::cmp::Reverse;
+use std::collections::HashSet;
+use types::{check_jwt, NobleId};
+
+const MAX_PAGE_SIZE: u32 = 100;
+// As for `get_posts_by_category`, a page may come back short when most of what followed users
+// did is hidden from the caller.
+const MAX_ITEMS_SCANNED: usize = 1_000;
+
+#[query]
+fn get_home_feed(args: Args) -> Response {
+    read_state(|state| get_home_feed_impl(args, state))
+}
+
+fn get_home_feed_impl(
+    args: Args,
+    state: &RuntimeState
+) -> Response {
+    let now = state.env.now();
+
+    let noble_id = match check_jwt(&args.jwt, now) {
+        Some(jwt) => jwt.noble_id,
+        None => return PermissionDenied,
+    };
+
+    let mut last = match &args.cursor {
+        Some(cursor) => match decode_cursor(cursor) {
+            Some(key) => Some(key),
+            None => return InvalidCursor,
+        },
+        None => None,
+    };
+    let limit = args.limit.clamp(1, MAX_PAGE_SIZE) as usize;
+
+    let social_graph = &state.data.social_graph;
+    // Users whose content is hidden are left out, so their comments don't bring posts up either.
+    let users: HashSet<NobleId> = social_graph
+        .following(noble_id)
+        .filter(|user| !args.muted_users.contains(user) && !state.data.suspended_users.hides_content_from(*user, noble_id, now))
+        .collect();
+
+    let mut items = Vec::new();
+    let mut next_cursor = None;
+    for (scanned, (key, activity)) in state.data.posts.iter_feed(&users, args.include_comments, last).enumerate() {
+        if items.len() == limit || scanned == MAX_ITEMS_SCANNED {
+            next_cursor = last.map(encode_cursor);
+            break;
+        }
+        // Several followed users may have posted or commented at the same time.
+        if last == Some(key) {
+            continue;
+        }
+        last = Some(key);
+
+        let (Reverse(timestamp), post_id) = key;
+        let item = match state.data.posts.get(post_id) {
+            Some(item) => item,
+            None => continue,
+        };
+        // The post is listed once, for the latest thing followed users did with it.
+        if state.data.posts.latest_activity(&item, &users, args.include_comments) != Some(timestamp) {
+            continue;
+        }
+        if item.can_show(noble_id, &None, social_graph) && !state.data.suspended_users.hides_content_from(item.noble_id, noble_id, now) {
+            items.push(FeedItem {
+                post: item.to_summary(args.liked_posts.contains(&item.post_id), args.bookmarks.contains(&item.post_id)),
+                reason: match activity {
+                    Activity::Posted => FeedReason::Posted,
+                    Activity::Commented(noble_id) => FeedReason::Commented(noble_id),
+                },
+                timestamp,
+            });
+        }
+    }
+
+    Success(SuccessResult { items, next_cursor, timestamp: now })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::post::Post;
+    use crate::Data;
+    use types::{JwtSigningKey, JWT, PostId, PostPrivacy, Suspension};
+    use utils::env::test::TestEnv;
+
+    #[test]
+    fn feed_merges_followed_users() {
+        let state = setup_runtime_state();
+
+        let result = feed(args(None, 10, false), &state);
+        assert_eq!(ids(&result), vec![5, 2, 1]);
+        assert!(result.items.iter().all(|item| item.reason == FeedReason::Posted));
+        assert_eq!(result.next_cursor, None);
+    }
+
+    #[test]
+    fn comments_bring_posts_up_once() {
+        let state = setup_runtime_state();
+
+        let result = feed(args(None, 10, true), &state);
+        assert_eq!(ids(&result), vec![3, 1, 5, 2]);
+        assert_eq!(result.items[0].reason, FeedReason::Commented(2));
+        assert_eq!(result.items[1].reason, FeedReason::Commented(1));
+        assert_eq!(result.items[1].timestamp, 700);
+    }
+
+    #[test]
+    fn deleted_comments_leave_the_feed() {
+        let mut state = setup_runtime_state();
+        state.data.posts.remove_comment(3, 2);
+        state.data.posts.remove_comment(1, 1);
+
+        let result = feed(args(None, 10, true), &state);
+        assert_eq!(ids(&result), vec![1, 5, 2]);
+        assert_eq!(result.items[0].reason, FeedReason::Commented(2));
+        assert_eq!(result.items[0].timestamp, 600);
+    }
+
+    #[test]
+    fn hidden_users_leave_the_feed() {
+        let mut state = setup_runtime_state();
+        let now = state.env.now();
+        state.data.suspended_users.suspend(2, Suspension { until: Some(now + 1_000), hide_content: true });
+
+        let result = feed(args(None, 10, true), &state);
+        assert_eq!(ids(&result), vec![1, 5]);
+        assert_eq!(result.items[0].reason, FeedReason::Commented(1));
+    }
+
+    #[test]
+    fn feed_is_paged() {
+        let state = setup_runtime_state();
+
+        let first = feed(args(None, 2, true), &state);
+        assert_eq!(ids(&first), vec![3, 1]);
+
+        let second = feed(args(first.next_cursor, 2, true), &state);
+        assert_eq!(ids(&second), vec![5, 2]);
+
+        assert!(matches!(get_home_feed_impl(args(Some("feed".to_string()), 2, true), &state), InvalidCursor));
+    }
+
+    fn feed(args: Args, state: &RuntimeState) -> SuccessResult {
+        match get_home_feed_impl(args, state) {
+            Success(result) => result,
+            _ => panic!(),
+        }
+    }
+
+    fn ids(result: &SuccessResult) -> Vec<PostId> {
+        result.items.iter().map(|item| item.post.post_id).collect()
+    }
+
+    fn args(cursor: Option<String>, limit: u32, include_comments: bool) -> Args {
+        Args {
+            jwt: JWT::new_for_test(10, TestEnv::default().now).sign(&JwtSigningKey::for_test()).unwrap(),
+            cursor,
+            limit,
+            muted_users: vec![3],
+            include_comments,
+            liked_posts: vec![],
+            bookmarks: vec![],
+        }
+    }
+
+    fn setup_runtime_state() -> RuntimeState {
+        let env = TestEnv::default();
+        let mut data = Data::default();
+
+        data.posts.add_test_post(Post { post_id: 1, noble_id: 1, date_created: 100, ..Default::default() });
+        data.posts.add_test_post(Post { post_id: 2, noble_id: 2, date_created: 200, ..Default::default() });
+        // Muted.
+        data.posts.add_test_post(Post { post_id: 3, noble_id: 3, date_created: 300, ..Default::default() });
+        // Not followed.
+        data.posts.add_test_post(Post { post_id: 4, noble_id: 4, date_created: 400, ..Default::default() });
+        data.posts.add_test_post(Post {
+            post_id: 5,
+            noble_id: 1,
+            post_privacy: PostPrivacy::Followers,
+            date_created: 500,
+            ..Default::default()
+        });
+
+        // Followed users commenting on the muted user's post and on their own.
+        data.posts.add_comment(3, 2, 800);
+        data.posts.add_comment(1, 2, 600);
+        data.posts.add_comment(1, 1, 700);
+        // Comments by users who aren't followed don't count.
+        data.posts.add_comment(4, 4, 900);
+        data.posts.add_comment(2, 4, 1000);
+
+        data.social_graph.follow(10, 1);
+        data.social_graph.follow(10, 2);
+        data.social_graph.follow(10, 3);
+
+        RuntimeState::new(Box::new(env), data)
+    }
+}
@@ -1,9 +1,14 @@
-use std::cmp::Ordering;
-
-use crate::{read_state, RuntimeState, model::post::Post};
+use crate::{read_state, RuntimeState};
+use crate::model::post::Post;
+use crate::model::sorted_index::{decode_cursor, encode_cursor, SortKey};
 use ic_cdk_macros::query;
 use post_index_canister::get_posts_by_category::{Response::*, *};
-use types::check_jwt;
+use types::{check_jwt, NobleId, PostSummary};
+
+const MAX_PAGE_SIZE: u32 = 100;
+// Bounds the work done for a page when most posts are hidden from the caller. The page may come
+// back short, the cursor carries on from the last post looked at.
+const MAX_POSTS_SCANNED: usize = 1_000;
 
 #[query]
 fn get_posts_by_category(args: Args) -> Response {
@@ -18,56 +23,55 @@ fn get_posts_by_category_impl(
 
     let noble_id = check_jwt(&args.jwt, now).unwrap_or_default().noble_id;
 
-    let mut matches: Vec<&Post> = state.data.posts.iter().filter(|item| item.can_show(noble_id, &args.category, &args.following_list, &args.block_me_users)).collect();
-
-    matches.sort_unstable_by(|lhs, rhs| {
-        order_posts(&args.sort, lhs, rhs)
-    });
-
-    let total_posts_count = matches.len() as u32;
-
-    let results = matches.iter()
-        .skip(args.from as usize - 1)
-        .take(args.limit as usize)
-        .map(|item| item.to_summary(args.liked_posts.contains(&item.post_id), args.bookmarks.contains(&item.post_id)))
-        .collect();
-
-    Success(SuccessResult { total_posts_count, posts: results, timestamp: now })
+    let last = match &args.cursor {
+        Some(cursor) => match decode_cursor(cursor) {
+            Some(key) => Some(key),
+            None => return InvalidCursor,
+        },
+        None => None,
+    };
+    let entries = state.data.posts.iter_sorted(args.sort, args.category, last);
+    let (posts, next_cursor) = read_page(entries, last, &args, noble_id, state);
+
+    Success(SuccessResult { posts, next_cursor, timestamp: now })
 }
 
-fn order_posts(sort_dir: &Sort, lhs: &Post, rhs: &Post) -> Ordering {
-    if *sort_dir == Sort::NewestPost {
-        if lhs.date_created > rhs.date_created {
-            Ordering::Less
-        } else if lhs.date_created < rhs.date_created {
-            Ordering::Greater
-        } else if lhs.post_id < rhs.post_id {
-            Ordering::Less
-        } else {
-            Ordering::Greater
+// Takes the posts the caller can see from `entries` until the page is full or MAX_POSTS_SCANNED
+// have been looked at.
+fn read_page(
+    entries: impl Iterator<Item = (SortKey, Post)>,
+    mut last: Option<SortKey>,
+    args: &Args,
+    noble_id: NobleId,
+    state: &RuntimeState,
+) -> (Vec<PostSummary>, Option<String>) {
+    let now = state.env.now();
+    let limit = args.limit.clamp(1, MAX_PAGE_SIZE) as usize;
+
+    let mut posts = Vec::new();
+    let mut next_cursor = None;
+    for (scanned, (key, item)) in entries.enumerate() {
+        if posts.len() == limit || scanned == MAX_POSTS_SCANNED {
+            next_cursor = last.map(encode_cursor);
+            break;
         }
-    } else if *sort_dir == Sort::RecentActivity {
-        if lhs.date_last_commented > rhs.date_last_commented {
-            Ordering::Less
-        } else if lhs.date_last_commented < rhs.date_last_commented {
-            Ordering::Greater
-        } else if lhs.post_id < rhs.post_id {
-            Ordering::Less
-        } else {
-            Ordering::Greater
+        last = Some(key);
+        if item.can_show(noble_id, &args.category, &state.data.social_graph)
+            && !state.data.suspended_users.hides_content_from(item.noble_id, noble_id, now)
+        {
+            posts.push(item.to_summary(args.liked_posts.contains(&item.post_id), args.bookmarks.contains(&item.post_id)));
         }
-    } else {
-        Ordering::Less
     }
+    (posts, next_cursor)
 }
 
 #[cfg(test)]
 mod tests {
+    use std::cmp::Reverse;
     use std::collections::HashSet;
     use super::*;
-    use crate::model::post::Post;
     use crate::Data;
-    use types::{JWT, Category, PostPrivacy, NobleId};
+    use types::{JwtSigningKey, JWT, Category, PostPrivacy, NobleId};
     use utils::env::test::TestEnv;
 
     #[test]
@@ -76,24 +80,30 @@ mod tests {
 
         assert_eq!(state.data.posts.len(), 6);
 
-        let response = get_posts_by_category_impl(
-            Args {
-                jwt : JWT::new_for_test(5, state.env.now()).to_string().unwrap(),
-                from: 3,
-                limit: 2,
-                category: None,
-                sort: Sort::NewestPost,
-                following_list: vec![],
-                block_me_users: vec![],
-                liked_posts: vec![],
-                bookmarks: vec![],
-            },
-            &state,
-        );
-
-        if let Success(result) = response {
+        let args = |cursor: Option<String>| Args {
+            jwt : JWT::new_for_test(5, state.env.now()).sign(&JwtSigningKey::for_test()).unwrap(),
+            cursor,
+            limit: 2,
+            category: None,
+            sort: Sort::NewestPost,
+            liked_posts: vec![],
+            bookmarks: vec![],
+        };
+
+        let next_cursor = match get_posts_by_category_impl(args(None), &state) {
+            Success(result) => {
+                assert_eq!(result.posts.len(), 2);
+                assert_eq!(result.posts[0].post_id, 2);
+                assert_eq!(result.posts[1].post_id, 3);
+                result.next_cursor
+            }
+            _ => panic!(),
+        };
+
+        if let Success(result) = get_posts_by_category_impl(args(next_cursor), &state) {
             assert_eq!(result.posts.len(), 1);
             assert_eq!(result.posts[0].post_id, 1);
+            assert_eq!(result.next_cursor, None);
         } else {
             assert!(false);
         }
@@ -107,13 +117,11 @@ mod tests {
 
         let response = get_posts_by_category_impl(
             Args {
-                jwt : JWT::new_for_test(5, state.env.now()).to_string().unwrap(),
-                from: 1,
+                jwt : JWT::new_for_test(5, state.env.now()).sign(&JwtSigningKey::for_test()).unwrap(),
+                cursor: None,
                 limit: 5,
                 category: None,
                 sort: Sort::RecentActivity,
-                following_list: vec![],
-                block_me_users: vec![],
                 liked_posts: vec![],
                 bookmarks: vec![],
             },
@@ -131,29 +139,32 @@ mod tests {
     }
 
     #[test]
-    fn no_search_results_by_over_page() {
-        let state = setup_runtime_state();
-
-        let response = get_posts_by_category_impl(
-            Args {
-                jwt : JWT::new_for_test(5, state.env.now()).to_string().unwrap(),
-                from: 5,
-                limit: 2,
-                category: None,
-                sort: Sort::NewestPost,
-                following_list: vec![],
-                block_me_users: vec![],
-                liked_posts: vec![],
-                bookmarks: vec![],
-            },
-            &state,
-        );
-
-        if let Success(result) = response {
-            assert_eq!(result.posts.len(), 0);
+    fn pages_dont_shift_when_posts_are_added() {
+        let mut state = setup_runtime_state();
+        let now = state.env.now();
+
+        let args = |cursor: Option<String>| Args {
+            jwt : JWT::new_for_test(5, now).sign(&JwtSigningKey::for_test()).unwrap(),
+            cursor,
+            limit: 1,
+            category: None,
+            sort: Sort::NewestPost,
+            liked_posts: vec![],
+            bookmarks: vec![],
+        };
+
+        let next_cursor = match get_posts_by_category_impl(args(None), &state) {
+            Success(result) => result.next_cursor,
+            _ => panic!(),
+        };
+        state.data.posts.add_test_post(Post { post_id: 7, date_created: 1000, ..Default::default() });
+
+        if let Success(result) = get_posts_by_category_impl(args(next_cursor), &state) {
+            assert_eq!(result.posts[0].post_id, 3);
         } else {
             assert!(false);
         }
+        assert!(matches!(get_posts_by_category_impl(args(Some("x".to_string())), &state), InvalidCursor));
     }
 
     #[test]
@@ -162,13 +173,11 @@ mod tests {
 
         let response = get_posts_by_category_impl(
             Args {
-                jwt : JWT::new_for_test(5, state.env.now()).to_string().unwrap(),
-                from: 1,
+                jwt : JWT::new_for_test(5, state.env.now()).sign(&JwtSigningKey::for_test()).unwrap(),
+                cursor: None,
                 limit: 2,
                 category: Some(Category::GeneralDiscussion),
                 sort: Sort::NewestPost,
-                following_list: vec![],
-                block_me_users: vec![],
                 liked_posts: vec![],
                 bookmarks: vec![],
             },
@@ -190,13 +199,11 @@ mod tests {
 
         let response = get_posts_by_category_impl(
             Args {
-                jwt : JWT::new_for_test(2, state.env.now()).to_string().unwrap(),
-                from: 1,
+                jwt : JWT::new_for_test(2, state.env.now()).sign(&JwtSigningKey::for_test()).unwrap(),
+                cursor: None,
                 limit: 5,
                 category: None,
                 sort: Sort::NewestPost,
-                following_list: vec![4 as NobleId, 1 as NobleId],
-                block_me_users: vec![],
                 liked_posts: vec![],
                 bookmarks: vec![],
             },
@@ -221,13 +228,11 @@ mod tests {
 
         let response = get_posts_by_category_impl(
             Args {
-                jwt : JWT::new_for_test(3, state.env.now()).to_string().unwrap(),
-                from: 1,
+                jwt : JWT::new_for_test(3, state.env.now()).sign(&JwtSigningKey::for_test()).unwrap(),
+                cursor: None,
                 limit: 5,
                 category: None,
                 sort: Sort::NewestPost,
-                following_list: vec![],
-                block_me_users: vec![],
                 liked_posts: vec![],
                 bookmarks: vec![],
             },
@@ -251,13 +256,11 @@ mod tests {
 
         let response = get_posts_by_category_impl(
             Args {
-                jwt : JWT::new_for_test(5, state.env.now()).to_string().unwrap(),
-                from: 1,
+                jwt : JWT::new_for_test(7, state.env.now()).sign(&JwtSigningKey::for_test()).unwrap(),
+                cursor: None,
                 limit: 5,
                 category: None,
                 sort: Sort::NewestPost,
-                following_list: vec![4 as NobleId],
-                block_me_users: vec![4 as NobleId, 2 as NobleId],
                 liked_posts: vec![],
                 bookmarks: vec![],
             },
@@ -273,6 +276,42 @@ mod tests {
         }
     }
 
+    // A page is read from the sorted index starting at the cursor, so it looks at the same number of
+    // posts however many there are.
+    #[test]
+    fn pages_visit_the_same_entries_as_posts_grow() {
+        for post_count in [1_000, 100_000] {
+            let mut data = Data::default();
+            for post_id in 0..post_count {
+                data.posts.add_test_post(Post { post_id, date_created: post_id, date_last_commented: post_id, ..Default::default() });
+            }
+            let state = RuntimeState::new(Box::new(TestEnv::default()), data);
+            let middle = post_count / 2;
+
+            for sort in [Sort::NewestPost, Sort::RecentActivity] {
+                let args = Args {
+                    jwt: String::new(),
+                    cursor: None,
+                    limit: 20,
+                    category: Some(Category::GeneralDiscussion),
+                    sort,
+                    liked_posts: vec![],
+                    bookmarks: vec![],
+                };
+                let cursor = Some((Reverse(middle), middle));
+                let mut visited = 0;
+                let entries = state.data.posts.iter_sorted(sort, args.category, cursor).inspect(|_| visited += 1);
+
+                let (posts, next_cursor) = read_page(entries, cursor, &args, 0, &state);
+
+                // The page, and one more to know there is a next page.
+                assert_eq!(visited, 21, "{post_count} posts");
+                assert_eq!(posts.first().map(|post| post.post_id), Some(middle - 1));
+                assert_eq!(next_cursor, Some(encode_cursor((Reverse(middle - 20), middle - 20))));
+            }
+        }
+    }
+
     fn setup_runtime_state() -> RuntimeState {
         let env = TestEnv::default();
         let mut data = Data::default();
@@ -339,6 +378,13 @@ mod tests {
             ..Default::default()
         });
 
+        data.social_graph.follow(2, 4);
+        data.social_graph.follow(2, 1);
+        // Followed a user who then blocked them.
+        data.social_graph.follow(7, 4);
+        data.social_graph.block(4, 7);
+        data.social_graph.block(2, 7);
+
         RuntimeState::new(Box::new(env), data)
     }
 }
\ No newline at end of file
@@ -1,4 +1,7 @@
+pub mod c2c_get_post_canister;
 pub mod c2c_is_nobleblocks_post;
+pub mod get_home_feed;
 pub mod get_post_info;
 pub mod get_posts_by_category;
-pub mod http_request;
\ No newline at end of file
+pub mod http_request;
+pub mod search_posts;
\ No newline at end of file
@@ -0,0 +1,170 @@
+use crate::{read_state, RuntimeState, model::post::Post};
+use ic_cdk_macros::query;
+use post_index_canister::search_posts::{Response::*, *};
+use types::check_jwt;
+use utils::text_search::{query_terms, tokenize};
+
+// Any further words in the query are ignored.
+const MAX_QUERY_TERMS: usize = 10;
+
+#[query]
+fn search_posts(args: Args) -> Response {
+    read_state(|state| search_posts_impl(args, state))
+}
+
+fn search_posts_impl(
+    args: Args,
+    state: &RuntimeState
+) -> Response {
+    let now = state.env.now();
+
+    let noble_id = check_jwt(&args.jwt, now).unwrap_or_default().noble_id;
+    let terms = query_terms(&args.query, MAX_QUERY_TERMS);
+
+    let mut matches: Vec<(Post, f64)> = state.data.posts.search(&terms)
+        .into_iter()
+        .filter(|(item, _)| item.can_show(noble_id, &args.category, &state.data.social_graph))
+        .filter(|(item, _)| !state.data.suspended_users.hides_content_from(item.noble_id, noble_id, now))
+        .collect();
+
+    matches.sort_by(|(lhs, lhs_score), (rhs, rhs_score)| {
+        rhs_score.total_cmp(lhs_score).then(rhs.date_created.cmp(&lhs.date_created))
+    });
+
+    let total_posts_count = matches.len() as u32;
+
+    let results = matches.iter()
+        .skip((args.from as usize).saturating_sub(1))
+        .take(args.limit as usize)
+        .map(|(item, _)| SearchResult {
+            post: item.to_summary(args.liked_posts.contains(&item.post_id), args.bookmarks.contains(&item.post_id)),
+            title_highlights: highlights(&item.title, &terms),
+            description_highlights: highlights(&item.description, &terms),
+        })
+        .collect();
+
+    Success(SuccessResult { total_posts_count, posts: results, timestamp: now })
+}
+
+fn highlights(text: &str, terms: &[String]) -> Vec<TextRange> {
+    tokenize(text)
+        .into_iter()
+        .filter(|token| terms.contains(&token.term))
+        .map(|token| TextRange { start: token.start as u32, end: token.end as u32 })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Data;
+    use types::{JwtSigningKey, JWT, Category, PostPrivacy, Suspension};
+    use utils::env::test::TestEnv;
+
+    #[test]
+    fn results_are_ranked_and_highlighted() {
+        let state = setup_runtime_state();
+
+        let response = search_posts_impl(args(5, "Canister upgrades", 1, 10), &state);
+
+        let Success(result) = response;
+        let ids: Vec<_> = result.posts.iter().map(|r| r.post.post_id).collect();
+        assert_eq!(ids, vec![2, 1]);
+        assert_eq!(result.posts[0].title_highlights, vec![TextRange { start: 0, end: 9 }]);
+        assert_eq!(result.posts[0].description_highlights, vec![
+            TextRange { start: 8, end: 16 },
+            TextRange { start: 30, end: 38 },
+            TextRange { start: 39, end: 47 },
+        ]);
+    }
+
+    #[test]
+    fn hidden_posts_are_not_found() {
+        let state = setup_runtime_state();
+
+        let Success(result) = search_posts_impl(args(5, "private", 1, 10), &state);
+        assert_eq!(result.total_posts_count, 0);
+
+        let Success(result) = search_posts_impl(args(3, "private", 1, 10), &state);
+        assert_eq!(result.total_posts_count, 1);
+        assert_eq!(result.posts[0].post.post_id, 3);
+    }
+
+    #[test]
+    fn results_are_paged() {
+        let state = setup_runtime_state();
+
+        let Success(result) = search_posts_impl(args(5, "canisters", 2, 1), &state);
+        assert_eq!(result.total_posts_count, 2);
+        assert_eq!(result.posts.len(), 1);
+        assert_eq!(result.posts[0].post.post_id, 1);
+
+        let Success(result) = search_posts_impl(args(5, "the", 1, 10), &state);
+        assert_eq!(result.total_posts_count, 0);
+    }
+
+    #[test]
+    fn suspended_users_posts_can_be_hidden() {
+        let mut state = setup_runtime_state();
+        let now = state.env.now();
+        state.data.suspended_users.suspend(2, Suspension { until: None, hide_content: true });
+
+        let Success(result) = search_posts_impl(args(5, "canisters", 1, 10), &state);
+        assert_eq!(result.total_posts_count, 1);
+        assert_eq!(result.posts[0].post.post_id, 1);
+
+        // The author still sees their own posts.
+        let Success(result) = search_posts_impl(args(2, "canisters", 1, 10), &state);
+        assert_eq!(result.total_posts_count, 2);
+
+        state.data.suspended_users.suspend(2, Suspension { until: Some(now + 1), hide_content: false });
+        let Success(result) = search_posts_impl(args(5, "canisters", 1, 10), &state);
+        assert_eq!(result.total_posts_count, 2);
+    }
+
+    fn args(noble_id: u64, query: &str, from: u32, limit: u32) -> Args {
+        Args {
+            jwt: JWT::new_for_test(noble_id, TestEnv::default().now).sign(&JwtSigningKey::for_test()).unwrap(),
+            query: query.to_string(),
+            from,
+            limit,
+            category: None,
+            liked_posts: vec![],
+            bookmarks: vec![],
+        }
+    }
+
+    fn setup_runtime_state() -> RuntimeState {
+        let env = TestEnv::default();
+        let mut data = Data::default();
+
+        data.posts.add_test_post(Post {
+            post_id: 1,
+            noble_id: 1,
+            description: "Notes from my first week, mostly about canisters".to_string(),
+            date_created: 100,
+            ..Default::default()
+        });
+
+        data.posts.add_test_post(Post {
+            post_id: 2,
+            noble_id: 2,
+            title: "Canisters".to_string(),
+            description: "Keeping canister state across canister upgrades".to_string(),
+            category: Category::Questions,
+            date_created: 200,
+            ..Default::default()
+        });
+
+        data.posts.add_test_post(Post {
+            post_id: 3,
+            noble_id: 3,
+            description: "A private post".to_string(),
+            post_privacy: PostPrivacy::Followers,
+            date_created: 300,
+            ..Default::default()
+        });
+
+        RuntimeState::new(Box::new(env), data)
+    }
+}
@@ -1,14 +1,14 @@
-use crate::guards::caller_is_governance_principal;
 use crate::{mutate_state, RuntimeState, LOCAL_POST_INDEX_CANISTER_INITIAL_CYCLES_BALANCE};
 use canister_api_macros::proposal;
 use local_post_index_canister::init::Args as InitLocalPostIndexCanisterArgs;
+use local_post_index_canister::{Event as LocalPostIndexEvent, EditWindowUpdated, JwtKeysUpdated, RolesUpdated, FollowUser, BlockUser, UserSuspended};
 use types::{CanisterId, CanisterWasm, Cycles, Version};
 use post_index_canister::add_local_post_index_canister::{Response::*, *};
 use utils::canister;
 use utils::consts::{CREATE_CANISTER_CYCLES_FEE, MIN_CYCLES_BALANCE};
 use user_index_canister::{Event as UserIndexEvent, LocalPostIndexAdded};
 
-#[proposal(guard = "caller_is_governance_principal")]
+#[proposal(permission = "ManageCanisters")]
 async fn add_local_post_index_canister(args: Args) -> Response {
     let PrepareOk {
         canister_id,
@@ -88,4 +88,37 @@ fn commit(canister_id: CanisterId, wasm_version: Version, state: &mut RuntimeSta
     state.push_event_to_user_index(UserIndexEvent::LocalPostIndexAdded(Box::new(LocalPostIndexAdded{
         canister_id,
     })));
+    let keys = state.data.jwt_verification_keys.clone();
+    state.push_event_to_local_post_index(canister_id, LocalPostIndexEvent::JwtKeysUpdated(Box::new(JwtKeysUpdated {
+        keys,
+    })));
+    let grants = state.data.roles.grants().to_vec();
+    state.push_event_to_local_post_index(canister_id, LocalPostIndexEvent::RolesUpdated(Box::new(RolesUpdated {
+        grants,
+    })));
+    let edit_window = state.data.edit_window;
+    state.push_event_to_local_post_index(canister_id, LocalPostIndexEvent::EditWindowUpdated(Box::new(EditWindowUpdated {
+        edit_window,
+    })));
+    let follows: Vec<_> = state.data.social_graph.follows().collect();
+    for (sender_id, receiver_id) in follows {
+        state.push_event_to_local_post_index(canister_id, LocalPostIndexEvent::UserFollowed(Box::new(FollowUser {
+            sender_id,
+            receiver_id,
+        })));
+    }
+    let blocks: Vec<_> = state.data.social_graph.blocks().collect();
+    for (sender_id, receiver_id) in blocks {
+        state.push_event_to_local_post_index(canister_id, LocalPostIndexEvent::UserBlocked(Box::new(BlockUser {
+            sender_id,
+            receiver_id,
+        })));
+    }
+    let suspended_users: Vec<_> = state.data.suspended_users.iter().collect();
+    for (noble_id, suspension) in suspended_users {
+        state.push_event_to_local_post_index(canister_id, LocalPostIndexEvent::UserSuspended(Box::new(UserSuspended {
+            noble_id,
+            suspension,
+        })));
+    }
 }
\ No newline at end of file
@@ -3,8 +3,12 @@ use std::collections::HashSet;
 use crate::guards::caller_is_known_canister;
 use crate::{mutate_state, RuntimeState};
 use canister_api_macros::update_msgpack;
-use local_post_index_canister::{Event as LocalPostIndexEvent, LocalUserIndexCanisterAdded};
-use types::{NobleId, PostId, TimestampMillis, PostPrivacy, CanisterId};
+use local_post_index_canister::{
+    Event as LocalPostIndexEvent, LocalUserIndexCanisterAdded, JwtKeysUpdated, SessionRevoked, FollowUser, BlockUser,
+    ContentModerated, RolesUpdated, UserSuspended, SuspensionLifted,
+};
+use types::{NobleId, PostId, TimestampMillis, PostPrivacy, CanisterId, JwtVerificationKey, SessionRevocation, FileId, ModerationAction, RoleGrant};
+use utils::rbac::Roles;
 use post_index_canister::c2c_notify_events::{Response::*, *};
 use post_index_canister::Event;
 
@@ -24,23 +28,71 @@ fn c2c_notify_events_impl(args: Args, state: &mut RuntimeState) -> Response {
 fn handle_event(event: Event, state: &mut RuntimeState) {
     match event {
         Event::NewComment(ev) => new_comment(ev.noble_id, ev.post_id, ev.date_create, state),
-        Event::CommentDeleted(ev) => delete_comment(ev.post_id, ev.comments_count, state),
+        Event::CommentDeleted(ev) => delete_comment(ev.post_id, ev.noble_id, ev.comments_count, state),
         Event::PostLiked(ev) => post_liked(ev.post_id, state),
         Event::PostUnliked(ev) => post_unliked(ev.post_id, state),
         Event::PostEdited(ev) => post_edited(ev.post_id, ev.title, ev.description, ev.post_privacy, ev.invited_users, state),
         Event::PostDeleted(ev) => post_deleted(ev.post_id, state),
+        Event::PostTrashed(ev) => set_trashed(ev.post_id, true, state),
+        Event::PostRestored(ev) => set_trashed(ev.post_id, false, state),
+        Event::PostFileAttached(ev) => file_attached(ev.post_id, ev.attached_file_id, state),
         Event::LocalUserIndexAdded(ev) => add_local_user_index_canister_id(ev.canister_id, state),
+        Event::JwtKeysUpdated(ev) => jwt_keys_updated(ev.keys, state),
+        Event::SessionRevoked(ev) => session_revoked(ev.revocation, state),
+        Event::UserFollowed(ev) => {
+            state.data.social_graph.follow(ev.sender_id, ev.receiver_id);
+            state.push_event_to_all_local_post_index(LocalPostIndexEvent::UserFollowed(Box::new(FollowUser {
+                sender_id: ev.sender_id,
+                receiver_id: ev.receiver_id,
+            })));
+        },
+        Event::UserUnfollowed(ev) => {
+            state.data.social_graph.unfollow(ev.sender_id, ev.receiver_id);
+            state.push_event_to_all_local_post_index(LocalPostIndexEvent::UserUnfollowed(Box::new(FollowUser {
+                sender_id: ev.sender_id,
+                receiver_id: ev.receiver_id,
+            })));
+        },
+        Event::UserBlocked(ev) => {
+            state.data.social_graph.block(ev.sender_id, ev.receiver_id);
+            state.push_event_to_all_local_post_index(LocalPostIndexEvent::UserBlocked(Box::new(BlockUser {
+                sender_id: ev.sender_id,
+                receiver_id: ev.receiver_id,
+            })));
+        },
+        Event::UserUnblocked(ev) => {
+            state.data.social_graph.unblock(ev.sender_id, ev.receiver_id);
+            state.push_event_to_all_local_post_index(LocalPostIndexEvent::UserUnblocked(Box::new(BlockUser {
+                sender_id: ev.sender_id,
+                receiver_id: ev.receiver_id,
+            })));
+        },
+        Event::ContentModerated(ev) => content_moderated(*ev, state),
+        Event::RolesUpdated(ev) => roles_updated(ev.grants, state),
+        Event::UserSuspended(ev) => {
+            state.data.suspended_users.suspend(ev.noble_id, ev.suspension);
+            state.push_event_to_all_local_post_index(LocalPostIndexEvent::UserSuspended(Box::new(UserSuspended {
+                noble_id: ev.noble_id,
+                suspension: ev.suspension,
+            })));
+        },
+        Event::SuspensionLifted(ev) => {
+            state.data.suspended_users.lift(ev.noble_id);
+            state.push_event_to_all_local_post_index(LocalPostIndexEvent::SuspensionLifted(Box::new(SuspensionLifted {
+                noble_id: ev.noble_id,
+            })));
+        },
     }
 }
 
 fn new_comment(
     noble_id: NobleId,
     post_id: PostId,
-    now: TimestampMillis,
+    date_created: TimestampMillis,
     state: &mut RuntimeState,
 ) {
-    if let Some(post) = state.data.posts.get_mut(post_id) {
-        post.date_last_commented = now;
+    state.data.posts.update(post_id, state.env.now(), |post| {
+        post.date_last_commented = date_created;
         post.comments_count += 1;
         if !post.contributed_users.contains(&noble_id) {
             if post.contributed_users.len() < 2 {
@@ -57,29 +109,27 @@ fn new_comment(
                 }
             }
         }
-    }
+    });
+    state.data.posts.add_comment(post_id, noble_id, date_created);
 }
 
+// Also how comments hidden or removed by moderators come back from the post's local_post_index.
 fn delete_comment(
     post_id: PostId,
+    noble_id: NobleId,
     comments_count: u32,
     state: &mut RuntimeState,
 ) {
-    if let Some(post) = state.data.posts.get_mut(post_id) {
-        post.comments_count = comments_count;
-    }
+    state.data.posts.update(post_id, state.env.now(), |post| post.comments_count = comments_count);
+    state.data.posts.remove_comment(post_id, noble_id);
 }
 
 fn post_liked(post_id: PostId, state: &mut RuntimeState) {
-    if let Some(post) = state.data.posts.get_mut(post_id) {
-        post.liked_users_count += 1;
-    }
+    state.data.posts.update(post_id, state.env.now(), |post| post.liked_users_count += 1);
 }
 
 fn post_unliked(post_id: PostId, state: &mut RuntimeState) {
-    if let Some(post) = state.data.posts.get_mut(post_id) {
-        post.liked_users_count -= 1;
-    }
+    state.data.posts.update(post_id, state.env.now(), |post| post.liked_users_count = post.liked_users_count.saturating_sub(1));
 }
 
 fn post_edited(
@@ -90,12 +140,7 @@ fn post_edited(
     invited_users: HashSet<NobleId>,
     state: &mut RuntimeState,
 ) {
-    if let Some(post) = state.data.posts.get_mut(post_id) {
-        post.title = title;
-        post.description = description;
-        post.post_privacy = post_privacy;
-        post.invited_users = invited_users;
-    }
+    state.data.posts.edit_post(post_id, title, description, post_privacy, invited_users);
 }
 
 fn post_deleted(post_id: PostId, state: &mut RuntimeState) {
@@ -105,9 +150,57 @@ fn post_deleted(post_id: PostId, state: &mut RuntimeState) {
     }
 }
 
+fn set_trashed(post_id: PostId, trashed: bool, state: &mut RuntimeState) {
+    state.data.posts.update(post_id, state.env.now(), |post| post.trashed = trashed);
+}
+
+// The post is taken out of the listings straight away, its canister does the rest.
+fn content_moderated(ev: post_index_canister::ContentModerated, state: &mut RuntimeState) {
+    let canister_id = match state.data.posts.get(ev.post_id) {
+        Some(post) => post.canister_id,
+        None => return,
+    };
+    if ev.comment_id.is_none() && matches!(ev.action, ModerationAction::Hide | ModerationAction::Remove) {
+        set_trashed(ev.post_id, true, state);
+    }
+    state.push_event_to_local_post_index(canister_id, LocalPostIndexEvent::ContentModerated(Box::new(ContentModerated {
+        post_id: ev.post_id,
+        comment_id: ev.comment_id,
+        action: ev.action,
+        reason: ev.reason,
+    })));
+}
+
+fn file_attached(post_id: PostId, attached_file_id: FileId, state: &mut RuntimeState) {
+    state.data.posts.update(post_id, state.env.now(), |post| post.attached_file_id = attached_file_id);
+}
+
 fn add_local_user_index_canister_id(canister_id: CanisterId, state: &mut RuntimeState) {
     state.data.local_user_index_canister_ids.insert(canister_id);
     state.push_event_to_all_local_post_index(LocalPostIndexEvent::LocalUserIndexCanisterAdded(Box::new(LocalUserIndexCanisterAdded{
         canister_id
     })));
-}
\ No newline at end of file
+}
+
+fn jwt_keys_updated(keys: Vec<JwtVerificationKey>, state: &mut RuntimeState) {
+    types::set_jwt_verification_keys(&keys);
+    state.data.jwt_verification_keys = keys.clone();
+    state.push_event_to_all_local_post_index(LocalPostIndexEvent::JwtKeysUpdated(Box::new(JwtKeysUpdated {
+        keys,
+    })));
+}
+
+fn roles_updated(grants: Vec<RoleGrant>, state: &mut RuntimeState) {
+    state.data.roles = Roles::from_grants(grants.clone());
+    state.push_event_to_all_local_post_index(LocalPostIndexEvent::RolesUpdated(Box::new(RolesUpdated {
+        grants,
+    })));
+}
+
+fn session_revoked(revocation: SessionRevocation, state: &mut RuntimeState) {
+    state.data.session_revocations.apply(revocation.clone(), state.env.now());
+    types::set_session_revocations(&state.data.session_revocations);
+    state.push_event_to_all_local_post_index(LocalPostIndexEvent::SessionRevoked(Box::new(SessionRevoked {
+        revocation,
+    })));
+}
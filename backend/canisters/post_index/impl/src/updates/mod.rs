@@ -1,4 +1,5 @@
 pub mod add_local_post_index_canister;
 pub mod c2c_notify_events;
 pub mod new_post;
+pub mod set_edit_window;
 pub mod upgrade_local_post_index_canister_wasm;
\ No newline at end of file
@@ -10,6 +10,10 @@ async fn new_post(args: Args) -> Response {
     let (user_index_canister_id, now) = read_state(|state| (state.data.user_index_canister_id, state.env.now()));
 
     if let Some(jwt) = check_jwt(&args.jwt, now) {
+        if let Some(suspension) = read_state(|state| state.data.suspended_users.get(jwt.noble_id, now)) {
+            return Suspended { until: suspension.until };
+        }
+
         match user_index_canister_c2c_client::c2c_is_nobleblocks_user(
             user_index_canister_id,
             &user_index_canister::c2c_is_nobleblocks_user::Args{noble_id: jwt.noble_id}
@@ -66,7 +70,7 @@ fn new_post_impl(args: &Args, state: &mut RuntimeState) -> Result<(CanisterId, P
     };
 
     let mut post_id = state.env.rng().gen_range(1000000000u64..10000000000u64);
-    while state.data.posts.get(post_id).is_some() {
+    while state.data.posts.contains(post_id) {
         post_id = state.env.rng().gen_range(1000000000u64..10000000000u64);
     }
 
@@ -0,0 +1,20 @@
+use crate::{mutate_state, RuntimeState};
+use canister_api_macros::proposal;
+use local_post_index_canister::{Event as LocalPostIndexEvent, EditWindowUpdated};
+use post_index_canister::set_edit_window::{Response::*, *};
+use tracing::info;
+
+// After the window only moderators can edit posts and comments.
+#[proposal(permission = "ManageSettings")]
+fn set_edit_window(args: Args) -> Response {
+    mutate_state(|state| set_edit_window_impl(args, state))
+}
+
+fn set_edit_window_impl(args: Args, state: &mut RuntimeState) -> Response {
+    state.data.edit_window = args.edit_window;
+    state.push_event_to_all_local_post_index(LocalPostIndexEvent::EditWindowUpdated(Box::new(EditWindowUpdated {
+        edit_window: args.edit_window,
+    })));
+    info!(edit_window = ?args.edit_window, "Edit window updated");
+    Success
+}
@@ -1,3 +1,4 @@
+use candid::CandidType;
 use serde::{Serialize, Deserialize};
 
 mod lifecycle;
@@ -6,7 +7,7 @@ mod updates;
 
 pub use lifecycle::*;
 pub use queries::*;
-use types::{NobleId, Country, AcademicDegree, PostId, CommentId, AvatarId, CanisterId};
+use types::{NobleId, Country, AcademicDegree, PostId, CommentId, AvatarId, CanisterId, ReportReason, ReportTarget};
 pub use updates::*;
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -23,7 +24,12 @@ pub enum Event {
     PhotoChanged(Box<PhotoChanged>),
     CommentLiked(Box<CommentLiked>),
     CommentUnliked(Box<CommentUnliked>),
+    CommentReplied(Box<CommentReplied>),
+    UsersMentioned(Box<UsersMentioned>),
+    PostDeleted(Box<PostDeleted>),
     LocalPostIndexAdded(Box<LocalPostIndexAdded>),
+    SocialGraphSynced(Box<SocialGraphSynced>),
+    UserWarned(Box<UserWarned>),
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -43,6 +49,40 @@ pub struct CommentLiked {
     pub noble_id: NobleId,
     pub post_id: PostId,
     pub comment_id: CommentId,
+    // The author of the post or comment, None for events queued before notifications.
+    #[serde(default)]
+    pub author_id: Option<NobleId>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct CommentReplied {
+    pub noble_id: NobleId,
+    pub recipient_id: NobleId,
+    pub post_id: PostId,
+    pub comment_id: CommentId,
+    pub parent_comment_id: CommentId,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct UsersMentioned {
+    pub noble_id: NobleId,
+    pub usernames: Vec<String>,
+    pub post_id: PostId,
+    pub comment_id: CommentId,
+}
+
+// A moderator warned the author of reported content.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct UserWarned {
+    pub noble_id: NobleId,
+    pub target: ReportTarget,
+    pub reason: ReportReason,
+}
+
+// The post has been removed for good, any bookmarks and likes of it are dropped.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct PostDeleted {
+    pub post_id: PostId,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -68,6 +108,14 @@ pub struct BlockUser {
     pub receiver_id: NobleId,
 }
 
+// A user's follows and blocks from before the post canisters kept track of them, sent once.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct SocialGraphSynced {
+    pub noble_id: NobleId,
+    pub following: Vec<NobleId>,
+    pub blocked: Vec<NobleId>,
+}
+
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct FollowRequest {
     pub sender_id: NobleId,
@@ -86,11 +134,11 @@ pub struct ProfileChanged {
     pub avatar_id: AvatarId,
 }
 
+// No email, user_index is the only place it changes, once the new address is verified.
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub struct AccountChanged {
     pub noble_id: NobleId,
     pub username: String,
-    pub email: String,
     pub search_by_email: bool,
 }
 
@@ -103,9 +151,55 @@ pub struct PhotoChanged {
 #[derive(Serialize, Deserialize, Clone, Debug)]
 pub enum EmailEvent {
     RegisterUser(Box<RegisterUser>),
-    ResetPassword(Box<ResetPassword>),
+    // Events queued before passwords stopped being emailed were named ResetPassword. Their password
+    // is dropped and they go out as this notice, which tells the user to start a new reset.
+    #[serde(alias = "ResetPassword")]
+    PasswordChanged(Box<PasswordChanged>),
     ResetPasswordVerify(Box<ResetPasswordVerify>),
     Feedback(Box<Feedback>),
+    ChangeEmailVerify(Box<ChangeEmailVerify>),
+    EmailChangeRequested(Box<EmailChangeRequested>),
+}
+
+impl EmailEvent {
+    pub fn recipient(&self) -> &str {
+        match self {
+            EmailEvent::RegisterUser(data) => &data.email,
+            EmailEvent::PasswordChanged(data) => &data.email,
+            EmailEvent::ResetPasswordVerify(data) => &data.email,
+            EmailEvent::Feedback(data) => &data.email,
+            EmailEvent::ChangeEmailVerify(data) => &data.email,
+            EmailEvent::EmailChangeRequested(data) => &data.email,
+        }
+    }
+
+    pub fn template_name(&self) -> EmailTemplateName {
+        match self {
+            EmailEvent::RegisterUser(_) => EmailTemplateName::RegisterUser,
+            EmailEvent::PasswordChanged(_) => EmailTemplateName::PasswordChanged,
+            EmailEvent::ResetPasswordVerify(_) => EmailTemplateName::ResetPasswordVerify,
+            EmailEvent::Feedback(_) => EmailTemplateName::Feedback,
+            EmailEvent::ChangeEmailVerify(_) => EmailTemplateName::ChangeEmailVerify,
+            EmailEvent::EmailChangeRequested(_) => EmailTemplateName::EmailChangeRequested,
+        }
+    }
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum EmailTemplateName {
+    RegisterUser,
+    PasswordChanged,
+    ResetPasswordVerify,
+    Feedback,
+    ChangeEmailVerify,
+    EmailChangeRequested,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum EmailProvider {
+    #[default]
+    Mailgun,
+    SendGrid,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -119,6 +213,9 @@ pub struct RegisterUser {
     pub email: String,
     pub name: String,
     pub passkey: String,
+    // Picks the template language, the default one if None.
+    #[serde(default)]
+    pub locale: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
@@ -126,11 +223,35 @@ pub struct ResetPasswordVerify {
     pub email: String,
     pub name: String,
     pub passkey: String,
+    // Picks the template language, the default one if None.
+    #[serde(default)]
+    pub locale: Option<String>,
+}
+
+// Sent to the new address, the change is applied once the passkey is verified.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct ChangeEmailVerify {
+    pub email: String,
+    pub name: String,
+    pub passkey: String,
+    #[serde(default)]
+    pub locale: Option<String>,
+}
+
+// Sent to the current address when a change to `new_email` is requested.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct EmailChangeRequested {
+    pub email: String,
+    pub name: String,
+    pub new_email: String,
+    #[serde(default)]
+    pub locale: Option<String>,
 }
 
 #[derive(Serialize, Deserialize, Clone, Debug)]
-pub struct ResetPassword {
+pub struct PasswordChanged {
     pub email: String,
     pub name: String,
-    pub password: String,
+    #[serde(default)]
+    pub locale: Option<String>,
 }
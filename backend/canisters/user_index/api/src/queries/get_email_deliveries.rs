@@ -0,0 +1,38 @@
+use candid::CandidType;
+use serde::{Deserialize, Serialize};
+use types::TimestampMillis;
+
+#[derive(CandidType, Serialize, Deserialize, Debug)]
+pub struct Args {
+    // None lists every status.
+    pub status: Option<EmailDeliveryStatus>,
+    pub max_results: u32,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Debug)]
+pub enum Response {
+    Success(Vec<EmailDeliverySummary>),
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EmailDeliveryStatus {
+    Pending,
+    // Dead-lettered after running out of attempts.
+    Failed,
+    Delivered,
+}
+
+// Leaves out the body, which can hold verification codes.
+#[derive(CandidType, Serialize, Deserialize, Debug, Clone)]
+pub struct EmailDeliverySummary {
+    pub id: u64,
+    pub kind: String,
+    pub recipient: String,
+    pub status: EmailDeliveryStatus,
+    pub attempts: u32,
+    pub created: TimestampMillis,
+    pub last_attempt: Option<TimestampMillis>,
+    pub next_attempt: Option<TimestampMillis>,
+    pub last_error: Option<String>,
+    pub message_id: Option<String>,
+}
@@ -0,0 +1,49 @@
+use candid::CandidType;
+use serde::{Deserialize, Serialize};
+use types::{ModerationAction, NobleId, ReportId, ReportTarget, TimestampMillis};
+
+#[derive(CandidType, Serialize, Deserialize, Debug)]
+pub struct Args {
+    pub jwt: String,
+    // Newest first, starting before this entry.
+    pub before: Option<u64>,
+    pub limit: u32,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Debug)]
+pub enum Response {
+    Success(Vec<ModerationLogEntry>),
+    PermissionDenied,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct ModerationLogEntry {
+    pub id: u64,
+    pub timestamp: TimestampMillis,
+    pub moderator_id: NobleId,
+    pub event: ModerationEvent,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub enum ModerationEvent {
+    ReportResolved {
+        report_ids: Vec<ReportId>,
+        target: ReportTarget,
+        action: ModerationAction,
+        note: String,
+    },
+    UserSuspended {
+        noble_id: NobleId,
+        until: Option<TimestampMillis>,
+        reason: String,
+        hide_content: bool,
+    },
+    SuspensionLifted {
+        noble_id: NobleId,
+        note: String,
+    },
+    AppealRejected {
+        noble_id: NobleId,
+        note: String,
+    },
+}
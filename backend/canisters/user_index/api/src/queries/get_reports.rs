@@ -0,0 +1,19 @@
+use candid::CandidType;
+use serde::{Deserialize, Serialize};
+use types::{Report, ReportId};
+
+#[derive(CandidType, Serialize, Deserialize, Debug)]
+pub struct Args {
+    pub jwt: String,
+    // Open reports are listed oldest first, resolved ones newest first.
+    pub resolved: bool,
+    // The last report of the previous page.
+    pub after: Option<ReportId>,
+    pub limit: u32,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Debug)]
+pub enum Response {
+    Success(Vec<Report>),
+    PermissionDenied,
+}
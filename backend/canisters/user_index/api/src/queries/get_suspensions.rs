@@ -0,0 +1,16 @@
+use candid::CandidType;
+use serde::{Deserialize, Serialize};
+use types::SuspensionDetails;
+
+#[derive(CandidType, Serialize, Deserialize, Debug)]
+pub struct Args {
+    pub jwt: String,
+    // Only the suspensions with an appeal waiting for a moderator.
+    pub pending_appeals_only: bool,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Debug)]
+pub enum Response {
+    Success(Vec<SuspensionDetails>),
+    PermissionDenied,
+}
@@ -0,0 +1,24 @@
+use candid::CandidType;
+use serde::{Deserialize, Serialize};
+use types::{SessionId, TimestampMillis};
+
+#[derive(CandidType, Serialize, Deserialize, Debug)]
+pub struct Args {
+    pub jwt: String,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Debug)]
+pub enum Response {
+    Success(Vec<SessionSummary>),
+    PermissionDenied,
+    UserNotFound,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Debug, Clone)]
+pub struct SessionSummary {
+    pub session_id: SessionId,
+    pub date_created: TimestampMillis,
+    pub expires: TimestampMillis,
+    // True for the session the request was made with.
+    pub current: bool,
+}
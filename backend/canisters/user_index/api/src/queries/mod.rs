@@ -1,11 +1,16 @@
 pub mod c2c_is_nobleblocks_user;
 pub mod check_email;
 pub mod check_username;
+pub mod get_email_deliveries;
+pub mod get_moderation_log;
 pub mod get_random_users;
+pub mod get_reports;
+pub mod get_suspensions;
 pub mod get_user_info;
 pub mod get_user_info_by_username;
 pub mod get_user_infos;
 pub mod get_users;
-pub mod login_user;
+pub mod list_roles;
+pub mod list_sessions;
 pub mod search_user_by_username;
 pub mod search_user;
\ No newline at end of file
@@ -0,0 +1,19 @@
+use candid::CandidType;
+use serde::{Deserialize, Serialize};
+
+// Suspended users can still sign in, to appeal.
+#[derive(CandidType, Serialize, Deserialize, Debug)]
+pub struct Args {
+    pub jwt: String,
+    pub text: String,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Debug)]
+pub enum Response {
+    Success,
+    PermissionDenied,
+    NotSuspended,
+    // Each suspension can only be appealed once.
+    AlreadyAppealed,
+    TextTooLong(u32),
+}
@@ -0,0 +1,21 @@
+use candid::CandidType;
+use serde::{Deserialize, Serialize};
+use types::NobleId;
+
+// A suspension with no end, it can still be appealed or lifted.
+#[derive(CandidType, Serialize, Deserialize, Debug)]
+pub struct Args {
+    pub jwt: String,
+    pub noble_id: NobleId,
+    pub reason: String,
+    pub hide_content: bool,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Debug)]
+pub enum Response {
+    Success,
+    PermissionDenied,
+    UserNotFound,
+    CannotSuspendModerator,
+    ReasonTooLong(u32),
+}
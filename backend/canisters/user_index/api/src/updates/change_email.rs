@@ -0,0 +1,20 @@
+use candid::CandidType;
+use serde::{Deserialize, Serialize};
+use types::TempId;
+
+#[derive(CandidType, Serialize, Deserialize, Debug)]
+pub struct Args {
+    pub jwt: String,
+    pub email: String,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub enum Response {
+    // A passkey has been sent to the new address, pass it to `verify_code` with this id.
+    Success(TempId),
+    EmailIsInvalid,
+    EmailTaken,
+    EmailUnchanged,
+    PermissionDenied,
+    UserNotFound,
+}
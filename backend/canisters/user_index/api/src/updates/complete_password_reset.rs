@@ -0,0 +1,34 @@
+use candid::CandidType;
+use serde::{Deserialize, Serialize};
+use types::{SuccessLogin, TempId};
+
+#[derive(CandidType, Serialize, Deserialize, Debug)]
+pub struct Args {
+    pub id: TempId,
+    // Returned by `verify_code` once the passkey has been verified.
+    pub reset_token: String,
+    pub new_password: String,
+    pub password_confirm: String,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Debug)]
+pub enum Response {
+    Success(SuccessLogin),
+    TempNotExist,
+    InvalidResetToken,
+    UserNotFound,
+    Error(ErrorResult),
+    InternalError(String),
+}
+
+#[derive(CandidType, Serialize, Deserialize, Debug, PartialEq, Eq, Default)]
+pub struct ErrorResult {
+    pub new_password: String,
+    pub password_confirm: String,
+}
+
+impl ErrorResult {
+    pub fn is_error(&self) -> bool {
+        !(self.new_password.is_empty() && self.password_confirm.is_empty())
+    }
+}
@@ -0,0 +1,20 @@
+use candid::CandidType;
+use serde::{Deserialize, Serialize};
+use types::NobleId;
+
+// Lifting a suspension which has been appealed accepts the appeal.
+#[derive(CandidType, Serialize, Deserialize, Debug)]
+pub struct Args {
+    pub jwt: String,
+    pub noble_id: NobleId,
+    // Why, for the audit log.
+    pub note: String,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Debug)]
+pub enum Response {
+    Success,
+    PermissionDenied,
+    NotSuspended,
+    NoteTooLong(u32),
+}
@@ -1,6 +1,6 @@
 use candid::CandidType;
 use serde::{Serialize, Deserialize};
-use types::SuccessLogin;
+use types::{SuccessLogin, TimestampMillis};
 
 #[derive(CandidType, Serialize, Deserialize, Debug)]
 pub struct Args {
@@ -13,5 +13,7 @@ pub enum Response {
     Success(SuccessLogin),
     UnregisteredUser,
     EmailOrPasswordIncorrect,
+    // Too many failed attempts for this account or caller.
+    LoginLocked { retry_after: TimestampMillis },
     InternalError(String),
 }
@@ -4,15 +4,16 @@ use types::SuccessLogin;
 
 #[derive(CandidType, Serialize, Deserialize, Debug)]
 pub struct Args {
-    pub email: String,
-    pub first_name: String,
-    pub last_name: String,
+    // The raw ID token (JWT) returned by Google Sign-In.
+    pub id_token: String,
 }
 
 #[derive(CandidType, Serialize, Deserialize, Debug)]
 pub enum Response {
     Success(SuccessLogin),
     UsernameRequire(UsernameRequireResult),
+    InvalidIdToken(String),
+    EmailNotVerified,
     InternalError(String),
     UserLimitReached,
 }
@@ -4,15 +4,11 @@ use serde::{Deserialize, Serialize};
 #[derive(CandidType, Serialize, Deserialize, Debug)]
 pub struct Args {
     pub jwt: String,
-    pub email: String,
 }
 
 #[derive(CandidType, Serialize, Deserialize, Debug, PartialEq, Eq)]
 pub enum Response {
     Success,
     PermissionDenied,
-    EmailIsInvalid,
-    EmailAlreadyExist,
     UserNotFound,
-    InternalError(String),
 }
@@ -0,0 +1,17 @@
+use candid::CandidType;
+use serde::{Deserialize, Serialize};
+use types::SuccessLogin;
+
+#[derive(CandidType, Serialize, Deserialize, Debug)]
+pub struct Args {
+    pub refresh_token: String,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Debug)]
+pub enum Response {
+    Success(SuccessLogin),
+    InvalidRefreshToken,
+    // The token had already been used, the session has been ended and the user has to log in again.
+    RefreshTokenReused,
+    InternalError(String),
+}
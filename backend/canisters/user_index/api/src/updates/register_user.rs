@@ -8,6 +8,9 @@ pub struct Args {
     pub email: String,
     pub password: String,
     pub password_confirm: String,
+    // eg. "en" or "pt-BR", used for the emails sent to the user.
+    #[serde(default)]
+    pub locale: Option<String>,
 }
 
 #[derive(CandidType, Serialize, Deserialize, Debug)]
@@ -0,0 +1,21 @@
+use candid::CandidType;
+use serde::{Deserialize, Serialize};
+use types::NobleId;
+
+#[derive(CandidType, Serialize, Deserialize, Debug)]
+pub struct Args {
+    pub jwt: String,
+    pub noble_id: NobleId,
+    // Why, for the audit log.
+    pub note: String,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Debug)]
+pub enum Response {
+    Success,
+    PermissionDenied,
+    NotSuspended,
+    // There's no appeal waiting for a moderator.
+    NoAppeal,
+    NoteTooLong(u32),
+}
@@ -0,0 +1,35 @@
+use candid::CandidType;
+use human_readable::ToHumanReadable;
+use serde::{Deserialize, Serialize};
+use types::{Role, RoleMember};
+
+#[derive(CandidType, Serialize, Deserialize, Debug)]
+pub struct Args {
+    pub member: RoleMember,
+    pub role: Role,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Debug)]
+pub enum Response {
+    Success,
+    NotGranted,
+    // Someone has to be left who can grant roles.
+    LastGovernor,
+}
+
+#[derive(Serialize)]
+pub struct HumanReadableArgs {
+    member: RoleMember,
+    role: Role,
+}
+
+impl ToHumanReadable for Args {
+    type Target = HumanReadableArgs;
+
+    fn to_human_readable(&self) -> Self::Target {
+        HumanReadableArgs {
+            member: self.member,
+            role: self.role,
+        }
+    }
+}
@@ -0,0 +1,22 @@
+use candid::CandidType;
+use serde::{Deserialize, Serialize};
+use types::{PostId, ReportId, ReportReason};
+
+#[derive(CandidType, Serialize, Deserialize, Debug)]
+pub struct Args {
+    pub jwt: String,
+    pub post_id: PostId,
+    pub reason: ReportReason,
+    pub details: String,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Debug)]
+pub enum Response {
+    Success(ReportId),
+    PermissionDenied,
+    PostNotFound,
+    AlreadyReported,
+    TooManyOpenReports,
+    DetailsTooLong(u32),
+    InternalError(String),
+}
@@ -0,0 +1,22 @@
+use candid::CandidType;
+use serde::{Deserialize, Serialize};
+use types::{NobleId, ReportId, ReportReason};
+
+#[derive(CandidType, Serialize, Deserialize, Debug)]
+pub struct Args {
+    pub jwt: String,
+    pub noble_id: NobleId,
+    pub reason: ReportReason,
+    pub details: String,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Debug)]
+pub enum Response {
+    Success(ReportId),
+    PermissionDenied,
+    UserNotFound,
+    CannotReportSelf,
+    AlreadyReported,
+    TooManyOpenReports,
+    DetailsTooLong(u32),
+}
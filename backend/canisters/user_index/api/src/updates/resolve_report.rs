@@ -0,0 +1,24 @@
+use candid::CandidType;
+use serde::{Deserialize, Serialize};
+use types::{ModerationAction, ReportId};
+
+#[derive(CandidType, Serialize, Deserialize, Debug)]
+pub struct Args {
+    pub jwt: String,
+    pub report_id: ReportId,
+    pub action: ModerationAction,
+    // Why, for the audit log.
+    pub note: String,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Debug)]
+pub enum Response {
+    // Every open report about the same thing is resolved along with it.
+    Success(Vec<ReportId>),
+    PermissionDenied,
+    ReportNotFound,
+    AlreadyResolved,
+    // Users can be warned but not hidden or removed.
+    InvalidAction,
+    NoteTooLong(u32),
+}
@@ -0,0 +1,31 @@
+use candid::CandidType;
+use human_readable::ToHumanReadable;
+use serde::{Deserialize, Serialize};
+use types::JwtKeyId;
+
+#[derive(CandidType, Serialize, Deserialize, Debug)]
+pub struct Args {
+    pub kid: JwtKeyId,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Debug)]
+pub enum Response {
+    Success,
+    KeyNotFound,
+    KeyInUse,
+}
+
+#[derive(Serialize)]
+pub struct HumanReadableArgs {
+    kid: JwtKeyId,
+}
+
+impl ToHumanReadable for Args {
+    type Target = HumanReadableArgs;
+
+    fn to_human_readable(&self) -> Self::Target {
+        HumanReadableArgs {
+            kid: self.kid,
+        }
+    }
+}
@@ -0,0 +1,26 @@
+use candid::CandidType;
+use human_readable::ToHumanReadable;
+use serde::{Deserialize, Serialize};
+use types::JwtKeyId;
+
+#[derive(CandidType, Serialize, Deserialize, Debug)]
+pub struct Args {
+}
+
+#[derive(CandidType, Serialize, Deserialize, Debug)]
+pub enum Response {
+    Success(JwtKeyId),
+}
+
+#[derive(Serialize)]
+pub struct HumanReadableArgs {
+}
+
+impl ToHumanReadable for Args {
+    type Target = HumanReadableArgs;
+
+    fn to_human_readable(&self) -> Self::Target {
+        HumanReadableArgs {
+        }
+    }
+}
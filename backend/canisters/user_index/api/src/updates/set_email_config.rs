@@ -0,0 +1,48 @@
+use candid::CandidType;
+use human_readable::ToHumanReadable;
+use serde::{Deserialize, Serialize};
+use crate::EmailProvider;
+
+#[derive(CandidType, Serialize, Deserialize)]
+pub struct Args {
+    // None keeps the current provider, Mailgun if there is none yet.
+    pub provider: Option<EmailProvider>,
+    pub host: String,
+    pub domain: String,
+    // None keeps the current key, so the other fields can be changed without handling the secret.
+    pub api_key: Option<String>,
+    pub from: String,
+    pub reply_to: Option<String>,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Debug)]
+pub enum Response {
+    Success,
+    ApiKeyRequired,
+    InvalidConfig(String),
+}
+
+#[derive(Serialize)]
+pub struct HumanReadableArgs {
+    provider: Option<String>,
+    host: String,
+    domain: String,
+    api_key: String,
+    from: String,
+    reply_to: Option<String>,
+}
+
+impl ToHumanReadable for Args {
+    type Target = HumanReadableArgs;
+
+    fn to_human_readable(&self) -> Self::Target {
+        HumanReadableArgs {
+            provider: self.provider.map(|p| format!("{p:?}")),
+            host: self.host.clone(),
+            domain: self.domain.clone(),
+            api_key: if self.api_key.is_some() { "<new key>".to_string() } else { "<unchanged>".to_string() },
+            from: self.from.clone(),
+            reply_to: self.reply_to.clone(),
+        }
+    }
+}
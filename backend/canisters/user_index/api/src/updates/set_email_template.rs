@@ -0,0 +1,46 @@
+use candid::CandidType;
+use human_readable::ToHumanReadable;
+use serde::{Deserialize, Serialize};
+use crate::EmailTemplateName;
+
+#[derive(CandidType, Serialize, Deserialize, Debug)]
+pub struct Args {
+    pub name: EmailTemplateName,
+    // eg. "en" or "pt-BR".
+    pub locale: String,
+    // None removes the template, falling back to the built-in one for the default locale.
+    pub template: Option<EmailTemplate>,
+}
+
+// Variables are written as {{name}}, they are HTML escaped in `html` only.
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct EmailTemplate {
+    pub subject: String,
+    pub html: String,
+    pub text: String,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Debug)]
+pub enum Response {
+    Success,
+    InvalidTemplate(String),
+}
+
+#[derive(Serialize)]
+pub struct HumanReadableArgs {
+    name: String,
+    locale: String,
+    template: Option<EmailTemplate>,
+}
+
+impl ToHumanReadable for Args {
+    type Target = HumanReadableArgs;
+
+    fn to_human_readable(&self) -> Self::Target {
+        HumanReadableArgs {
+            name: format!("{:?}", self.name),
+            locale: self.locale.clone(),
+            template: self.template.clone(),
+        }
+    }
+}
@@ -0,0 +1,17 @@
+use candid::CandidType;
+use serde::{Deserialize, Serialize};
+
+#[derive(CandidType, Serialize, Deserialize, Debug)]
+pub struct Args {
+    pub jwt: String,
+    // None goes back to the default language.
+    pub locale: Option<String>,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Debug, PartialEq, Eq)]
+pub enum Response {
+    Success,
+    InvalidLocale,
+    PermissionDenied,
+    UserNotFound,
+}
@@ -1,5 +1,6 @@
 use candid::CandidType;
 use serde::{Deserialize, Serialize};
+use types::SuccessLogin;
 
 #[derive(CandidType, Serialize, Deserialize, Debug)]
 pub struct Args {
@@ -9,12 +10,13 @@ pub struct Args {
     pub password_confirm: String,
 }
 
-#[derive(CandidType, Serialize, Deserialize, Debug, PartialEq, Eq)]
+#[derive(CandidType, Serialize, Deserialize, Debug)]
 pub enum Response {
-    Success,
+    Success(SuccessLogin),
     PermissionDenied,
     UserNotFound,
     Error(ErrorResult),
+    InternalError(String),
 }
 
 #[derive(CandidType, Serialize, Deserialize, Debug, PartialEq, Eq, Default)]
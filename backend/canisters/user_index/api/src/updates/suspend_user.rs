@@ -0,0 +1,26 @@
+use candid::CandidType;
+use serde::{Deserialize, Serialize};
+use types::{NobleId, TimestampMillis};
+
+#[derive(CandidType, Serialize, Deserialize, Debug)]
+pub struct Args {
+    pub jwt: String,
+    pub noble_id: NobleId,
+    pub until: TimestampMillis,
+    // Shown to the user, and kept for the audit log.
+    pub reason: String,
+    // Hides the user's posts and comments from everyone else until the suspension is over.
+    pub hide_content: bool,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Debug)]
+pub enum Response {
+    Success,
+    PermissionDenied,
+    UserNotFound,
+    // Moderators can't suspend each other, or themselves.
+    CannotSuspendModerator,
+    // `until` has already passed.
+    InvalidUntil,
+    ReasonTooLong(u32),
+}
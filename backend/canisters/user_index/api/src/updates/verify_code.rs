@@ -1,6 +1,6 @@
 use candid::CandidType;
 use serde::{Deserialize, Serialize};
-use types::{TempId, SuccessLogin};
+use types::{TempId, SuccessLogin, TimestampMillis};
 
 #[derive(CandidType, Serialize, Deserialize, Debug)]
 pub struct Args {
@@ -11,7 +11,18 @@ pub struct Args {
 #[derive(CandidType, Serialize, Deserialize, Debug)]
 pub enum Response {
     Success(SuccessLogin),
+    // The passkey of a password reset was correct, pass the token to `complete_password_reset`.
+    ResetPasswordVerified(ResetPasswordVerifiedResult),
+    // The passkey sent by `change_email` was correct and the new address is now the account's.
+    EmailChanged,
+    EmailTaken,
     TempNotExist,
     InvalidPasskey,
+    LoginLocked { retry_after: TimestampMillis },
     InternalError(String),
 }
+
+#[derive(CandidType, Serialize, Deserialize, Debug)]
+pub struct ResetPasswordVerifiedResult {
+    pub reset_token: String,
+}
@@ -0,0 +1,53 @@
+use super::{EmailTransport, MessageId, OutgoingEmail};
+use futures::future::{ready, FutureExt, LocalBoxFuture};
+use std::cell::RefCell;
+use std::rc::Rc;
+
+// Records what would have been sent, failing the first `failures` sends.
+#[derive(Clone, Default)]
+pub struct InMemoryTransport {
+    sent: Rc<RefCell<Vec<OutgoingEmail>>>,
+    failures: Rc<RefCell<u32>>,
+}
+
+impl InMemoryTransport {
+    pub fn failing(failures: u32) -> InMemoryTransport {
+        InMemoryTransport {
+            failures: Rc::new(RefCell::new(failures)),
+            ..Default::default()
+        }
+    }
+
+    pub fn sent(&self) -> Vec<OutgoingEmail> {
+        self.sent.borrow().clone()
+    }
+}
+
+impl EmailTransport for InMemoryTransport {
+    fn send(&self, email: OutgoingEmail) -> LocalBoxFuture<'static, Result<MessageId, String>> {
+        let result = {
+            let mut failures = self.failures.borrow_mut();
+            if *failures > 0 {
+                *failures -= 1;
+                Err("Connection refused".to_string())
+            } else {
+                let message_id = email.message_id.clone();
+                self.sent.borrow_mut().push(email);
+                Ok(message_id)
+            }
+        };
+        ready(result).boxed_local()
+    }
+}
+
+pub fn test_email() -> OutgoingEmail {
+    OutgoingEmail {
+        from: "info@nobleblocks.com".to_string(),
+        reply_to: "info@nobleblocks.com".to_string(),
+        to: "user@gmail.com".to_string(),
+        subject: "Subject".to_string(),
+        html: "<p>Hi</p>".to_string(),
+        text: "Hi".to_string(),
+        message_id: "<1.0@nobleblocks.com>".to_string(),
+    }
+}
@@ -0,0 +1,132 @@
+use super::{is_success, transform_context, HttpEmailApi, OutgoingEmail};
+use crate::model::email_config::EmailConfig;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use ic_cdk::api::management_canister::http_request::{CanisterHttpRequestArgument, HttpHeader, HttpMethod, HttpResponse};
+
+const BOUNDARY: &str = "___NOBLEBLOCKS_BOUNDARY___";
+const MAX_RESPONSE_BYTES: u64 = 1024;
+
+#[derive(Clone)]
+pub struct Mailgun {
+    host: String,
+    domain: String,
+    authorization: String,
+}
+
+impl Mailgun {
+    pub fn new(config: &EmailConfig) -> Mailgun {
+        Mailgun {
+            host: config.host.clone(),
+            domain: config.domain.clone(),
+            authorization: format!("Basic {}", STANDARD.encode(format!("api:{}", config.api_key()))),
+        }
+    }
+}
+
+impl HttpEmailApi for Mailgun {
+    fn build_request(&self, email: &OutgoingEmail) -> CanisterHttpRequestArgument {
+        let OutgoingEmail { from, reply_to, to, subject, html, text, message_id } = email;
+
+        let body = format!(r#"
+--{BOUNDARY}
+Content-Disposition: form-data; name="from"
+
+{from}
+--{BOUNDARY}
+Content-Disposition: form-data; name="h:Reply-To"
+
+{reply_to}
+--{BOUNDARY}
+Content-Disposition: form-data; name="h:Message-Id"
+
+{message_id}
+--{BOUNDARY}
+Content-Disposition: form-data; name="to"
+
+{to}
+--{BOUNDARY}
+Content-Disposition: form-data; name="subject"
+
+{subject}
+--{BOUNDARY}
+Content-Disposition: form-data; name="html"
+
+{html}
+--{BOUNDARY}
+Content-Disposition: form-data; name="text"
+
+{text}
+--{BOUNDARY}--
+"#);
+
+        CanisterHttpRequestArgument {
+            url: format!("https://{}/v3/{}/messages", self.host, self.domain),
+            max_response_bytes: Some(MAX_RESPONSE_BYTES),
+            method: HttpMethod::POST,
+            headers: vec![
+                HttpHeader {
+                    name: "Host".to_string(),
+                    value: self.host.clone(),
+                },
+                HttpHeader {
+                    name: "User-Agent".to_string(),
+                    value: "NobleBlocks".to_string(),
+                },
+                HttpHeader {
+                    name: "Authorization".to_string(),
+                    value: self.authorization.clone(),
+                },
+                HttpHeader {
+                    name: "Content-Type".to_string(),
+                    value: format!("multipart/form-data; boundary={BOUNDARY}"),
+                },
+                HttpHeader {
+                    name: "Accept".to_string(),
+                    value: "*/*".to_string(),
+                },
+            ],
+            body: Some(body.into_bytes()),
+            transform: transform_context(),
+        }
+    }
+
+    fn check_response(&self, response: &HttpResponse) -> Result<(), String> {
+        if is_success(response) {
+            Ok(())
+        } else {
+            Err(format!("Mailgun returned {}", response.status))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::email::in_memory::test_email;
+    use user_index_canister::EmailProvider;
+
+    #[test]
+    fn request_targets_domain_with_basic_auth() {
+        let mailgun = Mailgun::new(&EmailConfig::new_for_test(EmailProvider::Mailgun));
+
+        let request = mailgun.build_request(&test_email());
+
+        assert_eq!(request.url, "https://api.example.com/v3/nobleblocks.com/messages");
+        let authorization = request.headers.iter().find(|h| h.name == "Authorization").unwrap();
+        assert_eq!(authorization.value, format!("Basic {}", STANDARD.encode("api:secret-key")));
+    }
+
+    #[test]
+    fn message_id_is_sent_and_status_checked() {
+        let mailgun = Mailgun::new(&EmailConfig::new_for_test(EmailProvider::Mailgun));
+
+        let request = mailgun.build_request(&test_email());
+        let body = String::from_utf8(request.body.unwrap()).unwrap();
+        assert!(body.contains("name=\"h:Message-Id\"\n\n<1.0@nobleblocks.com>\n"));
+
+        let accepted = HttpResponse { status: 200u32.into(), headers: vec![], body: vec![] };
+        assert_eq!(mailgun.check_response(&accepted), Ok(()));
+        let rejected = HttpResponse { status: 401u32.into(), headers: vec![], body: vec![] };
+        assert!(mailgun.check_response(&rejected).is_err());
+    }
+}
@@ -0,0 +1,118 @@
+use crate::jobs::sync_events_to_send_email::get_cost;
+use crate::model::email_config::EmailConfig;
+use candid::Nat;
+use futures::future::{FutureExt, LocalBoxFuture};
+use ic_cdk::api::management_canister::http_request::{
+    http_request, CanisterHttpRequestArgument, HttpResponse, TransformArgs, TransformContext,
+};
+use ic_cdk_macros::query;
+use user_index_canister::EmailProvider;
+
+mod mailgun;
+mod sendgrid;
+pub mod template;
+#[cfg(test)]
+pub mod in_memory;
+
+pub use mailgun::Mailgun;
+pub use sendgrid::SendGrid;
+
+// The Message-Id of an accepted email. It's generated by the canister rather than read back from the
+// provider, whose own ids differ between the replicas' requests.
+pub type MessageId = String;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct OutgoingEmail {
+    pub from: String,
+    pub reply_to: String,
+    pub to: String,
+    pub subject: String,
+    pub html: String,
+    // Plain-text alternative of `html`.
+    pub text: String,
+    // The same for every replica and every attempt, so all replicas record the same id. Providers
+    // don't drop duplicates by it, each replica's request still sends its own copy.
+    pub message_id: String,
+}
+
+pub trait EmailTransport {
+    fn send(&self, email: OutgoingEmail) -> LocalBoxFuture<'static, Result<MessageId, String>>;
+}
+
+// A provider reached through a single HTTPS outcall per email.
+pub trait HttpEmailApi {
+    fn build_request(&self, email: &OutgoingEmail) -> CanisterHttpRequestArgument;
+
+    // Only the status is left by the time the response gets here, see `transform_email_response`.
+    fn check_response(&self, response: &HttpResponse) -> Result<(), String>;
+}
+
+impl<T: HttpEmailApi + Clone + 'static> EmailTransport for T {
+    fn send(&self, email: OutgoingEmail) -> LocalBoxFuture<'static, Result<MessageId, String>> {
+        let request = self.build_request(&email);
+        let cost = get_cost(&request);
+        let api = self.clone();
+
+        async move {
+            match http_request(request, cost).await {
+                Ok((response,)) => api.check_response(&response).map(|_| email.message_id),
+                Err((code, message)) => Err(format!("{code:?}: {message}")),
+            }
+        }
+        .boxed_local()
+    }
+}
+
+pub fn transport_for(config: &EmailConfig) -> Box<dyn EmailTransport> {
+    match config.provider {
+        EmailProvider::Mailgun => Box::new(Mailgun::new(config)),
+        EmailProvider::SendGrid => Box::new(SendGrid::new(config)),
+    }
+}
+
+pub fn transform_context() -> Option<TransformContext> {
+    Some(TransformContext::from_name("transform_email_response".to_string(), vec![]))
+}
+
+pub fn is_success(response: &HttpResponse) -> bool {
+    response.status >= Nat::from(200u32) && response.status < Nat::from(300u32)
+}
+
+// Every replica makes its own request, and the providers answer each one with a different message
+// id, so only the status is kept for the responses to reach consensus.
+#[query]
+fn transform_email_response(args: TransformArgs) -> HttpResponse {
+    strip_response(args.response)
+}
+
+fn strip_response(response: HttpResponse) -> HttpResponse {
+    HttpResponse {
+        status: response.status,
+        headers: vec![],
+        body: vec![],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ic_cdk::api::management_canister::http_request::HttpHeader;
+
+    #[test]
+    fn replicas_responses_are_identical_once_transformed() {
+        let response = |id: &str| HttpResponse {
+            status: 200u32.into(),
+            headers: vec![
+                HttpHeader { name: "X-Message-Id".to_string(), value: id.to_string() },
+                HttpHeader { name: "Content-Type".to_string(), value: "application/json".to_string() },
+            ],
+            body: format!(r#"{{"id":"<{id}@nobleblocks.com>","message":"Queued. Thank you."}}"#).into_bytes(),
+        };
+
+        let first = strip_response(response("20230901.1"));
+        let second = strip_response(response("20230901.2"));
+
+        assert_eq!(first, second);
+        assert!(is_success(&first));
+    }
+}
@@ -0,0 +1,96 @@
+use super::{is_success, transform_context, HttpEmailApi, OutgoingEmail};
+use crate::model::email_config::EmailConfig;
+use ic_cdk::api::management_canister::http_request::{CanisterHttpRequestArgument, HttpHeader, HttpMethod, HttpResponse};
+use serde_json::json;
+
+const MAX_RESPONSE_BYTES: u64 = 1024;
+
+// SendGrid's v3 mail API, also spoken by several SMTP relays' HTTP front ends.
+#[derive(Clone)]
+pub struct SendGrid {
+    host: String,
+    authorization: String,
+}
+
+impl SendGrid {
+    pub fn new(config: &EmailConfig) -> SendGrid {
+        SendGrid {
+            host: config.host.clone(),
+            authorization: format!("Bearer {}", config.api_key()),
+        }
+    }
+}
+
+impl HttpEmailApi for SendGrid {
+    fn build_request(&self, email: &OutgoingEmail) -> CanisterHttpRequestArgument {
+        let body = json!({
+            "personalizations": [{ "to": [{ "email": email.to }] }],
+            "from": { "email": email.from },
+            "reply_to": { "email": email.reply_to },
+            "subject": email.subject,
+            "headers": { "Message-Id": email.message_id },
+            // SendGrid requires text/plain to come before text/html.
+            "content": [
+                { "type": "text/plain", "value": email.text },
+                { "type": "text/html", "value": email.html },
+            ],
+        });
+
+        CanisterHttpRequestArgument {
+            url: format!("https://{}/v3/mail/send", self.host),
+            max_response_bytes: Some(MAX_RESPONSE_BYTES),
+            method: HttpMethod::POST,
+            headers: vec![
+                HttpHeader {
+                    name: "Authorization".to_string(),
+                    value: self.authorization.clone(),
+                },
+                HttpHeader {
+                    name: "Content-Type".to_string(),
+                    value: "application/json".to_string(),
+                },
+            ],
+            body: Some(body.to_string().into_bytes()),
+            transform: transform_context(),
+        }
+    }
+
+    fn check_response(&self, response: &HttpResponse) -> Result<(), String> {
+        if is_success(response) {
+            Ok(())
+        } else {
+            Err(format!("SendGrid returned {}", response.status))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::email::in_memory::test_email;
+    use user_index_canister::EmailProvider;
+
+    #[test]
+    fn request_is_json_with_bearer_auth() {
+        let sendgrid = SendGrid::new(&EmailConfig::new_for_test(EmailProvider::SendGrid));
+
+        let request = sendgrid.build_request(&test_email());
+
+        assert_eq!(request.url, "https://api.example.com/v3/mail/send");
+        let body: serde_json::Value = serde_json::from_slice(&request.body.unwrap()).unwrap();
+        assert_eq!(body["personalizations"][0]["to"][0]["email"], "user@gmail.com");
+        assert_eq!(body["headers"]["Message-Id"], "<1.0@nobleblocks.com>");
+        let authorization = request.headers.iter().find(|h| h.name == "Authorization").unwrap();
+        assert_eq!(authorization.value, "Bearer secret-key");
+    }
+
+    #[test]
+    fn any_success_status_is_accepted() {
+        let sendgrid = SendGrid::new(&EmailConfig::new_for_test(EmailProvider::SendGrid));
+
+        let accepted = HttpResponse { status: 202u32.into(), headers: vec![], body: vec![] };
+        assert_eq!(sendgrid.check_response(&accepted), Ok(()));
+        let rejected = HttpResponse { status: 400u32.into(), headers: vec![], body: vec![] };
+        assert!(sendgrid.check_response(&rejected).is_err());
+    }
+}
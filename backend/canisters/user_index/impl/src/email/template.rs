@@ -0,0 +1,171 @@
+use user_index_canister::set_email_template::EmailTemplate;
+use user_index_canister::EmailTemplateName;
+
+pub const DEFAULT_LOCALE: &str = "en";
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct RenderedEmail {
+    pub subject: String,
+    pub html: String,
+    pub text: String,
+}
+
+// The variables each template is rendered with, anything else in a template is rejected.
+pub fn variables(name: EmailTemplateName) -> &'static [&'static str] {
+    match name {
+        EmailTemplateName::RegisterUser | EmailTemplateName::ResetPasswordVerify | EmailTemplateName::ChangeEmailVerify => {
+            &["name", "passkey"]
+        }
+        EmailTemplateName::EmailChangeRequested => &["name", "new_email"],
+        EmailTemplateName::PasswordChanged => &["name"],
+        EmailTemplateName::Feedback => &["sender", "feedback"],
+    }
+}
+
+// The English templates used when governance hasn't set one.
+pub fn built_in(name: EmailTemplateName) -> EmailTemplate {
+    let (subject, html, text) = match name {
+        EmailTemplateName::RegisterUser => (
+            "Request To Verify Your Email Address(nobleblock.com)",
+            include_str!("templates/register_user.html"),
+            include_str!("templates/register_user.txt"),
+        ),
+        EmailTemplateName::ResetPasswordVerify => (
+            "Request To Reset Your NOBLEBLOCKS Password(nobleblock.com)",
+            include_str!("templates/reset_password_verify.html"),
+            include_str!("templates/reset_password_verify.txt"),
+        ),
+        EmailTemplateName::PasswordChanged => (
+            "Your NOBLEBLOCKS password has been changed(nobleblock.com)",
+            include_str!("templates/password_changed.html"),
+            include_str!("templates/password_changed.txt"),
+        ),
+        EmailTemplateName::Feedback => (
+            "User feedback",
+            include_str!("templates/feedback.html"),
+            include_str!("templates/feedback.txt"),
+        ),
+        EmailTemplateName::ChangeEmailVerify => (
+            "Request To Verify Your New Email Address(nobleblock.com)",
+            include_str!("templates/change_email_verify.html"),
+            include_str!("templates/change_email_verify.txt"),
+        ),
+        EmailTemplateName::EmailChangeRequested => (
+            "Your NOBLEBLOCKS email address is being changed(nobleblock.com)",
+            include_str!("templates/email_change_requested.html"),
+            include_str!("templates/email_change_requested.txt"),
+        ),
+    };
+
+    EmailTemplate {
+        subject: subject.to_string(),
+        html: html.to_string(),
+        text: text.to_string(),
+    }
+}
+
+pub fn render(template: &EmailTemplate, vars: &[(&str, &str)]) -> RenderedEmail {
+    RenderedEmail {
+        // Header values can't span lines.
+        subject: substitute(&template.subject, |name| lookup(vars, name).replace(&['\r', '\n'][..], " ")),
+        html: substitute(&template.html, |name| escape_html(lookup(vars, name))),
+        text: substitute(&template.text, |name| lookup(vars, name).to_string()),
+    }
+}
+
+// Checks every {{variable}} in the template is one `name` is rendered with.
+pub fn validate(name: EmailTemplateName, template: &EmailTemplate) -> Result<(), String> {
+    if template.subject.trim().is_empty() || template.html.trim().is_empty() || template.text.trim().is_empty() {
+        return Err("Subject, html and text are required.".to_string());
+    }
+
+    let allowed = variables(name);
+    for part in [&template.subject, &template.html, &template.text] {
+        let mut unknown = None;
+        substitute(part, |var| {
+            if unknown.is_none() && !allowed.contains(&var) {
+                unknown = Some(var.to_string());
+            }
+            String::new()
+        });
+        if let Some(var) = unknown {
+            return Err(format!("Unknown variable {{{{{var}}}}}, expected one of {allowed:?}."));
+        }
+    }
+    Ok(())
+}
+
+pub fn escape_html(value: &str) -> String {
+    let mut escaped = String::with_capacity(value.len());
+    for c in value.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&#39;"),
+            _ => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+fn lookup<'a>(vars: &[(&str, &'a str)], name: &str) -> &'a str {
+    vars.iter().find(|(n, _)| *n == name).map_or("", |(_, v)| v)
+}
+
+// Replaces each {{variable}} with `value(variable)`, leaving unterminated braces as they are.
+fn substitute(template: &str, mut value: impl FnMut(&str) -> String) -> String {
+    let mut output = String::with_capacity(template.len());
+    let mut rest = template;
+    while let Some(start) = rest.find("{{") {
+        match rest[start + 2..].find("}}") {
+            Some(len) => {
+                output.push_str(&rest[..start]);
+                output.push_str(&value(rest[start + 2..start + 2 + len].trim()));
+                rest = &rest[start + 2 + len + 2..];
+            }
+            None => break,
+        }
+    }
+    output.push_str(rest);
+    output
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn variables_are_escaped_in_html_only() {
+        let template = EmailTemplate {
+            subject: "Hi {{ name }}".to_string(),
+            html: "<p>Hi, {{name}}</p>".to_string(),
+            text: "Hi, {{name}}".to_string(),
+        };
+
+        let rendered = render(&template, &[("name", "<b>bob</b>")]);
+
+        assert_eq!(rendered.subject, "Hi <b>bob</b>");
+        assert_eq!(rendered.html, "<p>Hi, &lt;b&gt;bob&lt;/b&gt;</p>");
+        assert_eq!(rendered.text, "Hi, <b>bob</b>");
+    }
+
+    #[test]
+    fn built_in_templates_only_use_known_variables() {
+        for name in [
+            EmailTemplateName::RegisterUser,
+            EmailTemplateName::PasswordChanged,
+            EmailTemplateName::ResetPasswordVerify,
+            EmailTemplateName::Feedback,
+            EmailTemplateName::ChangeEmailVerify,
+            EmailTemplateName::EmailChangeRequested,
+        ] {
+            assert_eq!(validate(name, &built_in(name)), Ok(()));
+        }
+
+        let mut template = built_in(EmailTemplateName::PasswordChanged);
+        template.text.push_str("{{passkey}}");
+        assert!(validate(EmailTemplateName::PasswordChanged, &template).is_err());
+    }
+}
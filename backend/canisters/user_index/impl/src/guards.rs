@@ -1,4 +1,5 @@
 use crate::read_state;
+use utils::rbac::Permission;
 
 #[allow(dead_code)]
 pub fn caller_is_local_user_index_canister() -> Result<(), String> {
@@ -17,8 +18,8 @@ pub fn caller_is_known_canister() -> Result<(), String> {
     }
 }
 
-pub fn caller_is_governance_principal() -> Result<(), String> {
-    if read_state(|state| state.caller_is_governance_principal()) {
+pub fn caller_has_permission(permission: Permission) -> Result<(), String> {
+    if read_state(|state| state.caller_has_permission(permission)) {
         Ok(())
     } else {
         Err("Permission Denied".to_owned())
@@ -0,0 +1,34 @@
+use crate::{mutate_state, RuntimeState};
+use ic_cdk_timers::TimerId;
+use std::cell::Cell;
+use std::time::Duration;
+use tracing::{info, trace};
+
+// Expired suspensions are already ignored everywhere, this just tidies them away.
+const INTERVAL: Duration = Duration::from_secs(60 * 60);
+
+thread_local! {
+    static TIMER_ID: Cell<Option<TimerId>> = Cell::default();
+}
+
+pub(crate) fn start_job_if_required(_state: &RuntimeState) -> bool {
+    if TIMER_ID.with(|t| t.get().is_none()) {
+        let timer_id = ic_cdk_timers::set_timer_interval(INTERVAL, run);
+        TIMER_ID.with(|t| t.set(Some(timer_id)));
+        trace!("'lift_expired_suspensions' job started");
+        true
+    } else {
+        false
+    }
+}
+
+fn run() {
+    mutate_state(lift_expired_suspensions);
+}
+
+fn lift_expired_suspensions(state: &mut RuntimeState) {
+    for noble_id in state.data.suspensions.expired(state.env.now()) {
+        state.lift_suspension(noble_id);
+        info!(noble_id, "Expired suspension lifted");
+    }
+}
@@ -0,0 +1,72 @@
+use crate::jobs::sync_events_to_send_email::get_cost;
+use crate::model::google_jwks::GoogleJwks;
+use crate::{mutate_state, RuntimeState};
+use ic_cdk::api::management_canister::http_request::{
+    http_request, CanisterHttpRequestArgument, HttpMethod, HttpResponse, TransformArgs, TransformContext,
+};
+use ic_cdk_macros::query;
+use ic_cdk_timers::TimerId;
+use std::cell::Cell;
+use std::time::Duration;
+use tracing::{error, info, trace};
+
+const GOOGLE_JWKS_URL: &str = "https://www.googleapis.com/oauth2/v3/certs";
+// Google rotates its signing keys roughly every few days, so hourly is plenty.
+const REFRESH_INTERVAL: Duration = Duration::from_secs(60 * 60);
+const MAX_RESPONSE_BYTES: u64 = 10 * 1024;
+
+thread_local! {
+    static TIMER_ID: Cell<Option<TimerId>> = Cell::default();
+}
+
+pub(crate) fn start_job_if_required(_state: &RuntimeState) -> bool {
+    if TIMER_ID.with(|t| t.get().is_none()) {
+        ic_cdk_timers::set_timer(Duration::ZERO, run);
+        let timer_id = ic_cdk_timers::set_timer_interval(REFRESH_INTERVAL, run);
+        TIMER_ID.with(|t| t.set(Some(timer_id)));
+        trace!("'refresh_google_jwks' job started");
+        true
+    } else {
+        false
+    }
+}
+
+pub fn run() {
+    ic_cdk::spawn(refresh());
+}
+
+async fn refresh() {
+    let request = CanisterHttpRequestArgument {
+        url: GOOGLE_JWKS_URL.to_string(),
+        max_response_bytes: Some(MAX_RESPONSE_BYTES),
+        method: HttpMethod::GET,
+        headers: vec![],
+        body: None,
+        transform: Some(TransformContext::from_name("transform_google_jwks".to_string(), vec![])),
+    };
+
+    let cost = get_cost(&request);
+
+    match http_request(request, cost).await {
+        Ok((response,)) => mutate_state(|state| {
+            match GoogleJwks::from_json(&response.body, state.env.now()) {
+                Ok(jwks) => {
+                    state.data.google_jwks = jwks;
+                    info!("Google JWKS refreshed");
+                }
+                Err(err) => error!("Failed to parse Google JWKS: {err}"),
+            }
+        }),
+        Err(err) => error!("Failed to fetch Google JWKS: {:?}", err),
+    }
+}
+
+// Strips the headers (Date, Expires, ...) which differ between replicas so that consensus can be reached.
+#[query]
+fn transform_google_jwks(args: TransformArgs) -> HttpResponse {
+    HttpResponse {
+        status: args.response.status,
+        headers: vec![],
+        body: args.response.body,
+    }
+}
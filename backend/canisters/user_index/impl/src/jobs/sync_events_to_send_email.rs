@@ -1,32 +1,50 @@
-use crate::{mutate_state, RuntimeState};
-use ic_cdk::api::management_canister::http_request::{CanisterHttpRequestArgument, HttpMethod, HttpHeader, http_request};
-
+use crate::email::template::RenderedEmail;
+use crate::email::{transport_for, EmailTransport, MessageId, OutgoingEmail};
+use crate::model::email_config::EmailConfig;
+use crate::model::email_deliveries::{DeliveryId, EmailDelivery};
+use crate::model::email_templates::EmailTemplates;
+use crate::{mutate_state, read_state, RuntimeState};
+use futures::FutureExt;
+use ic_cdk::api::management_canister::http_request::CanisterHttpRequestArgument;
 use ic_cdk_timers::TimerId;
 use crate::EmailEvent;
 use std::cell::Cell;
 use std::time::Duration;
-use tracing::{trace, info};
+use tracing::{error, info, trace, warn};
+
+const MAX_EMAILS_PER_BATCH: usize = 10;
 
 thread_local! {
     static TIMER_ID: Cell<Option<TimerId>> = Cell::default();
+    static RETRY_TIMER_ID: Cell<Option<TimerId>> = Cell::default();
 }
 
-#[allow(dead_code)]
 pub(crate) fn start_job_if_required(state: &RuntimeState) -> bool {
-    if TIMER_ID.with(|t| t.get().is_none()) && !state.data.email_event_sync_queue.is_empty() {
+    if TIMER_ID.with(|t| t.get().is_some()) || state.data.email_config.is_none() {
+        false
+    } else if has_work(state) {
+        if let Some(timer_id) = RETRY_TIMER_ID.with(|t| t.take()) {
+            ic_cdk_timers::clear_timer(timer_id);
+        }
         let timer_id = ic_cdk_timers::set_timer_interval(Duration::ZERO, run);
         TIMER_ID.with(|t| t.set(Some(timer_id)));
         trace!("'sync_events_to_send_email_canisters' job started");
         true
     } else {
+        schedule_retry(state);
         false
     }
 }
 
+fn has_work(state: &RuntimeState) -> bool {
+    !state.data.email_event_sync_queue.is_empty()
+        || state.data.email_deliveries.next_attempt().map_or(false, |t| t <= state.env.now())
+}
+
 pub fn run() {
     match mutate_state(try_get_next) {
-        GetNextResult::Success(batch) => {
-            ic_cdk::spawn(process_batch(batch));
+        GetNextResult::Success(transport, batch) => {
+            ic_cdk::spawn(process_batch(transport, batch));
         }
         GetNextResult::Continue => {}
         GetNextResult::QueueEmpty => {
@@ -34,122 +52,137 @@ pub fn run() {
                 ic_cdk_timers::clear_timer(timer_id);
                 trace!("'sync_events_to_send_email_canisters' job stopped");
             }
+            read_state(schedule_retry);
         }
     }
 }
 
 enum GetNextResult {
-    Success(Vec<(String, Vec<EmailEvent>)>),
+    Success(Box<dyn EmailTransport>, Vec<(DeliveryId, OutgoingEmail)>),
     Continue,
     QueueEmpty,
 }
 
 fn try_get_next(state: &mut RuntimeState) -> GetNextResult {
-    if state.data.email_event_sync_queue.is_empty() {
-        GetNextResult::QueueEmpty
-    } else if let Some(batch) = state.data.email_event_sync_queue.try_start_batch() {
-        GetNextResult::Success(batch)
-    } else {
+    let now = state.env.now();
+    let config = match state.data.email_config.as_ref() {
+        Some(config) => config,
+        // Everything stays queued until set_email_config starts the job again.
+        None => return GetNextResult::QueueEmpty,
+    };
+
+    // Queued events become deliveries, which are then sent and retried from there.
+    if let Some(batch) = state.data.email_event_sync_queue.try_start_batch() {
+        for (email, events) in batch {
+            for event in events {
+                state.data.email_deliveries.push(email.clone(), event, now);
+            }
+        }
+        state.data.email_event_sync_queue.mark_batch_completed();
+    }
+
+    let batch: Vec<_> = state
+        .data
+        .email_deliveries
+        .take_due(now, MAX_EMAILS_PER_BATCH)
+        .into_iter()
+        .map(|delivery| (delivery.id, build_email(config, &state.data.email_templates, delivery)))
+        .collect();
+
+    if !batch.is_empty() {
+        GetNextResult::Success(transport_for(config), batch)
+    } else if !state.data.email_event_sync_queue.is_empty() {
         GetNextResult::Continue
+    } else {
+        GetNextResult::QueueEmpty
     }
 }
 
-async fn process_batch(batch: Vec<(String, Vec<EmailEvent>)>) {
+// Wakes the job up when the earliest retry is due.
+fn schedule_retry(state: &RuntimeState) {
+    if let Some(next_attempt) = state.data.email_deliveries.next_attempt() {
+        let delay = Duration::from_millis(next_attempt.saturating_sub(state.env.now()));
+        let timer_id = ic_cdk_timers::set_timer(delay, || {
+            RETRY_TIMER_ID.with(|t| t.set(None));
+            read_state(|state| start_job_if_required(state));
+        });
+        if let Some(previous) = RETRY_TIMER_ID.with(|t| t.replace(Some(timer_id))) {
+            ic_cdk_timers::clear_timer(previous);
+        }
+    }
+}
+
+async fn process_batch(transport: Box<dyn EmailTransport>, batch: Vec<(DeliveryId, OutgoingEmail)>) {
+    let results = send_batch(transport.as_ref(), batch).await;
+
+    mutate_state(|state| {
+        record_results(results, state);
+        start_job_if_required(state);
+    });
+}
+
+async fn send_batch(
+    transport: &dyn EmailTransport,
+    batch: Vec<(DeliveryId, OutgoingEmail)>,
+) -> Vec<(DeliveryId, Result<MessageId, String>)> {
     let futures: Vec<_> = batch
         .into_iter()
-        .map(|(email, events)| sync_events(email, events))
+        .map(|(id, email)| transport.send(email).map(move |result| (id, result)))
         .collect();
 
-    futures::future::join_all(futures).await;
-
-    mutate_state(|state| state.data.email_event_sync_queue.mark_batch_completed());
+    futures::future::join_all(futures).await
 }
 
-async fn sync_events(email: String, events: Vec<EmailEvent>) {
-    for event in events {
-        send_msg(&email, event).await;
+fn record_results(results: Vec<(DeliveryId, Result<MessageId, String>)>, state: &mut RuntimeState) {
+    let now = state.env.now();
+    for (id, result) in results {
+        match result {
+            Ok(message_id) => {
+                info!(id, %message_id, "Email delivered");
+                state.data.email_deliveries.mark_delivered(id, message_id);
+            }
+            Err(err) => {
+                if state.data.email_deliveries.mark_failed(id, err.clone(), now) {
+                    error!(id, %err, "Email dead-lettered");
+                } else {
+                    warn!(id, %err, "Email delivery failed, will retry");
+                }
+            }
+        }
     }
 }
 
-async fn send_msg(email: &str, event: EmailEvent) {
-    let host = "api.mailgun.net";
-    let domain = "nobleblocks.com";
-    let url = format!("https://{host}/v3/{domain}/messages");
-
-    let request_headers = vec![
-        HttpHeader {
-            name: "Host".to_string(),
-            value: format!("{host}"),
-        },
-        HttpHeader {
-            name: "User-Agent".to_string(),
-            value: "NobleBlocks".to_string(),
-        },
-        HttpHeader {
-            name: "Authorization".to_string(),
-            value: "Basic YXBpOjFhN2NlMDE3NDI2ZWU5NzcwM2M2MzY4ODhhOWUwMmI1LWYwZTUwYTQyLTQ3MzA3NzMz".to_string(),
-        },
-        HttpHeader {
-            name: "Content-Type".to_string(),
-            value: "multipart/form-data; boundary=___NOBLEBLOCKS_BOUNDARY___".to_string(),
-        },
-        HttpHeader {
-            name: "Accept".to_string(),
-            value: "*/*".to_string(),
-        },
-        HttpHeader {
-            name: "Accept-Encoding".to_string(),
-            value: "gzip, deflate, br".to_string(),
-        },
-        HttpHeader {
-            name: "Connection".to_string(),
-            value: "keep-alive".to_string(),
-        },
-    ];
-
-    let (to, subject, content) = get_content(email, event);
-
-    let json_string: String = format!(r#"
---___NOBLEBLOCKS_BOUNDARY___
-Content-Disposition: form-data; name="from"
-
-{email}
---___NOBLEBLOCKS_BOUNDARY___
-Content-Disposition: form-data; name="to"
-
-{to}
---___NOBLEBLOCKS_BOUNDARY___
-Content-Disposition: form-data; name="subject"
-
-{subject}
---___NOBLEBLOCKS_BOUNDARY___
-Content-Disposition: form-data; name="html"
-
-{content}
---___NOBLEBLOCKS_BOUNDARY___--
-"#);
-
-    let json_utf8: Vec<u8> = json_string.into_bytes();
-    let request_body: Option<Vec<u8>> = Some(json_utf8);
-
-    let request = CanisterHttpRequestArgument {
-        url: url.to_string(),
-        max_response_bytes: Some(300), //optional for request
-        method: HttpMethod::POST,
-        headers: request_headers,
-        body: request_body,
-        transform: None, //optional for request
+fn build_email(config: &EmailConfig, templates: &EmailTemplates, delivery: EmailDelivery) -> OutgoingEmail {
+    let from = config.from.clone();
+    // Feedback is queued under the sender's address, replies should go back to them.
+    let reply_to = match &delivery.event {
+        EmailEvent::Feedback(_) => delivery.sender.clone(),
+        _ => config.reply_to.clone().unwrap_or_else(|| from.clone()),
     };
 
-    let cost = get_cost(&request);
+    let name = delivery.event.template_name();
+    let (locale, vars) = match &delivery.event {
+        EmailEvent::RegisterUser(data) => (data.locale.as_deref(), vec![("name", data.name.as_str()), ("passkey", data.passkey.as_str())]),
+        EmailEvent::ResetPasswordVerify(data) => (data.locale.as_deref(), vec![("name", data.name.as_str()), ("passkey", data.passkey.as_str())]),
+        EmailEvent::PasswordChanged(data) => (data.locale.as_deref(), vec![("name", data.name.as_str())]),
+        EmailEvent::ChangeEmailVerify(data) => (data.locale.as_deref(), vec![("name", data.name.as_str()), ("passkey", data.passkey.as_str())]),
+        EmailEvent::EmailChangeRequested(data) => (data.locale.as_deref(), vec![("name", data.name.as_str()), ("new_email", data.new_email.as_str())]),
+        EmailEvent::Feedback(data) => (None, vec![("sender", delivery.sender.as_str()), ("feedback", data.feedback.as_str())]),
+    };
+    let RenderedEmail { subject, html, text } = templates.render(name, locale, &vars);
 
-    match http_request(request, cost).await {
-        Ok(_) => info!("success", ),
-        Err(err) => info!("error: {:?}", err),
+    OutgoingEmail {
+        from,
+        reply_to,
+        to: delivery.event.recipient().to_string(),
+        subject,
+        html,
+        text,
+        message_id: format!("<{}.{}@{}>", delivery.id, delivery.created, config.domain),
     }
 }
 
-fn get_cost(arg: &CanisterHttpRequestArgument) -> u128 {
+pub(crate) fn get_cost(arg: &CanisterHttpRequestArgument) -> u128 {
     let max_response_bytes = match arg.max_response_bytes {
         Some(ref n) => *n as u128,
         None => 2 * 1024 * 1024u128, // default 2MiB
@@ -165,81 +198,92 @@ fn get_cost(arg: &CanisterHttpRequestArgument) -> u128 {
     per_call_cost + per_request_byte_cost * (arg_raw.len() as u128 + 12 - 182) + per_response_byte_cost * max_response_bytes
 }
 
-fn get_content(_email: &str, event: EmailEvent) -> (String, String, String) {
-    match event {
-        EmailEvent::RegisterUser(data) => {
-            (data.email, format!("Request To Verify Your Email Address(nobleblock.com)"), format!(r#"
-<div style="width: 100%; padding: 10 auto;">
-    <div style="max-width: 1000px;">
-        <div style="font-size: 40px; font-weight: bold;display: flex; justify-content: center; max-width: 1600px;font-family: Arial,'Helvetica Neue',Helvetica,sans-serif;padding-top: 30px;">
-            <span style="font-size: 40;">Request To Verify Your Email Address</span>
-        </div>
-        <div style="width: 100%; margin: 30px;">
-            <p style="font-size: 16px;font-family: Arial,'Helvetica Neue',Helvetica,sans-serif;color: black;">Hi, {}</p>
-            <p style="font-size: 16px;font-family: Arial,'Helvetica Neue',Helvetica,sans-serif;color: black;">A request to verify your email address has been detected on NOBLEBLOCKS. To proceed, please</p>
-            <p style="font-size: 16px;font-family: Arial,'Helvetica Neue',Helvetica,sans-serif;color: black;">enter the verification code provided below:</p>
-            <p style="font-size: 20px;font-weight: bold;font-family: Arial,'Helvetica Neue',Helvetica,sans-serif;">Verification Code: {}</p>
-            <p style="font-size: 16px;font-family: Arial,'Helvetica Neue',Helvetica,sans-serif;color: black;">For your security, this code will expire in 3 minutes and is valid for only one use.</p>
-            <br />
-            <p style="font-size: 16px;font-family: Arial,'Helvetica Neue',Helvetica,sans-serif;color: black;">Best regards</p>
-            <p style="font-size: 16px;font-family: Arial,'Helvetica Neue',Helvetica,sans-serif;color: black;">NOBLEBLOCKS Team</p>
-            <a href="https://nobleblocks.com" style="margin-top: 20px; color: #1155cc">www.nobleblocks.com</a>
-            <p style="background: #888888; width: 100%; height: 2px;"></p>
-            <p style="font-size: 16px;font-family: Arial,'Helvetica Neue',Helvetica,sans-serif;color: black;"><i>Please do not reply to this email as it is automatically generated.</i></p>
-            <p style="background: #888888; width: 100%; height: 2px;"></p>
-        </div>
-    </div>
-</div>"#, data.name, data.passkey))
-        },
-        EmailEvent::ResetPasswordVerify(data) => {
-            (data.email, format!("Request To Reset Your NOBLEBLOCKS Password(nobleblock.com)"), format!(r#"
-<div style="width: 100%; padding: 10 auto;">
-    <div style="max-width: 1000px;">
-        <div style="font-size: 40px; font-weight: bold;display: flex; justify-content: center; max-width: 1600px;font-family: Arial,'Helvetica Neue',Helvetica,sans-serif;padding-top: 30px;">
-            <span style="font-size: 40;">Request To Reset Your NOBLEBLOCKS Password</span>
-        </div>
-        <div style="width: 100%; margin: 30px;">
-            <p style="font-size: 16px;font-family: Arial,'Helvetica Neue',Helvetica,sans-serif;color: black;">Hi, {}</a></p>
-            <p style="font-size: 16px;font-family: Arial,'Helvetica Neue',Helvetica,sans-serif;color: black;">A request to reset your password has been detected on NOBLEBLOCKS. To proceed, please</p>
-            <p style="font-size: 16px;font-family: Arial,'Helvetica Neue',Helvetica,sans-serif;color: black;">Enter the verification code provided below:</p>
-            <p style="font-size: 20px;font-weight: bold;font-family: Arial,'Helvetica Neue',Helvetica,sans-serif;">Verification Code: {}</p>
-            <p style="font-size: 16px;font-family: Arial,'Helvetica Neue',Helvetica,sans-serif;color: black;">For your security, this code will expire in 3 minutes and is valid for only one use.</p>
-            <br />
-            <p style="font-size: 16px;font-family: Arial,'Helvetica Neue',Helvetica,sans-serif;color: black;">Best regards</p>
-            <p style="font-size: 16px;font-family: Arial,'Helvetica Neue',Helvetica,sans-serif;color: black;">NOBLEBLOCKS Team</p>
-            <a href="https://nobleblocks.com" style="margin-top: 20px; color: #1155cc">www.nobleblocks.com</a>
-            <p style="background: #888888; width: 100%; height: 2px;"></p>
-            <p style="font-size: 16px;font-family: Arial,'Helvetica Neue',Helvetica,sans-serif;color: black;"><i>Please do not reply to this email as it is automatically generated.</i></p>
-            <p style="background: #888888; width: 100%; height: 2px;"></p>
-        </div>
-    </div>
-</div>"#, data.name, data.passkey))
-        },
-        EmailEvent::ResetPassword(data) => {
-            (data.email, format!("Your password has been successfully reset(nobleblock.com)"), format!(r#"
-<div style="width: 100%; padding: 10 auto;">
-    <div style="max-width: 1000px;">
-        <div style="font-size: 40px; font-weight: bold;display: flex; justify-content: center; max-width: 1600px;font-family: Arial,'Helvetica Neue',Helvetica,sans-serif;padding-top: 30px;">
-            <span style="font-size: 40;">Your password has been successfully reset</span>
-        </div>
-        <div style="width: 100%; margin: 30px;">
-            <p style="font-size: 16px;font-family: Arial,'Helvetica Neue',Helvetica,sans-serif;color: black;">Hi, {}</a></p>
-            <p style="font-size: 16px;font-family: Arial,'Helvetica Neue',Helvetica,sans-serif;color: black;">Your password has been successfully reset.</p>
-            <p style="font-size: 16px;font-family: Arial,'Helvetica Neue',Helvetica,sans-serif;color: black;">Please use following password from now:</p>
-            <p style="font-size: 20px;font-weight: bold;font-family: Arial,'Helvetica Neue',Helvetica,sans-serif;">New password: {}</p>
-            <br />
-            <p style="font-size: 16px;font-family: Arial,'Helvetica Neue',Helvetica,sans-serif;color: black;">Best regards</p>
-            <p style="font-size: 16px;font-family: Arial,'Helvetica Neue',Helvetica,sans-serif;color: black;">NOBLEBLOCKS Team</p>
-            <a href="https://nobleblocks.com" style="margin-top: 20px; color: #1155cc">www.nobleblocks.com</a>
-            <p style="background: #888888; width: 100%; height: 2px;"></p>
-            <p style="font-size: 16px;font-family: Arial,'Helvetica Neue',Helvetica,sans-serif;color: black;"><i>Please do not reply to this email as it is automatically generated.</i></p>
-            <p style="background: #888888; width: 100%; height: 2px;"></p>
-        </div>
-    </div>
-</div>"#, data.name, data.password))
-        },
-        EmailEvent::Feedback(data) => {
-            (data.email, format!("User feedback"), data.feedback)
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::email::in_memory::InMemoryTransport;
+    use crate::model::email_deliveries::MAX_ATTEMPTS;
+    use crate::Data;
+    use futures::executor::block_on;
+    use user_index_canister::{EmailProvider, Feedback};
+    use utils::env::test::TestEnv;
+
+    #[test]
+    fn failed_send_is_retried_until_delivered() {
+        let mut state = state();
+        let transport = InMemoryTransport::failing(1);
+
+        let batch = take_batch(&mut state);
+        record_results(block_on(send_batch(&transport, batch)), &mut state);
+        assert!(transport.sent().is_empty());
+        assert!(matches!(try_get_next(&mut state), GetNextResult::QueueEmpty));
+
+        let retry_at = state.data.email_deliveries.next_attempt().unwrap();
+        set_now(&mut state, retry_at);
+        let batch = take_batch(&mut state);
+        record_results(block_on(send_batch(&transport, batch)), &mut state);
+
+        let sent = transport.sent();
+        assert_eq!(sent.len(), 1);
+        assert_eq!(sent[0].to, "info@nobleblocks.com");
+        assert_eq!(sent[0].reply_to, "user@gmail.com");
+        assert!(sent[0].html.contains("&lt;b&gt;Great&lt;/b&gt; site"));
+        assert!(sent[0].text.contains("<b>Great</b> site"));
+
+        let delivered = state.data.email_deliveries.delivered().next().unwrap();
+        assert_eq!(delivered.attempts, 2);
+        assert_eq!(delivered.message_id.as_deref(), Some(sent[0].message_id.as_str()));
+        assert_eq!(sent[0].message_id, format!("<0.{}@nobleblocks.com>", delivered.created));
+    }
+
+    #[test]
+    fn email_is_dead_lettered_after_max_attempts() {
+        let mut state = state();
+        let transport = InMemoryTransport::failing(MAX_ATTEMPTS);
+
+        for _ in 0..MAX_ATTEMPTS {
+            let batch = take_batch(&mut state);
+            record_results(block_on(send_batch(&transport, batch)), &mut state);
+            if let Some(retry_at) = state.data.email_deliveries.next_attempt() {
+                set_now(&mut state, retry_at);
+            }
+        }
+
+        assert_eq!(state.data.email_deliveries.pending_count(), 0);
+        assert_eq!(state.data.email_deliveries.failed_count(), 1);
+        assert!(transport.sent().is_empty());
+    }
+
+    #[test]
+    fn events_stay_queued_without_config() {
+        let mut state = state();
+        state.data.email_config = None;
+
+        assert!(matches!(try_get_next(&mut state), GetNextResult::QueueEmpty));
+        assert!(!state.data.email_event_sync_queue.is_empty());
+    }
+
+    fn state() -> RuntimeState {
+        let mut state = RuntimeState::new(Box::new(TestEnv::default()), Data::default());
+        state.data.email_config = Some(EmailConfig::new_for_test(EmailProvider::Mailgun));
+        state.push_event_to_send_email(
+            "user@gmail.com",
+            EmailEvent::Feedback(Box::new(Feedback {
+                email: "info@nobleblocks.com".to_string(),
+                feedback: "<b>Great</b> site".to_string(),
+            })),
+        );
+        state
+    }
+
+    fn take_batch(state: &mut RuntimeState) -> Vec<(DeliveryId, OutgoingEmail)> {
+        match try_get_next(state) {
+            GetNextResult::Success(_, batch) => batch,
+            _ => panic!("Expected a batch"),
         }
     }
+
+    fn set_now(state: &mut RuntimeState, now: u64) {
+        state.env = Box::new(TestEnv { now, ..Default::default() });
+    }
 }
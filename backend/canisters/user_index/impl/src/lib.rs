@@ -1,20 +1,25 @@
-use std::{collections::HashSet, cell::RefCell};
+use std::{collections::{BTreeMap, HashSet}, cell::RefCell};
 
 use crate::model::{
     user_map::UserMap,
     follow_request_map::FollowRequestMap,
+    moderation_log::ModerationLog,
+    reports::Reports,
+    suspensions::Suspensions,
 };
 use canister_state_macros::canister_state;
 use candid::{Principal, CandidType};
-use local_user_index_canister::Event as LocalUserIndexEvent;
+use local_user_index_canister::{Event as LocalUserIndexEvent, JwtKeysUpdated, NotificationAdded, NotificationKind, SessionRevoked};
 use post_index_canister::Event as PostIndexEvent;
-use model::{local_user_index_map::{LocalUserIndexMap, LocalUserIndex}, temp_map::TempMap};
+use model::{local_user_index_map::{LocalUserIndexMap, LocalUserIndex}, temp_map::TempMap, google_jwks::GoogleJwks, jwt_key_ring::JwtKeyRing, login_attempts::LoginAttempts, email_config::EmailConfig, email_deliveries::EmailDeliveries, email_templates::EmailTemplates};
+use rand::Rng;
 use serde::{Deserialize, Serialize};
 use tracing::info;
-use types::{CanisterId, NobleId, TimestampMillis, Cycles, CanisterWasm, Timestamped, Version};
+use types::{check_jwt, CanisterId, NobleId, TimestampMillis, Cycles, CanisterWasm, Timestamped, Version, SessionRevocation, SessionRevocations, SuccessLogin, ReportReason, ReportTarget, Role, RoleMember, SuspensionDetails};
 use user_index_canister::EmailEvent;
-use utils::{env::Environment, canister_event_sync_queue::CanisterEventSyncQueue, email_event_sync_queue::EmailEventSyncQueue, canister::{CanistersRequiringUpgrade, FailedUpgradeCount}, consts::{CYCLES_REQUIRED_FOR_UPGRADE, DEV_TEAM_PRINCIPAL}};
+use utils::{env::Environment, canister_event_sync_queue::CanisterEventSyncQueue, email_event_sync_queue::EmailEventSyncQueue, canister::{CanistersRequiringUpgrade, FailedUpgradeCount}, consts::CYCLES_REQUIRED_FOR_UPGRADE, rbac::{Permission, Roles}};
 
+mod email;
 mod jobs;
 mod guards;
 mod lifecycle;
@@ -60,10 +65,28 @@ impl RuntimeState {
         self.data.post_index_canister_id == caller
     }
 
-    pub fn caller_is_governance_principal(&self) -> bool {
-        let caller = self.env.caller();
-        DEV_TEAM_PRINCIPAL == caller ||
-        self.data.super_admin == caller
+    pub fn caller_has_permission(&self, permission: Permission) -> bool {
+        self.data.roles.principal_has_permission(self.env.caller(), permission)
+    }
+
+    pub fn is_platform_moderator(&self, noble_id: NobleId) -> bool {
+        self.data.roles.user_has_permission(noble_id, Permission::ModerateContent)
+    }
+
+    // Who is calling, if the JWT is valid and they are a platform moderator.
+    pub fn moderator_from_jwt(&self, jwt: &str) -> Option<NobleId> {
+        check_jwt(jwt, self.env.now())
+            .map(|jwt| jwt.noble_id)
+            .filter(|noble_id| self.is_platform_moderator(*noble_id))
+    }
+
+    // The warning comes from the moderators rather than a user, so there's no actor.
+    pub fn warn_user(&mut self, noble_id: NobleId, target: ReportTarget, reason: ReportReason) {
+        self.push_event_to_local_user_index(noble_id, LocalUserIndexEvent::NotificationAdded(Box::new(NotificationAdded {
+            recipient_id: noble_id,
+            actor_id: 0,
+            kind: NotificationKind::Warned { target, reason },
+        })));
     }
 
     pub fn push_event_to_local_user_index(&mut self, noble_id: NobleId, event: LocalUserIndexEvent) {
@@ -74,6 +97,12 @@ impl RuntimeState {
         }
     }
 
+    pub fn push_event_to_local_user_index_canister(&mut self, canister_id: CanisterId, event: LocalUserIndexEvent) {
+        self.data.user_index_event_sync_queue.push(canister_id, event);
+        #[cfg(not(test))]
+        jobs::sync_events_to_local_user_index_canisters::start_job_if_required(self);
+    }
+
     pub fn push_event_to_post_index(&mut self, event: PostIndexEvent) {
         self.data
         .post_index_event_sync_queue
@@ -90,9 +119,85 @@ impl RuntimeState {
         jobs::sync_events_to_local_user_index_canisters::start_job_if_required(self);
     }
 
+    // Ends the user's sessions and has the other canisters reject what the user does from now on.
+    pub fn suspend_user(&mut self, details: SuspensionDetails) {
+        let noble_id = details.noble_id;
+        let suspension = details.suspension;
+        self.data.suspensions.add(details);
+
+        if let Some(mut user) = self.data.users.get(noble_id) {
+            let generation = user.end_all_sessions();
+            self.data.users.update(&user);
+            self.revoke_sessions(SessionRevocation::AllSessions { noble_id, generation });
+        }
+
+        self.push_event_to_all_local_user_index(LocalUserIndexEvent::UserSuspended(Box::new(local_user_index_canister::UserSuspended {
+            noble_id,
+            suspension,
+        })));
+        self.push_event_to_post_index(PostIndexEvent::UserSuspended(Box::new(post_index_canister::UserSuspended { noble_id, suspension })));
+    }
+
+    pub fn lift_suspension(&mut self, noble_id: NobleId) -> bool {
+        if self.data.suspensions.remove(noble_id).is_none() {
+            return false;
+        }
+        self.push_event_to_all_local_user_index(LocalUserIndexEvent::SuspensionLifted(Box::new(local_user_index_canister::SuspensionLifted {
+            noble_id,
+        })));
+        self.push_event_to_post_index(PostIndexEvent::SuspensionLifted(Box::new(post_index_canister::SuspensionLifted { noble_id })));
+        true
+    }
+
+    // The post canisters replace their copy of the grants with these.
+    pub fn on_roles_changed(&mut self) {
+        let grants = self.data.roles.grants().to_vec();
+        self.push_event_to_post_index(PostIndexEvent::RolesUpdated(Box::new(post_index_canister::RolesUpdated { grants })));
+    }
+
+    // Installs the verification keys locally and hands them to every canister which checks JWTs.
+    pub fn on_jwt_keys_changed(&mut self) {
+        let keys = self.data.jwt_keys.verification_keys().clone();
+        types::set_jwt_verification_keys(&keys);
+
+        self.push_event_to_all_local_user_index(LocalUserIndexEvent::JwtKeysUpdated(Box::new(JwtKeysUpdated {
+            keys: keys.clone(),
+        })));
+        self.push_event_to_post_index(PostIndexEvent::JwtKeysUpdated(Box::new(post_index_canister::JwtKeysUpdated {
+            keys,
+        })));
+    }
+
+    pub fn start_session(&mut self, noble_id: NobleId) -> Result<SuccessLogin, String> {
+        let now = self.env.now();
+        let session_id = self.env.rng().gen();
+        let refresh_secret = self.env.rng().gen();
+        match self.data.users.get(noble_id) {
+            Some(mut user) => {
+                let result = user.start_session(session_id, refresh_secret, self.data.jwt_keys.signing_key(), now);
+                self.data.users.update(&user);
+                result
+            },
+            None => Err(format!("User not found")),
+        }
+    }
+
+    // Rejects the session(s) here and in every canister which checks JWTs.
+    pub fn revoke_sessions(&mut self, revocation: SessionRevocation) {
+        self.data.session_revocations.apply(revocation.clone(), self.env.now());
+        types::set_session_revocations(&self.data.session_revocations);
+
+        self.push_event_to_all_local_user_index(LocalUserIndexEvent::SessionRevoked(Box::new(SessionRevoked {
+            revocation: revocation.clone(),
+        })));
+        self.push_event_to_post_index(PostIndexEvent::SessionRevoked(Box::new(post_index_canister::SessionRevoked {
+            revocation,
+        })));
+    }
+
     pub fn push_event_to_send_email(&mut self, email: &str, event: EmailEvent) {
-        info!("from: {email} {:?}", event);
-        ic_cdk::println!("from: {email} {:?}", event);
+        // Not the event itself, passkeys in it would let anyone reading the logs take over the account.
+        info!(from = email, to = event.recipient(), kind = ?event.template_name(), "Email queued");
         self.data.email_event_sync_queue.push(String::from(email), event);
         #[cfg(not(test))]
         jobs::sync_events_to_send_email::start_job_if_required(self);
@@ -110,10 +215,11 @@ impl RuntimeState {
             canister_upgrades_failed: canister_upgrades_metrics.failed,
             canister_upgrades_pending: canister_upgrades_metrics.pending as u64,
             canister_upgrades_in_progress: canister_upgrades_metrics.in_progress as u64,
-            governance_principals: self.data.governance_principals.iter().copied().collect(),
             local_user_index_wasm_version: self.data.local_user_index_canister_wasm_for_new_canisters.version,
-            platform_moderators: self.data.platform_moderators.len() as u8,
-            platform_operators: self.data.platform_operators.len() as u8,
+            role_counts: self.data.roles.counts(),
+            open_reports: self.data.reports.open_count(),
+            moderation_log_length: self.data.moderation_log.len(),
+            suspended_users: self.data.suspensions.len(),
             user_index_events_queue_length: self.data.user_index_event_sync_queue.len(),
             local_user_indexes: self.data.local_index_map.iter().map(|(c, i)| (*c, i.clone())).collect(),
             total_cycles_spent_on_canisters: self.data.total_cycles_spent_on_canisters,
@@ -121,6 +227,9 @@ impl RuntimeState {
                 post_index_canister_id: self.data.post_index_canister_id,
                 local_post_index_canister_ids: self.data.local_post_index_canister_ids.clone()
             },
+            email_provider_configured: self.data.email_config.is_some(),
+            emails_pending: self.data.email_deliveries.pending_count(),
+            emails_failed: self.data.email_deliveries.failed_count(),
         }
     }
 }
@@ -145,13 +254,38 @@ struct Data {
     #[serde(default)]
     pub canisters_requiring_upgrade: CanistersRequiringUpgrade,
     #[serde(default)]
-    pub governance_principals: HashSet<Principal>,
+    pub roles: Roles,
+    // Who had a role before there were roles, moved into `roles` by `migrate_roles`.
+    #[serde(default, rename = "governance_principals", skip_serializing)]
+    pub legacy_governance_principals: HashSet<Principal>,
+    #[serde(default, rename = "platform_moderators", skip_serializing)]
+    pub legacy_platform_moderators: HashSet<NobleId>,
+    #[serde(default, rename = "platform_operators", skip_serializing)]
+    pub legacy_platform_operators: HashSet<NobleId>,
     #[serde(default)]
-    pub platform_moderators: HashSet<NobleId>,
+    pub total_cycles_spent_on_canisters: Cycles,
     #[serde(default)]
-    pub platform_operators: HashSet<NobleId>,
+    pub google_jwks: GoogleJwks,
     #[serde(default)]
-    pub total_cycles_spent_on_canisters: Cycles,
+    pub google_client_ids: HashSet<String>,
+    #[serde(default)]
+    pub jwt_keys: JwtKeyRing,
+    #[serde(default)]
+    pub session_revocations: SessionRevocations,
+    #[serde(default)]
+    pub login_attempts: LoginAttempts,
+    #[serde(default)]
+    pub email_config: Option<EmailConfig>,
+    #[serde(default)]
+    pub email_deliveries: EmailDeliveries,
+    #[serde(default)]
+    pub email_templates: EmailTemplates,
+    #[serde(default)]
+    pub reports: Reports,
+    #[serde(default)]
+    pub moderation_log: ModerationLog,
+    #[serde(default)]
+    pub suspensions: Suspensions,
 }
 
 impl Data {
@@ -159,6 +293,7 @@ impl Data {
         post_index_canister_id: CanisterId,
         local_post_index_canister_ids: HashSet<CanisterId>,
         super_admin: Principal,
+        now: TimestampMillis,
     ) -> Self {
         Data {
             users: UserMap::default(),
@@ -174,11 +309,42 @@ impl Data {
             local_user_index_canister_wasm_for_new_canisters: CanisterWasm::default(),
             local_user_index_canister_wasm_for_upgrades: CanisterWasm::default(),
             canisters_requiring_upgrade: CanistersRequiringUpgrade::default(),
-            governance_principals: HashSet::default(),
-            platform_moderators: HashSet::default(),
-            platform_operators: HashSet::default(),
+            roles: Roles::new(super_admin, now),
+            legacy_governance_principals: HashSet::default(),
+            legacy_platform_moderators: HashSet::default(),
+            legacy_platform_operators: HashSet::default(),
             total_cycles_spent_on_canisters: Cycles::default(),
+            google_jwks: GoogleJwks::default(),
+            google_client_ids: HashSet::default(),
+            jwt_keys: JwtKeyRing::default(),
+            session_revocations: SessionRevocations::default(),
+            login_attempts: LoginAttempts::default(),
+            email_config: None,
+            email_deliveries: EmailDeliveries::default(),
+            email_templates: EmailTemplates::default(),
+            reports: Reports::default(),
+            moderation_log: ModerationLog::default(),
+            suspensions: Suspensions::default(),
+        }
+    }
+
+    // Upgrading from before roles, the super admin and the dev team keep governing and whoever was
+    // a governance principal, moderator or operator keeps that role. Returns whether anything changed.
+    pub fn migrate_roles(&mut self, now: TimestampMillis) -> bool {
+        if !self.roles.is_empty() {
+            return false;
+        }
+        self.roles = Roles::new(self.super_admin, now);
+        for principal in std::mem::take(&mut self.legacy_governance_principals) {
+            self.roles.add(RoleMember::Principal(principal), Role::Governance, now);
+        }
+        for noble_id in std::mem::take(&mut self.legacy_platform_operators) {
+            self.roles.add(RoleMember::User(noble_id), Role::Operator, now);
+        }
+        for noble_id in std::mem::take(&mut self.legacy_platform_moderators) {
+            self.roles.add(RoleMember::User(noble_id), Role::Moderator, now);
         }
+        true
     }
 
     pub fn get_anonymous_username(&self) -> String {
@@ -210,10 +376,22 @@ impl Default for Data {
             local_user_index_canister_wasm_for_new_canisters: CanisterWasm::default(),
             local_user_index_canister_wasm_for_upgrades: CanisterWasm::default(),
             canisters_requiring_upgrade: CanistersRequiringUpgrade::default(),
-            governance_principals: HashSet::default(),
-            platform_moderators: HashSet::default(),
-            platform_operators: HashSet::default(),
+            roles: Roles::default(),
+            legacy_governance_principals: HashSet::default(),
+            legacy_platform_moderators: HashSet::default(),
+            legacy_platform_operators: HashSet::default(),
             total_cycles_spent_on_canisters: Cycles::default(),
+            google_jwks: GoogleJwks::default(),
+            google_client_ids: HashSet::default(),
+            jwt_keys: JwtKeyRing::new_for_test(),
+            session_revocations: SessionRevocations::default(),
+            login_attempts: LoginAttempts::default(),
+            email_config: None,
+            email_deliveries: EmailDeliveries::default(),
+            email_templates: EmailTemplates::default(),
+            reports: Reports::default(),
+            moderation_log: ModerationLog::default(),
+            suspensions: Suspensions::default(),
         }
     }
 }
@@ -228,15 +406,19 @@ pub struct Metrics {
     pub canister_upgrades_failed: Vec<FailedUpgradeCount>,
     pub canister_upgrades_pending: u64,
     pub canister_upgrades_in_progress: u64,
-    pub governance_principals: Vec<Principal>,
     pub wasm_version: Version,
     pub local_user_index_wasm_version: Version,
-    pub platform_moderators: u8,
-    pub platform_operators: u8,
+    pub role_counts: BTreeMap<Role, usize>,
+    pub open_reports: usize,
+    pub moderation_log_length: usize,
+    pub suspended_users: usize,
     pub user_index_events_queue_length: usize,
     pub local_user_indexes: Vec<(CanisterId, LocalUserIndex)>,
     pub total_cycles_spent_on_canisters: Cycles,
     pub canister_ids: CanisterIds,
+    pub email_provider_configured: bool,
+    pub emails_pending: usize,
+    pub emails_failed: usize,
 }
 
 #[derive(CandidType, Serialize, Debug)]
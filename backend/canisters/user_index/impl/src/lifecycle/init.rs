@@ -14,6 +14,7 @@ fn init(args: Args) {
         args.post_index_canister_id,
         args.local_post_index_canister_ids.into_iter().collect(),
         args.super_admin,
+        env.now(),
     );
     for canister_id in args.local_user_index_canister_ids {
         data.local_index_map.add_index(canister_id, Version::default());
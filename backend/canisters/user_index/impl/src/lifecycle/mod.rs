@@ -22,11 +22,32 @@ fn init_state(env: Box<dyn Environment>, data: Data, wasm_version: Version) {
     let now = env.now();
     let state = RuntimeState::new(env, data);
 
+    types::set_jwt_verification_keys(state.data.jwt_keys.verification_keys());
+    types::set_session_revocations(&state.data.session_revocations);
+    if state.data.jwt_keys.signing_key().is_none() {
+        ic_cdk_timers::set_timer(Duration::ZERO, init_jwt_keys);
+    }
+
     crate::jobs::start(&state);
     crate::init_state(state);
     WASM_VERSION.with(|v| *v.borrow_mut() = Timestamped::new(wasm_version, now));
 }
 
+fn init_jwt_keys() {
+    ic_cdk::spawn(init_jwt_keys_inner());
+
+    async fn init_jwt_keys_inner() {
+        let seed = get_random_seed().await;
+        mutate_state(|state| {
+            if state.data.jwt_keys.signing_key().is_none() {
+                let kid = state.data.jwt_keys.rotate(seed);
+                state.on_jwt_keys_changed();
+                info!(kid, "Generated initial JWT signing key");
+            }
+        });
+    }
+}
+
 fn reseed_rng() {
     ic_cdk::spawn(reseed_rng_inner());
 
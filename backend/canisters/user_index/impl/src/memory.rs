@@ -4,6 +4,10 @@ use ic_stable_structures::{
 };
 
 const UPGRADES: MemoryId = MemoryId::new(0);
+const USERS_INDEX: MemoryId = MemoryId::new(1);
+const USERS: MemoryId = MemoryId::new(2);
+const MODERATION_LOG_INDEX: MemoryId = MemoryId::new(3);
+const MODERATION_LOG: MemoryId = MemoryId::new(4);
 
 pub type Memory = VirtualMemory<DefaultMemoryImpl>;
 
@@ -16,6 +20,14 @@ pub fn get_upgrades_memory() -> Memory {
     get_memory(UPGRADES)
 }
 
+pub fn get_users_memories() -> (Memory, Memory) {
+    (get_memory(USERS_INDEX), get_memory(USERS))
+}
+
+pub fn get_moderation_log_memories() -> (Memory, Memory) {
+    (get_memory(MODERATION_LOG_INDEX), get_memory(MODERATION_LOG))
+}
+
 fn get_memory(id: MemoryId) -> Memory {
     MEMORY_MANAGER.with(|m| m.get(id))
 }
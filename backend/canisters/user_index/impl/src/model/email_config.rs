@@ -0,0 +1,71 @@
+use serde::{Deserialize, Serialize};
+use std::fmt::{Debug, Formatter};
+use user_index_canister::EmailProvider;
+
+#[derive(Serialize, Deserialize, Clone, PartialEq, Eq)]
+pub struct EmailConfig {
+    #[serde(default)]
+    pub provider: EmailProvider,
+    pub host: String,
+    pub domain: String,
+    api_key: String,
+    pub from: String,
+    pub reply_to: Option<String>,
+}
+
+impl EmailConfig {
+    pub fn new(
+        provider: EmailProvider,
+        host: String,
+        domain: String,
+        api_key: String,
+        from: String,
+        reply_to: Option<String>,
+    ) -> EmailConfig {
+        EmailConfig { provider, host, domain, api_key, from, reply_to }
+    }
+
+    // Only for building the provider's auth header, never log it.
+    pub fn api_key(&self) -> &str {
+        &self.api_key
+    }
+}
+
+// Never print the API key.
+impl Debug for EmailConfig {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("EmailConfig")
+            .field("provider", &self.provider)
+            .field("host", &self.host)
+            .field("domain", &self.domain)
+            .field("from", &self.from)
+            .field("reply_to", &self.reply_to)
+            .finish()
+    }
+}
+
+#[cfg(test)]
+impl EmailConfig {
+    pub fn new_for_test(provider: EmailProvider) -> EmailConfig {
+        EmailConfig::new(
+            provider,
+            "api.example.com".to_string(),
+            "nobleblocks.com".to_string(),
+            "secret-key".to_string(),
+            "info@nobleblocks.com".to_string(),
+            None,
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn debug_hides_api_key() {
+        let config = EmailConfig::new_for_test(EmailProvider::Mailgun);
+
+        assert!(!format!("{config:?}").contains("secret-key"));
+    }
+}
@@ -0,0 +1,190 @@
+use crate::email::MessageId;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, VecDeque};
+use types::TimestampMillis;
+use user_index_canister::EmailEvent;
+
+// Attempts after which an email is moved to the dead-letter list.
+pub const MAX_ATTEMPTS: u32 = 5;
+// Delay after the first failed attempt, doubled on each further failure.
+pub const BASE_RETRY_DELAY: TimestampMillis = 60 * 1000; // 1 min.
+// An attempt whose outcome was never recorded (eg. lost over an upgrade) is retried after this long.
+pub const SEND_TIMEOUT: TimestampMillis = 5 * 60 * 1000; // 5 mins.
+// How many delivered and dead-lettered emails are kept for the admin listing.
+const MAX_HISTORY: usize = 1_000;
+
+pub type DeliveryId = u64;
+
+#[derive(Serialize, Deserialize, Default)]
+pub struct EmailDeliveries {
+    next_id: DeliveryId,
+    pending: BTreeMap<DeliveryId, EmailDelivery>,
+    failed: VecDeque<EmailDelivery>,
+    delivered: VecDeque<EmailDelivery>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct EmailDelivery {
+    pub id: DeliveryId,
+    // The address the event was queued under, which isn't always the recipient.
+    pub sender: String,
+    pub event: EmailEvent,
+    pub created: TimestampMillis,
+    pub attempts: u32,
+    pub last_attempt: Option<TimestampMillis>,
+    pub next_attempt: TimestampMillis,
+    pub last_error: Option<String>,
+    // Recorded as provider_message_id before it was generated by the canister.
+    #[serde(alias = "provider_message_id")]
+    pub message_id: Option<MessageId>,
+}
+
+impl EmailDeliveries {
+    pub fn push(&mut self, sender: String, event: EmailEvent, now: TimestampMillis) -> DeliveryId {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.pending.insert(
+            id,
+            EmailDelivery {
+                id,
+                sender,
+                event,
+                created: now,
+                attempts: 0,
+                last_attempt: None,
+                next_attempt: now,
+                last_error: None,
+                message_id: None,
+            },
+        );
+        id
+    }
+
+    // Claims up to `max` emails which are due, counting the attempt and leasing them for `SEND_TIMEOUT`.
+    pub fn take_due(&mut self, now: TimestampMillis, max: usize) -> Vec<EmailDelivery> {
+        self.pending
+            .values_mut()
+            .filter(|d| d.next_attempt <= now)
+            .take(max)
+            .map(|d| {
+                d.attempts += 1;
+                d.last_attempt = Some(now);
+                d.next_attempt = now + SEND_TIMEOUT;
+                d.clone()
+            })
+            .collect()
+    }
+
+    pub fn mark_delivered(&mut self, id: DeliveryId, message_id: MessageId) {
+        if let Some(mut delivery) = self.pending.remove(&id) {
+            delivery.message_id = Some(message_id);
+            delivery.last_error = None;
+            push_capped(&mut self.delivered, delivery);
+        }
+    }
+
+    // Returns true if the email has been dead-lettered.
+    pub fn mark_failed(&mut self, id: DeliveryId, error: String, now: TimestampMillis) -> bool {
+        let delivery = match self.pending.get_mut(&id) {
+            Some(delivery) => delivery,
+            None => return false,
+        };
+
+        delivery.last_error = Some(error);
+        if delivery.attempts >= MAX_ATTEMPTS {
+            let delivery = self.pending.remove(&id).unwrap();
+            push_capped(&mut self.failed, delivery);
+            true
+        } else {
+            delivery.next_attempt = now + (BASE_RETRY_DELAY << (delivery.attempts - 1));
+            false
+        }
+    }
+
+    pub fn next_attempt(&self) -> Option<TimestampMillis> {
+        self.pending.values().map(|d| d.next_attempt).min()
+    }
+
+    pub fn pending(&self) -> impl Iterator<Item = &EmailDelivery> {
+        self.pending.values()
+    }
+
+    pub fn failed(&self) -> impl Iterator<Item = &EmailDelivery> {
+        self.failed.iter().rev()
+    }
+
+    pub fn delivered(&self) -> impl Iterator<Item = &EmailDelivery> {
+        self.delivered.iter().rev()
+    }
+
+    pub fn pending_count(&self) -> usize {
+        self.pending.len()
+    }
+
+    pub fn failed_count(&self) -> usize {
+        self.failed.len()
+    }
+}
+
+fn push_capped(history: &mut VecDeque<EmailDelivery>, delivery: EmailDelivery) {
+    if history.len() >= MAX_HISTORY {
+        history.pop_front();
+    }
+    history.push_back(delivery);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use user_index_canister::Feedback;
+
+    const NOW: TimestampMillis = 1_000_000;
+
+    #[test]
+    fn failures_back_off_then_dead_letter() {
+        let mut deliveries = EmailDeliveries::default();
+        let id = deliveries.push("user@gmail.com".to_string(), feedback(), NOW);
+
+        let mut now = NOW;
+        for attempt in 1..MAX_ATTEMPTS {
+            assert_eq!(deliveries.take_due(now, 10).len(), 1);
+            assert!(deliveries.take_due(now, 10).is_empty());
+            assert!(!deliveries.mark_failed(id, "Connection refused".to_string(), now));
+
+            let next_attempt = deliveries.next_attempt().unwrap();
+            assert_eq!(next_attempt, now + (BASE_RETRY_DELAY << (attempt - 1)));
+            assert!(deliveries.take_due(next_attempt - 1, 10).is_empty());
+            now = next_attempt;
+        }
+
+        assert_eq!(deliveries.take_due(now, 10).len(), 1);
+        assert!(deliveries.mark_failed(id, "Connection refused".to_string(), now));
+        assert_eq!(deliveries.pending_count(), 0);
+
+        let dead = deliveries.failed().next().unwrap();
+        assert_eq!(dead.attempts, MAX_ATTEMPTS);
+        assert_eq!(dead.last_error.as_deref(), Some("Connection refused"));
+    }
+
+    #[test]
+    fn lost_attempt_is_retried_after_timeout() {
+        let mut deliveries = EmailDeliveries::default();
+        let id = deliveries.push("user@gmail.com".to_string(), feedback(), NOW);
+
+        deliveries.take_due(NOW, 10);
+        assert!(deliveries.take_due(NOW + SEND_TIMEOUT - 1, 10).is_empty());
+        assert_eq!(deliveries.take_due(NOW + SEND_TIMEOUT, 10).len(), 1);
+
+        deliveries.mark_delivered(id, "abc123".to_string());
+        let delivered = deliveries.delivered().next().unwrap();
+        assert_eq!(delivered.attempts, 2);
+        assert_eq!(delivered.message_id.as_deref(), Some("abc123"));
+    }
+
+    fn feedback() -> EmailEvent {
+        EmailEvent::Feedback(Box::new(Feedback {
+            email: "info@nobleblocks.com".to_string(),
+            feedback: "Great site".to_string(),
+        }))
+    }
+}
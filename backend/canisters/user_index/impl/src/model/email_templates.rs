@@ -0,0 +1,97 @@
+use crate::email::template::{self, RenderedEmail, DEFAULT_LOCALE};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use user_index_canister::set_email_template::EmailTemplate;
+use user_index_canister::EmailTemplateName;
+
+const MAX_LOCALE_LENGTH: usize = 35;
+
+// Templates set by governance, keyed by normalized locale. Anything missing falls back to the
+// built-in English template.
+#[derive(Serialize, Deserialize, Default)]
+pub struct EmailTemplates {
+    templates: HashMap<EmailTemplateName, HashMap<String, EmailTemplate>>,
+}
+
+impl EmailTemplates {
+    pub fn set(&mut self, name: EmailTemplateName, locale: &str, template: EmailTemplate) -> Result<(), String> {
+        let locale = normalize_locale(locale).ok_or_else(|| format!("Locale is invalid."))?;
+        template::validate(name, &template)?;
+
+        self.templates.entry(name).or_default().insert(locale, template);
+        Ok(())
+    }
+
+    pub fn remove(&mut self, name: EmailTemplateName, locale: &str) -> bool {
+        match (normalize_locale(locale), self.templates.get_mut(&name)) {
+            (Some(locale), Some(templates)) => templates.remove(&locale).is_some(),
+            _ => false,
+        }
+    }
+
+    // Tries "pt-br", then "pt", then the default locale, then the built-in template.
+    pub fn render(&self, name: EmailTemplateName, locale: Option<&str>, vars: &[(&str, &str)]) -> RenderedEmail {
+        let locale = locale.and_then(normalize_locale);
+        let language = locale.as_deref().and_then(|l| l.split('-').next());
+        let candidates = [locale.as_deref(), language, Some(DEFAULT_LOCALE)];
+
+        let found = self
+            .templates
+            .get(&name)
+            .and_then(|templates| candidates.iter().flatten().find_map(|l| templates.get(*l)));
+
+        match found {
+            Some(template) => template::render(template, vars),
+            None => template::render(&template::built_in(name), vars),
+        }
+    }
+}
+
+// Lower-cases a BCP 47 style tag such as "pt_BR" into "pt-br", or None if it doesn't look like one.
+pub fn normalize_locale(locale: &str) -> Option<String> {
+    let locale = locale.trim().replace('_', "-").to_lowercase();
+    let mut parts = locale.split('-');
+    let language = parts.next()?;
+
+    let valid = locale.len() <= MAX_LOCALE_LENGTH
+        && (2..=3).contains(&language.len())
+        && language.chars().all(|c| c.is_ascii_lowercase())
+        && parts.all(|p| (1..=8).contains(&p.len()) && p.chars().all(|c| c.is_ascii_alphanumeric()));
+
+    valid.then_some(locale)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn render_falls_back_from_region_to_language_to_built_in() {
+        let mut templates = EmailTemplates::default();
+        let vars = [("name", "bob")];
+
+        let pt = EmailTemplate {
+            subject: "Senha alterada".to_string(),
+            html: "<p>Olá, {{name}}</p>".to_string(),
+            text: "Olá, {{name}}".to_string(),
+        };
+        templates.set(EmailTemplateName::PasswordChanged, "pt", pt).unwrap();
+
+        assert_eq!(templates.render(EmailTemplateName::PasswordChanged, Some("pt_BR"), &vars).text, "Olá, bob");
+        assert_eq!(
+            templates.render(EmailTemplateName::PasswordChanged, Some("de"), &vars).subject,
+            "Your NOBLEBLOCKS password has been changed(nobleblock.com)"
+        );
+
+        assert!(templates.remove(EmailTemplateName::PasswordChanged, "PT"));
+        assert!(templates.render(EmailTemplateName::PasswordChanged, Some("pt"), &vars).text.starts_with("Hi, bob"));
+    }
+
+    #[test]
+    fn invalid_locales_are_rejected() {
+        assert_eq!(normalize_locale("en_US"), Some("en-us".to_string()));
+        assert_eq!(normalize_locale("english"), None);
+        assert_eq!(normalize_locale("en-"), None);
+        assert_eq!(normalize_locale("../en"), None);
+    }
+}
@@ -0,0 +1,262 @@
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use rsa::{BigUint, Pkcs1v15Sign, RsaPublicKey};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use types::TimestampMillis;
+
+pub const GOOGLE_ISSUERS: [&str; 2] = ["accounts.google.com", "https://accounts.google.com"];
+// Allowed clock drift between Google and the IC when checking exp / iat.
+pub const CLOCK_SKEW: TimestampMillis = 60 * 1000; // 1 min.
+
+#[derive(Serialize, Deserialize, Default)]
+pub struct GoogleJwks {
+    keys: HashMap<String, GoogleJwk>,
+    last_updated: TimestampMillis,
+}
+
+// Modulus and exponent, both big-endian bytes.
+#[derive(Serialize, Deserialize, Clone)]
+struct GoogleJwk {
+    n: Vec<u8>,
+    e: Vec<u8>,
+}
+
+#[derive(Deserialize)]
+struct JwksJson {
+    keys: Vec<JwkJson>,
+}
+
+#[derive(Deserialize)]
+struct JwkJson {
+    kid: String,
+    kty: String,
+    n: String,
+    e: String,
+}
+
+#[derive(Deserialize)]
+struct HeaderJson {
+    alg: String,
+    kid: Option<String>,
+}
+
+#[derive(Deserialize, Debug, Clone)]
+pub struct GoogleIdTokenClaims {
+    pub iss: String,
+    pub aud: String,
+    pub sub: String,
+    pub exp: u64,
+    pub iat: u64,
+    pub email: String,
+    #[serde(default)]
+    pub email_verified: bool,
+    #[serde(default)]
+    pub given_name: String,
+    #[serde(default)]
+    pub family_name: String,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum GoogleIdTokenError {
+    Malformed,
+    UnsupportedAlgorithm,
+    UnknownKey,
+    InvalidSignature,
+    InvalidIssuer,
+    InvalidAudience,
+    Expired,
+    EmailNotVerified,
+}
+
+impl GoogleJwks {
+    pub fn from_json(body: &[u8], now: TimestampMillis) -> Result<GoogleJwks, String> {
+        let jwks: JwksJson = serde_json::from_slice(body).map_err(|e| format!("{e:?}"))?;
+
+        let mut keys = HashMap::new();
+        for key in jwks.keys.into_iter().filter(|k| k.kty == "RSA") {
+            let n = URL_SAFE_NO_PAD.decode(&key.n).map_err(|e| format!("{e:?}"))?;
+            let e = URL_SAFE_NO_PAD.decode(&key.e).map_err(|e| format!("{e:?}"))?;
+            keys.insert(key.kid, GoogleJwk { n, e });
+        }
+
+        if keys.is_empty() {
+            return Err(format!("No RSA keys found"));
+        }
+
+        Ok(GoogleJwks { keys, last_updated: now })
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.keys.is_empty()
+    }
+
+    pub fn last_updated(&self) -> TimestampMillis {
+        self.last_updated
+    }
+
+    pub fn verify_id_token(
+        &self,
+        token: &str,
+        client_ids: &HashSet<String>,
+        now: TimestampMillis,
+    ) -> Result<GoogleIdTokenClaims, GoogleIdTokenError> {
+        let mut parts = token.split('.');
+        let (header, payload, signature) = match (parts.next(), parts.next(), parts.next(), parts.next()) {
+            (Some(h), Some(p), Some(s), None) => (h, p, s),
+            _ => return Err(GoogleIdTokenError::Malformed),
+        };
+
+        let header: HeaderJson = decode_json(header)?;
+        if header.alg != "RS256" {
+            return Err(GoogleIdTokenError::UnsupportedAlgorithm);
+        }
+
+        let jwk = header.kid.and_then(|kid| self.keys.get(&kid)).ok_or(GoogleIdTokenError::UnknownKey)?;
+        let public_key = RsaPublicKey::new(BigUint::from_bytes_be(&jwk.n), BigUint::from_bytes_be(&jwk.e))
+            .map_err(|_| GoogleIdTokenError::UnknownKey)?;
+
+        let signature = URL_SAFE_NO_PAD.decode(signature).map_err(|_| GoogleIdTokenError::Malformed)?;
+        let hashed = Sha256::digest(&token.as_bytes()[..signed_part_len(token)]);
+        public_key
+            .verify(Pkcs1v15Sign::new::<Sha256>(), &hashed, &signature)
+            .map_err(|_| GoogleIdTokenError::InvalidSignature)?;
+
+        let claims: GoogleIdTokenClaims = decode_json(payload)?;
+
+        if !GOOGLE_ISSUERS.contains(&claims.iss.as_str()) {
+            return Err(GoogleIdTokenError::InvalidIssuer);
+        }
+
+        if !client_ids.contains(&claims.aud) {
+            return Err(GoogleIdTokenError::InvalidAudience);
+        }
+
+        // Google uses seconds, we use milliseconds.
+        if claims.exp * 1000 + CLOCK_SKEW < now || claims.iat * 1000 > now + CLOCK_SKEW {
+            return Err(GoogleIdTokenError::Expired);
+        }
+
+        if !claims.email_verified {
+            return Err(GoogleIdTokenError::EmailNotVerified);
+        }
+
+        Ok(claims)
+    }
+}
+
+// Length of "<header>.<payload>", the part covered by the signature.
+fn signed_part_len(token: &str) -> usize {
+    token.rfind('.').unwrap_or_default()
+}
+
+fn decode_json<T: for<'de> Deserialize<'de>>(part: &str) -> Result<T, GoogleIdTokenError> {
+    let bytes = URL_SAFE_NO_PAD.decode(part).map_err(|_| GoogleIdTokenError::Malformed)?;
+    serde_json::from_slice(&bytes).map_err(|_| GoogleIdTokenError::Malformed)
+}
+
+#[cfg(test)]
+pub mod test_fixture {
+    use super::*;
+    use rsa::{pkcs8::DecodePrivateKey, RsaPrivateKey};
+
+    pub const CLIENT_ID: &str = "nobleblocks-test.apps.googleusercontent.com";
+    pub const JWKS: &str = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/test_data/google_jwks.json"));
+    const PRIVATE_KEY: &str = include_str!(concat!(env!("CARGO_MANIFEST_DIR"), "/test_data/google_test_key.pem"));
+
+    pub fn jwks() -> GoogleJwks {
+        GoogleJwks::from_json(JWKS.as_bytes(), 0).unwrap()
+    }
+
+    pub fn client_ids() -> HashSet<String> {
+        [CLIENT_ID.to_string()].into_iter().collect()
+    }
+
+    pub fn sign(claims: serde_json::Value) -> String {
+        let header = URL_SAFE_NO_PAD.encode(r#"{"alg":"RS256","kid":"test-key-1","typ":"JWT"}"#);
+        let payload = URL_SAFE_NO_PAD.encode(claims.to_string());
+        let message = format!("{header}.{payload}");
+
+        let key = RsaPrivateKey::from_pkcs8_pem(PRIVATE_KEY).unwrap();
+        let signature = key.sign(Pkcs1v15Sign::new::<Sha256>(), &Sha256::digest(message.as_bytes())).unwrap();
+
+        format!("{message}.{}", URL_SAFE_NO_PAD.encode(signature))
+    }
+
+    pub fn claims(email: &str, aud: &str, now: TimestampMillis) -> serde_json::Value {
+        serde_json::json!({
+            "iss": "https://accounts.google.com",
+            "aud": aud,
+            "sub": "1234567890",
+            "iat": now / 1000,
+            "exp": now / 1000 + 3600,
+            "email": email,
+            "email_verified": true,
+            "given_name": "Test",
+            "family_name": "User",
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::test_fixture::*;
+    use super::*;
+
+    const NOW: TimestampMillis = 1_700_000_000_000;
+
+    #[test]
+    fn valid_token() {
+        let token = sign(claims("test@gmail.com", CLIENT_ID, NOW));
+        let result = jwks().verify_id_token(&token, &client_ids(), NOW).unwrap();
+        assert_eq!(result.email, "test@gmail.com");
+    }
+
+    #[test]
+    fn forged_token() {
+        let token = sign(claims("test@gmail.com", CLIENT_ID, NOW));
+        let mut parts: Vec<&str> = token.split('.').collect();
+        let forged_payload = URL_SAFE_NO_PAD.encode(claims("admin@nobleblocks.com", CLIENT_ID, NOW).to_string());
+        parts[1] = &forged_payload;
+
+        let result = jwks().verify_id_token(&parts.join("."), &client_ids(), NOW);
+        assert_eq!(result.unwrap_err(), GoogleIdTokenError::InvalidSignature);
+    }
+
+    #[test]
+    fn expired_token() {
+        let issued = NOW - 2 * 3600 * 1000;
+        let token = sign(claims("test@gmail.com", CLIENT_ID, issued));
+        let result = jwks().verify_id_token(&token, &client_ids(), NOW);
+        assert_eq!(result.unwrap_err(), GoogleIdTokenError::Expired);
+    }
+
+    #[test]
+    fn wrong_audience() {
+        let token = sign(claims("test@gmail.com", "someone-else.apps.googleusercontent.com", NOW));
+        let result = jwks().verify_id_token(&token, &client_ids(), NOW);
+        assert_eq!(result.unwrap_err(), GoogleIdTokenError::InvalidAudience);
+    }
+
+    #[test]
+    fn wrong_issuer() {
+        let mut claims = claims("test@gmail.com", CLIENT_ID, NOW);
+        claims["iss"] = serde_json::json!("https://evil.example.com");
+        let result = jwks().verify_id_token(&sign(claims), &client_ids(), NOW);
+        assert_eq!(result.unwrap_err(), GoogleIdTokenError::InvalidIssuer);
+    }
+
+    #[test]
+    fn unverified_email() {
+        let mut claims = claims("test@gmail.com", CLIENT_ID, NOW);
+        claims["email_verified"] = serde_json::json!(false);
+        let result = jwks().verify_id_token(&sign(claims), &client_ids(), NOW);
+        assert_eq!(result.unwrap_err(), GoogleIdTokenError::EmailNotVerified);
+    }
+
+    #[test]
+    fn unknown_key() {
+        let result = GoogleJwks::default().verify_id_token(&sign(claims("test@gmail.com", CLIENT_ID, NOW)), &client_ids(), NOW);
+        assert_eq!(result.unwrap_err(), GoogleIdTokenError::UnknownKey);
+    }
+}
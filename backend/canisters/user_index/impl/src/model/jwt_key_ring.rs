@@ -0,0 +1,79 @@
+use serde::{Deserialize, Serialize};
+use types::{JwtKeyId, JwtSigningKey, JwtVerificationKey};
+
+#[derive(Serialize, Deserialize, Default)]
+pub struct JwtKeyRing {
+    signing_key: Option<JwtSigningKey>,
+    // The current key plus every previous key which has not been retired yet.
+    verification_keys: Vec<JwtVerificationKey>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum RetireKeyError {
+    KeyNotFound,
+    KeyInUse,
+}
+
+impl JwtKeyRing {
+    pub fn signing_key(&self) -> Option<&JwtSigningKey> {
+        self.signing_key.as_ref()
+    }
+
+    pub fn verification_keys(&self) -> &Vec<JwtVerificationKey> {
+        &self.verification_keys
+    }
+
+    // Starts signing with a new key. Tokens signed with the previous key stay valid until it is retired.
+    pub fn rotate(&mut self, seed: [u8; 32]) -> JwtKeyId {
+        let kid = self.verification_keys.iter().map(|k| k.kid).max().map_or(1, |kid| kid + 1);
+        let signing_key = JwtSigningKey::new(kid, seed);
+
+        self.verification_keys.push(signing_key.verification_key());
+        self.signing_key = Some(signing_key);
+        kid
+    }
+
+    pub fn retire(&mut self, kid: JwtKeyId) -> Result<(), RetireKeyError> {
+        if self.signing_key.as_ref().map_or(false, |k| k.kid == kid) {
+            return Err(RetireKeyError::KeyInUse);
+        }
+
+        let len = self.verification_keys.len();
+        self.verification_keys.retain(|k| k.kid != kid);
+        if self.verification_keys.len() == len {
+            Err(RetireKeyError::KeyNotFound)
+        } else {
+            Ok(())
+        }
+    }
+}
+
+#[cfg(test)]
+impl JwtKeyRing {
+    pub fn new_for_test() -> Self {
+        let mut ring = JwtKeyRing::default();
+        ring.rotate([7; 32]);
+        ring
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn rotate_then_retire() {
+        let mut ring = JwtKeyRing::default();
+
+        let first = ring.rotate([1; 32]);
+        let second = ring.rotate([2; 32]);
+
+        assert_eq!(ring.signing_key().unwrap().kid, second);
+        assert_eq!(ring.verification_keys().len(), 2);
+
+        assert_eq!(ring.retire(second), Err(RetireKeyError::KeyInUse));
+        assert_eq!(ring.retire(first), Ok(()));
+        assert_eq!(ring.retire(first), Err(RetireKeyError::KeyNotFound));
+        assert_eq!(ring.verification_keys().len(), 1);
+    }
+}
@@ -0,0 +1,144 @@
+use candid::Principal;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use types::{NobleId, TimestampMillis};
+
+// Failures allowed before any delay is imposed.
+pub const FREE_ATTEMPTS: u32 = 3;
+// Delay after the first failure past the free ones, doubled on each further failure.
+pub const BASE_BACKOFF: TimestampMillis = 2 * 1000; // 2 secs.
+// Failures after which the account / principal is locked out.
+pub const LOCKOUT_ATTEMPTS: u32 = 10;
+pub const LOCKOUT_DURATION: TimestampMillis = 60 * 60 * 1000; // 1 hour.
+// Counters are dropped once this long has passed since the last failure.
+pub const FORGET_AFTER: TimestampMillis = 24 * 60 * 60 * 1000; // 1 day.
+
+#[derive(Serialize, Deserialize, Default)]
+pub struct LoginAttempts {
+    accounts: HashMap<NobleId, FailedAttempts>,
+    principals: HashMap<Principal, FailedAttempts>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy, Default, Debug)]
+struct FailedAttempts {
+    count: u32,
+    last_failed: TimestampMillis,
+    retry_after: TimestampMillis,
+}
+
+impl LoginAttempts {
+    // When the next attempt is allowed, if that's in the future.
+    pub fn retry_after(&self, noble_id: Option<NobleId>, principal: Principal, now: TimestampMillis) -> Option<TimestampMillis> {
+        let account = noble_id.and_then(|id| self.accounts.get(&id));
+        let principal = Self::tracked(principal).and_then(|p| self.principals.get(&p));
+
+        account
+            .into_iter()
+            .chain(principal)
+            .map(|attempts| attempts.retry_after)
+            .max()
+            .filter(|retry_after| *retry_after > now)
+    }
+
+    pub fn record_failure(&mut self, noble_id: Option<NobleId>, principal: Principal, now: TimestampMillis) {
+        self.accounts.retain(|_, a| a.last_failed + FORGET_AFTER >= now);
+        self.principals.retain(|_, a| a.last_failed + FORGET_AFTER >= now);
+
+        if let Some(noble_id) = noble_id {
+            self.accounts.entry(noble_id).or_default().record_failure(now);
+        }
+        if let Some(principal) = Self::tracked(principal) {
+            self.principals.entry(principal).or_default().record_failure(now);
+        }
+    }
+
+    pub fn record_success(&mut self, noble_id: Option<NobleId>, principal: Principal) {
+        if let Some(noble_id) = noble_id {
+            self.accounts.remove(&noble_id);
+        }
+        self.principals.remove(&principal);
+    }
+
+    // Every browser which isn't logged in with Internet Identity calls as the anonymous principal.
+    fn tracked(principal: Principal) -> Option<Principal> {
+        (principal != Principal::anonymous()).then_some(principal)
+    }
+}
+
+impl FailedAttempts {
+    fn record_failure(&mut self, now: TimestampMillis) {
+        self.count += 1;
+        self.last_failed = now;
+        self.retry_after = now + delay(self.count);
+    }
+}
+
+fn delay(count: u32) -> TimestampMillis {
+    if count >= LOCKOUT_ATTEMPTS {
+        LOCKOUT_DURATION
+    } else if count <= FREE_ATTEMPTS {
+        0
+    } else {
+        BASE_BACKOFF << (count - FREE_ATTEMPTS - 1)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const NOW: TimestampMillis = 1_000_000;
+
+    #[test]
+    fn backoff_then_lockout() {
+        let mut attempts = LoginAttempts::default();
+        let principal = Principal::from_slice(&[1]);
+
+        for _ in 0..FREE_ATTEMPTS {
+            attempts.record_failure(Some(1), principal, NOW);
+        }
+        assert_eq!(attempts.retry_after(Some(1), principal, NOW), None);
+
+        attempts.record_failure(Some(1), principal, NOW);
+        assert_eq!(attempts.retry_after(Some(1), principal, NOW), Some(NOW + BASE_BACKOFF));
+        attempts.record_failure(Some(1), principal, NOW);
+        assert_eq!(attempts.retry_after(Some(1), principal, NOW), Some(NOW + 2 * BASE_BACKOFF));
+
+        for _ in FREE_ATTEMPTS + 2..LOCKOUT_ATTEMPTS {
+            attempts.record_failure(Some(1), principal, NOW);
+        }
+        assert_eq!(attempts.retry_after(Some(1), principal, NOW), Some(NOW + LOCKOUT_DURATION));
+        assert_eq!(attempts.retry_after(Some(1), principal, NOW + LOCKOUT_DURATION), None);
+
+        // The principal is locked out of other accounts too.
+        assert!(attempts.retry_after(Some(2), principal, NOW).is_some());
+
+        attempts.record_success(Some(1), principal);
+        assert_eq!(attempts.retry_after(Some(1), principal, NOW), None);
+    }
+
+    #[test]
+    fn anonymous_principal_is_not_tracked() {
+        let mut attempts = LoginAttempts::default();
+
+        for _ in 0..LOCKOUT_ATTEMPTS {
+            attempts.record_failure(Some(1), Principal::anonymous(), NOW);
+        }
+
+        assert!(attempts.retry_after(Some(1), Principal::anonymous(), NOW).is_some());
+        assert_eq!(attempts.retry_after(Some(2), Principal::anonymous(), NOW), None);
+    }
+
+    #[test]
+    fn counters_are_forgotten() {
+        let mut attempts = LoginAttempts::default();
+        let principal = Principal::from_slice(&[1]);
+
+        for _ in 0..LOCKOUT_ATTEMPTS {
+            attempts.record_failure(Some(1), principal, NOW);
+        }
+        attempts.record_failure(Some(2), principal, NOW + FORGET_AFTER + 1);
+
+        assert_eq!(attempts.retry_after(Some(1), Principal::anonymous(), NOW + FORGET_AFTER + 1), None);
+    }
+}
@@ -1,5 +1,15 @@
+pub mod email_config;
+pub mod email_deliveries;
+pub mod email_templates;
 pub mod follow_request_map;
+pub mod google_jwks;
+pub mod jwt_key_ring;
 pub mod local_user_index_map;
+pub mod login_attempts;
+pub mod moderation_log;
+pub mod refresh_token;
+pub mod reports;
+pub mod suspensions;
 pub mod temp;
 pub mod temp_map;
 pub mod user;
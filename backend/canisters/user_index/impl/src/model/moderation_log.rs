@@ -0,0 +1,105 @@
+use crate::memory::{get_moderation_log_memories, Memory};
+use serde::{Deserialize, Serialize};
+use stable_map::StableMap;
+use types::{NobleId, TimestampMillis};
+use user_index_canister::get_moderation_log::{ModerationEvent, ModerationLogEntry};
+
+// For the notes moderators leave saying why they did something.
+pub const MAX_NOTE_LENGTH: usize = 1_000;
+
+// Everything moderators have done, for auditing. Nothing is ever dropped, so the entries are kept in
+// stable memory, keyed by their id, which counts up from 0.
+#[derive(Serialize, Deserialize)]
+#[serde(from = "ModerationLogTrimmed")]
+pub struct ModerationLog {
+    #[serde(skip)]
+    entries: StableMap<u64, ModerationLogEntry, Memory>,
+}
+
+impl ModerationLog {
+    pub fn push(&mut self, moderator_id: NobleId, event: ModerationEvent, now: TimestampMillis) {
+        let id = self.entries.len() as u64;
+        self.entries.insert(id, &ModerationLogEntry {
+            id,
+            timestamp: now,
+            moderator_id,
+            event,
+        });
+    }
+
+    // Newest first.
+    pub fn get(&self, before: Option<u64>, limit: usize) -> Vec<ModerationLogEntry> {
+        let end = before.map_or(self.entries.len() as u64, |before| before.min(self.entries.len() as u64));
+        (0..end).rev().take(limit).filter_map(|id| self.entries.get(id)).collect()
+    }
+
+    pub fn len(&self) -> usize {
+        self.entries.len()
+    }
+}
+
+impl Default for ModerationLog {
+    fn default() -> Self {
+        let (index_memory, entries_memory) = get_moderation_log_memories();
+        ModerationLog {
+            entries: StableMap::init(index_memory, entries_memory),
+        }
+    }
+}
+
+#[derive(Deserialize)]
+struct ModerationLogTrimmed {
+    // Only set when upgrading from the version that kept the log on the heap.
+    #[serde(default)]
+    entries: Vec<ModerationLogEntry>,
+}
+
+impl From<ModerationLogTrimmed> for ModerationLog {
+    fn from(value: ModerationLogTrimmed) -> Self {
+        let mut log = ModerationLog::default();
+        for entry in value.entries {
+            log.entries.insert(entry.id, &entry);
+        }
+        log
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn upgrade_moves_the_log_out_of_the_heap() {
+        #[derive(Serialize)]
+        struct LegacyModerationLog {
+            entries: Vec<ModerationLogEntry>,
+        }
+
+        let entries = (0..100)
+            .map(|id| ModerationLogEntry {
+                id,
+                timestamp: id,
+                moderator_id: 1,
+                event: ModerationEvent::SuspensionLifted { noble_id: id, note: "n".repeat(MAX_NOTE_LENGTH) },
+            })
+            .collect();
+        let legacy_bytes = upgrade(&LegacyModerationLog { entries });
+
+        let mut log: ModerationLog = serializer::deserialize(legacy_bytes.as_slice()).unwrap();
+        assert_eq!(log.len(), 100);
+        log.push(1, ModerationEvent::SuspensionLifted { noble_id: 100, note: String::new() }, 100);
+
+        let bytes = upgrade(&log);
+        assert!(bytes.len() < 16);
+
+        let log: ModerationLog = serializer::deserialize(bytes.as_slice()).unwrap();
+        assert_eq!(log.get(None, 2).iter().map(|entry| entry.id).collect::<Vec<_>>(), vec![100, 99]);
+        assert_eq!(log.get(Some(3), 10).iter().map(|entry| entry.id).collect::<Vec<_>>(), vec![2, 1, 0]);
+    }
+
+    fn upgrade<T: Serialize>(value: &T) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        serializer::serialize(value, &mut bytes).unwrap();
+        bytes
+    }
+}
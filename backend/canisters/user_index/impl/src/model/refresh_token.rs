@@ -0,0 +1,66 @@
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use sha2::{Digest, Sha256};
+use types::{NobleId, SessionId, TimestampMillis};
+use utils::constant_time;
+
+pub const REFRESH_TOKEN_EXP_TIME: TimestampMillis = 30 * 24 * 60 * 60 * 1000; // 30 days
+
+// "<noble_id>.<session_id>.<secret>". Only the hash of the secret is stored.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RefreshToken {
+    pub noble_id: NobleId,
+    pub session_id: SessionId,
+    pub secret: [u8; 32],
+}
+
+impl RefreshToken {
+    pub fn parse(token: &str) -> Option<RefreshToken> {
+        let mut parts = token.split('.');
+        let (noble_id, session_id, secret) = match (parts.next(), parts.next(), parts.next(), parts.next()) {
+            (Some(n), Some(s), Some(secret), None) => (n, s, secret),
+            _ => return None,
+        };
+
+        Some(RefreshToken {
+            noble_id: noble_id.parse().ok()?,
+            session_id: session_id.parse().ok()?,
+            secret: URL_SAFE_NO_PAD.decode(secret).ok()?.try_into().ok()?,
+        })
+    }
+
+    pub fn to_string(&self) -> String {
+        format!("{}.{}.{}", self.noble_id, self.session_id, URL_SAFE_NO_PAD.encode(self.secret))
+    }
+
+    pub fn hash(&self) -> Vec<u8> {
+        Sha256::digest(self.secret).to_vec()
+    }
+
+    pub fn matches(&self, hash: &[u8]) -> bool {
+        constant_time::eq(&self.hash(), hash)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parse_round_trip() {
+        let token = RefreshToken { noble_id: 5, session_id: 42, secret: [3; 32] };
+
+        assert_eq!(RefreshToken::parse(&token.to_string()), Some(token));
+        assert_eq!(RefreshToken::parse("5.42"), None);
+        assert_eq!(RefreshToken::parse("5.42.AAAA"), None);
+    }
+
+    #[test]
+    fn matches_own_hash_only() {
+        let token = RefreshToken { noble_id: 5, session_id: 42, secret: [3; 32] };
+        let other = RefreshToken { secret: [4; 32], ..token.clone() };
+
+        assert!(token.matches(&token.hash()));
+        assert!(!token.matches(&other.hash()));
+        assert!(!token.matches(&[]));
+    }
+}
@@ -0,0 +1,178 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use types::{ModerationAction, NobleId, Report, ReportId, ReportReason, ReportResolution, ReportTarget, TimestampMillis};
+
+// Reports a user can have waiting for a moderator, so no one can flood the queue.
+pub const MAX_OPEN_REPORTS_PER_USER: usize = 20;
+pub const MAX_REPORT_DETAILS_LENGTH: usize = 1_000;
+
+// Resolved reports are kept, with who resolved them and how.
+#[derive(Serialize, Deserialize, Default)]
+pub struct Reports {
+    next_id: ReportId,
+    open: BTreeMap<ReportId, Report>,
+    resolved: BTreeMap<ReportId, Report>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum AddReportResult {
+    Success(ReportId),
+    AlreadyReported,
+    TooManyOpenReports,
+}
+
+#[derive(Debug)]
+pub enum ResolveReportResult {
+    Success(Vec<Report>),
+    NotFound,
+    AlreadyResolved,
+}
+
+impl Reports {
+    pub fn add(
+        &mut self,
+        target: ReportTarget,
+        reporter_id: NobleId,
+        reason: ReportReason,
+        details: String,
+        now: TimestampMillis,
+    ) -> AddReportResult {
+        let mut open_count = 0;
+        for report in self.open.values().filter(|report| report.reporter_id == reporter_id) {
+            if report.target == target {
+                return AddReportResult::AlreadyReported;
+            }
+            open_count += 1;
+        }
+        if open_count >= MAX_OPEN_REPORTS_PER_USER {
+            return AddReportResult::TooManyOpenReports;
+        }
+
+        // Ids start at 1.
+        self.next_id += 1;
+        let report_id = self.next_id;
+        self.open.insert(report_id, Report {
+            report_id,
+            target,
+            reporter_id,
+            reason,
+            details,
+            date_created: now,
+            resolution: None,
+        });
+        AddReportResult::Success(report_id)
+    }
+
+    pub fn get(&self, report_id: ReportId) -> Option<&Report> {
+        self.open.get(&report_id).or_else(|| self.resolved.get(&report_id))
+    }
+
+    // Open reports oldest first, so the queue is worked through in order, resolved ones newest first.
+    pub fn list(&self, resolved: bool, after: Option<ReportId>, limit: usize) -> Vec<Report> {
+        if resolved {
+            self.resolved
+                .range(..after.unwrap_or(ReportId::MAX))
+                .rev()
+                .take(limit)
+                .map(|(_, report)| report.clone())
+                .collect()
+        } else {
+            self.open
+                .range(after.map_or(0, |report_id| report_id + 1)..)
+                .take(limit)
+                .map(|(_, report)| report.clone())
+                .collect()
+        }
+    }
+
+    pub fn open_count(&self) -> usize {
+        self.open.len()
+    }
+
+    // Every open report about the same thing is resolved along with this one.
+    pub fn resolve(
+        &mut self,
+        report_id: ReportId,
+        moderator_id: NobleId,
+        action: ModerationAction,
+        note: String,
+        now: TimestampMillis,
+    ) -> ResolveReportResult {
+        let target = match self.open.get(&report_id) {
+            Some(report) => report.target,
+            None if self.resolved.contains_key(&report_id) => return ResolveReportResult::AlreadyResolved,
+            None => return ResolveReportResult::NotFound,
+        };
+
+        let report_ids: Vec<ReportId> = self
+            .open
+            .values()
+            .filter(|report| report.target == target)
+            .map(|report| report.report_id)
+            .collect();
+
+        let mut resolved = Vec::with_capacity(report_ids.len());
+        for report_id in report_ids {
+            if let Some(mut report) = self.open.remove(&report_id) {
+                report.resolution = Some(ReportResolution {
+                    moderator_id,
+                    action,
+                    note: note.clone(),
+                    date_resolved: now,
+                });
+                self.resolved.insert(report_id, report.clone());
+                resolved.push(report);
+            }
+        }
+        ResolveReportResult::Success(resolved)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const POST: ReportTarget = ReportTarget::Post { post_id: 5 };
+    const USER: ReportTarget = ReportTarget::User { noble_id: 9 };
+
+    #[test]
+    fn reports_about_the_same_thing_are_resolved_together() {
+        let mut reports = Reports::default();
+        assert_eq!(reports.add(POST, 1, ReportReason::Spam, String::new(), 10), AddReportResult::Success(1));
+        assert_eq!(reports.add(USER, 1, ReportReason::Harassment, String::new(), 20), AddReportResult::Success(2));
+        assert_eq!(reports.add(POST, 2, ReportReason::Plagiarism, String::new(), 30), AddReportResult::Success(3));
+        assert_eq!(reports.add(POST, 1, ReportReason::Other, String::new(), 40), AddReportResult::AlreadyReported);
+
+        match reports.resolve(3, 100, ModerationAction::Hide, "copied".to_string(), 50) {
+            ResolveReportResult::Success(resolved) => {
+                assert_eq!(resolved.iter().map(|report| report.report_id).collect::<Vec<_>>(), vec![1, 3]);
+            }
+            result => panic!("{result:?}"),
+        }
+        assert!(matches!(reports.resolve(1, 100, ModerationAction::Dismiss, String::new(), 60), ResolveReportResult::AlreadyResolved));
+        assert!(matches!(reports.resolve(7, 100, ModerationAction::Dismiss, String::new(), 60), ResolveReportResult::NotFound));
+
+        let open = reports.list(false, None, 10);
+        assert_eq!(open.iter().map(|report| report.report_id).collect::<Vec<_>>(), vec![2]);
+        let resolved = reports.list(true, None, 10);
+        assert_eq!(resolved.iter().map(|report| report.report_id).collect::<Vec<_>>(), vec![3, 1]);
+        assert_eq!(reports.list(true, Some(3), 10).len(), 1);
+        assert_eq!(reports.get(1).unwrap().resolution.as_ref().unwrap().action, ModerationAction::Hide);
+
+        // Once dealt with, the same thing can be reported again.
+        assert_eq!(reports.add(POST, 1, ReportReason::Spam, String::new(), 70), AddReportResult::Success(4));
+    }
+
+    #[test]
+    fn open_reports_are_capped_per_user() {
+        let mut reports = Reports::default();
+        for post_id in 0..MAX_OPEN_REPORTS_PER_USER as u64 {
+            assert!(matches!(reports.add(ReportTarget::Post { post_id }, 1, ReportReason::Spam, String::new(), 10), AddReportResult::Success(_)));
+        }
+        assert_eq!(reports.add(USER, 1, ReportReason::Spam, String::new(), 10), AddReportResult::TooManyOpenReports);
+        assert!(matches!(reports.add(USER, 2, ReportReason::Spam, String::new(), 10), AddReportResult::Success(_)));
+
+        reports.resolve(1, 100, ModerationAction::Dismiss, String::new(), 20);
+        assert!(matches!(reports.add(USER, 1, ReportReason::Spam, String::new(), 30), AddReportResult::Success(_)));
+    }
+}
@@ -0,0 +1,140 @@
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use types::{Appeal, NobleId, SuspensionDetails, TimestampMillis};
+
+pub const MAX_SUSPENSION_REASON_LENGTH: usize = 1_000;
+pub const MAX_APPEAL_LENGTH: usize = 2_000;
+
+// Suspensions which are over are ignored, and lifted by `lift_expired_suspensions`.
+#[derive(Serialize, Deserialize, Default)]
+pub struct Suspensions {
+    users: BTreeMap<NobleId, SuspensionDetails>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum AppealResult {
+    Success,
+    NotSuspended,
+    AlreadyAppealed,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum RejectAppealResult {
+    Success,
+    NotSuspended,
+    NoAppeal,
+}
+
+impl Suspensions {
+    pub fn get(&self, noble_id: NobleId, now: TimestampMillis) -> Option<&SuspensionDetails> {
+        self.users
+            .get(&noble_id)
+            .filter(|details| details.suspension.until.map_or(true, |until| now < until))
+    }
+
+    // Replaces any suspension the user already had, a ban included, along with its appeal.
+    pub fn add(&mut self, details: SuspensionDetails) {
+        self.users.insert(details.noble_id, details);
+    }
+
+    pub fn remove(&mut self, noble_id: NobleId) -> Option<SuspensionDetails> {
+        self.users.remove(&noble_id)
+    }
+
+    pub fn appeal(&mut self, noble_id: NobleId, text: String, now: TimestampMillis) -> AppealResult {
+        let details = match self.users.get_mut(&noble_id) {
+            Some(details) if details.suspension.until.map_or(true, |until| now < until) => details,
+            _ => return AppealResult::NotSuspended,
+        };
+        if details.appeal.is_some() {
+            return AppealResult::AlreadyAppealed;
+        }
+        details.appeal = Some(Appeal {
+            text,
+            date_submitted: now,
+            date_rejected: None,
+        });
+        AppealResult::Success
+    }
+
+    pub fn reject_appeal(&mut self, noble_id: NobleId, now: TimestampMillis) -> RejectAppealResult {
+        let details = match self.users.get_mut(&noble_id) {
+            Some(details) if details.suspension.until.map_or(true, |until| now < until) => details,
+            _ => return RejectAppealResult::NotSuspended,
+        };
+        match details.appeal.as_mut() {
+            Some(appeal) if appeal.date_rejected.is_none() => {
+                appeal.date_rejected = Some(now);
+                RejectAppealResult::Success
+            }
+            _ => RejectAppealResult::NoAppeal,
+        }
+    }
+
+    // Those still in force, or only those with an appeal waiting for a moderator.
+    pub fn list(&self, pending_appeals_only: bool, now: TimestampMillis) -> Vec<SuspensionDetails> {
+        self.users
+            .values()
+            .filter(|details| details.suspension.until.map_or(true, |until| now < until))
+            .filter(|details| !pending_appeals_only || details.appeal.as_ref().map_or(false, |appeal| appeal.date_rejected.is_none()))
+            .cloned()
+            .collect()
+    }
+
+    pub fn expired(&self, now: TimestampMillis) -> Vec<NobleId> {
+        self.users
+            .values()
+            .filter(|details| details.suspension.until.map_or(false, |until| now >= until))
+            .map(|details| details.noble_id)
+            .collect()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = &SuspensionDetails> {
+        self.users.values()
+    }
+
+    pub fn len(&self) -> usize {
+        self.users.len()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use types::Suspension;
+
+    #[test]
+    fn suspension_can_be_appealed_once() {
+        let mut suspensions = Suspensions::default();
+        suspensions.add(details(1, Some(1_000)));
+        suspensions.add(details(2, None));
+
+        assert_eq!(suspensions.appeal(3, "sorry".to_string(), 10), AppealResult::NotSuspended);
+        assert_eq!(suspensions.appeal(1, "sorry".to_string(), 10), AppealResult::Success);
+        assert_eq!(suspensions.appeal(1, "sorry".to_string(), 20), AppealResult::AlreadyAppealed);
+        assert_eq!(suspensions.list(true, 30).len(), 1);
+
+        assert_eq!(suspensions.reject_appeal(2, 30), RejectAppealResult::NoAppeal);
+        assert_eq!(suspensions.reject_appeal(1, 30), RejectAppealResult::Success);
+        assert_eq!(suspensions.reject_appeal(1, 40), RejectAppealResult::NoAppeal);
+        assert!(suspensions.list(true, 40).is_empty());
+        assert_eq!(suspensions.list(false, 40).len(), 2);
+
+        // Over, but not lifted yet.
+        assert!(suspensions.get(1, 1_000).is_none());
+        assert_eq!(suspensions.appeal(1, "sorry".to_string(), 1_000), AppealResult::NotSuspended);
+        assert_eq!(suspensions.expired(1_000), vec![1]);
+        assert!(suspensions.get(2, 1_000).is_some());
+    }
+
+    fn details(noble_id: NobleId, until: Option<TimestampMillis>) -> SuspensionDetails {
+        SuspensionDetails {
+            noble_id,
+            suspension: Suspension { until, hide_content: false },
+            reason: "spam".to_string(),
+            suspended_by: 9,
+            date_suspended: 0,
+            appeal: None,
+        }
+    }
+}
@@ -1,6 +1,9 @@
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
 use candid::CandidType;
 use serde::{Deserialize, Serialize};
-use types::{TimestampMillis, TempId};
+use sha2::{Digest, Sha256};
+use types::{TimestampMillis, TempId, NobleId};
+use utils::constant_time;
 
 use user_index_canister::register_user::Args as RegisterUserArgs;
 
@@ -10,6 +13,9 @@ pub struct Temp {
     pub is_used: bool,
     pub expired_time: TimestampMillis,
     pub passkey: String,
+    // Wrong passkeys entered, the temp is dropped once it reaches MAX_PASSKEY_ATTEMPTS.
+    #[serde(default)]
+    pub failed_attempts: u32,
     pub email: String,
     pub temp_data: TempData,
 }
@@ -18,15 +24,46 @@ pub struct Temp {
 pub enum TempData {
     RegisterUser(RegisterUserArgs),
     ResetPassword(ResetPassword),
+    ChangeEmail(ChangeEmail),
 }
 
+// Temps created before passwords were chosen by the user also carry a generated `password`, which
+// is ignored when they are read back. The user picks the password in `complete_password_reset`.
 #[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
 pub struct ResetPassword {
     pub name: String,
-    pub password: String,
+    // Set once the passkey has been verified, see `complete_password_reset`.
+    #[serde(default)]
+    pub reset_token_hash: Option<Vec<u8>>,
+}
+
+impl ResetPassword {
+    pub fn new(name: String) -> ResetPassword {
+        ResetPassword { name, reset_token_hash: None }
+    }
+
+    // Returns the token to hand to the user, only its hash is kept.
+    pub fn issue_reset_token(&mut self, secret: [u8; 32]) -> String {
+        let token = URL_SAFE_NO_PAD.encode(secret);
+        self.reset_token_hash = Some(Sha256::digest(token.as_bytes()).to_vec());
+        token
+    }
+
+    pub fn is_reset_token_valid(&self, token: &str) -> bool {
+        self.reset_token_hash.as_ref().map_or(false, |hash| constant_time::eq(hash, &Sha256::digest(token.as_bytes())))
+    }
+}
+
+// The temp's `email` is the new address, the passkey is sent there.
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct ChangeEmail {
+    pub noble_id: NobleId,
+    // The change is dropped if the address was changed some other way in the meantime.
+    pub previous_email: String,
 }
 
 pub enum TempDataType {
     RegisterUser,
     ResetPassword,
+    ChangeEmail,
 }
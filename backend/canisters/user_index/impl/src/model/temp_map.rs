@@ -1,7 +1,7 @@
 use rand::{Rng, rngs::StdRng};
 use serde::{Serialize, Deserialize};
 use std::collections::HashMap;
-use types::{TimestampMillis, TempId};
+use types::{TimestampMillis, TempId, NobleId};
 use crate::model::temp::{Temp, TempData};
 
 
@@ -12,6 +12,9 @@ pub struct TempMap {
 
 pub const TEMP_EXPIRED_DURATION: TimestampMillis = 3 * 60 * 1000; // 3 mins.
 pub const AVAILABLE_RESEND_DURATION: TimestampMillis = 30 * 1000; // 30 secs.
+pub const MAX_PASSKEY_ATTEMPTS: u32 = 5;
+// Time to choose a new password once the reset passkey has been verified.
+pub const PASSWORD_RESET_DURATION: TimestampMillis = 15 * 60 * 1000; // 15 mins.
 
 impl TempMap {
     pub fn get(&self, temp_id: TempId) -> Option<&Temp> {
@@ -33,6 +36,14 @@ impl TempMap {
         self.temps.remove(&temp_id);
     }
 
+    // A new email change replaces any pending one of the same user.
+    pub fn remove_change_email(&mut self, noble_id: NobleId) {
+        self.temps.retain(|_, temp| match &temp.temp_data {
+            TempData::ChangeEmail(data) => data.noble_id != noble_id,
+            _ => true,
+        });
+    }
+
     pub fn new_passkey(&self, rnd: &mut StdRng) -> String {
         // let mut passkey: String = rnd.sample_iter(&Alphanumeric).take(6).map(char::from).collect();
         let mut passkey = String::new();
@@ -52,6 +63,7 @@ impl TempMap {
         self.temps.insert(new_temp_id, Temp {
             temp_id: new_temp_id,
             is_used: false,
+            failed_attempts: 0,
             expired_time: now + TEMP_EXPIRED_DURATION,
             email,
             passkey: passkey.clone(),
@@ -74,6 +86,7 @@ impl TempMap {
         self.temps.iter().any(|item| {
             match &item.1.temp_data {
                 TempData::RegisterUser(user) => user.email == email,
+                TempData::ChangeEmail(_) => item.1.email == email,
                 _ => false,
             }
         })
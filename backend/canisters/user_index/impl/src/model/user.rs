@@ -1,6 +1,10 @@
 use candid::{CandidType, Principal};
 use serde::{Deserialize, Serialize};
-use types::{NobleId, CanisterId, TimestampMillis, UserSummary, Country, AcademicDegree, UserInfo, SuccessLogin, JWT, AvatarId};
+use crate::model::refresh_token::{RefreshToken, REFRESH_TOKEN_EXP_TIME};
+use types::{NobleId, CanisterId, TimestampMillis, UserSummary, Country, AcademicDegree, UserInfo, SuccessLogin, JWT, AvatarId, JwtSigningKey, SessionId};
+
+// How many of a session's swapped refresh tokens are remembered to tell reuse from a bad token.
+const MAX_PREVIOUS_REFRESH_TOKENS: usize = 10;
 
 #[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
 pub struct User {
@@ -21,6 +25,33 @@ pub struct User {
     pub password: String,
     #[serde(default)]
     pub avatar_id: AvatarId,
+    #[serde(default)]
+    pub session_generation: u32,
+    #[serde(default)]
+    pub sessions: Vec<Session>,
+    // Normalized, eg. "pt-br", picks the language of the emails sent to the user.
+    #[serde(default)]
+    pub locale: Option<String>,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct Session {
+    pub session_id: SessionId,
+    pub date_created: TimestampMillis,
+    // When the current refresh token stops being accepted, pushed back each time it's swapped.
+    pub expires: TimestampMillis,
+    #[serde(default)]
+    pub refresh_token_hash: Vec<u8>,
+    // Hashes of the refresh tokens already swapped for newer ones, oldest first.
+    #[serde(default)]
+    pub previous_refresh_token_hashes: Vec<Vec<u8>>,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum RefreshSessionError {
+    InvalidRefreshToken,
+    TokenReused,
+    InternalError(String),
 }
 
 impl User {
@@ -49,6 +80,9 @@ impl User {
             degree: None,
             city: String::new(),
             avatar_id: 0,
+            session_generation: 0,
+            sessions: Vec::new(),
+            locale: None,
         }
     }
 
@@ -90,16 +124,96 @@ impl User {
         }
     }
 
-    pub fn get_login_info(&self, now: TimestampMillis) -> Result<SuccessLogin, String> {
-        let jwt = JWT::new(self.noble_id, self.canister_id, self.email.clone(), self.username.clone(), now);
+    // Issues an access token and a refresh token for a new session and records it so that it can be listed, refreshed and revoked.
+    pub fn start_session(
+        &mut self,
+        session_id: SessionId,
+        refresh_secret: [u8; 32],
+        signing_key: Option<&JwtSigningKey>,
+        now: TimestampMillis,
+    ) -> Result<SuccessLogin, String> {
+        let refresh_token = RefreshToken { noble_id: self.noble_id, session_id, secret: refresh_secret };
+        let login = self.issue_tokens(&refresh_token, signing_key, now)?;
 
-        let jwt = match jwt.to_string() {
+        self.sessions.retain(|s| s.expires >= now);
+        self.sessions.push(Session {
+            session_id,
+            date_created: now,
+            expires: now + REFRESH_TOKEN_EXP_TIME,
+            refresh_token_hash: refresh_token.hash(),
+            previous_refresh_token_hashes: Vec::new(),
+        });
+
+        Ok(login)
+    }
+
+    // Swaps the presented refresh token for a new one. Presenting a token which has already been
+    // swapped means it leaked, so the whole session is ended. Any other token is just rejected, the
+    // session id alone can be read from an access token.
+    pub fn refresh_session(
+        &mut self,
+        refresh_token: &RefreshToken,
+        new_secret: [u8; 32],
+        signing_key: Option<&JwtSigningKey>,
+        now: TimestampMillis,
+    ) -> Result<SuccessLogin, RefreshSessionError> {
+        let index = match self.sessions.iter().position(|s| s.session_id == refresh_token.session_id && s.expires >= now) {
+            Some(index) => index,
+            None => return Err(RefreshSessionError::InvalidRefreshToken),
+        };
+
+        let session = &self.sessions[index];
+        if !refresh_token.matches(&session.refresh_token_hash) {
+            if session.previous_refresh_token_hashes.iter().any(|hash| refresh_token.matches(hash)) {
+                self.sessions.remove(index);
+                return Err(RefreshSessionError::TokenReused);
+            }
+            return Err(RefreshSessionError::InvalidRefreshToken);
+        }
+
+        let new_token = RefreshToken { secret: new_secret, ..refresh_token.clone() };
+        let login = self.issue_tokens(&new_token, signing_key, now).map_err(RefreshSessionError::InternalError)?;
+
+        let session = &mut self.sessions[index];
+        let previous_hash = std::mem::replace(&mut session.refresh_token_hash, new_token.hash());
+        session.previous_refresh_token_hashes.push(previous_hash);
+        if session.previous_refresh_token_hashes.len() > MAX_PREVIOUS_REFRESH_TOKENS {
+            session.previous_refresh_token_hashes.remove(0);
+        }
+        session.expires = now + REFRESH_TOKEN_EXP_TIME;
+
+        Ok(login)
+    }
+
+    fn issue_tokens(
+        &self,
+        refresh_token: &RefreshToken,
+        signing_key: Option<&JwtSigningKey>,
+        now: TimestampMillis,
+    ) -> Result<SuccessLogin, String> {
+        let signing_key = match signing_key {
+            Some(key) => key,
+            None => return Err(format!("JWT signing key is not initialized")),
+        };
+
+        let jwt = JWT::new(
+            self.noble_id,
+            self.canister_id,
+            self.email.clone(),
+            self.username.clone(),
+            refresh_token.session_id,
+            self.session_generation,
+            now,
+        );
+
+        let token = match jwt.sign(signing_key) {
             Some(j) => j,
             None => return Err(format!("JWT parsing error")),
         };
 
         Ok(SuccessLogin{
-            jwt,
+            jwt: token,
+            refresh_token: refresh_token.to_string(),
             noble_id: self.noble_id,
             username: self.username.clone(),
             first_name: self.first_name.clone(),
@@ -108,6 +222,18 @@ impl User {
             avatar_id: self.avatar_id,
         })
     }
+
+    pub fn end_session(&mut self, session_id: SessionId) -> Option<Session> {
+        let index = self.sessions.iter().position(|s| s.session_id == session_id)?;
+        Some(self.sessions.remove(index))
+    }
+
+    // Every token issued before this call carries a lower generation and is rejected from now on.
+    pub fn end_all_sessions(&mut self) -> u32 {
+        self.session_generation += 1;
+        self.sessions.clear();
+        self.session_generation
+    }
 }
 
 #[cfg(test)]
@@ -129,6 +255,9 @@ impl Default for User {
             city: String::new(),
             bio: String::new(), 
             avatar_id: 0,
+            session_generation: 0,
+            sessions: Vec::new(),
+            locale: None,
         }
     }
 }
\ No newline at end of file
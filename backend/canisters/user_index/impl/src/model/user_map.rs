@@ -1,20 +1,22 @@
+use crate::memory::{get_users_memories, Memory};
 use crate::model::user::User;
 use candid::Principal;
 use rand::{Rng, rngs::StdRng};
 use serde::{Serialize, Deserialize};
+use stable_map::StableMap;
 use std::collections::HashMap;
 use types::{NobleId, CanisterId, TimestampMillis};
 use utils::case_insensitive_hash_map::CaseInsensitiveHashMap;
 
-#[derive(Serialize, Deserialize, Default)]
+// Users are kept in stable memory, so upgrading doesn't copy them, only the lookups below. Reads
+// return a copy of the user, changes are saved with `update`.
+#[derive(Serialize, Deserialize)]
 #[serde(from = "UserMapTrimmed")]
 pub struct UserMap {
-    users: HashMap<NobleId, User>,
     #[serde(skip)]
+    users: StableMap<NobleId, User, Memory>,
     username_to_noble_id: CaseInsensitiveHashMap<NobleId>,
-    #[serde(skip)]
     principal_to_noble_id: HashMap<Principal, NobleId>,
-    #[serde(skip)]
     email_to_noble_id: HashMap<String, NobleId>,
 }
 
@@ -25,41 +27,28 @@ pub enum UpdateUserResult {
 }
 
 impl UserMap {
-    pub fn get(&self, noble_id: NobleId) -> Option<&User> {
-        self.users.get(&noble_id)
+    pub fn get(&self, noble_id: NobleId) -> Option<User> {
+        self.users.get(noble_id)
     }
 
-    pub fn get_mut(&mut self, noble_id: NobleId) -> Option<&mut User> {
-        self.users.get_mut(&noble_id)
+    pub fn contains(&self, noble_id: NobleId) -> bool {
+        self.users.contains_key(noble_id)
     }
 
     pub fn new_noble_id(&self, rnd: &mut StdRng) -> NobleId {
         let mut noble_id = rnd.gen_range(1000000000u64..10000000000u64);
-        while self.users.get(&noble_id).is_some() {
+        while self.users.contains_key(noble_id) {
             noble_id = rnd.gen_range(1000000000u64..10000000000u64);
         }
         noble_id
     }
 
-    #[allow(dead_code)]
-    pub fn get_canister_id_by_principal(&self, principal: &Principal) -> Option<&Principal> {
-        let user = self.principal_to_noble_id.get(principal).and_then(|u| self.users.get(u));
-        if user.is_none() {
-            return None;
-        }
-        Some(&user.unwrap().canister_id)
-    }
-
-    pub fn get_by_principal(&self, principal: &Principal) -> Option<&User> {
-        self.principal_to_noble_id.get(principal).and_then(|u| self.users.get(u))
-    }
-
-    pub fn get_by_email(&self, email: &str) -> Option<&User> {
-        self.email_to_noble_id.get(email).and_then(|u| self.users.get(u))
+    pub fn get_by_principal(&self, principal: &Principal) -> Option<User> {
+        self.principal_to_noble_id.get(principal).and_then(|u| self.users.get(*u))
     }
 
-    pub fn get_mut_by_email(&mut self, email: &str) -> Option<&mut User> {
-        self.email_to_noble_id.get(email).and_then(|u| self.users.get_mut(u))
+    pub fn get_by_email(&self, email: &str) -> Option<User> {
+        self.email_to_noble_id.get(email).and_then(|u| self.users.get(*u))
     }
 
     pub fn does_username_exist(&self, username: &str) -> bool {
@@ -70,18 +59,19 @@ impl UserMap {
         self.email_to_noble_id.contains_key(email)
     }
 
-    pub fn get_by_username(&self, username: &str) -> Option<&User> {
-        self.username_to_noble_id.get(username).and_then(|u| self.users.get(u))
+    pub fn get_by_username(&self, username: &str) -> Option<User> {
+        self.username_to_noble_id.get(username).and_then(|u| self.users.get(*u))
     }
 
     pub fn len(&self) -> usize {
         self.users.len()
     }
 
-    pub fn search(&self, term: &str) -> impl Iterator<Item = (&User, bool)> {
+    // Only the matching users are loaded.
+    pub fn search<'a>(&'a self, term: &str) -> impl Iterator<Item = (User, bool)> + 'a {
         self.username_to_noble_id
             .search(term)
-            .filter_map(move |(uid, p)| self.users.get(uid).map(|u| (u, p)))
+            .filter_map(move |(uid, p)| self.users.get(*uid).map(|u| (u, p)))
     }
 
     pub fn register(
@@ -99,24 +89,14 @@ impl UserMap {
         self.email_to_noble_id.insert(email.clone(), noble_id);
 
         let user = User::new(principal, noble_id, email, username, password, canister_id, now);
-        self.users.insert(noble_id, user);
-    }
-
-    pub fn update_username(&mut self, previous_username: String, username: String, noble_id: NobleId) {
-        self.username_to_noble_id.remove(&previous_username);
-        self.username_to_noble_id.insert(&username, noble_id);
-    }
-
-    pub fn update_email(&mut self, previous_email: String, email: String, noble_id: NobleId) {
-        self.email_to_noble_id.remove(&previous_email);
-        self.email_to_noble_id.insert(email, noble_id);
+        self.users.insert(noble_id, &user);
     }
 
-    #[allow(dead_code)]
-    pub fn update(&mut self, user: User) -> UpdateUserResult {
+    // Saves the changes made to a user read with `get`, the username and email lookups included.
+    pub fn update(&mut self, user: &User) -> UpdateUserResult {
         let noble_id = user.noble_id;
 
-        if let Some(previous) = self.users.get(&noble_id) {
+        if let Some(previous) = self.users.get(noble_id) {
             let previous_username = &previous.username;
             let username = &user.username;
             let username_case_insensitive_changed = previous_username.to_uppercase() != username.to_uppercase();
@@ -140,16 +120,16 @@ impl UserMap {
     }
 
     pub fn remove(&mut self, noble_id: NobleId) -> UpdateUserResult {
-        if self.users.get(&noble_id).is_some() {
-            self.users.remove(&noble_id);
+        if self.users.remove(noble_id).is_some() {
             UpdateUserResult::Success
         } else {
             UpdateUserResult::UserNotFound
         }
     }
 
-    pub fn iter(&self) -> impl Iterator<Item = &User> {
-        self.users.values()
+    // Loads every user, only for when all of them are needed.
+    pub fn iter(&self) -> impl Iterator<Item = User> + '_ {
+        self.users.iter().map(|(_, user)| user)
     }
 
     #[cfg(test)]
@@ -163,28 +143,130 @@ impl UserMap {
             user.canister_id,
             user.date_created,
         );
-        self.update(user);
+        self.update(&user);
+    }
+}
+
+impl Default for UserMap {
+    fn default() -> Self {
+        let (users_index_memory, users_memory) = get_users_memories();
+        UserMap {
+            users: StableMap::init(users_index_memory, users_memory),
+            username_to_noble_id: CaseInsensitiveHashMap::default(),
+            principal_to_noble_id: HashMap::default(),
+            email_to_noble_id: HashMap::default(),
+        }
     }
 }
 
 #[derive(Deserialize)]
 struct UserMapTrimmed {
+    // Only set when upgrading from the version that kept users on the heap, the lookups were
+    // rebuilt from them then.
+    #[serde(default)]
     users: HashMap<NobleId, User>,
+    #[serde(default)]
+    username_to_noble_id: CaseInsensitiveHashMap<NobleId>,
+    #[serde(default)]
+    principal_to_noble_id: HashMap<Principal, NobleId>,
+    #[serde(default)]
+    email_to_noble_id: HashMap<String, NobleId>,
 }
 
 impl From<UserMapTrimmed> for UserMap {
     fn from(value: UserMapTrimmed) -> Self {
         let mut user_map = UserMap {
-            users: value.users,
+            username_to_noble_id: value.username_to_noble_id,
+            principal_to_noble_id: value.principal_to_noble_id,
+            email_to_noble_id: value.email_to_noble_id,
             ..Default::default()
         };
 
-        for (noble_id, user) in user_map.users.iter_mut() {
-            user_map.username_to_noble_id.insert(&user.username, *noble_id);
-            user_map.principal_to_noble_id.insert(user.principal, *noble_id);
-            user_map.email_to_noble_id.insert(user.email.clone(), *noble_id);
+        for (noble_id, user) in value.users {
+            user_map.username_to_noble_id.insert(&user.username, noble_id);
+            user_map.principal_to_noble_id.insert(user.principal, noble_id);
+            user_map.email_to_noble_id.insert(user.email.clone(), noble_id);
+            user_map.users.insert(noble_id, &user);
         }
 
         user_map
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::user::Session;
+
+    const USER_COUNT: NobleId = 10_000;
+
+    #[test]
+    fn upgrade_moves_users_out_of_the_heap() {
+        #[derive(Serialize)]
+        struct LegacyUserMap {
+            users: HashMap<NobleId, User>,
+        }
+
+        let users: HashMap<NobleId, User> = (1..=USER_COUNT)
+            .map(|noble_id| (noble_id, User {
+                principal: Principal::from_slice(&noble_id.to_be_bytes()),
+                noble_id,
+                username: format!("user{noble_id}"),
+                email: format!("user{noble_id}@gmail.com"),
+                bio: "b".repeat(250),
+                sessions: (0..5).map(|session_id| Session {
+                    session_id,
+                    date_created: 0,
+                    expires: 0,
+                    refresh_token_hash: vec![1; 32],
+                    previous_refresh_token_hashes: vec![vec![2; 32]; 10],
+                }).collect(),
+                ..Default::default()
+            }))
+            .collect();
+        let legacy_bytes = upgrade(&LegacyUserMap { users });
+
+        let user_map: UserMap = serializer::deserialize(legacy_bytes.as_slice()).unwrap();
+        check(&user_map);
+
+        // From now on only the lookups are copied.
+        let bytes = upgrade(&user_map);
+        assert!(bytes.len() * 10 < legacy_bytes.len());
+
+        let user_map: UserMap = serializer::deserialize(bytes.as_slice()).unwrap();
+        check(&user_map);
+    }
+
+    #[test]
+    fn updates_keep_the_lookups_in_sync() {
+        let mut user_map = UserMap::default();
+        user_map.add_test_user(User { noble_id: 1, username: "ada".to_string(), email: "ada@gmail.com".to_string(), ..Default::default() });
+
+        let mut user = user_map.get(1).unwrap();
+        user.username = "Lovelace".to_string();
+        user.email = "lovelace@gmail.com".to_string();
+        user_map.update(&user);
+
+        assert!(!user_map.does_username_exist("ada"));
+        assert_eq!(user_map.get_by_username("lovelace").unwrap().noble_id, 1);
+        assert!(user_map.get_by_email("ada@gmail.com").is_none());
+        assert_eq!(user_map.get_by_email("lovelace@gmail.com").unwrap().username, "Lovelace");
+    }
+
+    fn upgrade<T: Serialize>(value: &T) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        serializer::serialize(value, &mut bytes).unwrap();
+        bytes
+    }
+
+    fn check(user_map: &UserMap) {
+        assert_eq!(user_map.len(), USER_COUNT as usize);
+        for noble_id in [1, 100, USER_COUNT] {
+            let user = user_map.get_by_username(&format!("USER{noble_id}")).unwrap();
+            assert_eq!(user.noble_id, noble_id);
+            assert_eq!(user.sessions.len(), 5);
+            assert_eq!(user_map.get_by_email(&format!("user{noble_id}@gmail.com")).unwrap().noble_id, noble_id);
+            assert_eq!(user_map.get_by_principal(&Principal::from_slice(&noble_id.to_be_bytes())).unwrap().noble_id, noble_id);
+        }
+    }
+}
@@ -8,7 +8,7 @@ fn c2c_is_nobleblocks_user(args: Args) -> Response {
 }
 
 fn c2c_is_nobleblocks_user_impl(args: Args, state: &RuntimeState) -> Response {
-    if state.data.users.get(args.noble_id).is_some() {
+    if state.data.users.contains(args.noble_id) {
         return Yes;
     }
     No
@@ -0,0 +1,40 @@
+use crate::model::email_deliveries::EmailDelivery;
+use crate::{read_state, RuntimeState};
+use canister_api_macros::admin_query;
+use user_index_canister::get_email_deliveries::{Response::*, *};
+
+#[admin_query(permission = "ViewEmailDeliveries")]
+fn get_email_deliveries(args: Args) -> Response {
+    read_state(|state| get_email_deliveries_impl(args, state))
+}
+
+fn get_email_deliveries_impl(args: Args, state: &RuntimeState) -> Response {
+    let deliveries = &state.data.email_deliveries;
+    let pending = deliveries.pending().map(|d| summarize(d, EmailDeliveryStatus::Pending));
+    let failed = deliveries.failed().map(|d| summarize(d, EmailDeliveryStatus::Failed));
+    let delivered = deliveries.delivered().map(|d| summarize(d, EmailDeliveryStatus::Delivered));
+
+    Success(
+        pending
+            .chain(failed)
+            .chain(delivered)
+            .filter(|d| args.status.map_or(true, |s| s == d.status))
+            .take(args.max_results as usize)
+            .collect(),
+    )
+}
+
+fn summarize(delivery: &EmailDelivery, status: EmailDeliveryStatus) -> EmailDeliverySummary {
+    EmailDeliverySummary {
+        id: delivery.id,
+        kind: format!("{:?}", delivery.event.template_name()),
+        recipient: delivery.event.recipient().to_string(),
+        status,
+        attempts: delivery.attempts,
+        created: delivery.created,
+        last_attempt: delivery.last_attempt,
+        next_attempt: (status == EmailDeliveryStatus::Pending).then_some(delivery.next_attempt),
+        last_error: delivery.last_error.clone(),
+        message_id: delivery.message_id.clone(),
+    }
+}
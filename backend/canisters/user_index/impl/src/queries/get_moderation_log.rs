@@ -0,0 +1,18 @@
+use crate::{read_state, RuntimeState};
+use ic_cdk_macros::query;
+use user_index_canister::get_moderation_log::{Response::*, *};
+
+const MAX_PAGE_SIZE: u32 = 100;
+
+#[query]
+fn get_moderation_log(args: Args) -> Response {
+    read_state(|state| get_moderation_log_impl(args, state))
+}
+
+fn get_moderation_log_impl(args: Args, state: &RuntimeState) -> Response {
+    if state.moderator_from_jwt(&args.jwt).is_none() {
+        return PermissionDenied;
+    }
+
+    Success(state.data.moderation_log.get(args.before, args.limit.min(MAX_PAGE_SIZE) as usize))
+}
@@ -12,6 +12,6 @@ fn get_random_users(_args: Args) -> Response {
 fn get_random_users_impl(
     state: &mut RuntimeState
 ) -> Response {
-    let results: Vec<&User> = state.data.users.iter().filter(|item| item.avatar_id != 0).choose_multiple(state.env.rng(), 5);
+    let results: Vec<User> = state.data.users.iter().filter(|item| item.avatar_id != 0).choose_multiple(state.env.rng(), 5);
     Success(results.iter().map(|item| item.get_user_info()).collect())
 }
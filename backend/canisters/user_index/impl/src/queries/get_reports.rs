@@ -0,0 +1,18 @@
+use crate::{read_state, RuntimeState};
+use ic_cdk_macros::query;
+use user_index_canister::get_reports::{Response::*, *};
+
+const MAX_PAGE_SIZE: u32 = 100;
+
+#[query]
+fn get_reports(args: Args) -> Response {
+    read_state(|state| get_reports_impl(args, state))
+}
+
+fn get_reports_impl(args: Args, state: &RuntimeState) -> Response {
+    if state.moderator_from_jwt(&args.jwt).is_none() {
+        return PermissionDenied;
+    }
+
+    Success(state.data.reports.list(args.resolved, args.after, args.limit.min(MAX_PAGE_SIZE) as usize))
+}
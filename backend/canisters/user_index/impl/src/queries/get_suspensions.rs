@@ -0,0 +1,17 @@
+use crate::{read_state, RuntimeState};
+use ic_cdk_macros::query;
+use user_index_canister::get_suspensions::{Response::*, *};
+
+#[query]
+fn get_suspensions(args: Args) -> Response {
+    read_state(|state| get_suspensions_impl(args, state))
+}
+
+fn get_suspensions_impl(args: Args, state: &RuntimeState) -> Response {
+    let now = state.env.now();
+    if state.moderator_from_jwt(&args.jwt).is_none() {
+        return PermissionDenied;
+    }
+
+    Success(state.data.suspensions.list(args.pending_appeals_only, now))
+}
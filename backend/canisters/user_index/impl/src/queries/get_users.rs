@@ -17,7 +17,7 @@ fn get_users_impl(
 
     let noble_id = check_jwt(&args.jwt, now).unwrap_or_default().noble_id;
 
-    let mut users: Vec<&User> = state.data.users.iter().filter(|item| is_filtered(item, noble_id, &args.block_me_users)).collect();
+    let mut users: Vec<User> = state.data.users.iter().filter(|item| is_filtered(item, noble_id, &args.block_me_users)).collect();
 
     users.sort_unstable_by(|lhs, rhs| {
         rhs.date_created.cmp(&lhs.date_created)
@@ -0,0 +1,12 @@
+use crate::{read_state, RuntimeState};
+use canister_api_macros::admin_query;
+use user_index_canister::list_roles::{Response::*, *};
+
+#[admin_query(permission = "ManageRoles")]
+fn list_roles(_args: Args) -> Response {
+    read_state(list_roles_impl)
+}
+
+fn list_roles_impl(state: &RuntimeState) -> Response {
+    Success(state.data.roles.grants().to_vec())
+}
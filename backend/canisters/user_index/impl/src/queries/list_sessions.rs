@@ -0,0 +1,33 @@
+use crate::{read_state, RuntimeState};
+use ic_cdk_macros::query;
+use types::check_jwt;
+use user_index_canister::list_sessions::{Response::*, *};
+
+#[query]
+fn list_sessions(args: Args) -> Response {
+    read_state(|state| list_sessions_impl(args, state))
+}
+
+fn list_sessions_impl(args: Args, state: &RuntimeState) -> Response {
+    let now = state.env.now();
+    if let Some(jwt) = check_jwt(&args.jwt, now) {
+        if let Some(user) = state.data.users.get(jwt.noble_id) {
+            let sessions = user
+                .sessions
+                .iter()
+                .filter(|s| s.expires >= now)
+                .map(|s| SessionSummary {
+                    session_id: s.session_id,
+                    date_created: s.date_created,
+                    expires: s.expires,
+                    current: s.session_id == jwt.session_id,
+                })
+                .collect();
+            Success(sessions)
+        } else {
+            UserNotFound
+        }
+    } else {
+        PermissionDenied
+    }
+}
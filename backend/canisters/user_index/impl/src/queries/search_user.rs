@@ -20,7 +20,7 @@ fn search_user_impl(
         let mut search_term = args.search_term;
         search_term = search_term.trim().to_string().to_lowercase();
     
-        let matches: Vec<&User> = users.iter().filter(|item| is_filtered(item, &search_term, jwt.noble_id, &args.block_me_users, &args.exclude_users)).collect();
+        let matches: Vec<User> = users.iter().filter(|item| is_filtered(item, &search_term, jwt.noble_id, &args.block_me_users, &args.exclude_users)).collect();
     
         // Page
         let results = matches
@@ -72,7 +72,7 @@ mod tests {
     use crate::model::user::User;
     use crate::Data;
     use candid::Principal;
-    use types::JWT;
+    use types::{JwtSigningKey, JWT};
     use utils::env::test::TestEnv;
 
     #[test]
@@ -83,7 +83,7 @@ mod tests {
 
         let response = search_user_impl(
             Args {
-                jwt : JWT::new_for_test(1, state.env.now()).to_string().unwrap(),
+                jwt : JWT::new_for_test(1, state.env.now()).sign(&JwtSigningKey::for_test()).unwrap(),
                 max_results: 1,
                 search_term: "viktor".to_string(),
                 following_list: vec![],
@@ -106,7 +106,7 @@ mod tests {
 
         let response = search_user_impl(
             Args {
-                jwt : JWT::new_for_test(1, state.env.now()).to_string().unwrap(),
+                jwt : JWT::new_for_test(1, state.env.now()).sign(&JwtSigningKey::for_test()).unwrap(),
                 max_results: 10,
                 search_term: "viktor".to_string(),
                 following_list: vec![],
@@ -130,7 +130,7 @@ mod tests {
 
         let response = search_user_impl(
             Args {
-                jwt : JWT::new_for_test(1, state.env.now()).to_string().unwrap(),
+                jwt : JWT::new_for_test(1, state.env.now()).sign(&JwtSigningKey::for_test()).unwrap(),
                 max_results: 10,
                 search_term: "rustdev".to_string(),
                 following_list: vec![],
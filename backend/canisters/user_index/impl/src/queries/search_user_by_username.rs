@@ -26,7 +26,7 @@ fn search_user_by_username_impl(
         search_term.truncate(MAX_SEARCH_TERM_LENGTH);
 
         // Filter
-        let mut matches: Vec<(&User, bool)> = users.search(&search_term).filter(|(u, _)| is_filtered(*u, jwt.noble_id, &args.block_me_users, &args.exclude_users)).collect();
+        let mut matches: Vec<(User, bool)> = users.search(&search_term).filter(|(u, _)| is_filtered(u, jwt.noble_id, &args.block_me_users, &args.exclude_users)).collect();
     
         // Sort
         matches.sort_unstable_by(|(u1, u1_starts_ci), (u2, u2_starts_ci)| {
@@ -114,7 +114,7 @@ mod tests {
     use crate::model::user::User;
     use crate::Data;
     use candid::Principal;
-    use types::JWT;
+    use types::{JwtSigningKey, JWT};
     use utils::env::test::TestEnv;
 
     #[test]
@@ -123,7 +123,7 @@ mod tests {
 
         let response = search_user_by_username_impl(
             Args {
-                jwt : JWT::new_for_test(1, state.env.now()).to_string().unwrap(),
+                jwt : JWT::new_for_test(1, state.env.now()).sign(&JwtSigningKey::for_test()).unwrap(),
                 max_results: 2,
                 search_term: "ma".to_string(),
                 following_list: vec![],
@@ -146,7 +146,7 @@ mod tests {
 
         let response = search_user_by_username_impl(
             Args {
-                jwt : JWT::new_for_test(1, state.env.now()).to_string().unwrap(),
+                jwt : JWT::new_for_test(1, state.env.now()).sign(&JwtSigningKey::for_test()).unwrap(),
                 max_results: 10,
                 search_term: "MA".to_string(),
                 following_list: vec![],
@@ -169,7 +169,7 @@ mod tests {
 
         let response = search_user_by_username_impl(
             Args {
-                jwt : JWT::new_for_test(1, state.env.now()).to_string().unwrap(),
+                jwt : JWT::new_for_test(1, state.env.now()).sign(&JwtSigningKey::for_test()).unwrap(),
                 max_results: 10,
                 search_term: "Ma".to_string(),
                 following_list: vec![],
@@ -198,7 +198,7 @@ mod tests {
 
         let response = search_user_by_username_impl(
             Args {
-                jwt : JWT::new_for_test(1, state.env.now()).to_string().unwrap(),
+                jwt : JWT::new_for_test(1, state.env.now()).sign(&JwtSigningKey::for_test()).unwrap(),
                 max_results: 10,
                 search_term: "Ma".to_string(),
                 following_list: vec![2],
@@ -226,7 +226,7 @@ mod tests {
 
         let response = search_user_by_username_impl(
             Args {
-                jwt : JWT::new_for_test(1, state.env.now()).to_string().unwrap(),
+                jwt : JWT::new_for_test(1, state.env.now()).sign(&JwtSigningKey::for_test()).unwrap(),
                 max_results: 10,
                 search_term: "".to_string(),
                 following_list: vec![],
@@ -249,7 +249,7 @@ mod tests {
 
         let response = search_user_by_username_impl(
             Args {
-                jwt : JWT::new_for_test(1, state.env.now()).to_string().unwrap(),
+                jwt : JWT::new_for_test(1, state.env.now()).sign(&JwtSigningKey::for_test()).unwrap(),
                 max_results: 10,
                 search_term: "hamish".to_string(),
                 following_list: vec![],
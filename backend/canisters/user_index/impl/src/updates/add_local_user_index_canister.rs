@@ -1,14 +1,14 @@
-use crate::guards::caller_is_governance_principal;
 use crate::{mutate_state, RuntimeState, LOCAL_USER_INDEX_CANISTER_INITIAL_CYCLES_BALANCE};
 use canister_api_macros::proposal;
 use local_user_index_canister::init::Args as InitLocalUserIndexCanisterArgs;
+use local_user_index_canister::{Event as LocalUserIndexEvent, JwtKeysUpdated, UserSuspended};
 use post_index_canister::{Event as PostIndexEvent, LocalUserIndexAdded};
 use types::{CanisterId, CanisterWasm, Cycles, Version};
 use user_index_canister::add_local_user_index_canister::{Response::*, *};
 use utils::canister;
 use utils::consts::{CREATE_CANISTER_CYCLES_FEE, MIN_CYCLES_BALANCE};
 
-#[proposal(guard = "caller_is_governance_principal")]
+#[proposal(permission = "ManageCanisters")]
 async fn add_local_user_index_canister(args: Args) -> Response {
     let PrepareOk {
         canister_id,
@@ -88,4 +88,15 @@ fn commit(canister_id: CanisterId, wasm_version: Version, state: &mut RuntimeSta
     state.push_event_to_post_index(PostIndexEvent::LocalUserIndexAdded(Box::new(LocalUserIndexAdded{
         canister_id,
     })));
+    let keys = state.data.jwt_keys.verification_keys().clone();
+    state.push_event_to_local_user_index_canister(canister_id, LocalUserIndexEvent::JwtKeysUpdated(Box::new(JwtKeysUpdated {
+        keys,
+    })));
+    let suspended_users: Vec<_> = state.data.suspensions.iter().map(|details| (details.noble_id, details.suspension)).collect();
+    for (noble_id, suspension) in suspended_users {
+        state.push_event_to_local_user_index_canister(canister_id, LocalUserIndexEvent::UserSuspended(Box::new(UserSuspended {
+            noble_id,
+            suspension,
+        })));
+    }
 }
\ No newline at end of file
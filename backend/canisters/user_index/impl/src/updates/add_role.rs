@@ -0,0 +1,52 @@
+use crate::{mutate_state, RuntimeState};
+use canister_api_macros::proposal;
+use tracing::info;
+use types::RoleMember;
+use user_index_canister::add_role::{Response::*, *};
+
+#[proposal(permission = "ManageRoles")]
+fn add_role(args: Args) -> Response {
+    mutate_state(|state| add_role_impl(args, state))
+}
+
+fn add_role_impl(args: Args, state: &mut RuntimeState) -> Response {
+    if let RoleMember::User(noble_id) = args.member {
+        if !state.data.users.contains(noble_id) {
+            return UserNotFound;
+        }
+    }
+    let now = state.env.now();
+    if !state.data.roles.add(args.member, args.role, now) {
+        return AlreadyGranted;
+    }
+    state.on_roles_changed();
+    info!(member = ?args.member, role = ?args.role, "Role granted");
+    Success
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Data;
+    use crate::model::user::User;
+    use types::Role;
+    use utils::env::test::TestEnv;
+    use utils::rbac::Permission;
+
+    #[test]
+    fn granted_roles_are_sent_everywhere() {
+        let mut data = Data::default();
+        data.users.add_test_user(User { noble_id: 7, ..Default::default() });
+        let mut state = RuntimeState::new(Box::new(TestEnv::default()), data);
+
+        let args = || Args { member: RoleMember::User(7), role: Role::Moderator };
+        assert!(matches!(add_role_impl(args(), &mut state), Success));
+        assert!(matches!(add_role_impl(args(), &mut state), AlreadyGranted));
+        assert!(matches!(add_role_impl(Args { member: RoleMember::User(8), role: Role::Moderator }, &mut state), UserNotFound));
+
+        assert_eq!(state.data.roles.grants().len(), 1);
+        assert!(state.is_platform_moderator(7));
+        assert!(!state.data.roles.user_has_permission(7, Permission::ManageRoles));
+        assert_eq!(state.data.post_index_event_sync_queue.len(), 1);
+    }
+}
@@ -0,0 +1,31 @@
+use crate::model::suspensions::{AppealResult, MAX_APPEAL_LENGTH};
+use crate::{mutate_state, RuntimeState};
+use ic_cdk_macros::update;
+use tracing::info;
+use types::check_jwt;
+use user_index_canister::appeal_suspension::{Response::*, *};
+
+#[update]
+fn appeal_suspension(args: Args) -> Response {
+    mutate_state(|state| appeal_suspension_impl(args, state))
+}
+
+fn appeal_suspension_impl(args: Args, state: &mut RuntimeState) -> Response {
+    let now = state.env.now();
+    let noble_id = match check_jwt(&args.jwt, now) {
+        Some(jwt) => jwt.noble_id,
+        None => return PermissionDenied,
+    };
+    if args.text.len() > MAX_APPEAL_LENGTH {
+        return TextTooLong(MAX_APPEAL_LENGTH as u32);
+    }
+
+    match state.data.suspensions.appeal(noble_id, args.text, now) {
+        AppealResult::Success => {
+            info!(noble_id, "Suspension appealed");
+            Success
+        }
+        AppealResult::NotSuspended => NotSuspended,
+        AppealResult::AlreadyAppealed => AlreadyAppealed,
+    }
+}
@@ -0,0 +1,51 @@
+use crate::model::suspensions::MAX_SUSPENSION_REASON_LENGTH;
+use crate::{mutate_state, RuntimeState};
+use ic_cdk_macros::update;
+use tracing::info;
+use types::{Suspension, SuspensionDetails};
+use user_index_canister::ban_user::{Response::*, *};
+use user_index_canister::get_moderation_log::ModerationEvent;
+
+#[update]
+fn ban_user(args: Args) -> Response {
+    mutate_state(|state| ban_user_impl(args, state))
+}
+
+fn ban_user_impl(args: Args, state: &mut RuntimeState) -> Response {
+    let now = state.env.now();
+    let moderator_id = match state.moderator_from_jwt(&args.jwt) {
+        Some(moderator_id) => moderator_id,
+        None => return PermissionDenied,
+    };
+    if !state.data.users.contains(args.noble_id) {
+        return UserNotFound;
+    }
+    if state.is_platform_moderator(args.noble_id) {
+        return CannotSuspendModerator;
+    }
+    if args.reason.len() > MAX_SUSPENSION_REASON_LENGTH {
+        return ReasonTooLong(MAX_SUSPENSION_REASON_LENGTH as u32);
+    }
+
+    state.suspend_user(SuspensionDetails {
+        noble_id: args.noble_id,
+        suspension: Suspension {
+            until: None,
+            hide_content: args.hide_content,
+        },
+        reason: args.reason.clone(),
+        suspended_by: moderator_id,
+        date_suspended: now,
+        appeal: None,
+    });
+
+    info!(moderator_id, noble_id = args.noble_id, "User banned");
+    state.data.moderation_log.push(moderator_id, ModerationEvent::UserSuspended {
+        noble_id: args.noble_id,
+        until: None,
+        reason: args.reason,
+        hide_content: args.hide_content,
+    }, now);
+
+    Success
+}
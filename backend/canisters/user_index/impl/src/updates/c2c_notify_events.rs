@@ -2,9 +2,13 @@ use crate::guards::caller_is_known_canister;
 use crate::model::follow_request_map::FollowRequest;
 use crate::{mutate_state, RuntimeState};
 use canister_api_macros::update_msgpack;
-use types::{NobleId, Country, AcademicDegree, AvatarId, CanisterId};
-use local_user_index_canister::{Event as LocalUserIndexEvent, FollowUser, BlockUser, CommentLiked, CommentUnliked, LocalPostIndexCanisterAdded};
+use types::{NobleId, Country, AcademicDegree, AvatarId, CanisterId, SessionRevocation, PostId, CommentId};
+use local_user_index_canister::{
+    Event as LocalUserIndexEvent, FollowUser, BlockUser, CommentLiked, CommentUnliked, LocalPostIndexCanisterAdded,
+    NotificationAdded, NotificationKind, PostDeleted,
+};
 use user_index_canister::c2c_notify_events::{Response::*, *};
+use post_index_canister::Event as PostIndexEvent;
 use user_index_canister::Event;
 
 #[update_msgpack(guard = "caller_is_known_canister")]
@@ -24,65 +28,147 @@ fn handle_event(event: Event, state: &mut RuntimeState) {
     match event {
         Event::UsernameChanged(ev) => set_username(ev.noble_id, ev.username, state),
         Event::AccountDeleted(ev) => remove_user(ev.noble_id, state),
-        Event::UserFollowed(ev) => {
-            state.push_event_to_local_user_index(ev.receiver_id, LocalUserIndexEvent::UserFollowed(Box::new(
-                FollowUser{sender_id: ev.sender_id, receiver_id: ev.receiver_id}
-            )));
-        },
-        Event::UserUnfollowed(ev) => {
-            state.push_event_to_local_user_index(ev.receiver_id, LocalUserIndexEvent::UserUnfollowed(Box::new(
-                FollowUser{sender_id: ev.sender_id, receiver_id: ev.receiver_id}
-            )));
-        },
-        Event::UserBlocked(ev) => {
-            state.push_event_to_local_user_index(ev.receiver_id, LocalUserIndexEvent::UserBlocked(Box::new(
-                BlockUser{sender_id: ev.sender_id, receiver_id: ev.receiver_id}
-            )));
-        },
-        Event::UserUnblocked(ev) => {
-            state.push_event_to_local_user_index(ev.receiver_id, LocalUserIndexEvent::UserUnblocked(Box::new(
-                BlockUser{sender_id: ev.sender_id, receiver_id: ev.receiver_id}
-            )));
-        },
+        Event::UserFollowed(ev) => follow_user(ev.sender_id, ev.receiver_id, true, state),
+        Event::UserUnfollowed(ev) => follow_user(ev.sender_id, ev.receiver_id, false, state),
+        Event::UserBlocked(ev) => block_user(ev.sender_id, ev.receiver_id, true, state),
+        Event::UserUnblocked(ev) => block_user(ev.sender_id, ev.receiver_id, false, state),
         Event::FollowRequest(ev) => follow_request(ev.sender_id, ev.receiver_id, state),
         Event::ProfileChanged(ev) => set_profile(ev.noble_id, ev.first_name, ev.last_name, ev.degree, ev.country, ev.city, ev.bio, ev.avatar_id, state),
-        Event::AccountChanged(ev) => set_account(ev.noble_id, ev.username, ev.email, ev.search_by_email, state),
+        Event::AccountChanged(ev) => set_account(ev.noble_id, ev.username, ev.search_by_email, state),
         Event::PhotoChanged(ev) => set_photo(ev.noble_id, ev.avatar_id, state),
         Event::CommentLiked(ev) => {
             state.push_event_to_local_user_index(ev.noble_id, LocalUserIndexEvent::CommentLiked(Box::new(
                 CommentLiked { noble_id: ev.noble_id, post_id: ev.post_id, comment_id: ev.comment_id }
             )));
+            if let Some(author_id) = ev.author_id {
+                let kind = if ev.comment_id == 0 {
+                    NotificationKind::PostLiked { post_id: ev.post_id }
+                } else {
+                    NotificationKind::CommentLiked { post_id: ev.post_id, comment_id: ev.comment_id }
+                };
+                notify(author_id, ev.noble_id, kind, state);
+            }
         },
         Event::CommentUnliked(ev) => {
             state.push_event_to_local_user_index(ev.noble_id, LocalUserIndexEvent::CommentUnliked(Box::new(
                 CommentUnliked { noble_id: ev.noble_id, post_id: ev.post_id, comment_id: ev.comment_id }
             )));
         },
+        Event::CommentReplied(ev) => notify(
+            ev.recipient_id,
+            ev.noble_id,
+            NotificationKind::CommentReplied { post_id: ev.post_id, comment_id: ev.comment_id },
+            state,
+        ),
+        Event::UsersMentioned(ev) => users_mentioned(ev.noble_id, ev.usernames, ev.post_id, ev.comment_id, state),
+        Event::PostDeleted(ev) => {
+            // Any user may have bookmarked or liked it.
+            state.push_event_to_all_local_user_index(LocalUserIndexEvent::PostDeleted(Box::new(
+                PostDeleted { post_id: ev.post_id }
+            )));
+        },
         Event::LocalPostIndexAdded(ev) => add_local_post_index_canister(ev.canister_id, state),
+        Event::SocialGraphSynced(ev) => social_graph_synced(ev.noble_id, ev.following, ev.blocked, state),
+        Event::UserWarned(ev) => state.warn_user(ev.noble_id, ev.target, ev.reason),
+    }
+}
+
+// The sender's canister has already updated the receiver if they're on the same one.
+fn is_on_other_canister(sender_id: NobleId, receiver_id: NobleId, state: &RuntimeState) -> bool {
+    let local_index_map = &state.data.local_index_map;
+    local_index_map.get_index_canister(&sender_id) != local_index_map.get_index_canister(&receiver_id)
+}
+
+fn follow_user(sender_id: NobleId, receiver_id: NobleId, follow: bool, state: &mut RuntimeState) {
+    if is_on_other_canister(sender_id, receiver_id, state) {
+        let ev = Box::new(FollowUser { sender_id, receiver_id });
+        state.push_event_to_local_user_index(receiver_id, if follow {
+            LocalUserIndexEvent::UserFollowed(ev)
+        } else {
+            LocalUserIndexEvent::UserUnfollowed(ev)
+        });
+    }
+    let ev = Box::new(post_index_canister::FollowUser { sender_id, receiver_id });
+    state.push_event_to_post_index(if follow {
+        PostIndexEvent::UserFollowed(ev)
+    } else {
+        PostIndexEvent::UserUnfollowed(ev)
+    });
+}
+
+fn block_user(sender_id: NobleId, receiver_id: NobleId, block: bool, state: &mut RuntimeState) {
+    if is_on_other_canister(sender_id, receiver_id, state) {
+        let ev = Box::new(BlockUser { sender_id, receiver_id });
+        state.push_event_to_local_user_index(receiver_id, if block {
+            LocalUserIndexEvent::UserBlocked(ev)
+        } else {
+            LocalUserIndexEvent::UserUnblocked(ev)
+        });
+    }
+    let ev = Box::new(post_index_canister::BlockUser { sender_id, receiver_id });
+    state.push_event_to_post_index(if block {
+        PostIndexEvent::UserBlocked(ev)
+    } else {
+        PostIndexEvent::UserUnblocked(ev)
+    });
+}
+
+// Only the post canisters need these, the users' canisters already know.
+fn social_graph_synced(noble_id: NobleId, following: Vec<NobleId>, blocked: Vec<NobleId>, state: &mut RuntimeState) {
+    for receiver_id in following {
+        state.push_event_to_post_index(PostIndexEvent::UserFollowed(Box::new(
+            post_index_canister::FollowUser { sender_id: noble_id, receiver_id }
+        )));
+    }
+    for receiver_id in blocked {
+        state.push_event_to_post_index(PostIndexEvent::UserBlocked(Box::new(
+            post_index_canister::BlockUser { sender_id: noble_id, receiver_id }
+        )));
+    }
+}
+
+fn notify(recipient_id: NobleId, actor_id: NobleId, kind: NotificationKind, state: &mut RuntimeState) {
+    if recipient_id != actor_id {
+        state.push_event_to_local_user_index(recipient_id, LocalUserIndexEvent::NotificationAdded(Box::new(
+            NotificationAdded { recipient_id, actor_id, kind }
+        )));
+    }
+}
+
+fn users_mentioned(noble_id: NobleId, usernames: Vec<String>, post_id: PostId, comment_id: CommentId, state: &mut RuntimeState) {
+    let recipients: Vec<NobleId> = usernames
+        .iter()
+        .filter_map(|username| state.data.users.get_by_username(username))
+        .map(|user| user.noble_id)
+        .collect();
+
+    for recipient_id in recipients {
+        notify(recipient_id, noble_id, NotificationKind::Mentioned { post_id, comment_id }, state);
     }
 }
 
 fn set_username(noble_id: NobleId, username: String, state: &mut RuntimeState) {
-    if let Some(user) = state.data.users.get_mut(noble_id) {
-        let prev_username = user.username.clone();
-        if prev_username != username {
-            user.username = username.clone();
-        }
-        if prev_username.to_uppercase() != username.to_uppercase() {
-            state.data.users.update_username(prev_username, username, noble_id);
+    if let Some(mut user) = state.data.users.get(noble_id) {
+        if user.username != username {
+            user.username = username;
+            state.data.users.update(&user);
         }
     }
 }
 
 fn remove_user(noble_id: NobleId, state: &mut RuntimeState) {
-    if let Some(user) = state.data.users.get(noble_id) {
-        state.data.local_index_map.remove_user(user.canister_id, noble_id);
+    if let Some(mut user) = state.data.users.get(noble_id) {
+        let canister_id = user.canister_id;
+        let generation = user.end_all_sessions();
+        state.revoke_sessions(SessionRevocation::AllSessions { noble_id, generation });
+
+        state.data.local_index_map.remove_user(canister_id, noble_id);
         state.data.users.remove(noble_id);
     }
 }
 
 fn follow_request(sender_id: NobleId, receiver_id: NobleId, state: &mut RuntimeState) {
-    if state.data.users.get(receiver_id).is_some() {
+    if state.data.users.contains(receiver_id) {
         let request = FollowRequest {
             sender: sender_id,
             receiver: receiver_id,
@@ -90,6 +176,7 @@ fn follow_request(sender_id: NobleId, receiver_id: NobleId, state: &mut RuntimeS
         };
 
         state.data.follow_requests.add_request(&request);
+        notify(receiver_id, sender_id, NotificationKind::FollowRequested, state);
     }
 }
 
@@ -104,7 +191,7 @@ fn set_profile(
     avatar_id: AvatarId,
     state: &mut RuntimeState,
 ) {
-    if let Some(user) = state.data.users.get_mut(noble_id) {
+    if let Some(mut user) = state.data.users.get(noble_id) {
         user.noble_id = noble_id;
         user.first_name = first_name;
         user.last_name = last_name;
@@ -113,29 +200,20 @@ fn set_profile(
         user.city = city;
         user.bio = bio;
         user.avatar_id = avatar_id;
+        state.data.users.update(&user);
     }
 }
 
 fn set_account(
     noble_id: NobleId,
     username: String,
-    email: String,
     search_by_email: bool,
     state: &mut RuntimeState,
 ) {
-    if let Some(user) = state.data.users.get_mut(noble_id) {
-        let prev_username = user.username.clone();
-        let prev_email = user.email.clone();
-        user.username = username.clone();
-        user.email = email.clone();
+    if let Some(mut user) = state.data.users.get(noble_id) {
+        user.username = username;
         user.search_by_email = search_by_email;
-
-        if prev_username.to_uppercase() != username.to_uppercase() {
-            state.data.users.update_username(prev_username, username, noble_id);
-        }
-        if prev_email != email{
-            state.data.users.update_email(prev_email, email, noble_id);
-        }
+        state.data.users.update(&user);
     }
 }
 
@@ -144,9 +222,10 @@ fn set_photo(
     avatar_id: AvatarId,
     state: &mut RuntimeState,
 ) {
-    if let Some(user) = state.data.users.get_mut(noble_id) {
+    if let Some(mut user) = state.data.users.get(noble_id) {
         user.noble_id = noble_id;
         user.avatar_id = avatar_id;
+        state.data.users.update(&user);
     }
 }
 
@@ -0,0 +1,119 @@
+use crate::model::temp::{ChangeEmail, TempData};
+use crate::{mutate_state, RuntimeState, INFO_EMAIL};
+use ic_cdk_macros::update;
+use types::check_jwt;
+use user_index_canister::change_email::{Response::*, *};
+use user_index_canister::{ChangeEmailVerify, EmailChangeRequested, EmailEvent};
+
+// Nothing changes until the passkey sent to the new address is given to `verify_code`, and the
+// current address is told about the request so a stolen session can't quietly take it over.
+#[update]
+fn change_email(args: Args) -> Response {
+    mutate_state(|state| change_email_impl(args, state))
+}
+
+fn change_email_impl(args: Args, state: &mut RuntimeState) -> Response {
+    let now = state.env.now();
+    let noble_id = match check_jwt(&args.jwt, now) {
+        Some(jwt) => jwt.noble_id,
+        None => return PermissionDenied,
+    };
+
+    let (name, previous_email, locale) = match state.data.users.get(noble_id) {
+        Some(user) => (user.username.clone(), user.email.clone(), user.locale.clone()),
+        None => return UserNotFound,
+    };
+
+    if !email_address::EmailAddress::is_valid(&args.email) {
+        return EmailIsInvalid;
+    }
+    if args.email == previous_email {
+        return EmailUnchanged;
+    }
+
+    state.data.temps.remove_expired_temp(now);
+    state.data.temps.remove_change_email(noble_id);
+
+    if state.data.users.does_email_exist(&args.email) || state.data.temps.does_email_exist(&args.email) {
+        return EmailTaken;
+    }
+
+    let (temp_id, passkey) = state.data.temps.add_new_temp(
+        args.email.clone(),
+        TempData::ChangeEmail(ChangeEmail { noble_id, previous_email: previous_email.clone() }),
+        state.env.rng(),
+        now,
+    );
+
+    state.push_event_to_send_email(
+        INFO_EMAIL,
+        EmailEvent::ChangeEmailVerify(Box::new(ChangeEmailVerify {
+            email: args.email.clone(),
+            name: name.clone(),
+            passkey,
+            locale: locale.clone(),
+        })),
+    );
+    // Accounts created with Google or Internet Identity may not have an address yet.
+    if !previous_email.is_empty() {
+        state.push_event_to_send_email(
+            INFO_EMAIL,
+            EmailEvent::EmailChangeRequested(Box::new(EmailChangeRequested {
+                email: previous_email,
+                name,
+                new_email: args.email,
+                locale,
+            })),
+        );
+    }
+    Success(temp_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::user::User;
+    use crate::Data;
+    use utils::env::test::TestEnv;
+
+    #[test]
+    fn new_request_replaces_pending_one() {
+        let mut state = setup_runtime_state();
+        let jwt = state.start_session(1).unwrap().jwt;
+
+        let first = change_email_impl(Args { jwt: jwt.clone(), email: "new@gmail.com".to_string() }, &mut state);
+        let second = change_email_impl(Args { jwt: jwt.clone(), email: "new@gmail.com".to_string() }, &mut state);
+
+        match (first, second) {
+            (Success(first), Success(second)) => {
+                assert!(state.data.temps.get(first).is_none());
+                assert_eq!(state.data.temps.get(second).unwrap().email, "new@gmail.com");
+            }
+            _ => panic!(),
+        }
+        // The address isn't changed until the passkey is verified.
+        assert_eq!(state.data.users.get(1).unwrap().email, "test@gmail.com");
+
+        assert_eq!(change_email_impl(Args { jwt: jwt.clone(), email: "other@gmail.com".to_string() }, &mut state), EmailTaken);
+        assert_eq!(change_email_impl(Args { jwt, email: "test@gmail.com".to_string() }, &mut state), EmailUnchanged);
+    }
+
+    fn setup_runtime_state() -> RuntimeState {
+        let mut data = Data::default();
+        data.users.add_test_user(User {
+            noble_id: 1,
+            email: "test@gmail.com".to_string(),
+            username: "testuser".to_string(),
+            ..Default::default()
+        });
+        data.users.add_test_user(User {
+            noble_id: 2,
+            email: "other@gmail.com".to_string(),
+            username: "otheruser".to_string(),
+            ..Default::default()
+        });
+        let state = RuntimeState::new(Box::new(TestEnv::default()), data);
+        types::set_jwt_verification_keys(state.data.jwt_keys.verification_keys());
+        state
+    }
+}
@@ -0,0 +1,134 @@
+use crate::model::temp::TempData;
+use crate::{mutate_state, RuntimeState, INFO_EMAIL};
+use argon2::Config;
+use ic_cdk_macros::update;
+use rand::Rng;
+use types::SessionRevocation;
+use user_index_canister::complete_password_reset::{Response::*, *};
+use user_index_canister::{EmailEvent, PasswordChanged};
+
+#[update]
+fn complete_password_reset(args: Args) -> Response {
+    mutate_state(|state| complete_password_reset_impl(args, state))
+}
+
+fn complete_password_reset_impl(args: Args, state: &mut RuntimeState) -> Response {
+    let now = state.env.now();
+    state.data.temps.remove_expired_temp(now);
+
+    let email = match state.data.temps.get(args.id) {
+        Some(temp) => match &temp.temp_data {
+            TempData::ResetPassword(data) if data.is_reset_token_valid(&args.reset_token) => temp.email.clone(),
+            _ => return InvalidResetToken,
+        },
+        None => return TempNotExist,
+    };
+
+    let mut error = ErrorResult::default();
+    if args.new_password.is_empty() {
+        error.new_password = format!("New password is required.");
+    } else if args.new_password.len() < 5 || args.new_password.len() > 20 {
+        error.new_password = format!("Password should be between 5 and 20 characters.");
+    }
+    if args.password_confirm.is_empty() || args.new_password != args.password_confirm {
+        error.password_confirm = format!("Password isn't matched.");
+    }
+    if error.is_error() {
+        return Error(error);
+    }
+
+    let salt: [u8; 32] = state.env.rng().gen();
+    let password_hash = match argon2::hash_encoded(args.new_password.as_bytes(), &salt, &Config::default()) {
+        Ok(hash) => hash,
+        Err(_) => return InternalError(format!("Password hash error.")),
+    };
+
+    let (noble_id, generation, name, locale) = match state.data.users.get_by_email(&email) {
+        Some(mut user) => {
+            user.password = password_hash;
+            let generation = user.end_all_sessions();
+            state.data.users.update(&user);
+            (user.noble_id, generation, user.username, user.locale)
+        },
+        None => return UserNotFound,
+    };
+    state.data.temps.remove(args.id);
+
+    // Whoever knew the old password must not stay logged in.
+    state.revoke_sessions(SessionRevocation::AllSessions { noble_id, generation });
+    state.push_event_to_send_email(INFO_EMAIL, EmailEvent::PasswordChanged(Box::new(PasswordChanged { email, name, locale })));
+
+    match state.start_session(noble_id) {
+        Ok(ok) => Success(ok),
+        Err(error) => InternalError(error),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Data;
+    use crate::model::temp::ResetPassword;
+    use crate::model::user::User;
+    use types::check_jwt;
+    use utils::env::test::TestEnv;
+
+    #[test]
+    fn complete_password_reset_sets_password_and_ends_old_sessions() {
+        let mut state = setup_runtime_state();
+        let old_session = state.start_session(1).unwrap().jwt;
+        let (temp_id, reset_token) = add_verified_temp(&mut state);
+
+        let response = complete_password_reset_impl(args(temp_id, &reset_token, "new_password"), &mut state);
+
+        if let Success(result) = response {
+            assert!(check_jwt(&result.jwt, state.env.now()).is_some());
+        } else {
+            assert!(false);
+        }
+        assert!(check_jwt(&old_session, state.env.now()).is_none());
+        assert!(state.data.users.get(1).unwrap().verify_password("new_password"));
+        assert!(state.data.temps.get(temp_id).is_none());
+    }
+
+    #[test]
+    fn wrong_reset_token_is_rejected() {
+        let mut state = setup_runtime_state();
+        let (temp_id, _) = add_verified_temp(&mut state);
+
+        let response = complete_password_reset_impl(args(temp_id, "wrong", "new_password"), &mut state);
+
+        assert!(matches!(response, InvalidResetToken));
+    }
+
+    fn args(id: u32, reset_token: &str, password: &str) -> Args {
+        Args {
+            id,
+            reset_token: reset_token.to_string(),
+            new_password: password.to_string(),
+            password_confirm: password.to_string(),
+        }
+    }
+
+    fn add_verified_temp(state: &mut RuntimeState) -> (u32, String) {
+        let mut data = ResetPassword::new("testuser".to_string());
+        let reset_token = data.issue_reset_token([5; 32]);
+        let now = state.env.now();
+        let (temp_id, _) = state.data.temps.add_new_temp("test@gmail.com".to_string(), TempData::ResetPassword(data), state.env.rng(), now);
+        (temp_id, reset_token)
+    }
+
+    fn setup_runtime_state() -> RuntimeState {
+        let env = TestEnv::default();
+        let mut data = Data::default();
+        data.users.add_test_user(User {
+            noble_id: 1,
+            email: "test@gmail.com".to_string(),
+            username: "testuser".to_string(),
+            ..Default::default()
+        });
+        let state = RuntimeState::new(Box::new(env), data);
+        types::set_jwt_verification_keys(state.data.jwt_keys.verification_keys());
+        state
+    }
+}
@@ -0,0 +1,33 @@
+use crate::model::moderation_log::MAX_NOTE_LENGTH;
+use crate::{mutate_state, RuntimeState};
+use ic_cdk_macros::update;
+use tracing::info;
+use user_index_canister::get_moderation_log::ModerationEvent;
+use user_index_canister::lift_suspension::{Response::*, *};
+
+#[update]
+fn lift_suspension(args: Args) -> Response {
+    mutate_state(|state| lift_suspension_impl(args, state))
+}
+
+fn lift_suspension_impl(args: Args, state: &mut RuntimeState) -> Response {
+    let now = state.env.now();
+    let moderator_id = match state.moderator_from_jwt(&args.jwt) {
+        Some(moderator_id) => moderator_id,
+        None => return PermissionDenied,
+    };
+    if args.note.len() > MAX_NOTE_LENGTH {
+        return NoteTooLong(MAX_NOTE_LENGTH as u32);
+    }
+    if state.data.suspensions.get(args.noble_id, now).is_none() || !state.lift_suspension(args.noble_id) {
+        return NotSuspended;
+    }
+
+    info!(moderator_id, noble_id = args.noble_id, "Suspension lifted");
+    state.data.moderation_log.push(moderator_id, ModerationEvent::SuspensionLifted {
+        noble_id: args.noble_id,
+        note: args.note,
+    }, now);
+
+    Success
+}
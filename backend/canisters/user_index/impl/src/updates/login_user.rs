@@ -1,83 +1,97 @@
-use crate::{read_state, RuntimeState};
-use candid::Principal;
+use crate::{mutate_state, RuntimeState};
 use ic_cdk_macros::update;
 use user_index_canister::login_user::{Response::*, *};
-use types::{CanisterId, NobleId, JWT};
 
 #[update]
-async fn login_user(args: Args) -> Response {
-    let caller = read_state(get_data);
+fn login_user(args: Args) -> Response {
+    mutate_state(|state| login_user_impl(args, state))
+}
 
-    if caller == Principal::anonymous() {
-        let (noble_id, canister_id) = match read_state(|state| prepare(&args.email, state)) {
-            Ok(ok) => ok,
-            Err(respose) => return respose,
-        };
+fn login_user_impl(args: Args, state: &mut RuntimeState) -> Response {
+    let now = state.env.now();
+    let caller = state.env.caller();
 
-        match local_user_index_canister_c2c_client::check_password(canister_id,
-            &local_user_index_canister::check_password::Args{
-                noble_id,
-                password: args.password
-            }
-        ).await {
-            Ok(res) => {
-                println!("{:?}", res);
-                match res {
-                    local_user_index_canister::check_password::Response::Success => {
-                        read_state(|state| login_with_email(&args.email, state))
-                    },
-                    _ => EmailOrPasswordIncorrect,
-                }
-            },
-            Err(error) => InternalError(format!("{:?}", error))
-        }
-    } else {
-        read_state(|state| login_with_principal(&caller, state))
+    let noble_id = state.data.users.get_by_email(&args.email)
+        .or_else(|| state.data.users.get_by_username(&args.email))
+        .map(|user| user.noble_id);
+
+    // Checked before hashing so that a locked out caller can't burn our cycles either.
+    if let Some(retry_after) = state.data.login_attempts.retry_after(noble_id, caller, now) {
+        return LoginLocked { retry_after };
     }
-}
 
-fn login_with_principal(caller: &Principal, state: &RuntimeState) -> Response {
-    if let Some(user) = state.data.users.get_by_principal(caller) {
-        let jwt = JWT::new(user.noble_id, user.email.clone(), user.username.clone(), state.env.now());
+    let verified = noble_id
+        .and_then(|noble_id| state.data.users.get(noble_id))
+        .map_or(false, |user| user.verify_password(&args.password));
 
-        let jwt = match jwt.to_string() {
-            Some(j) => j,
-            None => return InternalError("".to_string()),
-        };
-        Success(SuccessResult{
-            canister_id: user.canister_id,
-            jwt,
-        })
-    } else {
-        UnregisteredUser
+    if !verified {
+        state.data.login_attempts.record_failure(noble_id, caller, now);
+        return EmailOrPasswordIncorrect;
     }
-}
 
-fn prepare(email: &str, state: &RuntimeState) -> Result<(NobleId, CanisterId), Response> {
-    if let Some(user) = state.data.users.get_by_email(email) {
-        Ok((user.noble_id, user.canister_id))
-    } else {
-        Err(EmailOrPasswordIncorrect)
+    let noble_id = noble_id.unwrap();
+    state.data.login_attempts.record_success(Some(noble_id), caller);
+
+    match state.start_session(noble_id) {
+        Ok(ok) => Success(ok),
+        Err(error) => InternalError(error),
     }
 }
 
-fn login_with_email(email: &str, state: &RuntimeState) -> Response {
-    if let Some(user) = state.data.users.get_by_email(email) {
-        let jwt = JWT::new(user.noble_id, user.email.clone(), user.username.clone(), state.env.now());
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Data;
+    use crate::model::login_attempts::{FREE_ATTEMPTS, LOCKOUT_ATTEMPTS};
+    use crate::model::user::User;
+    use utils::env::test::TestEnv;
+
+    #[test]
+    fn repeated_failures_lock_the_account() {
+        let mut state = setup_runtime_state();
+
+        for _ in 0..FREE_ATTEMPTS {
+            assert!(matches!(login_user_impl(args("wrong"), &mut state), EmailOrPasswordIncorrect));
+        }
+        assert!(matches!(login_user_impl(args("wrong"), &mut state), EmailOrPasswordIncorrect));
+
+        // Even the right password is refused until the backoff has passed.
+        assert!(matches!(login_user_impl(args("password"), &mut state), LoginLocked { .. }));
+    }
+
+    #[test]
+    fn lockout_expires() {
+        let mut state = setup_runtime_state();
 
-        let jwt = match jwt.to_string() {
-            Some(j) => j,
-            None => return InternalError("".to_string()),
+        for _ in 0..LOCKOUT_ATTEMPTS {
+            state.data.login_attempts.record_failure(Some(1), state.env.caller(), state.env.now());
+        }
+        let retry_after = match login_user_impl(args("password"), &mut state) {
+            LoginLocked { retry_after } => retry_after,
+            _ => panic!(),
         };
-        Success(SuccessResult{
-            canister_id: user.canister_id,
-            jwt,
-        })
-    } else {
-        UnregisteredUser
+
+        state.env = Box::new(TestEnv { now: retry_after, ..Default::default() });
+        assert!(matches!(login_user_impl(args("password"), &mut state), Success(_)));
     }
-}
 
-fn get_data(state: &RuntimeState) -> Principal {
-    state.env.caller()
-}
\ No newline at end of file
+    fn args(password: &str) -> Args {
+        Args {
+            email: "test@gmail.com".to_string(),
+            password: password.to_string(),
+        }
+    }
+
+    fn setup_runtime_state() -> RuntimeState {
+        let env = TestEnv::default();
+        let mut data = Data::default();
+        data.users.add_test_user(User {
+            noble_id: 1,
+            email: "test@gmail.com".to_string(),
+            username: "testuser".to_string(),
+            password: argon2::hash_encoded(b"password", &[0; 16], &argon2::Config::default()).unwrap(),
+            ..Default::default()
+        });
+        RuntimeState::new(Box::new(env), data)
+    }
+}
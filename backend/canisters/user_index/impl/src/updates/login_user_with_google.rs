@@ -1,4 +1,5 @@
 use crate::{RuntimeState, mutate_state, read_state};
+use crate::model::google_jwks::{GoogleIdTokenClaims, GoogleIdTokenError};
 use candid::Principal;
 use ic_cdk_macros::update;
 use user_index_canister::login_user_with_google::{Response::*, *};
@@ -9,31 +10,44 @@ use utils::username_validation::{validate_username, UsernameValidationError};
 async fn login_user_with_google(args: Args) -> Response {
     let caller = ic_cdk::caller();
 
-    if read_state(|state| is_new_user(&args.email, state)) {
-        let (canister_id, noble_id, username, now) = match mutate_state(|state| prepare(&args, state)) {
+    let claims = match read_state(|state| verify_id_token(&args.id_token, state)) {
+        Ok(ok) => ok,
+        Err(err) => return err,
+    };
+
+    if read_state(|state| is_new_user(&claims.email, state)) {
+        let (canister_id, noble_id, username, now) = match mutate_state(|state| prepare(&claims, state)) {
             Ok(ok) => ok,
             Err(err) => return err,
         };
-        match register_user(caller, canister_id, noble_id, args.email.clone(), username.clone()).await {
+        match register_user(caller, canister_id, noble_id, claims.email.clone(), username.clone()).await {
             Ok(()) => {
                 mutate_state(|state| {
-                    state.data.users.register(caller, noble_id, args.email.clone(), username, String::new(), canister_id, now);
+                    state.data.users.register(caller, noble_id, claims.email.clone(), username, String::new(), canister_id, now);
                     state.data.local_index_map.add_user(canister_id, noble_id);
                 });
-                return mutate_state(|state| login_user_with_google_impl(&args.email, state))
+                return mutate_state(|state| login_user_with_google_impl(&claims.email, state))
             },
             Err(err) => return err,
         }
     }
 
-    mutate_state(|state| login_user_with_google_impl(&args.email, state))
+    mutate_state(|state| login_user_with_google_impl(&claims.email, state))
+}
+
+fn verify_id_token(id_token: &str, state: &RuntimeState) -> Result<GoogleIdTokenClaims, Response> {
+    match state.data.google_jwks.verify_id_token(id_token, &state.data.google_client_ids, state.env.now()) {
+        Ok(claims) => Ok(claims),
+        Err(GoogleIdTokenError::EmailNotVerified) => Err(EmailNotVerified),
+        Err(error) => Err(InvalidIdToken(format!("{error:?}"))),
+    }
 }
 
 fn is_new_user(email: &str, state: &RuntimeState) -> bool {
     state.data.users.get_by_email(email).is_none()
 }
 
-fn prepare(args: &Args, state: &mut RuntimeState) -> Result<(Principal, NobleId, String, TimestampMillis), Response> {
+fn prepare(claims: &GoogleIdTokenClaims, state: &mut RuntimeState) -> Result<(Principal, NobleId, String, TimestampMillis), Response> {
     let canister_id = match state.data.local_index_map.index_for_new_user() {
         Some(index) => index,
         None => return Err(UserLimitReached),
@@ -41,7 +55,7 @@ fn prepare(args: &Args, state: &mut RuntimeState) -> Result<(Principal, NobleId,
 
     let noble_id = state.data.users.new_noble_id(state.env.rng());
 
-    let mut username = format!("{}{}", args.first_name, args.last_name);
+    let mut username = format!("{}{}", claims.given_name, claims.family_name);
 
     match validate_username(&username) {
         Ok(_) => {}
@@ -91,18 +105,105 @@ async fn register_user(
 }
 
 fn login_user_with_google_impl(email: &str, state: &mut RuntimeState) -> Response {
-    if let Some(user) = state.data.users.get_by_email(email) {
-        match user.get_login_info(state.env.now()) {
-            Ok(ok) => {
-                if user.username.is_empty() {
-                    UsernameRequire(UsernameRequireResult { jwt: ok.jwt })
-                } else {
-                    Success(ok)
-                }
-            },
-            Err(error) => InternalError(error),
+    let noble_id = match state.data.users.get_by_email(email) {
+        Some(user) => user.noble_id,
+        None => return InternalError(format!("Something went wrong")),
+    };
+
+    match state.start_session(noble_id) {
+        Ok(ok) => {
+            if ok.username.is_empty() {
+                UsernameRequire(UsernameRequireResult { jwt: ok.jwt })
+            } else {
+                Success(ok)
+            }
+        },
+        Err(error) => InternalError(error),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Data;
+    use crate::model::google_jwks::test_fixture::{self, CLIENT_ID};
+    use crate::model::user::User;
+    use types::Version;
+    use utils::env::test::TestEnv;
+
+    // Token checks themselves are tested in `google_jwks`, these cover what the endpoint does with them.
+
+    #[test]
+    fn login_links_account_by_email() {
+        let mut state = setup_runtime_state();
+        let token = test_fixture::sign(test_fixture::claims("test@gmail.com", CLIENT_ID, state.env.now()));
+
+        let claims = verify_id_token(&token, &state).unwrap();
+        assert!(!is_new_user(&claims.email, &state));
+
+        match login_user_with_google_impl(&claims.email, &mut state) {
+            Success(result) => assert_eq!(result.noble_id, 1),
+            response => panic!("{response:?}"),
         }
-    } else {
-        InternalError(format!("Something went wrong"))
+    }
+
+    #[test]
+    fn account_without_username_must_pick_one() {
+        let mut state = setup_runtime_state();
+        state.data.users.add_test_user(User { noble_id: 2, email: format!("nameless@gmail.com"), ..Default::default() });
+
+        assert!(matches!(login_user_with_google_impl("nameless@gmail.com", &mut state), UsernameRequire(_)));
+    }
+
+    #[test]
+    fn token_errors_map_to_responses() {
+        let state = setup_runtime_state();
+        let now = state.env.now();
+
+        let wrong_audience = test_fixture::sign(test_fixture::claims("test@gmail.com", "other.apps.googleusercontent.com", now));
+        assert!(matches!(verify_id_token(&wrong_audience, &state), Err(InvalidIdToken(_))));
+
+        let mut claims = test_fixture::claims("test@gmail.com", CLIENT_ID, now);
+        claims["email_verified"] = false.into();
+        assert!(matches!(verify_id_token(&test_fixture::sign(claims), &state), Err(EmailNotVerified)));
+    }
+
+    #[test]
+    fn new_user_is_named_from_the_token() {
+        let mut state = setup_runtime_state();
+        state.data.local_index_map.add_index(Principal::anonymous(), Version::default());
+        let mut claims = test_fixture::claims("new@gmail.com", CLIENT_ID, state.env.now());
+        claims["given_name"] = "Ada".into();
+        claims["family_name"] = "Lovelace".into();
+        let claims = verify_id_token(&test_fixture::sign(claims), &state).unwrap();
+        assert!(is_new_user(&claims.email, &state));
+
+        match prepare(&claims, &mut state) {
+            Ok((_, _, username, _)) => assert_eq!(username, "AdaLovelace"),
+            Err(response) => panic!("{response:?}"),
+        }
+
+        // Falls back to an anonymous name once the name is taken.
+        state.data.users.add_test_user(User { noble_id: 2, username: format!("adalovelace"), email: format!("ada@gmail.com"), ..Default::default() });
+        match prepare(&claims, &mut state) {
+            Ok((_, _, username, _)) => assert_eq!(username, "user00001"),
+            Err(response) => panic!("{response:?}"),
+        }
+    }
+
+    fn setup_runtime_state() -> RuntimeState {
+        let env = TestEnv::default();
+        let mut data = Data::default();
+        data.google_jwks = test_fixture::jwks();
+        data.google_client_ids = test_fixture::client_ids();
+
+        data.users.add_test_user(User {
+            noble_id: 1,
+            username: format!("testuser"),
+            email: format!("test@gmail.com"),
+            ..Default::default()
+        });
+
+        RuntimeState::new(Box::new(env), data)
     }
 }
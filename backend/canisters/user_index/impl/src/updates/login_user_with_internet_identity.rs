@@ -64,12 +64,13 @@ async fn register_user(caller: Principal, canister_id: CanisterId, noble_id: Nob
 }
 
 fn login_user_with_internet_identity_impl(caller: &Principal, _: Args, state: &mut RuntimeState) -> Response {
-    if let Some(user) = state.data.users.get_by_principal(&caller) {
-        match user.get_login_info(state.env.now()) {
-            Ok(ok) => Success(ok),
-            Err(error) => InternalError(error),
-        }
-    } else {
-        InternalError(format!("Something went wrong"))
+    let noble_id = match state.data.users.get_by_principal(&caller) {
+        Some(user) => user.noble_id,
+        None => return InternalError(format!("Something went wrong")),
+    };
+
+    match state.start_session(noble_id) {
+        Ok(ok) => Success(ok),
+        Err(error) => InternalError(error),
     }
 }
@@ -0,0 +1,59 @@
+use crate::{mutate_state, RuntimeState};
+use ic_cdk_macros::update;
+use types::{check_jwt, SessionRevocation, EXP_TIME};
+use user_index_canister::logout::{Response::*, *};
+
+#[update]
+fn logout(args: Args) -> Response {
+    mutate_state(|state| logout_impl(args, state))
+}
+
+fn logout_impl(args: Args, state: &mut RuntimeState) -> Response {
+    if let Some(jwt) = check_jwt(&args.jwt, state.env.now()) {
+        if let Some(mut user) = state.data.users.get(jwt.noble_id) {
+            user.end_session(jwt.session_id);
+            state.data.users.update(&user);
+        }
+
+        // Access tokens of the session issued later than this one expire no later than this.
+        let expires = state.env.now() + EXP_TIME;
+        state.revoke_sessions(SessionRevocation::Session {
+            session_id: jwt.session_id,
+            expires,
+        });
+        Success
+    } else {
+        PermissionDenied
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Data;
+    use crate::model::user::User;
+    use utils::env::test::TestEnv;
+
+    #[test]
+    fn logout_revokes_only_the_current_session() {
+        let env = TestEnv::default();
+        let mut data = Data::default();
+        data.users.add_test_user(User {
+            noble_id: 1,
+            ..Default::default()
+        });
+        let mut state = RuntimeState::new(Box::new(env), data);
+        types::set_jwt_verification_keys(state.data.jwt_keys.verification_keys());
+
+        let first = state.start_session(1).unwrap().jwt;
+        let second = state.start_session(1).unwrap().jwt;
+
+        let response = logout_impl(Args { jwt: first.clone() }, &mut state);
+
+        assert_eq!(response, Success);
+        assert!(check_jwt(&first, state.env.now()).is_none());
+        assert!(check_jwt(&second, state.env.now()).is_some());
+        assert_eq!(state.data.users.get(1).unwrap().sessions.len(), 1);
+        assert_eq!(logout_impl(Args { jwt: first }, &mut state), PermissionDenied);
+    }
+}
@@ -0,0 +1,63 @@
+use crate::{mutate_state, RuntimeState};
+use ic_cdk_macros::update;
+use types::{check_jwt, SessionRevocation};
+use user_index_canister::logout_all_sessions::{Response::*, *};
+
+#[update]
+fn logout_all_sessions(args: Args) -> Response {
+    mutate_state(|state| logout_all_sessions_impl(args, state))
+}
+
+fn logout_all_sessions_impl(args: Args, state: &mut RuntimeState) -> Response {
+    if let Some(jwt) = check_jwt(&args.jwt, state.env.now()) {
+        let generation = match state.data.users.get(jwt.noble_id) {
+            Some(mut user) => {
+                let generation = user.end_all_sessions();
+                state.data.users.update(&user);
+                generation
+            },
+            None => return UserNotFound,
+        };
+
+        state.revoke_sessions(SessionRevocation::AllSessions {
+            noble_id: jwt.noble_id,
+            generation,
+        });
+        Success
+    } else {
+        PermissionDenied
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Data;
+    use crate::model::user::User;
+    use utils::env::test::TestEnv;
+
+    #[test]
+    fn logout_all_sessions_revokes_every_session() {
+        let env = TestEnv::default();
+        let mut data = Data::default();
+        data.users.add_test_user(User {
+            noble_id: 1,
+            ..Default::default()
+        });
+        let mut state = RuntimeState::new(Box::new(env), data);
+        types::set_jwt_verification_keys(state.data.jwt_keys.verification_keys());
+
+        let first = state.start_session(1).unwrap().jwt;
+        let second = state.start_session(1).unwrap().jwt;
+
+        let response = logout_all_sessions_impl(Args { jwt: first.clone() }, &mut state);
+
+        assert_eq!(response, Success);
+        assert!(check_jwt(&first, state.env.now()).is_none());
+        assert!(check_jwt(&second, state.env.now()).is_none());
+        assert!(state.data.users.get(1).unwrap().sessions.is_empty());
+
+        let renewed = state.start_session(1).unwrap().jwt;
+        assert!(check_jwt(&renewed, state.env.now()).is_some());
+    }
+}
@@ -1,12 +1,35 @@
 pub mod add_local_user_index_canister;
+pub mod add_role;
+pub mod appeal_suspension;
+pub mod ban_user;
 pub mod c2c_notify_events;
+pub mod change_email;
+pub mod complete_password_reset;
+pub mod lift_suspension;
+pub mod login_user;
 pub mod login_user_with_google;
 pub mod login_user_with_internet_identity;
+pub mod logout;
+pub mod logout_all_sessions;
 pub mod reset_password;
+pub mod refresh_session;
 pub mod register_user;
+pub mod reject_appeal;
+pub mod remove_role;
+pub mod report_comment;
+pub mod report_post;
+pub mod report_user;
+pub mod resolve_report;
+pub mod retire_jwt_key;
+pub mod rotate_jwt_key;
 pub mod send_feedback;
+pub mod set_email_config;
+pub mod set_email_template;
+pub mod set_google_client_ids;
+pub mod set_locale;
 pub mod set_password;
 pub mod set_username;
+pub mod suspend_user;
 pub mod upgrade_local_user_index_canister_wasm;
 pub mod verify_code_resend;
 pub mod verify_code;
\ No newline at end of file
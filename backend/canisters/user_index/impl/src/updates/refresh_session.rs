@@ -0,0 +1,120 @@
+use crate::model::refresh_token::RefreshToken;
+use crate::model::user::RefreshSessionError;
+use crate::{mutate_state, RuntimeState};
+use ic_cdk_macros::update;
+use rand::Rng;
+use types::{SessionRevocation, EXP_TIME};
+use user_index_canister::refresh_session::{Response::*, *};
+
+#[update]
+fn refresh_session(args: Args) -> Response {
+    mutate_state(|state| refresh_session_impl(args, state))
+}
+
+fn refresh_session_impl(args: Args, state: &mut RuntimeState) -> Response {
+    let refresh_token = match RefreshToken::parse(&args.refresh_token) {
+        Some(token) => token,
+        None => return InvalidRefreshToken,
+    };
+
+    let now = state.env.now();
+    let new_secret: [u8; 32] = state.env.rng().gen();
+
+    let mut user = match state.data.users.get(refresh_token.noble_id) {
+        Some(user) => user,
+        None => return InvalidRefreshToken,
+    };
+
+    let result = user.refresh_session(&refresh_token, new_secret, state.data.jwt_keys.signing_key(), now);
+    state.data.users.update(&user);
+
+    match result {
+        Ok(ok) => Success(ok),
+        Err(RefreshSessionError::InvalidRefreshToken) => InvalidRefreshToken,
+        Err(RefreshSessionError::TokenReused) => {
+            state.revoke_sessions(SessionRevocation::Session {
+                session_id: refresh_token.session_id,
+                expires: now + EXP_TIME,
+            });
+            RefreshTokenReused
+        },
+        Err(RefreshSessionError::InternalError(error)) => InternalError(error),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Data;
+    use crate::model::user::User;
+    use types::check_jwt;
+    use utils::env::test::TestEnv;
+
+    #[test]
+    fn refresh_rotates_the_refresh_token() {
+        let mut state = setup_runtime_state();
+        let login = state.start_session(1).unwrap();
+
+        let refreshed = match refresh_session_impl(Args { refresh_token: login.refresh_token.clone() }, &mut state) {
+            Success(result) => result,
+            _ => panic!(),
+        };
+
+        assert_ne!(refreshed.refresh_token, login.refresh_token);
+        assert_eq!(check_jwt(&refreshed.jwt, state.env.now()).unwrap().noble_id, 1);
+        assert!(matches!(
+            refresh_session_impl(Args { refresh_token: refreshed.refresh_token }, &mut state),
+            Success(_)
+        ));
+    }
+
+    #[test]
+    fn reused_refresh_token_ends_the_session() {
+        let mut state = setup_runtime_state();
+        let login = state.start_session(1).unwrap();
+
+        let refreshed = match refresh_session_impl(Args { refresh_token: login.refresh_token.clone() }, &mut state) {
+            Success(result) => result,
+            _ => panic!(),
+        };
+
+        let response = refresh_session_impl(Args { refresh_token: login.refresh_token }, &mut state);
+
+        assert!(matches!(response, RefreshTokenReused));
+        assert!(check_jwt(&refreshed.jwt, state.env.now()).is_none());
+        assert!(state.data.users.get(1).unwrap().sessions.is_empty());
+        assert!(matches!(
+            refresh_session_impl(Args { refresh_token: refreshed.refresh_token }, &mut state),
+            InvalidRefreshToken
+        ));
+    }
+
+    #[test]
+    fn unknown_refresh_token_leaves_the_session_alone() {
+        let mut state = setup_runtime_state();
+        let login = state.start_session(1).unwrap();
+
+        let mut forged = RefreshToken::parse(&login.refresh_token).unwrap();
+        forged.secret = [7; 32];
+        let response = refresh_session_impl(Args { refresh_token: forged.to_string() }, &mut state);
+
+        assert!(matches!(response, InvalidRefreshToken));
+        assert!(check_jwt(&login.jwt, state.env.now()).is_some());
+        assert!(matches!(
+            refresh_session_impl(Args { refresh_token: login.refresh_token }, &mut state),
+            Success(_)
+        ));
+    }
+
+    fn setup_runtime_state() -> RuntimeState {
+        let env = TestEnv::default();
+        let mut data = Data::default();
+        data.users.add_test_user(User {
+            noble_id: 1,
+            ..Default::default()
+        });
+        let state = RuntimeState::new(Box::new(env), data);
+        types::set_jwt_verification_keys(state.data.jwt_keys.verification_keys());
+        state
+    }
+}
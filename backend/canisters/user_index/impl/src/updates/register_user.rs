@@ -21,7 +21,7 @@ fn register_user_impl(args: Args, state: &mut RuntimeState) -> Response {
     if let Some((temp_id, passkey)) = state.data.temps.does_exist(&args.email, &args.username, state.env.rng(), now) {
         state.push_event_to_send_email(
             INFO_EMAIL,
-            EmailEvent::RegisterUser(Box::new(RegisterUser { email: args.email, name: args.username, passkey }))
+            EmailEvent::RegisterUser(Box::new(RegisterUser { email: args.email, name: args.username, passkey, locale: args.locale }))
         );
         return Success(temp_id);
     }
@@ -71,6 +71,7 @@ fn register_user_impl(args: Args, state: &mut RuntimeState) -> Response {
 
     let email = args.email.clone();
     let name = args.username.clone();
+    let locale = args.locale.clone();
 
     let (temp_id, passkey) = state.data.temps.add_new_temp(
         args.email.clone(),
@@ -80,7 +81,7 @@ fn register_user_impl(args: Args, state: &mut RuntimeState) -> Response {
     );
     state.push_event_to_send_email(
         INFO_EMAIL,
-        EmailEvent::RegisterUser(Box::new(RegisterUser { email, name, passkey }))
+        EmailEvent::RegisterUser(Box::new(RegisterUser { email, name, passkey, locale }))
     );
     Success(temp_id)
 }
@@ -0,0 +1,37 @@
+use crate::model::moderation_log::MAX_NOTE_LENGTH;
+use crate::model::suspensions::RejectAppealResult;
+use crate::{mutate_state, RuntimeState};
+use ic_cdk_macros::update;
+use tracing::info;
+use user_index_canister::get_moderation_log::ModerationEvent;
+use user_index_canister::reject_appeal::{Response::*, *};
+
+#[update]
+fn reject_appeal(args: Args) -> Response {
+    mutate_state(|state| reject_appeal_impl(args, state))
+}
+
+fn reject_appeal_impl(args: Args, state: &mut RuntimeState) -> Response {
+    let now = state.env.now();
+    let moderator_id = match state.moderator_from_jwt(&args.jwt) {
+        Some(moderator_id) => moderator_id,
+        None => return PermissionDenied,
+    };
+    if args.note.len() > MAX_NOTE_LENGTH {
+        return NoteTooLong(MAX_NOTE_LENGTH as u32);
+    }
+
+    match state.data.suspensions.reject_appeal(args.noble_id, now) {
+        RejectAppealResult::Success => {},
+        RejectAppealResult::NotSuspended => return NotSuspended,
+        RejectAppealResult::NoAppeal => return NoAppeal,
+    }
+
+    info!(moderator_id, noble_id = args.noble_id, "Appeal rejected");
+    state.data.moderation_log.push(moderator_id, ModerationEvent::AppealRejected {
+        noble_id: args.noble_id,
+        note: args.note,
+    }, now);
+
+    Success
+}
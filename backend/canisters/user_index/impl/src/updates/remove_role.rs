@@ -0,0 +1,48 @@
+use crate::{mutate_state, RuntimeState};
+use canister_api_macros::proposal;
+use tracing::info;
+use types::{Role, RoleMember};
+use user_index_canister::remove_role::{Response::*, *};
+
+#[proposal(permission = "ManageRoles")]
+fn remove_role(args: Args) -> Response {
+    mutate_state(|state| remove_role_impl(args, state))
+}
+
+fn remove_role_impl(args: Args, state: &mut RuntimeState) -> Response {
+    if !state.data.roles.has_role(args.member, args.role) {
+        return NotGranted;
+    }
+    if args.role == Role::Governance
+        && matches!(args.member, RoleMember::Principal(_))
+        && state.data.roles.principal_count(Role::Governance) == 1
+    {
+        return LastGovernor;
+    }
+    state.data.roles.remove(args.member, args.role);
+    state.on_roles_changed();
+    info!(member = ?args.member, role = ?args.role, "Role revoked");
+    Success
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Data;
+    use candid::Principal;
+    use utils::env::test::TestEnv;
+
+    #[test]
+    fn last_governing_principal_is_kept() {
+        let mut data = Data::default();
+        let governor = RoleMember::Principal(Principal::from_slice(&[1]));
+        data.roles.add(governor, Role::Governance, 0);
+        data.roles.add(RoleMember::User(7), Role::Governance, 0);
+        let mut state = RuntimeState::new(Box::new(TestEnv::default()), data);
+
+        // The user's grant doesn't count, they can't call remove_role.
+        assert!(matches!(remove_role_impl(Args { member: governor, role: Role::Governance }, &mut state), LastGovernor));
+        assert!(matches!(remove_role_impl(Args { member: RoleMember::User(7), role: Role::Governance }, &mut state), Success));
+        assert!(matches!(remove_role_impl(Args { member: RoleMember::User(7), role: Role::Governance }, &mut state), NotGranted));
+    }
+}
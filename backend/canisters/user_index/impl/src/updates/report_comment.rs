@@ -0,0 +1,54 @@
+use crate::model::reports::{AddReportResult, MAX_REPORT_DETAILS_LENGTH};
+use crate::{mutate_state, read_state, RuntimeState};
+use ic_cdk_macros::update;
+use types::{check_jwt, CanisterId, NobleId, ReportTarget};
+use user_index_canister::report_comment::{Response::*, *};
+
+// The comment is checked in the local_post_index its post lives in, which post_index knows.
+#[update]
+async fn report_comment(args: Args) -> Response {
+    let (noble_id, post_index_canister_id) = match read_state(|state| prepare(&args, state)) {
+        Ok(result) => result,
+        Err(response) => return response,
+    };
+
+    let local_post_index_canister_id = match post_index_canister_c2c_client::c2c_get_post_canister(
+        post_index_canister_id,
+        &post_index_canister::c2c_get_post_canister::Args { post_id: args.post_id },
+    ).await {
+        Ok(post_index_canister::c2c_get_post_canister::Response::Success(canister_id)) => canister_id,
+        Ok(post_index_canister::c2c_get_post_canister::Response::PostNotFound) => return PostNotFound,
+        Err(error) => return InternalError(format!("{:?}", error)),
+    };
+
+    match local_post_index_canister_c2c_client::c2c_is_nobleblocks_comment(
+        local_post_index_canister_id,
+        &local_post_index_canister::c2c_is_nobleblocks_comment::Args { post_id: args.post_id, comment_id: args.comment_id },
+    ).await {
+        Ok(local_post_index_canister::c2c_is_nobleblocks_comment::Response::Yes) => {},
+        Ok(local_post_index_canister::c2c_is_nobleblocks_comment::Response::No) => return CommentNotFound,
+        Err(error) => return InternalError(format!("{:?}", error)),
+    }
+
+    mutate_state(|state| report_comment_impl(noble_id, args, state))
+}
+
+fn prepare(args: &Args, state: &RuntimeState) -> Result<(NobleId, CanisterId), Response> {
+    let noble_id = match check_jwt(&args.jwt, state.env.now()) {
+        Some(jwt) => jwt.noble_id,
+        None => return Err(PermissionDenied),
+    };
+    if args.details.len() > MAX_REPORT_DETAILS_LENGTH {
+        return Err(DetailsTooLong(MAX_REPORT_DETAILS_LENGTH as u32));
+    }
+    Ok((noble_id, state.data.post_index_canister_id))
+}
+
+fn report_comment_impl(noble_id: NobleId, args: Args, state: &mut RuntimeState) -> Response {
+    let now = state.env.now();
+    match state.data.reports.add(ReportTarget::Comment { post_id: args.post_id, comment_id: args.comment_id }, noble_id, args.reason, args.details, now) {
+        AddReportResult::Success(report_id) => Success(report_id),
+        AddReportResult::AlreadyReported => AlreadyReported,
+        AddReportResult::TooManyOpenReports => TooManyOpenReports,
+    }
+}
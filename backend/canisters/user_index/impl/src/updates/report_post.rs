@@ -0,0 +1,44 @@
+use crate::model::reports::{AddReportResult, MAX_REPORT_DETAILS_LENGTH};
+use crate::{mutate_state, read_state, RuntimeState};
+use ic_cdk_macros::update;
+use types::{check_jwt, CanisterId, NobleId, ReportTarget};
+use user_index_canister::report_post::{Response::*, *};
+
+#[update]
+async fn report_post(args: Args) -> Response {
+    let (noble_id, post_index_canister_id) = match read_state(|state| prepare(&args, state)) {
+        Ok(result) => result,
+        Err(response) => return response,
+    };
+
+    match post_index_canister_c2c_client::c2c_is_nobleblocks_post(
+        post_index_canister_id,
+        &post_index_canister::c2c_is_nobleblocks_post::Args { post_id: args.post_id },
+    ).await {
+        Ok(post_index_canister::c2c_is_nobleblocks_post::Response::Yes) => {},
+        Ok(post_index_canister::c2c_is_nobleblocks_post::Response::No) => return PostNotFound,
+        Err(error) => return InternalError(format!("{:?}", error)),
+    }
+
+    mutate_state(|state| report_post_impl(noble_id, args, state))
+}
+
+fn prepare(args: &Args, state: &RuntimeState) -> Result<(NobleId, CanisterId), Response> {
+    let noble_id = match check_jwt(&args.jwt, state.env.now()) {
+        Some(jwt) => jwt.noble_id,
+        None => return Err(PermissionDenied),
+    };
+    if args.details.len() > MAX_REPORT_DETAILS_LENGTH {
+        return Err(DetailsTooLong(MAX_REPORT_DETAILS_LENGTH as u32));
+    }
+    Ok((noble_id, state.data.post_index_canister_id))
+}
+
+fn report_post_impl(noble_id: NobleId, args: Args, state: &mut RuntimeState) -> Response {
+    let now = state.env.now();
+    match state.data.reports.add(ReportTarget::Post { post_id: args.post_id }, noble_id, args.reason, args.details, now) {
+        AddReportResult::Success(report_id) => Success(report_id),
+        AddReportResult::AlreadyReported => AlreadyReported,
+        AddReportResult::TooManyOpenReports => TooManyOpenReports,
+    }
+}
@@ -0,0 +1,33 @@
+use crate::model::reports::{AddReportResult, MAX_REPORT_DETAILS_LENGTH};
+use crate::{mutate_state, RuntimeState};
+use ic_cdk_macros::update;
+use types::{check_jwt, ReportTarget};
+use user_index_canister::report_user::{Response::*, *};
+
+#[update]
+fn report_user(args: Args) -> Response {
+    mutate_state(|state| report_user_impl(args, state))
+}
+
+fn report_user_impl(args: Args, state: &mut RuntimeState) -> Response {
+    let now = state.env.now();
+    let noble_id = match check_jwt(&args.jwt, now) {
+        Some(jwt) => jwt.noble_id,
+        None => return PermissionDenied,
+    };
+    if args.noble_id == noble_id {
+        return CannotReportSelf;
+    }
+    if args.details.len() > MAX_REPORT_DETAILS_LENGTH {
+        return DetailsTooLong(MAX_REPORT_DETAILS_LENGTH as u32);
+    }
+    if !state.data.users.contains(args.noble_id) {
+        return UserNotFound;
+    }
+
+    match state.data.reports.add(ReportTarget::User { noble_id: args.noble_id }, noble_id, args.reason, args.details, now) {
+        AddReportResult::Success(report_id) => Success(report_id),
+        AddReportResult::AlreadyReported => AlreadyReported,
+        AddReportResult::TooManyOpenReports => TooManyOpenReports,
+    }
+}
@@ -1,7 +1,6 @@
 use crate::{mutate_state, RuntimeState, INFO_EMAIL};
 use crate::model::temp::{TempData, ResetPassword};
 use ic_cdk_macros::update;
-use rand::{Rng, distributions::Alphanumeric};
 use user_index_canister::{EmailEvent, ResetPasswordVerify};
 use user_index_canister::reset_password::{Response::*, *};
 
@@ -17,18 +16,54 @@ fn reset_password_impl(args: &Args, state: &mut RuntimeState) -> Response {
         if user.email.is_empty() {
             return EmailNotSet;
         }
-        let password: String = state.env.rng().sample_iter(&Alphanumeric).take(10).map(char::from).collect();
         let now = state.env.now();
         let (temp_id, passkey) = state.data.temps.add_new_temp(
             args.email.clone(),
-            TempData::ResetPassword(ResetPassword { name: user.username.clone(), password: password.clone() }), state.env.rng(), now
+            TempData::ResetPassword(ResetPassword::new(user.username.clone())), state.env.rng(), now
         );
         state.push_event_to_send_email(
             INFO_EMAIL,
-            EmailEvent::ResetPasswordVerify(Box::new(ResetPasswordVerify { email: user.email.clone(), name: user.username.clone(), passkey }))
+            EmailEvent::ResetPasswordVerify(Box::new(ResetPasswordVerify { email: user.email.clone(), name: user.username.clone(), passkey, locale: user.locale.clone() }))
         );
         Success(temp_id)
     } else {
         UserNotFound
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Data;
+
+    // Resets queued before passwords stopped being emailed carry a password the account was set to.
+    // They go out as a password changed notice instead, which tells the user to start a new reset.
+    #[test]
+    fn resets_queued_before_the_upgrade_become_notices() {
+        #[derive(serde::Serialize)]
+        enum OldEmailEvent {
+            ResetPassword(Box<OldResetPassword>),
+        }
+        #[derive(serde::Serialize)]
+        struct OldResetPassword {
+            email: String,
+            name: String,
+            password: String,
+        }
+
+        let mut bytes = Vec::new();
+        let old = OldEmailEvent::ResetPassword(Box::new(OldResetPassword {
+            email: "bob@gmail.com".to_string(),
+            name: "bob".to_string(),
+            password: "secret".to_string(),
+        }));
+        serializer::serialize(&old, &mut bytes).unwrap();
+        let migrated: EmailEvent = serializer::deserialize(bytes.as_slice()).unwrap();
+
+        let mut data = Data::default();
+        data.email_event_sync_queue.push(INFO_EMAIL.to_string(), migrated);
+
+        let (_, events) = data.email_event_sync_queue.try_start_single().unwrap();
+        assert!(matches!(&events[..], [EmailEvent::PasswordChanged(event)] if event.email == "bob@gmail.com" && event.name == "bob"));
+    }
+}
@@ -0,0 +1,126 @@
+use crate::model::moderation_log::MAX_NOTE_LENGTH;
+use crate::model::reports::ResolveReportResult;
+use crate::{mutate_state, RuntimeState};
+use ic_cdk_macros::update;
+use post_index_canister::{ContentModerated, Event as PostIndexEvent};
+use tracing::info;
+use types::{CommentId, ModerationAction, PostId, ReportReason, ReportTarget};
+use user_index_canister::get_moderation_log::ModerationEvent;
+use user_index_canister::resolve_report::{Response::*, *};
+
+#[update]
+fn resolve_report(args: Args) -> Response {
+    mutate_state(|state| resolve_report_impl(args, state))
+}
+
+fn resolve_report_impl(args: Args, state: &mut RuntimeState) -> Response {
+    let now = state.env.now();
+    let moderator_id = match state.moderator_from_jwt(&args.jwt) {
+        Some(moderator_id) => moderator_id,
+        None => return PermissionDenied,
+    };
+    if args.note.len() > MAX_NOTE_LENGTH {
+        return NoteTooLong(MAX_NOTE_LENGTH as u32);
+    }
+
+    let report = match state.data.reports.get(args.report_id) {
+        Some(report) if report.resolution.is_some() => return AlreadyResolved,
+        Some(report) => report.clone(),
+        None => return ReportNotFound,
+    };
+    if matches!(report.target, ReportTarget::User { .. }) && matches!(args.action, ModerationAction::Hide | ModerationAction::Remove) {
+        return InvalidAction;
+    }
+
+    let report_ids: Vec<_> = match state.data.reports.resolve(args.report_id, moderator_id, args.action, args.note.clone(), now) {
+        ResolveReportResult::Success(resolved) => resolved.iter().map(|report| report.report_id).collect(),
+        ResolveReportResult::NotFound => return ReportNotFound,
+        ResolveReportResult::AlreadyResolved => return AlreadyResolved,
+    };
+
+    // Posts and comments are dealt with by the canisters holding them, which warn their authors too.
+    match (report.target, args.action) {
+        (_, ModerationAction::Dismiss) => {},
+        (ReportTarget::User { noble_id }, _) => state.warn_user(noble_id, report.target, report.reason),
+        (ReportTarget::Post { post_id }, action) => push_content_moderated(post_id, None, action, report.reason, state),
+        (ReportTarget::Comment { post_id, comment_id }, action) => push_content_moderated(post_id, Some(comment_id), action, report.reason, state),
+    }
+
+    info!(moderator_id, report_id = args.report_id, action = ?args.action, target = ?report.target, "Report resolved");
+    state.data.moderation_log.push(moderator_id, ModerationEvent::ReportResolved {
+        report_ids: report_ids.clone(),
+        target: report.target,
+        action: args.action,
+        note: args.note,
+    }, now);
+
+    Success(report_ids)
+}
+
+fn push_content_moderated(post_id: PostId, comment_id: Option<CommentId>, action: ModerationAction, reason: ReportReason, state: &mut RuntimeState) {
+    state.push_event_to_post_index(PostIndexEvent::ContentModerated(Box::new(ContentModerated {
+        post_id,
+        comment_id,
+        action,
+        reason,
+    })));
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::user::User;
+    use crate::Data;
+    use types::{Role, RoleMember, JwtSigningKey, JWT};
+    use utils::env::test::TestEnv;
+
+    #[test]
+    fn only_moderators_resolve_reports() {
+        let mut state = setup_runtime_state();
+        let now = state.env.now();
+        state.data.reports.add(ReportTarget::Post { post_id: 5 }, 2, ReportReason::Spam, String::new(), now);
+        state.data.reports.add(ReportTarget::Post { post_id: 5 }, 3, ReportReason::Plagiarism, String::new(), now);
+
+        assert!(matches!(resolve_report_impl(args(2, 1, ModerationAction::Remove), &mut state), PermissionDenied));
+
+        match resolve_report_impl(args(1, 1, ModerationAction::Remove), &mut state) {
+            Success(report_ids) => assert_eq!(report_ids, vec![1, 2]),
+            response => panic!("{response:?}"),
+        }
+        assert!(matches!(resolve_report_impl(args(1, 2, ModerationAction::Dismiss), &mut state), AlreadyResolved));
+        assert_eq!(state.data.post_index_event_sync_queue.len(), 1);
+
+        let log = state.data.moderation_log.get(None, 10);
+        assert_eq!(log.len(), 1);
+        assert_eq!(log[0].moderator_id, 1);
+    }
+
+    #[test]
+    fn users_can_only_be_warned() {
+        let mut state = setup_runtime_state();
+        let now = state.env.now();
+        state.data.reports.add(ReportTarget::User { noble_id: 3 }, 2, ReportReason::Harassment, String::new(), now);
+
+        assert!(matches!(resolve_report_impl(args(1, 1, ModerationAction::Hide), &mut state), InvalidAction));
+        assert!(matches!(resolve_report_impl(args(1, 1, ModerationAction::Warn), &mut state), Success(_)));
+        assert_eq!(state.data.post_index_event_sync_queue.len(), 0);
+    }
+
+    fn args(noble_id: u64, report_id: u64, action: ModerationAction) -> Args {
+        Args {
+            jwt: JWT::new_for_test(noble_id, TestEnv::default().now).sign(&JwtSigningKey::for_test()).unwrap(),
+            report_id,
+            action,
+            note: "checked".to_string(),
+        }
+    }
+
+    fn setup_runtime_state() -> RuntimeState {
+        let mut data = Data::default();
+        for noble_id in 1..=3 {
+            data.users.add_test_user(User { noble_id, ..Default::default() });
+        }
+        data.roles.add(RoleMember::User(1), Role::Moderator, 0);
+        RuntimeState::new(Box::new(TestEnv::default()), data)
+    }
+}
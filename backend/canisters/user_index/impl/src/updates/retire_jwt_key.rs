@@ -0,0 +1,48 @@
+use crate::model::jwt_key_ring::RetireKeyError;
+use crate::{mutate_state, RuntimeState};
+use canister_api_macros::proposal;
+use tracing::info;
+use user_index_canister::retire_jwt_key::{Response::*, *};
+
+#[proposal(permission = "ManageSettings")]
+fn retire_jwt_key(args: Args) -> Response {
+    mutate_state(|state| retire_jwt_key_impl(args, state))
+}
+
+fn retire_jwt_key_impl(args: Args, state: &mut RuntimeState) -> Response {
+    match state.data.jwt_keys.retire(args.kid) {
+        Ok(()) => {
+            state.on_jwt_keys_changed();
+            info!(kid = args.kid, "JWT signing key retired");
+            Success
+        }
+        Err(RetireKeyError::KeyNotFound) => KeyNotFound,
+        Err(RetireKeyError::KeyInUse) => KeyInUse,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Data;
+    use types::{check_jwt, JWT};
+    use utils::env::test::TestEnv;
+
+    #[test]
+    fn tokens_signed_with_retired_key_are_rejected() {
+        let mut state = RuntimeState::new(Box::new(TestEnv::default()), Data::default());
+        let now = state.env.now();
+        let old_kid = state.data.jwt_keys.signing_key().unwrap().kid;
+        let token = JWT::new_for_test(1, now).sign(state.data.jwt_keys.signing_key().unwrap()).unwrap();
+
+        state.data.jwt_keys.rotate([9; 32]);
+        state.on_jwt_keys_changed();
+        assert!(check_jwt(&token, now).is_some());
+
+        if let Success = retire_jwt_key_impl(Args { kid: old_kid }, &mut state) {
+            assert!(check_jwt(&token, now).is_none());
+        } else {
+            assert!(false);
+        }
+    }
+}
@@ -0,0 +1,20 @@
+use crate::{mutate_state, RuntimeState};
+use canister_api_macros::proposal;
+use tracing::info;
+use user_index_canister::rotate_jwt_key::{Response::*, *};
+use utils::canister::get_random_seed;
+
+// The previous key keeps validating existing sessions until it is retired with `retire_jwt_key`.
+#[proposal(permission = "ManageSettings")]
+async fn rotate_jwt_key(_args: Args) -> Response {
+    let seed = get_random_seed().await;
+
+    mutate_state(|state| rotate_jwt_key_impl(seed, state))
+}
+
+fn rotate_jwt_key_impl(seed: [u8; 32], state: &mut RuntimeState) -> Response {
+    let kid = state.data.jwt_keys.rotate(seed);
+    state.on_jwt_keys_changed();
+    info!(kid, "JWT signing key rotated");
+    Success(kid)
+}
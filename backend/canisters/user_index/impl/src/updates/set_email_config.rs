@@ -0,0 +1,91 @@
+use crate::model::email_config::EmailConfig;
+use crate::{mutate_state, RuntimeState};
+use canister_api_macros::proposal;
+use email_address::EmailAddress;
+use tracing::info;
+use user_index_canister::set_email_config::{Response::*, *};
+
+#[proposal(permission = "ManageSettings")]
+fn set_email_config(args: Args) -> Response {
+    mutate_state(|state| set_email_config_impl(args, state))
+}
+
+fn set_email_config_impl(args: Args, state: &mut RuntimeState) -> Response {
+    if args.host.is_empty() || args.domain.is_empty() {
+        return InvalidConfig(format!("Host and domain are required."));
+    }
+    if !EmailAddress::is_valid(&args.from) {
+        return InvalidConfig(format!("From address is invalid."));
+    }
+    if args.reply_to.as_ref().map_or(false, |r| !EmailAddress::is_valid(r)) {
+        return InvalidConfig(format!("Reply-to address is invalid."));
+    }
+
+    let api_key_rotated = args.api_key.is_some();
+    let current = state.data.email_config.take();
+    let provider = args.provider.or(current.as_ref().map(|c| c.provider)).unwrap_or_default();
+
+    let config = match (args.api_key, current) {
+        (Some(api_key), _) => EmailConfig::new(provider, args.host, args.domain, api_key, args.from, args.reply_to),
+        (None, Some(mut config)) => {
+            config.provider = provider;
+            config.host = args.host;
+            config.domain = args.domain;
+            config.from = args.from;
+            config.reply_to = args.reply_to;
+            config
+        }
+        (None, None) => return ApiKeyRequired,
+    };
+
+    info!(?config, api_key_rotated, "Email provider config updated");
+    state.data.email_config = Some(config);
+    // Emails queued while there was no config are sent now.
+    #[cfg(not(test))]
+    crate::jobs::sync_events_to_send_email::start_job_if_required(state);
+    Success
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Data;
+    use utils::env::test::TestEnv;
+
+    #[test]
+    fn api_key_is_kept_when_not_given() {
+        let mut state = RuntimeState::new(Box::new(TestEnv::default()), Data::default());
+
+        assert!(matches!(set_email_config_impl(args(None), &mut state), ApiKeyRequired));
+        assert!(matches!(set_email_config_impl(args(Some("key-1")), &mut state), Success));
+
+        let mut update = args(None);
+        update.from = "hello@nobleblocks.com".to_string();
+        assert!(matches!(set_email_config_impl(update, &mut state), Success));
+
+        let config = state.data.email_config.as_ref().unwrap();
+        assert_eq!(config.from, "hello@nobleblocks.com");
+        assert_eq!(config.api_key(), "key-1");
+    }
+
+    #[test]
+    fn invalid_from_address_is_rejected() {
+        let mut state = RuntimeState::new(Box::new(TestEnv::default()), Data::default());
+        let mut args = args(Some("key-1"));
+        args.from = "not an email".to_string();
+
+        assert!(matches!(set_email_config_impl(args, &mut state), InvalidConfig(_)));
+        assert!(state.data.email_config.is_none());
+    }
+
+    fn args(api_key: Option<&str>) -> Args {
+        Args {
+            provider: None,
+            host: "api.mailgun.net".to_string(),
+            domain: "nobleblocks.com".to_string(),
+            api_key: api_key.map(|k| k.to_string()),
+            from: "info@nobleblocks.com".to_string(),
+            reply_to: None,
+        }
+    }
+}
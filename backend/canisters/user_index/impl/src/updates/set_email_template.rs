@@ -0,0 +1,27 @@
+use crate::{mutate_state, RuntimeState};
+use canister_api_macros::proposal;
+use tracing::info;
+use user_index_canister::set_email_template::{Response::*, *};
+
+#[proposal(permission = "ManageSettings")]
+fn set_email_template(args: Args) -> Response {
+    mutate_state(|state| set_email_template_impl(args, state))
+}
+
+fn set_email_template_impl(args: Args, state: &mut RuntimeState) -> Response {
+    match args.template {
+        Some(template) => match state.data.email_templates.set(args.name, &args.locale, template) {
+            Ok(()) => {
+                info!(name = ?args.name, locale = %args.locale, "Email template updated");
+                Success
+            }
+            Err(error) => InvalidTemplate(error),
+        },
+        None => {
+            if state.data.email_templates.remove(args.name, &args.locale) {
+                info!(name = ?args.name, locale = %args.locale, "Email template removed");
+            }
+            Success
+        }
+    }
+}
@@ -0,0 +1,15 @@
+use crate::{mutate_state, RuntimeState};
+use canister_api_macros::proposal;
+use tracing::info;
+use user_index_canister::set_google_client_ids::{Response::*, *};
+
+#[proposal(permission = "ManageSettings")]
+fn set_google_client_ids(args: Args) -> Response {
+    mutate_state(|state| set_google_client_ids_impl(args, state))
+}
+
+fn set_google_client_ids_impl(args: Args, state: &mut RuntimeState) -> Response {
+    state.data.google_client_ids = args.client_ids.into_iter().collect();
+    info!(count = state.data.google_client_ids.len(), "Google client ids updated");
+    Success
+}
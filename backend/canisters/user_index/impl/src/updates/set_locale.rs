@@ -0,0 +1,58 @@
+use crate::model::email_templates::normalize_locale;
+use crate::{mutate_state, RuntimeState};
+use ic_cdk_macros::update;
+use types::check_jwt;
+use user_index_canister::set_locale::{Response::*, *};
+
+#[update]
+fn set_locale(args: Args) -> Response {
+    mutate_state(|state| set_locale_impl(args, state))
+}
+
+fn set_locale_impl(args: Args, state: &mut RuntimeState) -> Response {
+    if let Some(jwt) = check_jwt(&args.jwt, state.env.now()) {
+        let locale = match args.locale.as_deref().map(normalize_locale) {
+            Some(Some(locale)) => Some(locale),
+            Some(None) => return InvalidLocale,
+            None => None,
+        };
+
+        match state.data.users.get(jwt.noble_id) {
+            Some(mut user) => {
+                user.locale = locale;
+                state.data.users.update(&user);
+                Success
+            }
+            None => UserNotFound,
+        }
+    } else {
+        PermissionDenied
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::user::User;
+    use crate::Data;
+    use utils::env::test::TestEnv;
+
+    #[test]
+    fn locale_is_normalized() {
+        let mut data = Data::default();
+        data.users.add_test_user(User {
+            noble_id: 1,
+            ..Default::default()
+        });
+        let mut state = RuntimeState::new(Box::new(TestEnv::default()), data);
+        types::set_jwt_verification_keys(state.data.jwt_keys.verification_keys());
+        let jwt = state.start_session(1).unwrap().jwt;
+
+        let response = set_locale_impl(Args { jwt: jwt.clone(), locale: Some("pt_BR".to_string()) }, &mut state);
+        assert_eq!(response, Success);
+        assert_eq!(state.data.users.get(1).unwrap().locale.as_deref(), Some("pt-br"));
+
+        let response = set_locale_impl(Args { jwt, locale: Some("<script>".to_string()) }, &mut state);
+        assert_eq!(response, InvalidLocale);
+    }
+}
@@ -1,7 +1,8 @@
-use crate::{mutate_state, RuntimeState};
+use crate::{mutate_state, RuntimeState, INFO_EMAIL};
 use ic_cdk_macros::update;
 use user_index_canister::set_password::{Response::*, *};
-use types::{check_jwt, NobleId};
+use user_index_canister::{EmailEvent, PasswordChanged};
+use types::{check_jwt, NobleId, SessionRevocation};
 use argon2::Config;
 use rand::Rng;
 
@@ -14,11 +15,25 @@ fn set_password_impl(args: Args, state: &mut RuntimeState) -> Response {
     if let Some(jwt) = check_jwt(&args.jwt, state.env.now()) {
         match prepare(jwt.noble_id, &args, state) {
             Ok(hash) => {
-                if let Some(user) = state.data.users.get_mut(jwt.noble_id) {
-                    user.password = hash;
-                    Success
-                } else {
-                    UserNotFound
+                let (generation, email, name, locale) = match state.data.users.get(jwt.noble_id) {
+                    Some(mut user) => {
+                        user.password = hash;
+                        let generation = user.end_all_sessions();
+                        state.data.users.update(&user);
+                        (generation, user.email, user.username, user.locale)
+                    },
+                    None => return UserNotFound,
+                };
+
+                // Log out every other device, the caller continues with a fresh session.
+                state.revoke_sessions(SessionRevocation::AllSessions { noble_id: jwt.noble_id, generation });
+                if !email.is_empty() {
+                    state.push_event_to_send_email(INFO_EMAIL, EmailEvent::PasswordChanged(Box::new(PasswordChanged { email, name, locale })));
+                }
+
+                match state.start_session(jwt.noble_id) {
+                    Ok(ok) => Success(ok),
+                    Err(error) => InternalError(error),
                 }
             },
             Err(error) => return error,
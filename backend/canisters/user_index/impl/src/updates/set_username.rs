@@ -17,24 +17,19 @@ fn set_username_impl(args: &Args, state: &mut RuntimeState) -> Response {
             Err(response) => return response,
         };
 
-        if let Some(user) = state.data.users.get_mut(jwt.noble_id) {
+        if let Some(mut user) = state.data.users.get(jwt.noble_id) {
             let noble_id = jwt.noble_id;
 
-            let prev_username = user.username.clone();
-            if prev_username != args.username {
+            if user.username != args.username {
                 user.username = args.username.clone();
-            }
-            if prev_username.to_uppercase() != args.username.to_uppercase() {
-                state.data.users.update_username(prev_username, args.username.clone(), noble_id);
+                state.data.users.update(&user);
             }
 
             state.push_event_to_local_user_index(noble_id, LocalUserIndexEvent::UsernameChanged(Box::new(
                 UsernameChanged{noble_id, username: args.username.clone()}
             )));
 
-            let user = state.data.users.get(jwt.noble_id).unwrap();
-
-            match user.get_login_info(state.env.now()) {
+            match state.start_session(noble_id) {
                 Ok(ok) => return Success(ok),
                 Err(error) => return InternalError(error),
             }
@@ -0,0 +1,98 @@
+use crate::model::suspensions::MAX_SUSPENSION_REASON_LENGTH;
+use crate::{mutate_state, RuntimeState};
+use ic_cdk_macros::update;
+use tracing::info;
+use types::{Suspension, SuspensionDetails};
+use user_index_canister::get_moderation_log::ModerationEvent;
+use user_index_canister::suspend_user::{Response::*, *};
+
+#[update]
+fn suspend_user(args: Args) -> Response {
+    mutate_state(|state| suspend_user_impl(args, state))
+}
+
+fn suspend_user_impl(args: Args, state: &mut RuntimeState) -> Response {
+    let now = state.env.now();
+    let moderator_id = match state.moderator_from_jwt(&args.jwt) {
+        Some(moderator_id) => moderator_id,
+        None => return PermissionDenied,
+    };
+    if !state.data.users.contains(args.noble_id) {
+        return UserNotFound;
+    }
+    if state.is_platform_moderator(args.noble_id) {
+        return CannotSuspendModerator;
+    }
+    if args.until <= now {
+        return InvalidUntil;
+    }
+    if args.reason.len() > MAX_SUSPENSION_REASON_LENGTH {
+        return ReasonTooLong(MAX_SUSPENSION_REASON_LENGTH as u32);
+    }
+
+    let suspension = Suspension {
+        until: Some(args.until),
+        hide_content: args.hide_content,
+    };
+    state.suspend_user(SuspensionDetails {
+        noble_id: args.noble_id,
+        suspension,
+        reason: args.reason.clone(),
+        suspended_by: moderator_id,
+        date_suspended: now,
+        appeal: None,
+    });
+
+    info!(moderator_id, noble_id = args.noble_id, until = args.until, "User suspended");
+    state.data.moderation_log.push(moderator_id, ModerationEvent::UserSuspended {
+        noble_id: args.noble_id,
+        until: suspension.until,
+        reason: args.reason,
+        hide_content: args.hide_content,
+    }, now);
+
+    Success
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::model::user::User;
+    use crate::Data;
+    use types::{Role, RoleMember, JwtSigningKey, JWT};
+    use utils::env::test::TestEnv;
+
+    #[test]
+    fn only_regular_users_can_be_suspended() {
+        let mut state = setup_runtime_state();
+        let now = state.env.now();
+
+        assert!(matches!(suspend_user_impl(args(1, 2, now), &mut state), InvalidUntil));
+        assert!(matches!(suspend_user_impl(args(1, 1, now + 1_000), &mut state), CannotSuspendModerator));
+        assert!(matches!(suspend_user_impl(args(3, 2, now + 1_000), &mut state), PermissionDenied));
+
+        assert!(matches!(suspend_user_impl(args(1, 2, now + 1_000), &mut state), Success));
+        assert_eq!(state.data.post_index_event_sync_queue.len(), 1);
+        assert_eq!(state.data.suspensions.get(2, now).unwrap().suspension.until, Some(now + 1_000));
+        assert_eq!(state.data.moderation_log.len(), 1);
+    }
+
+    fn args(moderator_id: u64, noble_id: u64, until: u64) -> Args {
+        Args {
+            jwt: JWT::new_for_test(moderator_id, TestEnv::default().now).sign(&JwtSigningKey::for_test()).unwrap(),
+            noble_id,
+            until,
+            reason: "spam".to_string(),
+            hide_content: true,
+        }
+    }
+
+    fn setup_runtime_state() -> RuntimeState {
+        let mut data = Data::default();
+        for noble_id in 1..=3 {
+            data.users.add_test_user(User { noble_id, ..Default::default() });
+        }
+        data.roles.add(RoleMember::User(1), Role::Moderator, 0);
+        RuntimeState::new(Box::new(TestEnv::default()), data)
+    }
+}
@@ -1,12 +1,14 @@
-use crate::{mutate_state, RuntimeState, model::temp::{TempData, TempDataType}, read_state, INFO_EMAIL};
+use crate::{mutate_state, RuntimeState, model::{temp::{TempData, TempDataType}, temp_map::{MAX_PASSKEY_ATTEMPTS, PASSWORD_RESET_DURATION}}, read_state};
 use argon2::Config;
 use candid::Principal;
 use ic_cdk::api::management_canister::provisional::CanisterId;
 use ic_cdk_macros::update;
 use rand::Rng;
 use types::NobleId;
-use user_index_canister::{verify_code::{Response::*, *}, ResetPassword};
+use user_index_canister::verify_code::{Response::*, *};
 use user_index_canister::register_user::Args as RegisterUserArgs;
+use crate::model::email_templates::normalize_locale;
+use local_user_index_canister::Event as LocalUserIndexEvent;
 
 #[update]
 async fn verify_code(args: Args) -> Response {
@@ -14,6 +16,7 @@ async fn verify_code(args: Args) -> Response {
         Some(_type) => {
             match _type {
                 TempDataType::ResetPassword => mutate_state(|state| reset_password(&args, state)),
+                TempDataType::ChangeEmail => mutate_state(|state| change_email(&args, state)),
                 TempDataType::RegisterUser => register_user(args).await,
             }
         },
@@ -27,58 +30,104 @@ fn get_type(args: &Args, state: &RuntimeState) -> Option<TempDataType> {
             match temp.temp_data {
                 TempData::RegisterUser(_) => Some(TempDataType::RegisterUser),
                 TempData::ResetPassword(_) => Some(TempDataType::ResetPassword),
+                TempData::ChangeEmail(_) => Some(TempDataType::ChangeEmail),
             }
         },
         None => None,
     }
 }
 
+// Only proves ownership of the email, the password is chosen in `complete_password_reset`.
 fn reset_password(args: &Args, state: &mut RuntimeState) -> Response {
     let now = state.env.now();
     state.data.temps.remove_expired_temp(now);
 
-    if let Some(temp) = state.data.temps.get_mut(args.id) {
-        if temp.is_used {
-            return InvalidPasskey;
-        }
-        temp.is_used = true;
-        if temp.passkey == args.passkey {
-            match &temp.temp_data {
-                TempData::ResetPassword(data) => {
-                    if let Some(user) = state.data.users.get_mut_by_email(&temp.email) {
-                        let salt: [u8; 32] = state.env.rng().gen();
-                        let config = Config::default();
-                        let password = data.password.clone();
-
-                        let password_hash = match argon2::hash_encoded(data.password.as_bytes(), &salt, &config) {
-                            Ok(password) => password,
-                            Err(_) => return InternalError(format!("Unexpected error.")),
-                        };
-
-                        user.password = password_hash;
-
-                        match user.get_login_info(now) {
-                            Ok(ok) => {
-                                let email = user.email.clone();
-                                let name = user.username.clone();
-                                state.data.temps.remove(args.id);
-                                state.push_event_to_send_email(INFO_EMAIL, user_index_canister::EmailEvent::ResetPassword(Box::new(ResetPassword{
-                                    email,
-                                    name,
-                                    password,
-                                })));
-        
-                                return Success(ok);
-                            },
-                            Err(error) => return InternalError(error),
-                        }
-                    }
-                },
-                _ => return InternalError(format!("Unexpected error.")),
-            }
+    let noble_id = state.data.temps.get(args.id)
+        .and_then(|temp| state.data.users.get_by_email(&temp.email))
+        .map(|user| user.noble_id);
+
+    if let Err(response) = check_passkey(args, noble_id, state) {
+        return response;
+    }
+
+    let secret: [u8; 32] = state.env.rng().gen();
+    match state.data.temps.get_mut(args.id) {
+        Some(temp) => match &mut temp.temp_data {
+            TempData::ResetPassword(data) => {
+                let reset_token = data.issue_reset_token(secret);
+                temp.expired_time = now + PASSWORD_RESET_DURATION;
+                ResetPasswordVerified(ResetPasswordVerifiedResult { reset_token })
+            },
+            _ => InternalError(format!("Unexpected error.")),
+        },
+        None => TempNotExist,
+    }
+}
+
+fn change_email(args: &Args, state: &mut RuntimeState) -> Response {
+    state.data.temps.remove_expired_temp(state.env.now());
+
+    let (email, data) = match state.data.temps.get(args.id) {
+        Some(temp) => match &temp.temp_data {
+            TempData::ChangeEmail(data) => (temp.email.clone(), data.clone()),
+            _ => return InternalError(format!("Unexpected error.")),
+        },
+        None => return TempNotExist,
+    };
+
+    if let Err(response) = check_passkey(args, Some(data.noble_id), state) {
+        return response;
+    }
+    state.data.temps.remove(args.id);
+
+    match state.data.users.get_by_email(&email) {
+        Some(user) if user.noble_id != data.noble_id => return EmailTaken,
+        _ => {},
+    }
+    match state.data.users.get(data.noble_id) {
+        Some(mut user) if user.email == data.previous_email => {
+            user.email = email.clone();
+            state.data.users.update(&user);
+        },
+        _ => return TempNotExist,
+    }
+
+    state.push_event_to_local_user_index(data.noble_id, LocalUserIndexEvent::EmailChanged(Box::new(
+        local_user_index_canister::EmailChanged { noble_id: data.noble_id, email }
+    )));
+    EmailChanged
+}
+
+// Marks the temp as used if the passkey matches. Wrong guesses count against the caller and, for
+// an existing account, the account, and the temp is dropped after MAX_PASSKEY_ATTEMPTS of them.
+fn check_passkey(args: &Args, noble_id: Option<NobleId>, state: &mut RuntimeState) -> Result<(), Response> {
+    let now = state.env.now();
+    let caller = state.env.caller();
+
+    if let Some(retry_after) = state.data.login_attempts.retry_after(noble_id, caller, now) {
+        return Err(LoginLocked { retry_after });
+    }
+
+    let temp = match state.data.temps.get_mut(args.id) {
+        Some(temp) => temp,
+        None => return Err(TempNotExist),
+    };
+    if temp.is_used {
+        return Err(InvalidPasskey);
+    }
+
+    if temp.passkey != args.passkey {
+        temp.failed_attempts += 1;
+        if temp.failed_attempts >= MAX_PASSKEY_ATTEMPTS {
+            state.data.temps.remove(args.id);
         }
+        state.data.login_attempts.record_failure(noble_id, caller, now);
+        return Err(InvalidPasskey);
     }
-    InvalidPasskey
+
+    temp.is_used = true;
+    state.data.login_attempts.record_success(noble_id, caller);
+    Ok(())
 }
 
 async fn register_user(args: Args) -> Response {
@@ -111,17 +160,17 @@ async fn register_user(args: Args) -> Response {
                     state.env.now()
                 );
                 state.data.local_index_map.add_user(canister_id, noble_id);
+                if let Some(mut user) = state.data.users.get(noble_id) {
+                    user.locale = register_user_args.locale.as_deref().and_then(normalize_locale);
+                    state.data.users.update(&user);
+                }
 
-                if let Some(user) = state.data.users.get(noble_id) {
-                    match user.get_login_info(state.env.now()) {
-                        Ok(ok) => {
-                            state.data.temps.remove(args.id);
-                            Success(ok)
-                        },
-                        Err(error) => InternalError(error),
-                    }
-                } else {
-                    InternalError(format!("Unexpected error."))
+                match state.start_session(noble_id) {
+                    Ok(ok) => {
+                        state.data.temps.remove(args.id);
+                        Success(ok)
+                    },
+                    Err(error) => InternalError(error),
                 }
             })
         },
@@ -142,42 +191,35 @@ struct RegisterOk {
 fn register_user_impl(args: &Args, state: &mut RuntimeState) -> Result<RegisterOk, Response> {
     state.data.temps.remove_expired_temp(state.env.now());
 
-    if let Some(temp) = state.data.temps.get_mut(args.id) {
-        if temp.is_used {
-            return Err(InvalidPasskey);
-        }
-        temp.is_used = true;
-        if temp.passkey == args.passkey {
-            let caller = state.env.caller();
-
-            let canister_id = match state.data.local_index_map.index_for_new_user() {
-                Some(index) => index,
-                None => return Err(InternalError(format!("User limit reached."))),
-            };
-            let register_user_args = match &temp.temp_data {
-                TempData::RegisterUser(args) => args.clone(),
-                _ => return Err(InternalError(format!("Unexpected error."))),
-            };
-
-            let salt: [u8; 32] = state.env.rng().gen();
-            let config = Config::default();
-            let password_hash = match argon2::hash_encoded(register_user_args.password.as_bytes(), &salt, &config) {
-                Ok(password) => password,
-                Err(_) => return Err(InternalError(format!("Password hash error."))),
-            };
-        
-            let noble_id = state.data.users.new_noble_id(state.env.rng());
-
-            return Ok(RegisterOk{
-                principal: caller,
-                canister_id,
-                noble_id,
-                password_hash,
-                register_user_args,
-            })
-        }
-    }
-    Err(TempNotExist)
+    check_passkey(args, None, state)?;
+
+    let caller = state.env.caller();
+
+    let canister_id = match state.data.local_index_map.index_for_new_user() {
+        Some(index) => index,
+        None => return Err(InternalError(format!("User limit reached."))),
+    };
+    let register_user_args = match state.data.temps.get(args.id).map(|temp| &temp.temp_data) {
+        Some(TempData::RegisterUser(args)) => args.clone(),
+        _ => return Err(InternalError(format!("Unexpected error."))),
+    };
+
+    let salt: [u8; 32] = state.env.rng().gen();
+    let config = Config::default();
+    let password_hash = match argon2::hash_encoded(register_user_args.password.as_bytes(), &salt, &config) {
+        Ok(password) => password,
+        Err(_) => return Err(InternalError(format!("Password hash error."))),
+    };
+
+    let noble_id = state.data.users.new_noble_id(state.env.rng());
+
+    Ok(RegisterOk{
+        principal: caller,
+        canister_id,
+        noble_id,
+        password_hash,
+        register_user_args,
+    })
 }
 
 async fn commit_local_index(
@@ -206,3 +248,98 @@ async fn commit_local_index(
         Err(error) => Err(format!("{error:?}")),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::Data;
+    use crate::model::temp::{ChangeEmail as ChangeEmailData, ResetPassword as ResetPasswordData};
+    use crate::model::user::User;
+    use utils::env::test::TestEnv;
+
+    #[test]
+    fn passkey_is_invalidated_after_too_many_wrong_guesses() {
+        let mut state = setup_runtime_state();
+        let (temp_id, passkey) = add_reset_password_temp(&mut state);
+
+        for _ in 0..MAX_PASSKEY_ATTEMPTS {
+            let response = reset_password(&Args { id: temp_id, passkey: "wrong".to_string() }, &mut state);
+            assert!(matches!(response, InvalidPasskey));
+            advance_time(&mut state, 10 * 1000);
+        }
+
+        let response = reset_password(&Args { id: temp_id, passkey }, &mut state);
+        assert!(matches!(response, TempNotExist));
+    }
+
+    #[test]
+    fn correct_passkey_issues_reset_token() {
+        let mut state = setup_runtime_state();
+        let (temp_id, passkey) = add_reset_password_temp(&mut state);
+
+        let response = reset_password(&Args { id: temp_id, passkey: "wrong".to_string() }, &mut state);
+        assert!(matches!(response, InvalidPasskey));
+
+        let response = reset_password(&Args { id: temp_id, passkey: passkey.clone() }, &mut state);
+        let reset_token = match response {
+            ResetPasswordVerified(result) => result.reset_token,
+            _ => panic!(),
+        };
+
+        match &state.data.temps.get(temp_id).unwrap().temp_data {
+            TempData::ResetPassword(data) => assert!(data.is_reset_token_valid(&reset_token)),
+            _ => panic!(),
+        }
+        // The passkey can't be used twice.
+        assert!(matches!(reset_password(&Args { id: temp_id, passkey }, &mut state), InvalidPasskey));
+    }
+
+    #[test]
+    fn email_is_changed_once_passkey_is_verified() {
+        let mut state = setup_runtime_state();
+        let now = state.env.now();
+        let (temp_id, passkey) = state.data.temps.add_new_temp(
+            "new@gmail.com".to_string(),
+            TempData::ChangeEmail(ChangeEmailData { noble_id: 1, previous_email: "test@gmail.com".to_string() }),
+            state.env.rng(),
+            now,
+        );
+
+        let response = change_email(&Args { id: temp_id, passkey: "wrong".to_string() }, &mut state);
+        assert!(matches!(response, InvalidPasskey));
+        assert_eq!(state.data.users.get(1).unwrap().email, "test@gmail.com");
+
+        let response = change_email(&Args { id: temp_id, passkey }, &mut state);
+        assert!(matches!(response, EmailChanged));
+        assert_eq!(state.data.users.get_by_email("new@gmail.com").unwrap().noble_id, 1);
+        assert!(state.data.users.get_by_email("test@gmail.com").is_none());
+        assert!(state.data.temps.get(temp_id).is_none());
+    }
+
+    fn add_reset_password_temp(state: &mut RuntimeState) -> (u32, String) {
+        let now = state.env.now();
+        state.data.temps.add_new_temp(
+            "test@gmail.com".to_string(),
+            TempData::ResetPassword(ResetPasswordData::new("testuser".to_string())),
+            state.env.rng(),
+            now,
+        )
+    }
+
+    fn advance_time(state: &mut RuntimeState, millis: u64) {
+        let now = state.env.now();
+        state.env = Box::new(TestEnv { now: now + millis, ..Default::default() });
+    }
+
+    fn setup_runtime_state() -> RuntimeState {
+        let env = TestEnv::default();
+        let mut data = Data::default();
+        data.users.add_test_user(User {
+            noble_id: 1,
+            email: "test@gmail.com".to_string(),
+            username: "testuser".to_string(),
+            ..Default::default()
+        });
+        RuntimeState::new(Box::new(env), data)
+    }
+}
@@ -30,16 +30,27 @@ fn verify_code_resend_impl(args: &Args, state: &mut RuntimeState) -> Response {
         match &temp.temp_data {
             TempData::RegisterUser(data) => {
                 let name = data.username.clone();
+                let locale = data.locale.clone();
                 state.push_event_to_send_email(
                     INFO_EMAIL,
-                    EmailEvent::RegisterUser(Box::new(user_index_canister::RegisterUser { email, name, passkey }))
+                    EmailEvent::RegisterUser(Box::new(user_index_canister::RegisterUser { email, name, passkey, locale }))
                 );
             },
             TempData::ResetPassword(data) => {
                 let name = data.name.clone();
+                let locale = state.data.users.get_by_email(&email).and_then(|user| user.locale.clone());
                 state.push_event_to_send_email(
                     INFO_EMAIL,
-                    EmailEvent::ResetPasswordVerify(Box::new(user_index_canister::ResetPasswordVerify { email, name, passkey }))
+                    EmailEvent::ResetPasswordVerify(Box::new(user_index_canister::ResetPasswordVerify { email, name, passkey, locale }))
+                );
+            }
+            TempData::ChangeEmail(data) => {
+                let (name, locale) = state.data.users.get(data.noble_id)
+                    .map(|user| (user.username.clone(), user.locale.clone()))
+                    .unwrap_or_default();
+                state.push_event_to_send_email(
+                    INFO_EMAIL,
+                    EmailEvent::ChangeEmailVerify(Box::new(user_index_canister::ChangeEmailVerify { email, name, passkey, locale }))
                 );
             }
         };
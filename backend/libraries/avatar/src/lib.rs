@@ -0,0 +1,227 @@
+use exif::{In, Tag};
+use image::imageops::FilterType;
+use image::io::{Limits, Reader};
+use image::{DynamicImage, ImageError, ImageFormat, ImageOutputFormat};
+use serde::{Deserialize, Serialize};
+use serde_bytes::ByteBuf;
+use std::io::Cursor;
+
+// Every avatar is kept at these sizes, in pixels, cropped to a square.
+pub const THUMBNAIL_SIZES: [u32; 3] = [64, 128, 256];
+
+// Anything wider or higher is refused before it's decoded, so a small file can't take up the
+// whole heap.
+pub const MAX_DIMENSION: u32 = 4_096;
+// What the decoder may allocate, room for the largest image with a few bytes per pixel to spare.
+const MAX_DECODED_SIZE: u64 = 128 * 1_024 * 1_024; // 128 MB
+
+const JPEG_QUALITY: u8 = 85;
+
+// Only the thumbnails are kept, re-encoded from the decoded pixels, so whatever metadata the
+// upload carried, eg. where a photo was taken, is gone.
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Avatar {
+    // Smallest first.
+    pub thumbnails: Vec<Thumbnail>,
+}
+
+#[derive(Serialize, Deserialize, Clone, Debug)]
+pub struct Thumbnail {
+    pub size: u32,
+    pub mime_type: String,
+    pub bytes: ByteBuf,
+}
+
+#[derive(Debug, PartialEq, Eq)]
+pub enum AvatarError {
+    UnsupportedFormat,
+    TooLarge,
+    Invalid,
+}
+
+impl Avatar {
+    // The smallest thumbnail at least as big as asked for, or the biggest there is.
+    pub fn thumbnail(&self, size: Option<u32>) -> Option<&Thumbnail> {
+        match size {
+            Some(size) => self.thumbnails.iter().find(|thumbnail| thumbnail.size >= size).or_else(|| self.thumbnails.last()),
+            None => self.thumbnails.last(),
+        }
+    }
+}
+
+pub fn process_avatar(bytes: &[u8]) -> Result<Avatar, AvatarError> {
+    let format = match image::guess_format(bytes) {
+        Ok(format @ (ImageFormat::Png | ImageFormat::Jpeg | ImageFormat::WebP)) => format,
+        _ => return Err(AvatarError::UnsupportedFormat),
+    };
+
+    let mut limits = Limits::default();
+    limits.max_image_width = Some(MAX_DIMENSION);
+    limits.max_image_height = Some(MAX_DIMENSION);
+    limits.max_alloc = Some(MAX_DECODED_SIZE);
+
+    let mut reader = Reader::with_format(Cursor::new(bytes), format);
+    reader.limits(limits);
+    let image = match reader.decode() {
+        Ok(image) => image,
+        Err(ImageError::Limits(_)) => return Err(AvatarError::TooLarge),
+        Err(_) => return Err(AvatarError::Invalid),
+    };
+
+    // The orientation is lost along with the rest of the metadata, so it's applied first.
+    let image = apply_orientation(image, orientation(bytes));
+
+    let side = image.width().min(image.height());
+    if side == 0 {
+        return Err(AvatarError::Invalid);
+    }
+    let square = image.crop_imm((image.width() - side) / 2, (image.height() - side) / 2, side, side);
+
+    // The smaller thumbnails are made from the largest, which is much cheaper than from the upload.
+    let largest = THUMBNAIL_SIZES[THUMBNAIL_SIZES.len() - 1];
+    let largest = if side > largest {
+        square.thumbnail_exact(largest, largest)
+    } else {
+        square.resize_exact(largest, largest, FilterType::Triangle)
+    };
+
+    let mut thumbnails = Vec::with_capacity(THUMBNAIL_SIZES.len());
+    for size in THUMBNAIL_SIZES {
+        let thumbnail = if size == largest.width() {
+            encode(&largest)
+        } else {
+            encode(&largest.resize_exact(size, size, FilterType::Triangle))
+        };
+        match thumbnail {
+            Some((mime_type, bytes)) => thumbnails.push(Thumbnail {
+                size,
+                mime_type: mime_type.to_string(),
+                bytes: ByteBuf::from(bytes),
+            }),
+            None => return Err(AvatarError::Invalid),
+        }
+    }
+
+    Ok(Avatar { thumbnails })
+}
+
+// PNG keeps transparency, anything opaque is smaller as a JPEG.
+fn encode(image: &DynamicImage) -> Option<(&'static str, Vec<u8>)> {
+    let mut bytes = Vec::new();
+    if image.color().has_alpha() {
+        DynamicImage::ImageRgba8(image.to_rgba8())
+            .write_to(&mut Cursor::new(&mut bytes), ImageOutputFormat::Png)
+            .ok()?;
+        Some(("image/png", bytes))
+    } else {
+        DynamicImage::ImageRgb8(image.to_rgb8())
+            .write_to(&mut Cursor::new(&mut bytes), ImageOutputFormat::Jpeg(JPEG_QUALITY))
+            .ok()?;
+        Some(("image/jpeg", bytes))
+    }
+}
+
+// The EXIF orientation, 1 (as stored) when there's none or it can't be read.
+fn orientation(bytes: &[u8]) -> u32 {
+    exif::Reader::new()
+        .read_from_container(&mut Cursor::new(bytes))
+        .ok()
+        .and_then(|exif| exif.get_field(Tag::Orientation, In::PRIMARY).and_then(|field| field.value.get_uint(0)))
+        .unwrap_or(1)
+}
+
+fn apply_orientation(image: DynamicImage, orientation: u32) -> DynamicImage {
+    match orientation {
+        2 => image.fliph(),
+        3 => image.rotate180(),
+        4 => image.flipv(),
+        5 => image.rotate90().fliph(),
+        6 => image.rotate90(),
+        7 => image.rotate270().fliph(),
+        8 => image.rotate270(),
+        _ => image,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{GenericImageView, Rgb, RgbImage, Rgba, RgbaImage};
+
+    #[test]
+    fn thumbnails_are_square() {
+        let upload = encode_test_image(DynamicImage::ImageRgb8(RgbImage::from_fn(600, 400, |x, y| Rgb([x as u8, y as u8, 0]))), ImageOutputFormat::Png);
+
+        let avatar = process_avatar(&upload).unwrap();
+
+        assert_eq!(avatar.thumbnails.iter().map(|thumbnail| thumbnail.size).collect::<Vec<_>>(), THUMBNAIL_SIZES);
+        for thumbnail in avatar.thumbnails.iter() {
+            assert_eq!(thumbnail.mime_type, "image/jpeg");
+            let decoded = image::load_from_memory(&thumbnail.bytes).unwrap();
+            assert_eq!(decoded.dimensions(), (thumbnail.size, thumbnail.size));
+        }
+        assert_eq!(avatar.thumbnail(Some(100)).unwrap().size, 128);
+        assert_eq!(avatar.thumbnail(Some(1_000)).unwrap().size, 256);
+        assert_eq!(avatar.thumbnail(None).unwrap().size, 256);
+    }
+
+    #[test]
+    fn transparency_is_kept() {
+        let upload = encode_test_image(DynamicImage::ImageRgba8(RgbaImage::from_pixel(40, 40, Rgba([0, 0, 0, 0]))), ImageOutputFormat::Png);
+
+        let avatar = process_avatar(&upload).unwrap();
+
+        assert!(avatar.thumbnails.iter().all(|thumbnail| thumbnail.mime_type == "image/png"));
+    }
+
+    #[test]
+    fn metadata_is_dropped() {
+        let jpeg = encode_test_image(DynamicImage::ImageRgb8(RgbImage::from_pixel(300, 300, Rgb([200, 10, 10]))), ImageOutputFormat::Jpeg(90));
+        // An APP1 segment right after the start of image marker.
+        let metadata = b"Exif\0\0GPS 51.5007 N 0.1246 W";
+        let mut upload = jpeg[..2].to_vec();
+        upload.extend_from_slice(&[0xFF, 0xE1]);
+        upload.extend_from_slice(&(metadata.len() as u16 + 2).to_be_bytes());
+        upload.extend_from_slice(metadata);
+        upload.extend_from_slice(&jpeg[2..]);
+
+        let avatar = process_avatar(&upload).unwrap();
+
+        for thumbnail in avatar.thumbnails.iter() {
+            assert!(!thumbnail.bytes.windows(3).any(|window| window == b"GPS"));
+        }
+    }
+
+    #[test]
+    fn orientation_is_applied() {
+        // Red on the left, blue on the right.
+        let image = DynamicImage::ImageRgb8(RgbImage::from_fn(2, 1, |x, _| if x == 0 { Rgb([255, 0, 0]) } else { Rgb([0, 0, 255]) }));
+
+        // Turned a quarter clockwise, red ends up on top.
+        let rotated = apply_orientation(image.clone(), 6);
+        assert_eq!(rotated.dimensions(), (1, 2));
+        assert_eq!(rotated.to_rgb8().get_pixel(0, 0), &Rgb([255, 0, 0]));
+
+        let transposed = apply_orientation(image.clone(), 5);
+        assert_eq!(transposed.to_rgb8().get_pixel(0, 0), &Rgb([255, 0, 0]));
+
+        let mirrored = apply_orientation(image, 2);
+        assert_eq!(mirrored.to_rgb8().get_pixel(0, 0), &Rgb([0, 0, 255]));
+    }
+
+    #[test]
+    fn only_images_are_accepted() {
+        assert_eq!(process_avatar(b"GIF89a\x01\x00\x01\x00").unwrap_err(), AvatarError::UnsupportedFormat);
+        assert_eq!(process_avatar(b"just some text").unwrap_err(), AvatarError::UnsupportedFormat);
+        assert_eq!(process_avatar(b"\x89PNG\r\n\x1a\n\x00\x00\x00\x0dIHDR").unwrap_err(), AvatarError::Invalid);
+
+        let too_wide = encode_test_image(DynamicImage::ImageRgb8(RgbImage::new(MAX_DIMENSION + 1, 1)), ImageOutputFormat::Png);
+        assert_eq!(process_avatar(&too_wide).unwrap_err(), AvatarError::TooLarge);
+    }
+
+    fn encode_test_image(image: DynamicImage, format: ImageOutputFormat) -> Vec<u8> {
+        let mut bytes = Vec::new();
+        image.write_to(&mut Cursor::new(&mut bytes), format).unwrap();
+        bytes
+    }
+}
@@ -1,5 +1,5 @@
 use proc_macro::TokenStream;
-use proc_macro2::Span;
+use proc_macro2::{Span, TokenStream as TokenStream2};
 use quote::{format_ident, quote};
 use serde::Deserialize;
 use serde_tokenstream::from_tokenstream;
@@ -25,6 +25,8 @@ impl std::fmt::Display for MethodType {
 struct AttributeInput {
     pub name: Option<String>,
     pub guard: Option<String>,
+    // A `utils::rbac::Permission` the caller must have, instead of a `guard`.
+    pub permission: Option<String>,
     #[serde(default)]
     pub manual_reply: bool,
 }
@@ -49,14 +51,64 @@ pub fn query_msgpack(attr: TokenStream, item: TokenStream) -> TokenStream {
     canister_api_method(MethodType::Query, attr, item, false)
 }
 
+// A candid query only callers with the given `permission` may call. Updates which need a permission
+// go through `proposal`.
+#[proc_macro_attribute]
+pub fn admin_query(attr: TokenStream, item: TokenStream) -> TokenStream {
+    admin_method(MethodType::Query, attr, item)
+}
+
+fn admin_method(method_type: MethodType, attr: TokenStream, item: TokenStream) -> TokenStream {
+    let attr: AttributeInput = from_tokenstream(&attr.into()).unwrap();
+    let item = parse_macro_input!(item as ItemFn);
+
+    if attr.permission.is_none() {
+        panic!("`permission` must be set");
+    }
+
+    let method_type = Ident::new(method_type.to_string().as_str(), Span::call_site());
+
+    let name = attr.name.clone().unwrap_or_else(|| item.sig.ident.to_string());
+    let (guard, guard_fn) = guard(&attr, &name);
+    let manual_reply = attr.manual_reply.then_some(quote! { manual_reply = "true", });
+
+    TokenStream::from(quote! {
+        #guard_fn
+
+        #[ic_cdk_macros::#method_type(name = #name, #guard #manual_reply)]
+        #item
+    })
+}
+
+// The `guard` argument for the method and, when a `permission` is asked for, the guard function
+// checking it, which calls the canister's `guards::caller_has_permission`.
+fn guard(attr: &AttributeInput, name: &str) -> (Option<TokenStream2>, TokenStream2) {
+    match (&attr.guard, &attr.permission) {
+        (Some(_), Some(_)) => panic!("Only one of `guard` and `permission` can be set"),
+        (Some(guard), None) => (Some(quote! { guard = #guard, }), quote! {}),
+        (None, Some(permission)) => {
+            let guard_name = format!("{name}_guard");
+            let guard_ident = Ident::new(&guard_name, Span::call_site());
+            let permission_ident = Ident::new(permission, Span::call_site());
+            let guard_fn = quote! {
+                fn #guard_ident() -> Result<(), String> {
+                    crate::guards::caller_has_permission(utils::rbac::Permission::#permission_ident)
+                }
+            };
+            (Some(quote! { guard = #guard_name, }), guard_fn)
+        }
+        (None, None) => (None, quote! {}),
+    }
+}
+
 fn canister_api_method(method_type: MethodType, attr: TokenStream, item: TokenStream, include_candid: bool) -> TokenStream {
     let attr: AttributeInput = from_tokenstream(&attr.into()).unwrap();
     let item = parse_macro_input!(item as ItemFn);
 
     let method_type = Ident::new(method_type.to_string().as_str(), Span::call_site());
 
-    let name = attr.name.unwrap_or_else(|| item.sig.ident.to_string());
-    let guard = attr.guard.map(|g| quote! { guard = #g, });
+    let name = attr.name.clone().unwrap_or_else(|| item.sig.ident.to_string());
+    let (guard, guard_fn) = guard(&attr, &name);
     let manual_reply = attr.manual_reply.then_some(quote! { manual_reply = "true", });
 
     let msgpack_name = format!("{name}_msgpack");
@@ -82,6 +134,8 @@ fn canister_api_method(method_type: MethodType, attr: TokenStream, item: TokenSt
         use msgpack::serialize_then_unwrap as #serializer_ident;
         use msgpack::deserialize_then_unwrap as #deserializer_ident;
 
+        #guard_fn
+
         #candid
         #msgpack
         #item
@@ -93,14 +147,16 @@ pub fn proposal(attr: TokenStream, item: TokenStream) -> TokenStream {
     let attr: AttributeInput = from_tokenstream(&attr.into()).unwrap();
     let original_fn = parse_macro_input!(item as ItemFn);
 
-    let name = attr.name.unwrap_or_else(|| original_fn.sig.ident.to_string());
+    let name = attr.name.clone().unwrap_or_else(|| original_fn.sig.ident.to_string());
     let validate_fn_name = format!("{name}_validate");
-    let guard = attr.guard.map(|g| quote! { guard = #g, });
+    let (guard, guard_fn) = guard(&attr, &name);
     let manual_reply = attr.manual_reply.then_some(quote! { manual_reply = "true", });
 
     let validate_fn = convert_to_validate_fn(original_fn.clone());
 
     TokenStream::from(quote! {
+        #guard_fn
+
         #[ic_cdk_macros::query(name = #validate_fn_name, #guard #manual_reply)]
         #validate_fn
 
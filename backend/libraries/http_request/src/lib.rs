@@ -1,5 +1,6 @@
 mod router;
 mod logs_handler;
+mod range;
 pub mod images;
 
 use serde::Serialize;
@@ -8,6 +9,7 @@ use types::{HeaderField, HttpResponse};
 
 pub use router::*;
 pub use logs_handler::*;
+pub use range::*;
 
 pub fn build_json_response<T: Serialize>(body: &T) -> HttpResponse {
     let bytes = serde_json::to_string(body).unwrap().into_bytes();
@@ -23,5 +25,6 @@ pub fn build_response(body: Vec<u8>, content_type: impl Into<String>) -> HttpRes
             HeaderField("Content-Length".to_string(), body.len().to_string()),
         ],
         body: ByteBuf::from(body),
+        streaming_strategy: None,
     }
 }
@@ -0,0 +1,56 @@
+#[derive(Debug, PartialEq, Eq)]
+pub enum ByteRange {
+    // Both ends inclusive.
+    Satisfiable { start: u64, end: u64 },
+    Unsatisfiable,
+}
+
+// Parses a `Range` header for a body of `size` bytes. Returns None for anything other than a
+// single byte range, in which case the whole body should be returned.
+pub fn parse_range(header: &str, size: u64) -> Option<ByteRange> {
+    let spec = header.trim().strip_prefix("bytes=")?;
+    if spec.contains(',') {
+        return None;
+    }
+    let (first, last) = spec.split_once('-')?;
+    let (first, last) = (first.trim(), last.trim());
+
+    if first.is_empty() {
+        // The last `n` bytes.
+        let n: u64 = last.parse().ok()?;
+        if n == 0 || size == 0 {
+            return Some(ByteRange::Unsatisfiable);
+        }
+        return Some(ByteRange::Satisfiable { start: size.saturating_sub(n), end: size - 1 });
+    }
+
+    let start: u64 = first.parse().ok()?;
+    let end = match last {
+        "" => u64::MAX,
+        last => last.parse().ok()?,
+    };
+    if end < start {
+        None
+    } else if start >= size {
+        Some(ByteRange::Unsatisfiable)
+    } else {
+        Some(ByteRange::Satisfiable { start, end: end.min(size - 1) })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn ranges() {
+        assert_eq!(parse_range("bytes=0-99", 1000), Some(ByteRange::Satisfiable { start: 0, end: 99 }));
+        assert_eq!(parse_range("bytes=900-", 1000), Some(ByteRange::Satisfiable { start: 900, end: 999 }));
+        assert_eq!(parse_range("bytes=-100", 1000), Some(ByteRange::Satisfiable { start: 900, end: 999 }));
+        assert_eq!(parse_range("bytes=500-5000", 1000), Some(ByteRange::Satisfiable { start: 500, end: 999 }));
+        assert_eq!(parse_range("bytes=1000-", 1000), Some(ByteRange::Unsatisfiable));
+        assert_eq!(parse_range("bytes=0-1,5-6", 1000), None);
+        assert_eq!(parse_range("bytes=9-1", 1000), None);
+        assert_eq!(parse_range("items=0-1", 1000), None);
+    }
+}
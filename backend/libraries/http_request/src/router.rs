@@ -1,14 +1,16 @@
 use std::str::FromStr;
 
-use types::{NobleId, PostId, TimestampMillis, AvatarId};
+use types::{NobleId, PostId, TimestampMillis, AvatarId, FileId};
 
 pub enum Route {
-    Avatar(Option<AvatarId>),
+    // The avatar and, optionally, the size wanted in pixels.
+    Avatar(Option<AvatarId>, Option<u32>),
     Metrics,
     Users(Option<usize>),
     User(Option<NobleId>),
     Posts(Option<usize>),
     Post(Option<PostId>),
+    File(Option<FileId>),
     Logs(Option<TimestampMillis>),
     Traces(Option<TimestampMillis>),
     Other(String, String),
@@ -28,7 +30,8 @@ pub fn extract_route(path: &str) -> Route {
     match parts[0] {
         "avatar" => {
             let blob_id = parts.get(1).and_then(|p| AvatarId::from_str(p).ok());
-            return Route::Avatar(blob_id);
+            let size = parts.get(2).and_then(|p| u32::from_str(p).ok());
+            return Route::Avatar(blob_id, size);
         },
         "users" => {
             let page = parts.get(1).and_then(|p| usize::from_str(p).ok());
@@ -46,6 +49,10 @@ pub fn extract_route(path: &str) -> Route {
             let blob_id = parts.get(1).and_then(|p| PostId::from_str(p).ok());
             return Route::Post(blob_id);
         },
+        "file" => {
+            let file_id = parts.get(1).and_then(|p| FileId::from_str(p).ok());
+            return Route::File(file_id);
+        },
         "logs" => {
             let since = parts.get(1).and_then(|p| TimestampMillis::from_str(p).ok());
             return Route::Logs(since);
@@ -69,7 +76,11 @@ mod tests {
     fn avatar() {
         const BLOB_ID: NobleId = 3672535213;
         match extract_route(&format!("/avatar/{BLOB_ID}")) {
-            Route::Avatar(Some(id)) => assert_eq!(BLOB_ID, id),
+            Route::Avatar(Some(id), None) => assert_eq!(BLOB_ID, id),
+            _ => panic!(),
+        }
+        match extract_route(&format!("/avatar/{BLOB_ID}/128")) {
+            Route::Avatar(Some(id), Some(size)) => assert_eq!((BLOB_ID, 128), (id, size)),
             _ => panic!(),
         }
     }
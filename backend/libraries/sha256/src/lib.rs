@@ -7,10 +7,34 @@ pub fn sha256(bytes: &[u8]) -> [u8; 32] {
     hasher.finalize().into()
 }
 
+// For data that is only available a piece at a time, such as a file stored in chunks.
+pub fn sha256_of_chunks<T: AsRef<[u8]>>(chunks: impl IntoIterator<Item = T>) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    for chunk in chunks {
+        hasher.update(chunk);
+    }
+    hasher.finalize().into()
+}
+
 pub fn sha256_string(bytes: &[u8]) -> String {
+    to_hex(&sha256(bytes))
+}
+
+pub fn to_hex(hash: &[u8; 32]) -> String {
     let mut hash_string = String::with_capacity(64);
-    for byte in sha256(bytes) {
+    for byte in hash {
         write!(hash_string, "{byte:02x}").unwrap();
     }
     hash_string
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn chunks_hash_like_the_whole() {
+        let bytes = b"manuscript, figures and datasets";
+        assert_eq!(sha256_of_chunks(bytes.chunks(5)), sha256(bytes));
+    }
+}
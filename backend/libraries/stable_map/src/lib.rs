@@ -0,0 +1,241 @@
+use ic_stable_structures::{BoundedStorable, Memory, StableBTreeMap, Storable};
+use serde::{de::DeserializeOwned, Serialize};
+use std::borrow::Cow;
+use std::marker::PhantomData;
+
+// Values are stored this many bytes at a time, so they can be of any size.
+const PAGE_SIZE: u32 = 8 * 1_024; // 8 KB
+
+pub trait Key: Copy + Ord {
+    fn to_key_bytes(self) -> [u8; 16];
+    fn from_key_bytes(bytes: [u8; 16]) -> Self;
+}
+
+impl Key for u64 {
+    fn to_key_bytes(self) -> [u8; 16] {
+        (self as u128).to_key_bytes()
+    }
+
+    fn from_key_bytes(bytes: [u8; 16]) -> Self {
+        u128::from_key_bytes(bytes) as u64
+    }
+}
+
+impl Key for u128 {
+    fn to_key_bytes(self) -> [u8; 16] {
+        self.to_be_bytes()
+    }
+
+    fn from_key_bytes(bytes: [u8; 16]) -> Self {
+        u128::from_be_bytes(bytes)
+    }
+}
+
+// Eg. a comment and the post it belongs to, ordered by the first then the second.
+impl Key for (u64, u32) {
+    fn to_key_bytes(self) -> [u8; 16] {
+        (((self.0 as u128) << 32) | self.1 as u128).to_key_bytes()
+    }
+
+    fn from_key_bytes(bytes: [u8; 16]) -> Self {
+        let key = u128::from_key_bytes(bytes);
+        ((key >> 32) as u64, key as u32)
+    }
+}
+
+// A map kept in stable memory, so it neither counts against the heap nor has to be copied on
+// upgrade. Values are serialized with msgpack, like the rest of the canister state, which means
+// fields can be added to them with `#[serde(default)]`. Reads return a copy, changes have to be
+// written back with `insert`.
+pub struct StableMap<K, V, M: Memory> {
+    // Key -> how many pages its value takes up.
+    index: StableBTreeMap<StoredKey, u32, M>,
+    pages: StableBTreeMap<PageKey, Page, M>,
+    phantom: PhantomData<(K, V)>,
+}
+
+impl<K: Key, V: Serialize + DeserializeOwned, M: Memory> StableMap<K, V, M> {
+    // Picks up whatever is already in the memories, eg. after an upgrade.
+    pub fn init(index_memory: M, pages_memory: M) -> Self {
+        StableMap {
+            index: StableBTreeMap::init(index_memory),
+            pages: StableBTreeMap::init(pages_memory),
+            phantom: PhantomData,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.index.len() as usize
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    pub fn contains_key(&self, key: K) -> bool {
+        self.index.contains_key(&StoredKey(key.to_key_bytes()))
+    }
+
+    pub fn get(&self, key: K) -> Option<V> {
+        let key = key.to_key_bytes();
+        let page_count = self.index.get(&StoredKey(key))?;
+        let mut bytes = Vec::new();
+        for page in 0..page_count {
+            // Pages are only removed along with their index entry, a missing one means the memory is
+            // corrupt. Trapping keeps that from passing for a value which isn't there.
+            let page = self.pages.get(&PageKey { key, page }).unwrap_or_else(|| panic!("Page {page} of {key:?} is missing"));
+            bytes.extend_from_slice(&page.0);
+        }
+        Some(msgpack::deserialize_then_unwrap(&bytes))
+    }
+
+    pub fn insert(&mut self, key: K, value: &V) {
+        let key = key.to_key_bytes();
+        let bytes = msgpack::serialize_then_unwrap(value);
+        let page_count = bytes.chunks(PAGE_SIZE as usize).len() as u32;
+        for (page, chunk) in bytes.chunks(PAGE_SIZE as usize).enumerate() {
+            self.pages.insert(PageKey { key, page: page as u32 }, Page(chunk.to_vec()));
+        }
+        if let Some(previous) = self.index.insert(StoredKey(key), page_count) {
+            self.remove_pages(key, page_count..previous);
+        }
+    }
+
+    pub fn remove(&mut self, key: K) -> Option<V> {
+        let value = self.get(key)?;
+        let key = key.to_key_bytes();
+        if let Some(page_count) = self.index.remove(&StoredKey(key)) {
+            self.remove_pages(key, 0..page_count);
+        }
+        Some(value)
+    }
+
+    pub fn keys(&self) -> impl Iterator<Item = K> + '_ {
+        self.index.iter().map(|(key, _)| K::from_key_bytes(key.0))
+    }
+
+    // Loads every value, only for when all of them are needed.
+    pub fn iter(&self) -> impl Iterator<Item = (K, V)> + '_ {
+        self.keys().filter_map(|key| self.get(key).map(|value| (key, value)))
+    }
+
+    fn remove_pages(&mut self, key: [u8; 16], pages: std::ops::Range<u32>) {
+        for page in pages {
+            self.pages.remove(&PageKey { key, page });
+        }
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+struct StoredKey([u8; 16]);
+
+impl Storable for StoredKey {
+    fn to_bytes(&self) -> Cow<[u8]> {
+        Cow::Borrowed(&self.0)
+    }
+
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        StoredKey(bytes.as_ref().try_into().unwrap())
+    }
+}
+
+impl BoundedStorable for StoredKey {
+    const MAX_SIZE: u32 = 16;
+    const IS_FIXED_SIZE: bool = true;
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+struct PageKey {
+    key: [u8; 16],
+    page: u32,
+}
+
+impl Storable for PageKey {
+    fn to_bytes(&self) -> Cow<[u8]> {
+        let mut bytes = Vec::with_capacity(Self::MAX_SIZE as usize);
+        bytes.extend_from_slice(&self.key);
+        bytes.extend_from_slice(&self.page.to_be_bytes());
+        Cow::Owned(bytes)
+    }
+
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        PageKey {
+            key: bytes[..16].try_into().unwrap(),
+            page: u32::from_be_bytes(bytes[16..].try_into().unwrap()),
+        }
+    }
+}
+
+impl BoundedStorable for PageKey {
+    const MAX_SIZE: u32 = 20;
+    const IS_FIXED_SIZE: bool = true;
+}
+
+struct Page(Vec<u8>);
+
+impl Storable for Page {
+    fn to_bytes(&self) -> Cow<[u8]> {
+        Cow::Borrowed(&self.0)
+    }
+
+    fn from_bytes(bytes: Cow<[u8]>) -> Self {
+        Page(bytes.into_owned())
+    }
+}
+
+impl BoundedStorable for Page {
+    const MAX_SIZE: u32 = PAGE_SIZE;
+    const IS_FIXED_SIZE: bool = false;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ic_stable_structures::DefaultMemoryImpl;
+
+    type Map = StableMap<u64, Vec<String>, DefaultMemoryImpl>;
+
+    #[test]
+    fn values_of_any_size() {
+        let index_memory = DefaultMemoryImpl::default();
+        let pages_memory = DefaultMemoryImpl::default();
+        let mut map = Map::init(index_memory.clone(), pages_memory.clone());
+
+        let large: Vec<String> = (0..10_000).map(|i| i.to_string()).collect();
+        map.insert(2, &large);
+        map.insert(1, &vec!["a".to_string()]);
+        assert_eq!(map.get(2), Some(large));
+        assert_eq!(map.len(), 2);
+
+        // Shrinking a value drops the pages it no longer needs.
+        map.insert(2, &vec!["b".to_string()]);
+        assert_eq!(map.pages.len(), 2);
+        assert_eq!(map.keys().collect::<Vec<_>>(), vec![1, 2]);
+
+        // What was written is still there for the next canister version.
+        let map = Map::init(index_memory, pages_memory);
+        assert_eq!(map.get(2), Some(vec!["b".to_string()]));
+        assert_eq!(map.iter().count(), 2);
+    }
+
+    #[test]
+    fn pairs_are_ordered_by_their_first_value() {
+        let mut map = StableMap::<(u64, u32), u32, _>::init(DefaultMemoryImpl::default(), DefaultMemoryImpl::default());
+        for key in [(2, 0), (1, u32::MAX), (u64::MAX, 1), (1, 0)] {
+            map.insert(key, &key.1);
+        }
+
+        assert_eq!(map.keys().collect::<Vec<_>>(), vec![(1, 0), (1, u32::MAX), (2, 0), (u64::MAX, 1)]);
+        assert_eq!(map.get((1, u32::MAX)), Some(u32::MAX));
+    }
+
+    #[test]
+    #[should_panic(expected = "is missing")]
+    fn missing_pages_trap() {
+        let mut map = Map::init(DefaultMemoryImpl::default(), DefaultMemoryImpl::default());
+        map.insert(1, &vec!["a".to_string()]);
+        map.pages.remove(&PageKey { key: 1u64.to_key_bytes(), page: 0 });
+
+        map.get(1);
+    }
+}
@@ -11,7 +11,15 @@ pub struct CommentDetail {
     pub liked_users_count: u32,
     pub comments_count: u32,
     pub date_created: TimestampMillis,
+    // Same as `date_created` until the comment is edited.
+    #[serde(default)]
+    pub date_updated: TimestampMillis,
+    #[serde(default)]
+    pub edited: bool,
     pub like_state: bool,
     pub loading_like: bool,
     pub loading_delete: bool,
+    // The comment was deleted but has replies, `description` is a placeholder.
+    #[serde(default)]
+    pub deleted: bool,
 }
\ No newline at end of file
@@ -1,4 +1,4 @@
-use candid::CandidType;
+use candid::{define_function, CandidType};
 use serde::Deserialize;
 use serde_bytes::ByteBuf;
 
@@ -18,6 +18,28 @@ pub struct HttpResponse {
     pub status_code: u16,
     pub headers: Vec<HeaderField>,
     pub body: ByteBuf,
+    #[serde(default)]
+    pub streaming_strategy: Option<StreamingStrategy>,
+}
+
+// The boundary nodes call back with the token for the rest of a response too large to return at once.
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub enum StreamingStrategy {
+    Callback { callback: CallbackFunc, token: Token },
+}
+
+define_function!(pub CallbackFunc : (Token) -> (StreamingCallbackHttpResponse) query);
+
+#[derive(CandidType, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct Token {
+    pub key: String,
+    pub index: u32,
+}
+
+#[derive(CandidType, Deserialize, Clone, Debug)]
+pub struct StreamingCallbackHttpResponse {
+    pub body: ByteBuf,
+    pub token: Option<Token>,
 }
 
 impl HttpRequest {
@@ -36,6 +58,7 @@ impl HttpResponse {
             status_code: code,
             headers: Vec::new(),
             body: ByteBuf::default(),
+            streaming_strategy: None,
         }
     }
 
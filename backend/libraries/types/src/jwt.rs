@@ -1,10 +1,31 @@
-use candid::Principal;
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use candid::{CandidType, Principal};
+use ed25519_compact::{KeyPair, PublicKey, Seed, Signature};
 use serde::{Deserialize, Serialize};
-use magic_crypt::{MagicCryptTrait, MagicCrypt256};
+use std::cell::RefCell;
+use std::collections::HashMap;
+use std::fmt::{Debug, Formatter};
 
 use crate::{NobleId, TimestampMillis, CanisterId};
 
-pub const EXP_TIME: u64 = 3 * 24 * 60 * 60 * 1_000; // 3 days
+// Access tokens are short lived, user_index hands out refresh tokens to renew them.
+pub const EXP_TIME: u64 = 15 * 60 * 1_000; // 15 mins
+
+const JWT_ALGORITHM: &str = "EdDSA";
+#[cfg(any(test, feature = "test-utils"))]
+const TEST_KEY_ID: JwtKeyId = 0;
+#[cfg(any(test, feature = "test-utils"))]
+const TEST_KEY_SEED: [u8; 32] = [7; 32];
+
+pub type JwtKeyId = u32;
+pub type SessionId = u64;
+
+thread_local! {
+    // Keys accepted by `check_jwt`, installed by each canister from its own state.
+    static VERIFICATION_KEYS: RefCell<HashMap<JwtKeyId, PublicKey>> = RefCell::default();
+    // Sessions rejected by `check_jwt`, installed by each canister from its own state.
+    static SESSION_REVOCATIONS: RefCell<SessionRevocations> = RefCell::default();
+}
 
 #[derive(Deserialize, Serialize, Debug, Clone)]
 pub struct JWT {
@@ -14,21 +35,137 @@ pub struct JWT {
     pub local_canister_id: CanisterId,
     pub email: String,
     pub username: String,
+    #[serde(default)]
+    pub session_id: SessionId,
+    // Bumped by user_index whenever all of a user's sessions are revoked.
+    #[serde(default)]
+    pub generation: u32,
+}
+
+#[derive(Deserialize, Serialize)]
+struct JwtHeader {
+    alg: String,
+    typ: String,
+    kid: String,
+}
+
+#[derive(CandidType, Deserialize, Serialize, Debug, Clone, PartialEq, Eq)]
+pub struct JwtVerificationKey {
+    pub kid: JwtKeyId,
+    #[serde(with = "serde_bytes")]
+    pub public_key: Vec<u8>,
+}
+
+#[derive(Deserialize, Serialize, Clone)]
+pub struct JwtSigningKey {
+    pub kid: JwtKeyId,
+    #[serde(with = "serde_bytes")]
+    seed: Vec<u8>,
+}
+
+impl JwtSigningKey {
+    pub fn new(kid: JwtKeyId, seed: [u8; 32]) -> Self {
+        JwtSigningKey { kid, seed: seed.to_vec() }
+    }
+
+    // Its seed is public, so it only exists in tests.
+    #[cfg(any(test, feature = "test-utils"))]
+    pub fn for_test() -> Self {
+        Self::new(TEST_KEY_ID, TEST_KEY_SEED)
+    }
+
+    pub fn verification_key(&self) -> JwtVerificationKey {
+        JwtVerificationKey {
+            kid: self.kid,
+            public_key: self.key_pair().pk.to_vec(),
+        }
+    }
+
+    fn key_pair(&self) -> KeyPair {
+        KeyPair::from_seed(Seed::from_slice(&self.seed).unwrap())
+    }
+}
+
+// Never print the seed.
+impl Debug for JwtSigningKey {
+    fn fmt(&self, f: &mut Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("JwtSigningKey").field("kid", &self.kid).finish()
+    }
+}
+
+#[derive(CandidType, Deserialize, Serialize, Debug, Clone)]
+pub enum SessionRevocation {
+    Session { session_id: SessionId, expires: TimestampMillis },
+    AllSessions { noble_id: NobleId, generation: u32 },
+}
+
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+pub struct SessionRevocations {
+    // Tokens of the user with a lower generation are rejected.
+    min_generations: HashMap<NobleId, u32>,
+    // Individually revoked sessions, kept until their token would have expired anyway.
+    sessions: HashMap<SessionId, TimestampMillis>,
+}
+
+impl SessionRevocations {
+    pub fn apply(&mut self, revocation: SessionRevocation, now: TimestampMillis) {
+        match revocation {
+            SessionRevocation::Session { session_id, expires } => {
+                self.sessions.insert(session_id, expires);
+            }
+            SessionRevocation::AllSessions { noble_id, generation } => {
+                let min_generation = self.min_generations.entry(noble_id).or_default();
+                *min_generation = (*min_generation).max(generation);
+            }
+        }
+        self.sessions.retain(|_, expires| *expires >= now);
+    }
+
+    pub fn is_revoked(&self, jwt: &JWT) -> bool {
+        self.sessions.contains_key(&jwt.session_id)
+            || self.min_generations.get(&jwt.noble_id).map_or(false, |g| jwt.generation < *g)
+    }
+}
+
+pub fn set_session_revocations(revocations: &SessionRevocations) {
+    SESSION_REVOCATIONS.with(|r| *r.borrow_mut() = revocations.clone());
+}
+
+pub fn set_jwt_verification_keys(keys: &[JwtVerificationKey]) {
+    let keys = keys
+        .iter()
+        .filter_map(|k| PublicKey::from_slice(&k.public_key).ok().map(|pk| (k.kid, pk)))
+        .collect();
+
+    VERIFICATION_KEYS.with(|k| *k.borrow_mut() = keys);
 }
 
 impl JWT {
-    pub fn new(noble_id: NobleId, local_canister_id: CanisterId, email: String, username: String, iat: u64) -> Self {
+    pub fn new(
+        noble_id: NobleId,
+        local_canister_id: CanisterId,
+        email: String,
+        username: String,
+        session_id: SessionId,
+        generation: u32,
+        iat: u64,
+    ) -> Self {
         Self {
             noble_id,
             local_canister_id,
             email,
             username,
+            session_id,
+            generation,
             iat,
             exp: iat + EXP_TIME,
         }
     }
 
+    // Also installs the test key so that tokens signed with `JwtSigningKey::for_test` pass `check_jwt`.
+    #[cfg(any(test, feature = "test-utils"))]
     pub fn new_for_test(noble_id: NobleId, now: TimestampMillis) -> Self {
+        set_jwt_verification_keys(&[JwtSigningKey::for_test().verification_key()]);
         Self {
             noble_id,
             iat: now,
@@ -37,32 +174,42 @@ impl JWT {
         }
     }
 
-    pub fn get_key() -> &'static str {
-        "magickey2"
-    }
+    pub fn sign(&self, key: &JwtSigningKey) -> Option<String> {
+        let header = JwtHeader {
+            alg: JWT_ALGORITHM.to_string(),
+            typ: "JWT".to_string(),
+            kid: key.kid.to_string(),
+        };
+        let header = serde_json::to_vec(&header).ok()?;
+        let payload = serde_json::to_vec(self).ok()?;
 
-    pub fn to_string(&self) -> Option<String> {
-        match serde_json::to_string(self) {
-            Ok(ok) => {
-                let mcrypt = MagicCrypt256::new(Self::get_key(), None::<String>);
-                let encrypted_string = mcrypt.encrypt_str_to_base64(&ok);
-                Some(encrypted_string.trim_end_matches("=").to_string())
-            },
-            Err(_) => None,
-        }
+        let message = format!("{}.{}", URL_SAFE_NO_PAD.encode(header), URL_SAFE_NO_PAD.encode(payload));
+        let signature = key.key_pair().sk.sign(message.as_bytes(), None);
+
+        Some(format!("{message}.{}", URL_SAFE_NO_PAD.encode(signature.as_ref())))
     }
 
     pub fn from_string(token: &str) -> Option<Self> {
-        let mcrypt = MagicCrypt256::new(Self::get_key(), None::<String>);
-        match mcrypt.decrypt_base64_to_string(token) {
-            Ok(ok) => {
-                match serde_json::from_str(&ok) {
-                    Ok(ok) => Some(ok),
-                    Err(_) => None,
-                }
-            },
-            Err(_) => None,
+        let (message, signature) = token.rsplit_once('.')?;
+        let (header, payload) = message.split_once('.')?;
+
+        let header: JwtHeader = serde_json::from_slice(&URL_SAFE_NO_PAD.decode(header).ok()?).ok()?;
+        if header.alg != JWT_ALGORITHM {
+            return None;
         }
+        let kid: JwtKeyId = header.kid.parse().ok()?;
+        let signature = Signature::from_slice(&URL_SAFE_NO_PAD.decode(signature).ok()?).ok()?;
+
+        let verified = VERIFICATION_KEYS.with(|keys| {
+            keys.borrow()
+                .get(&kid)
+                .map_or(false, |pk| pk.verify(message.as_bytes(), &signature).is_ok())
+        });
+        if !verified {
+            return None;
+        }
+
+        serde_json::from_slice(&URL_SAFE_NO_PAD.decode(payload).ok()?).ok()
     }
 
     pub fn is_expired(&self, now: TimestampMillis) -> bool {
@@ -72,7 +219,7 @@ impl JWT {
 
 pub fn check_jwt(str: &str, now: TimestampMillis) -> Option<JWT> {
     if let Some(jwt) = JWT::from_string(str) {
-        if jwt.is_expired(now) {
+        if jwt.is_expired(now) || SESSION_REVOCATIONS.with(|r| r.borrow().is_revoked(&jwt)) {
             return None;
         }
         return Some(jwt);
@@ -90,6 +237,78 @@ impl Default for JWT {
             local_canister_id: Principal::anonymous(),
             email: "".to_string(),
             username: "".to_string(),
+            session_id: 0,
+            generation: 0,
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn signed_token_round_trip() {
+        let key = JwtSigningKey::new(1, [1; 32]);
+        set_jwt_verification_keys(&[key.verification_key()]);
+
+        let token = JWT::new(5, Principal::anonymous(), "a@b.com".to_string(), "abc".to_string(), 1, 0, 100).sign(&key).unwrap();
+
+        assert_eq!(check_jwt(&token, 200).unwrap().noble_id, 5);
+        assert!(check_jwt(&token, 100 + EXP_TIME + 1).is_none());
+    }
+
+    #[test]
+    fn tampered_token_is_rejected() {
+        let key = JwtSigningKey::new(1, [1; 32]);
+        set_jwt_verification_keys(&[key.verification_key()]);
+
+        let token = JWT::new(5, Principal::anonymous(), String::new(), String::new(), 1, 0, 100).sign(&key).unwrap();
+        let forged = JWT::new(6, Principal::anonymous(), String::new(), String::new(), 1, 0, 100).sign(&JwtSigningKey::new(1, [2; 32])).unwrap();
+        let (_, signature) = token.rsplit_once('.').unwrap();
+        let (message, _) = forged.rsplit_once('.').unwrap();
+
+        assert!(check_jwt(&format!("{message}.{signature}"), 200).is_none());
+        assert!(check_jwt(&forged, 200).is_none());
+    }
+
+    #[test]
+    fn retired_key_stops_validating() {
+        let old_key = JwtSigningKey::new(1, [1; 32]);
+        let new_key = JwtSigningKey::new(2, [2; 32]);
+        set_jwt_verification_keys(&[old_key.verification_key(), new_key.verification_key()]);
+
+        let jwt = JWT::new(5, Principal::anonymous(), String::new(), String::new(), 1, 0, 100);
+        let old_token = jwt.sign(&old_key).unwrap();
+        let new_token = jwt.sign(&new_key).unwrap();
+        assert!(check_jwt(&old_token, 200).is_some());
+        assert!(check_jwt(&new_token, 200).is_some());
+
+        set_jwt_verification_keys(&[new_key.verification_key()]);
+        assert!(check_jwt(&old_token, 200).is_none());
+        assert!(check_jwt(&new_token, 200).is_some());
+    }
+
+    #[test]
+    fn revoked_sessions_are_rejected() {
+        let key = JwtSigningKey::new(1, [1; 32]);
+        set_jwt_verification_keys(&[key.verification_key()]);
+        let mut revocations = SessionRevocations::default();
+
+        let first = JWT::new(5, Principal::anonymous(), String::new(), String::new(), 1, 0, 100).sign(&key).unwrap();
+        let second = JWT::new(5, Principal::anonymous(), String::new(), String::new(), 2, 0, 100).sign(&key).unwrap();
+        let renewed = JWT::new(5, Principal::anonymous(), String::new(), String::new(), 3, 1, 100).sign(&key).unwrap();
+
+        revocations.apply(SessionRevocation::Session { session_id: 1, expires: 100 + EXP_TIME }, 200);
+        set_session_revocations(&revocations);
+        assert!(check_jwt(&first, 200).is_none());
+        assert!(check_jwt(&second, 200).is_some());
+
+        revocations.apply(SessionRevocation::AllSessions { noble_id: 5, generation: 1 }, 200);
+        set_session_revocations(&revocations);
+        assert!(check_jwt(&second, 200).is_none());
+        assert!(check_jwt(&renewed, 200).is_some());
+
+        set_session_revocations(&SessionRevocations::default());
+    }
+}
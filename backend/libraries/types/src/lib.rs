@@ -10,7 +10,12 @@ mod jwt;
 mod post_detail;
 mod post_summary;
 mod referral_codes;
+mod report;
+mod revision;
+mod role;
+mod social_graph;
 mod stable_principal;
+mod suspension;
 mod timestamped;
 mod user;
 mod user_detail;
@@ -26,7 +31,12 @@ pub use jwt::*;
 pub use post_detail::*;
 pub use post_summary::*;
 pub use referral_codes::*;
+pub use report::*;
+pub use revision::*;
+pub use role::*;
+pub use social_graph::*;
 pub use stable_principal::*;
+pub use suspension::*;
 pub use timestamped::*;
 pub use user::*;
 pub use user_detail::*;
@@ -176,7 +186,7 @@ impl Default for Gender {
     }
 }
 
-#[derive(CandidType, Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq)]
+#[derive(CandidType, Serialize, Deserialize, Clone, Copy, Debug, PartialEq, Eq, Hash)]
 pub enum Category {
     GeneralDiscussion,
     Questions,
@@ -196,6 +206,7 @@ pub struct Empty {}
 #[derive(CandidType, Serialize, Deserialize, Debug)]
 pub struct SuccessLogin {
     pub jwt: String,
+    pub refresh_token: String,
     pub noble_id: NobleId,
     pub username: String,
     pub first_name: String,
@@ -0,0 +1,54 @@
+use crate::{CommentId, NobleId, PostId, TimestampMillis};
+use candid::CandidType;
+use serde::{Deserialize, Serialize};
+
+pub type ReportId = u64;
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum ReportTarget {
+    Post { post_id: PostId },
+    Comment { post_id: PostId, comment_id: CommentId },
+    User { noble_id: NobleId },
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Hash, Debug)]
+pub enum ReportReason {
+    Spam,
+    Harassment,
+    HateSpeech,
+    Violence,
+    SexualContent,
+    Misinformation,
+    Plagiarism,
+    Impersonation,
+    Other,
+}
+
+// What a moderator did about a report. Hiding a post trashes it so only moderators can restore it,
+// removing it deletes it straight away. Comments are removed either way. Users can only be warned.
+#[derive(CandidType, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub enum ModerationAction {
+    Hide,
+    Remove,
+    Warn,
+    Dismiss,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct Report {
+    pub report_id: ReportId,
+    pub target: ReportTarget,
+    pub reporter_id: NobleId,
+    pub reason: ReportReason,
+    pub details: String,
+    pub date_created: TimestampMillis,
+    pub resolution: Option<ReportResolution>,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct ReportResolution {
+    pub moderator_id: NobleId,
+    pub action: ModerationAction,
+    pub note: String,
+    pub date_resolved: TimestampMillis,
+}
@@ -0,0 +1,18 @@
+use crate::{NobleId, TimestampMillis};
+use candid::CandidType;
+use serde::{Deserialize, Serialize};
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct PostRevision {
+    pub title: String,
+    pub description: String,
+    pub editor: NobleId,
+    pub date_updated: TimestampMillis,
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug, PartialEq, Eq)]
+pub struct CommentRevision {
+    pub description: String,
+    pub editor: NobleId,
+    pub date_updated: TimestampMillis,
+}
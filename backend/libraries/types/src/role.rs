@@ -0,0 +1,25 @@
+use crate::{NobleId, TimestampMillis};
+use candid::{CandidType, Principal};
+use serde::{Deserialize, Serialize};
+
+// What each role may do is decided by `utils::rbac`.
+#[derive(CandidType, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub enum Role {
+    Governance,
+    Operator,
+    Moderator,
+}
+
+// Principals are checked when they call admin endpoints themselves, users through their JWT.
+#[derive(CandidType, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Debug)]
+pub enum RoleMember {
+    Principal(Principal),
+    User(NobleId),
+}
+
+#[derive(CandidType, Serialize, Deserialize, Clone, PartialEq, Eq, Debug)]
+pub struct RoleGrant {
+    pub member: RoleMember,
+    pub role: Role,
+    pub date_granted: TimestampMillis,
+}
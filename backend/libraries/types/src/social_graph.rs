@@ -0,0 +1,88 @@
+use crate::NobleId;
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+// Who follows and who has blocked whom, kept by the post canisters from user_index's events so
+// they can decide what a viewer may see without trusting the client.
+#[derive(Deserialize, Serialize, Debug, Clone, Default)]
+pub struct SocialGraph {
+    // User -> the users they follow.
+    following: HashMap<NobleId, HashSet<NobleId>>,
+    // User -> the users they have blocked.
+    blocked: HashMap<NobleId, HashSet<NobleId>>,
+}
+
+impl SocialGraph {
+    pub fn follow(&mut self, sender_id: NobleId, receiver_id: NobleId) {
+        self.following.entry(sender_id).or_default().insert(receiver_id);
+    }
+
+    pub fn unfollow(&mut self, sender_id: NobleId, receiver_id: NobleId) {
+        remove(&mut self.following, sender_id, receiver_id);
+    }
+
+    pub fn block(&mut self, sender_id: NobleId, receiver_id: NobleId) {
+        self.blocked.entry(sender_id).or_default().insert(receiver_id);
+    }
+
+    pub fn unblock(&mut self, sender_id: NobleId, receiver_id: NobleId) {
+        remove(&mut self.blocked, sender_id, receiver_id);
+    }
+
+    pub fn is_following(&self, noble_id: NobleId, other: NobleId) -> bool {
+        self.following.get(&noble_id).map_or(false, |users| users.contains(&other))
+    }
+
+    pub fn has_blocked(&self, noble_id: NobleId, other: NobleId) -> bool {
+        self.blocked.get(&noble_id).map_or(false, |users| users.contains(&other))
+    }
+
+    pub fn following(&self, noble_id: NobleId) -> impl Iterator<Item = NobleId> + '_ {
+        self.following.get(&noble_id).into_iter().flatten().copied()
+    }
+
+    // Every (sender, receiver) pair, for copying the graph to a new canister.
+    pub fn follows(&self) -> impl Iterator<Item = (NobleId, NobleId)> + '_ {
+        pairs(&self.following)
+    }
+
+    pub fn blocks(&self) -> impl Iterator<Item = (NobleId, NobleId)> + '_ {
+        pairs(&self.blocked)
+    }
+}
+
+fn pairs(relations: &HashMap<NobleId, HashSet<NobleId>>) -> impl Iterator<Item = (NobleId, NobleId)> + '_ {
+    relations.iter().flat_map(|(sender_id, users)| users.iter().map(move |receiver_id| (*sender_id, *receiver_id)))
+}
+
+fn remove(relations: &mut HashMap<NobleId, HashSet<NobleId>>, sender_id: NobleId, receiver_id: NobleId) {
+    if let Some(users) = relations.get_mut(&sender_id) {
+        users.remove(&receiver_id);
+        if users.is_empty() {
+            relations.remove(&sender_id);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn relations_are_one_way() {
+        let mut graph = SocialGraph::default();
+        graph.follow(1, 2);
+        graph.block(3, 1);
+
+        assert!(graph.is_following(1, 2));
+        assert!(!graph.is_following(2, 1));
+        assert!(graph.has_blocked(3, 1));
+        assert!(!graph.has_blocked(1, 3));
+
+        graph.unfollow(1, 2);
+        graph.unblock(3, 1);
+        assert!(!graph.is_following(1, 2));
+        assert!(!graph.has_blocked(3, 1));
+        assert_eq!(graph.following(1).count(), 0);
+    }
+}
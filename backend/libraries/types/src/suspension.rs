@@ -0,0 +1,110 @@
+use crate::{NobleId, TimestampMillis};
+use candid::CandidType;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+#[derive(CandidType, Serialize, Deserialize, Clone, Copy, PartialEq, Eq, Debug)]
+pub struct Suspension {
+    // When the suspension ends, None for a ban. The `Suspended { until }` responses use the same.
+    pub until: Option<TimestampMillis>,
+    // Whether the user's posts and comments are hidden from everyone else while it lasts.
+    pub hide_content: bool,
+}
+
+// What moderators see of a suspension, user_index keeps one for each suspended user.
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct SuspensionDetails {
+    pub noble_id: NobleId,
+    pub suspension: Suspension,
+    pub reason: String,
+    pub suspended_by: NobleId,
+    pub date_suspended: TimestampMillis,
+    pub appeal: Option<Appeal>,
+}
+
+// Each suspension can be appealed once. Accepting the appeal lifts the suspension.
+#[derive(CandidType, Serialize, Deserialize, Clone, Debug)]
+pub struct Appeal {
+    pub text: String,
+    pub date_submitted: TimestampMillis,
+    pub date_rejected: Option<TimestampMillis>,
+}
+
+// Users who may not post, comment, like or follow, kept by user_index and copied to every canister
+// which checks it. Suspensions are ignored once they're over, user_index lifts them soon after.
+#[derive(Serialize, Deserialize, Clone, Default, Debug)]
+pub struct SuspendedUsers {
+    users: HashMap<NobleId, Suspension>,
+}
+
+impl SuspendedUsers {
+    pub fn suspend(&mut self, noble_id: NobleId, suspension: Suspension) {
+        self.users.insert(noble_id, suspension);
+    }
+
+    pub fn lift(&mut self, noble_id: NobleId) -> bool {
+        self.users.remove(&noble_id).is_some()
+    }
+
+    pub fn get(&self, noble_id: NobleId, now: TimestampMillis) -> Option<Suspension> {
+        self.users
+            .get(&noble_id)
+            .filter(|suspension| suspension.until.map_or(true, |until| now < until))
+            .copied()
+    }
+
+    // Users still see their own content.
+    pub fn hides_content_from(&self, noble_id: NobleId, viewer: NobleId, now: TimestampMillis) -> bool {
+        noble_id != viewer && self.hides_content(noble_id, now)
+    }
+
+    // Whether the user's content is hidden from viewers who aren't signed in.
+    pub fn hides_content(&self, noble_id: NobleId, now: TimestampMillis) -> bool {
+        self.get(noble_id, now).map_or(false, |suspension| suspension.hide_content)
+    }
+
+    pub fn expired(&self, now: TimestampMillis) -> Vec<NobleId> {
+        self.users
+            .iter()
+            .filter(|(_, suspension)| suspension.until.map_or(false, |until| now >= until))
+            .map(|(noble_id, _)| *noble_id)
+            .collect()
+    }
+
+    pub fn iter(&self) -> impl Iterator<Item = (NobleId, Suspension)> + '_ {
+        self.users.iter().map(|(noble_id, suspension)| (*noble_id, *suspension))
+    }
+
+    pub fn len(&self) -> usize {
+        self.users.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.users.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn suspensions_end() {
+        let mut suspended_users = SuspendedUsers::default();
+        suspended_users.suspend(1, Suspension { until: Some(100), hide_content: true });
+        suspended_users.suspend(2, Suspension { until: None, hide_content: false });
+
+        assert_eq!(suspended_users.get(1, 99).unwrap().until, Some(100));
+        assert!(suspended_users.hides_content_from(1, 3, 99));
+        assert!(!suspended_users.hides_content_from(1, 1, 99));
+        assert!(!suspended_users.hides_content_from(2, 3, 99));
+
+        assert!(suspended_users.get(1, 100).is_none());
+        assert!(!suspended_users.hides_content_from(1, 3, 100));
+        assert!(suspended_users.get(2, 1_000_000).is_some());
+        assert_eq!(suspended_users.expired(100), vec![1]);
+
+        assert!(suspended_users.lift(2));
+        assert!(!suspended_users.lift(2));
+    }
+}
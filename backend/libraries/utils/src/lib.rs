@@ -4,9 +4,13 @@ pub mod env;
 pub mod canister_event_sync_queue;
 pub mod case_insensitive_hash_map;
 pub mod consts;
+pub mod constant_time;
 pub mod email_event_sync_queue;
 pub mod field_validation;
 pub mod memory;
+pub mod mime;
+pub mod rbac;
+pub mod text_search;
 pub mod time;
 pub mod truncate_string;
 pub mod username_validation;
\ No newline at end of file
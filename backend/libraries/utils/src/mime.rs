@@ -0,0 +1,87 @@
+pub const DEFAULT_MIME_TYPE: &str = "application/octet-stream";
+
+// Formats recognised by their first bytes, which are trusted over whatever the file is called.
+const SIGNATURES: &[(&[u8], &str)] = &[
+    (b"%PDF-", "application/pdf"),
+    (b"\x89PNG\r\n\x1a\n", "image/png"),
+    (b"\xff\xd8\xff", "image/jpeg"),
+    (b"GIF87a", "image/gif"),
+    (b"GIF89a", "image/gif"),
+    (b"II*\x00", "image/tiff"),
+    (b"MM\x00*", "image/tiff"),
+    (b"\x1f\x8b", "application/gzip"),
+    (b"PK\x03\x04", "application/zip"),
+    (b"\x89HDF\r\n\x1a\n", "application/x-hdf5"),
+];
+
+// Text formats have no signature so they're named by extension, as long as the content looks like text.
+const TEXT_EXTENSIONS: &[(&str, &str)] = &[
+    ("txt", "text/plain"),
+    ("md", "text/markdown"),
+    ("csv", "text/csv"),
+    ("tsv", "text/tab-separated-values"),
+    ("json", "application/json"),
+    ("xml", "application/xml"),
+    ("tex", "application/x-tex"),
+    ("bib", "application/x-bibtex"),
+    ("svg", "image/svg+xml"),
+];
+
+// Zip containers that are more specific formats.
+const ZIP_EXTENSIONS: &[(&str, &str)] = &[
+    ("docx", "application/vnd.openxmlformats-officedocument.wordprocessingml.document"),
+    ("xlsx", "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet"),
+    ("pptx", "application/vnd.openxmlformats-officedocument.presentationml.presentation"),
+    ("odt", "application/vnd.oasis.opendocument.text"),
+    ("ods", "application/vnd.oasis.opendocument.spreadsheet"),
+];
+
+pub fn detect_mime_type(file_name: &str, first_bytes: &[u8]) -> &'static str {
+    let extension = file_name.rsplit_once('.').map(|(_, ext)| ext.to_lowercase()).unwrap_or_default();
+
+    if let Some((_, mime_type)) = SIGNATURES.iter().find(|(signature, _)| first_bytes.starts_with(signature)) {
+        if *mime_type == "application/zip" {
+            if let Some((_, mime_type)) = ZIP_EXTENSIONS.iter().find(|(ext, _)| *ext == extension) {
+                return mime_type;
+            }
+        }
+        return mime_type;
+    }
+
+    if looks_like_text(first_bytes) {
+        if let Some((_, mime_type)) = TEXT_EXTENSIONS.iter().find(|(ext, _)| *ext == extension) {
+            return mime_type;
+        }
+    }
+
+    DEFAULT_MIME_TYPE
+}
+
+fn looks_like_text(bytes: &[u8]) -> bool {
+    // The sample may end part way through a multi-byte character.
+    match std::str::from_utf8(bytes) {
+        Ok(text) => !text.contains('\0'),
+        Err(error) => error.error_len().is_none() && !bytes.contains(&0),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn content_wins_over_name() {
+        assert_eq!(detect_mime_type("paper.txt", b"%PDF-1.7\n..."), "application/pdf");
+        assert_eq!(detect_mime_type("figure", b"\x89PNG\r\n\x1a\n\x00\x00"), "image/png");
+        assert_eq!(detect_mime_type("data.xlsx", b"PK\x03\x04\x14\x00"), "application/vnd.openxmlformats-officedocument.spreadsheetml.sheet");
+        assert_eq!(detect_mime_type("data.bin", b"PK\x03\x04\x14\x00"), "application/zip");
+    }
+
+    #[test]
+    fn text_is_named_by_extension() {
+        assert_eq!(detect_mime_type("results.CSV", b"id,value\n1,2\n"), "text/csv");
+        assert_eq!(detect_mime_type("main.tex", "\\section{Résumé".as_bytes()), "application/x-tex");
+        assert_eq!(detect_mime_type("results.csv", b"\x00\x01\x02"), DEFAULT_MIME_TYPE);
+        assert_eq!(detect_mime_type("notes", b"plain words"), DEFAULT_MIME_TYPE);
+    }
+}
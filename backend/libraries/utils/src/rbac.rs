@@ -0,0 +1,143 @@
+use crate::consts::DEV_TEAM_PRINCIPAL;
+use candid::Principal;
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use types::{NobleId, Role, RoleGrant, RoleMember, TimestampMillis};
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Permission {
+    // Grant and revoke roles.
+    ManageRoles,
+    // Add local canisters and upgrade them.
+    ManageCanisters,
+    // Email, sign-in and post settings, and the JWT signing keys.
+    ManageSettings,
+    // Email deliveries, which show addresses.
+    ViewEmailDeliveries,
+    // Resolve reports, and edit, delete or restore anyone's posts and comments.
+    ModerateContent,
+}
+
+pub fn permissions(role: Role) -> &'static [Permission] {
+    match role {
+        Role::Governance => &[
+            Permission::ManageRoles,
+            Permission::ManageCanisters,
+            Permission::ManageSettings,
+            Permission::ViewEmailDeliveries,
+            Permission::ModerateContent,
+        ],
+        Role::Operator => &[Permission::ManageCanisters, Permission::ManageSettings, Permission::ViewEmailDeliveries],
+        Role::Moderator => &[Permission::ModerateContent],
+    }
+}
+
+// The role grants, kept by user_index and copied to post_index and the local post indexes, which
+// only replace their copy with what user_index sends.
+#[derive(Serialize, Deserialize, Clone, Default, Debug)]
+pub struct Roles {
+    grants: Vec<RoleGrant>,
+}
+
+impl Roles {
+    // What a canister starts with until it hears otherwise, the principal it was installed by and
+    // the dev team can govern.
+    pub fn new(super_admin: Principal, now: TimestampMillis) -> Roles {
+        let mut roles = Roles::default();
+        roles.add(RoleMember::Principal(super_admin), Role::Governance, now);
+        roles.add(RoleMember::Principal(DEV_TEAM_PRINCIPAL), Role::Governance, now);
+        roles
+    }
+
+    pub fn from_grants(grants: Vec<RoleGrant>) -> Roles {
+        Roles { grants }
+    }
+
+    pub fn add(&mut self, member: RoleMember, role: Role, now: TimestampMillis) -> bool {
+        if self.has_role(member, role) {
+            false
+        } else {
+            self.grants.push(RoleGrant { member, role, date_granted: now });
+            true
+        }
+    }
+
+    pub fn remove(&mut self, member: RoleMember, role: Role) -> bool {
+        let count = self.grants.len();
+        self.grants.retain(|grant| grant.member != member || grant.role != role);
+        self.grants.len() < count
+    }
+
+    pub fn has_role(&self, member: RoleMember, role: Role) -> bool {
+        self.grants.iter().any(|grant| grant.member == member && grant.role == role)
+    }
+
+    // Principals only, a user can't call the proposal endpoints whatever role they hold.
+    pub fn principal_count(&self, role: Role) -> usize {
+        self.grants
+            .iter()
+            .filter(|grant| grant.role == role && matches!(grant.member, RoleMember::Principal(_)))
+            .count()
+    }
+
+    pub fn has_permission(&self, member: RoleMember, permission: Permission) -> bool {
+        self.grants
+            .iter()
+            .any(|grant| grant.member == member && permissions(grant.role).contains(&permission))
+    }
+
+    pub fn principal_has_permission(&self, principal: Principal, permission: Permission) -> bool {
+        self.has_permission(RoleMember::Principal(principal), permission)
+    }
+
+    pub fn user_has_permission(&self, noble_id: NobleId, permission: Permission) -> bool {
+        self.has_permission(RoleMember::User(noble_id), permission)
+    }
+
+    pub fn grants(&self) -> &[RoleGrant] {
+        &self.grants
+    }
+
+    // How many members hold each role, for metrics, which anyone can read.
+    pub fn counts(&self) -> BTreeMap<Role, usize> {
+        let mut counts = BTreeMap::new();
+        for grant in &self.grants {
+            *counts.entry(grant.role).or_default() += 1;
+        }
+        counts
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.grants.is_empty()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn permissions_come_from_roles() {
+        let admin = Principal::from_slice(&[1]);
+        let mut roles = Roles::new(admin, 10);
+        assert!(roles.principal_has_permission(admin, Permission::ManageRoles));
+        assert!(roles.principal_has_permission(DEV_TEAM_PRINCIPAL, Permission::ModerateContent));
+
+        let operator = Principal::from_slice(&[2]);
+        assert!(roles.add(RoleMember::Principal(operator), Role::Operator, 20));
+        assert!(!roles.add(RoleMember::Principal(operator), Role::Operator, 30));
+        assert!(roles.principal_has_permission(operator, Permission::ManageCanisters));
+        assert!(!roles.principal_has_permission(operator, Permission::ManageRoles));
+
+        assert!(roles.add(RoleMember::User(7), Role::Moderator, 20));
+        assert!(roles.user_has_permission(7, Permission::ModerateContent));
+        assert!(!roles.user_has_permission(7, Permission::ManageSettings));
+        assert!(!roles.user_has_permission(8, Permission::ModerateContent));
+
+        assert!(roles.remove(RoleMember::User(7), Role::Moderator));
+        assert!(!roles.remove(RoleMember::User(7), Role::Moderator));
+        assert!(!roles.user_has_permission(7, Permission::ModerateContent));
+        assert!(roles.add(RoleMember::User(7), Role::Governance, 40));
+        assert_eq!(roles.principal_count(Role::Governance), 2);
+    }
+}
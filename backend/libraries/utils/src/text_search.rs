@@ -0,0 +1,295 @@
+// Turns text into the terms used by full-text search: lower-cased words with stop-words removed,
+// stemmed with the Porter algorithm so "posting", "posted" and "posts" all match "post".
+
+// Longer words are kept as they are, they're rarely real words and cost the index memory.
+const MAX_TERM_LENGTH: usize = 40;
+
+const STOP_WORDS: &[&str] = &[
+    "a", "about", "above", "after", "again", "against", "all", "am", "an", "and", "any", "are", "as", "at", "be",
+    "because", "been", "before", "being", "below", "between", "both", "but", "by", "can", "could", "did", "do",
+    "does", "doing", "down", "during", "each", "few", "for", "from", "further", "had", "has", "have", "having", "he",
+    "her", "here", "hers", "herself", "him", "himself", "his", "how", "i", "if", "in", "into", "is", "it", "its",
+    "itself", "just", "me", "more", "most", "my", "myself", "no", "nor", "not", "now", "of", "off", "on", "once",
+    "only", "or", "other", "our", "ours", "ourselves", "out", "over", "own", "same", "she", "should", "so", "some",
+    "such", "than", "that", "the", "their", "theirs", "them", "themselves", "then", "there", "these", "they", "this",
+    "those", "through", "to", "too", "under", "until", "up", "very", "was", "we", "were", "what", "when", "where",
+    "which", "while", "who", "whom", "why", "will", "with", "would", "you", "your", "yours", "yourself", "yourselves",
+];
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Token {
+    pub term: String,
+    // Character offsets of the word in the original text, for highlighting.
+    pub start: usize,
+    pub end: usize,
+}
+
+pub fn tokenize(text: &str) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    let mut word = String::new();
+    let mut start = 0;
+
+    for (index, c) in text.chars().chain(std::iter::once(' ')).enumerate() {
+        if c.is_alphanumeric() {
+            if word.is_empty() {
+                start = index;
+            }
+            word.extend(c.to_lowercase());
+        } else if !word.is_empty() {
+            if !STOP_WORDS.contains(&word.as_str()) {
+                tokens.push(Token { term: stem(&word), start, end: index });
+            }
+            word.clear();
+        }
+    }
+    tokens
+}
+
+// The distinct terms of a search query.
+pub fn query_terms(query: &str, max: usize) -> Vec<String> {
+    let mut terms: Vec<String> = Vec::new();
+    for token in tokenize(query) {
+        if terms.len() == max {
+            break;
+        }
+        if !terms.contains(&token.term) {
+            terms.push(token.term);
+        }
+    }
+    terms
+}
+
+// https://tartarus.org/martin/PorterStemmer/def.txt, only applied to ASCII words.
+pub fn stem(word: &str) -> String {
+    if word.len() <= 2 || word.len() > MAX_TERM_LENGTH || !word.bytes().all(|b| b.is_ascii_lowercase()) {
+        return word.to_string();
+    }
+    let mut w = word.as_bytes().to_vec();
+    step_1a(&mut w);
+    step_1b(&mut w);
+    step_1c(&mut w);
+    step_2(&mut w);
+    step_3(&mut w);
+    step_4(&mut w);
+    step_5(&mut w);
+    String::from_utf8(w).unwrap()
+}
+
+fn is_consonant(w: &[u8], i: usize) -> bool {
+    match w[i] {
+        b'a' | b'e' | b'i' | b'o' | b'u' => false,
+        b'y' => i == 0 || !is_consonant(w, i - 1),
+        _ => true,
+    }
+}
+
+// The number of vowel-consonant sequences, m in [C](VC){m}[V].
+fn measure(w: &[u8]) -> usize {
+    let mut m = 0;
+    let mut previous_vowel = false;
+    for i in 0..w.len() {
+        let vowel = !is_consonant(w, i);
+        if previous_vowel && !vowel {
+            m += 1;
+        }
+        previous_vowel = vowel;
+    }
+    m
+}
+
+fn contains_vowel(w: &[u8]) -> bool {
+    (0..w.len()).any(|i| !is_consonant(w, i))
+}
+
+fn ends_double_consonant(w: &[u8]) -> bool {
+    let n = w.len();
+    n >= 2 && w[n - 1] == w[n - 2] && is_consonant(w, n - 1)
+}
+
+// Consonant-vowel-consonant, where the last consonant isn't w, x or y.
+fn ends_cvc(w: &[u8]) -> bool {
+    let n = w.len();
+    n >= 3
+        && is_consonant(w, n - 3)
+        && !is_consonant(w, n - 2)
+        && is_consonant(w, n - 1)
+        && !matches!(w[n - 1], b'w' | b'x' | b'y')
+}
+
+fn stem_of<'a>(w: &'a [u8], suffix: &str) -> Option<&'a [u8]> {
+    w.strip_suffix(suffix.as_bytes())
+}
+
+// Replaces the first matching suffix if what's left has a measure above `min_measure`.
+fn replace_suffix(w: &mut Vec<u8>, rules: &[(&str, &str)], min_measure: usize) {
+    for (suffix, replacement) in rules {
+        if let Some(stem) = stem_of(w, suffix) {
+            if measure(stem) > min_measure {
+                let len = stem.len();
+                w.truncate(len);
+                w.extend_from_slice(replacement.as_bytes());
+            }
+            return;
+        }
+    }
+}
+
+fn step_1a(w: &mut Vec<u8>) {
+    if w.ends_with(b"sses") || w.ends_with(b"ies") {
+        w.truncate(w.len() - 2);
+    } else if w.ends_with(b"s") && !w.ends_with(b"ss") {
+        w.truncate(w.len() - 1);
+    }
+}
+
+fn step_1b(w: &mut Vec<u8>) {
+    if let Some(stem) = stem_of(w, "eed") {
+        if measure(stem) > 0 {
+            w.truncate(w.len() - 1);
+        }
+        return;
+    }
+
+    let stem_len = match (stem_of(w, "ed"), stem_of(w, "ing")) {
+        (Some(stem), _) | (_, Some(stem)) if contains_vowel(stem) => stem.len(),
+        _ => return,
+    };
+    w.truncate(stem_len);
+
+    if w.ends_with(b"at") || w.ends_with(b"bl") || w.ends_with(b"iz") {
+        w.push(b'e');
+    } else if ends_double_consonant(w) && !matches!(w[w.len() - 1], b'l' | b's' | b'z') {
+        w.pop();
+    } else if measure(w) == 1 && ends_cvc(w) {
+        w.push(b'e');
+    }
+}
+
+fn step_1c(w: &mut Vec<u8>) {
+    if let Some(stem) = stem_of(w, "y") {
+        if contains_vowel(stem) {
+            let len = w.len();
+            w[len - 1] = b'i';
+        }
+    }
+}
+
+fn step_2(w: &mut Vec<u8>) {
+    replace_suffix(
+        w,
+        &[
+            ("ational", "ate"),
+            ("tional", "tion"),
+            ("enci", "ence"),
+            ("anci", "ance"),
+            ("izer", "ize"),
+            ("abli", "able"),
+            ("alli", "al"),
+            ("entli", "ent"),
+            ("eli", "e"),
+            ("ousli", "ous"),
+            ("ization", "ize"),
+            ("ation", "ate"),
+            ("ator", "ate"),
+            ("alism", "al"),
+            ("iveness", "ive"),
+            ("fulness", "ful"),
+            ("ousness", "ous"),
+            ("aliti", "al"),
+            ("iviti", "ive"),
+            ("biliti", "ble"),
+        ],
+        0,
+    );
+}
+
+fn step_3(w: &mut Vec<u8>) {
+    replace_suffix(
+        w,
+        &[
+            ("icate", "ic"),
+            ("ative", ""),
+            ("alize", "al"),
+            ("iciti", "ic"),
+            ("ical", "ic"),
+            ("ful", ""),
+            ("ness", ""),
+        ],
+        0,
+    );
+}
+
+fn step_4(w: &mut Vec<u8>) {
+    const SUFFIXES: &[&str] = &[
+        "ement", "ment", "ance", "ence", "able", "ible", "ant", "ent", "ism", "ate", "iti", "ous", "ive", "ize", "ion",
+        "al", "er", "ic", "ou",
+    ];
+    for suffix in SUFFIXES {
+        if let Some(stem) = stem_of(w, suffix) {
+            let allowed = *suffix != "ion" || stem.ends_with(b"s") || stem.ends_with(b"t");
+            if allowed && measure(stem) > 1 {
+                let len = stem.len();
+                w.truncate(len);
+            }
+            return;
+        }
+    }
+}
+
+fn step_5(w: &mut Vec<u8>) {
+    if let Some(stem) = stem_of(w, "e") {
+        let m = measure(stem);
+        if m > 1 || (m == 1 && !ends_cvc(stem)) {
+            w.pop();
+        }
+    }
+    if w.ends_with(b"ll") && measure(w) > 1 {
+        w.pop();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn stems() {
+        for (word, expected) in [
+            ("caresses", "caress"),
+            ("ponies", "poni"),
+            ("cats", "cat"),
+            ("agreed", "agre"),
+            ("plastered", "plaster"),
+            ("motoring", "motor"),
+            ("hopping", "hop"),
+            ("filing", "file"),
+            ("happy", "happi"),
+            ("relational", "relat"),
+            ("generalization", "gener"),
+            ("hopefulness", "hope"),
+            ("adjustment", "adjust"),
+            ("controlling", "control"),
+            ("sky", "sky"),
+        ] {
+            assert_eq!(stem(word), expected, "{word}");
+        }
+        assert_eq!(stem("posting"), stem("posts"));
+        assert_eq!(stem("naïve"), "naïve");
+    }
+
+    #[test]
+    fn tokens_keep_their_offsets() {
+        let tokens = tokenize("The Über-cool posts, of 2023!");
+
+        let terms: Vec<_> = tokens.iter().map(|t| t.term.as_str()).collect();
+        assert_eq!(terms, vec!["über", "cool", "post", "2023"]);
+        assert_eq!((tokens[0].start, tokens[0].end), (4, 8));
+        assert_eq!((tokens[3].start, tokens[3].end), (24, 28));
+    }
+
+    #[test]
+    fn query_terms_are_distinct() {
+        assert_eq!(query_terms("Posts and posting about the post office", 10), vec!["post", "offic"]);
+        assert_eq!(query_terms("one two three", 2), vec!["on", "two"]);
+    }
+}
@@ -27,6 +27,30 @@ pub fn validate_username(username: &str) -> Result<(), UsernameValidationError>
     Ok(())
 }
 
+// Returns the distinct valid usernames written as "@username" in `text`, at most `max` of them.
+pub fn extract_mentions(text: &str, max: usize) -> Vec<String> {
+    let is_username_char = |c: char| c.is_ascii_alphanumeric() || c == '_';
+    let mut mentions: Vec<String> = Vec::new();
+    let mut prev = None;
+
+    for (index, c) in text.char_indices() {
+        // Skips email addresses and the like, where '@' follows a username character.
+        if c == '@' && !prev.map_or(false, is_username_char) {
+            let rest = &text[index + 1..];
+            let len = rest.find(|c: char| !is_username_char(c)).unwrap_or(rest.len());
+            let username = &rest[..len];
+
+            if validate_username(username).is_ok() && !mentions.iter().any(|m| m.eq_ignore_ascii_case(username)) {
+                if mentions.len() >= max {
+                    break;
+                }
+                mentions.push(username.to_string());
+            }
+        }
+        prev = Some(c);
+    }
+    mentions
+}
 
 #[cfg(test)]
 mod tests {
@@ -52,4 +76,11 @@ mod tests {
         assert!(matches!(validate_username("abcṷd"), Err(UsernameValidationError::Invalid)));
         assert!(matches!(validate_username("abc王d"), Err(UsernameValidationError::Invalid)));
     }
+
+    #[test]
+    fn mentions() {
+        assert_eq!(extract_mentions("Thanks @alice_b and @Carol1, (@alice_B)", 10), vec!["alice_b", "Carol1"]);
+        assert_eq!(extract_mentions("mail bob@gmail.com or @bob and @abcdefghijklmnopqrstuvwxyz", 10), Vec::<String>::new());
+        assert_eq!(extract_mentions("@user1 @user2 @user3", 2), vec!["user1", "user2"]);
+    }
 }